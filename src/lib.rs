@@ -1,4 +1,4 @@
-#![feature(alloc, heap_api)]
+#![feature(alloc, heap_api, i128_type)]
 
 extern crate alloc;
 extern crate core;
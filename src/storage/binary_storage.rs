@@ -1,4 +1,7 @@
-use error::{ Error };
+use uuid::Uuid;
+
+use error::{ Error, AssertionError };
+use storage::util;
 
 pub static ERR_STORAGE_ALLOC: &'static str = 
   "Storage allocation failed";
@@ -24,7 +27,114 @@ pub static ERR_WRITE_NOTHING: & 'static str =
   "End of write must be after start of write";
 pub static ERR_READ_NOTHING: & 'static str = 
   "End of read must be after start of read";
+pub static ERR_BLOB_TOO_LONG: & 'static str = 
+  "Blob is too long for its u32 length prefix";
+pub static ERR_RANGES_OVERLAP: & 'static str = 
+  "Ranges to swap must not overlap";
+pub static ERR_TRUNCATE_PAST_END: & 'static str = 
+  "Cannot truncate storage to more than its capacity";
+
+
+// Result of w_bytes_reporting(). capacity is the capacity after the write.
+#[derive(Debug, PartialEq)]
+pub struct WriteOutcome {
+  pub bytes_written: usize,
+  pub expanded: bool,
+  pub capacity: usize
+}
+
+// How a store is expected to be used, so callers who don't want to tune 
+// its parameters can still get reasonable ones. The defaults are:
+//
+//               expand_size  buffer_page_size  buffer_max_pages
+// Append        65536        4096              16
+// RandomWrite   4096         4096              256
+// ReadMostly    4096         16384             1024
+//
+// Appending grows the store often, so it expands in big steps and only 
+// caches the pages near the end. Random writes touch pages all over, so 
+// they get a bigger cache. Read-mostly stores rarely grow and benefit from 
+// bigger pages and the biggest cache.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WorkloadHint {
+  Append,
+  RandomWrite,
+  ReadMostly
+}
+impl WorkloadHint {
+
+  pub fn default_expand_size(&self) -> usize {
+    match *self {
+      WorkloadHint::Append => 65536,
+      WorkloadHint::RandomWrite => 4096,
+      WorkloadHint::ReadMostly => 4096
+    }
+  }
+
+  pub fn default_buffer_page_size(&self) -> usize {
+    match *self {
+      WorkloadHint::Append => 4096,
+      WorkloadHint::RandomWrite => 4096,
+      WorkloadHint::ReadMostly => 16384
+    }
+  }
+
+  pub fn default_buffer_max_pages(&self) -> u64 {
+    match *self {
+      WorkloadHint::Append => 16,
+      WorkloadHint::RandomWrite => 256,
+      WorkloadHint::ReadMostly => 1024
+    }
+  }
+
+}
+
+// Checks the arguments to swap_ranges() against the capacity
+pub fn check_swap_ranges(
+  a: usize, 
+  b: usize, 
+  len: usize, 
+  capacity: usize
+) -> Result<(), Error> {
+  try!(AssertionError::assert(len > 0, ERR_WRITE_NOTHING));
+  let a_end = try!(util::usize_add(a, len));
+  let b_end = try!(util::usize_add(b, len));
+  try!(AssertionError::assert(
+    a_end <= capacity && b_end <= capacity,
+    ERR_WRITE_PAST_END
+  ));
+  try!(AssertionError::assert(a_end <= b || b_end <= a, ERR_RANGES_OVERLAP));
+  Ok(())
+}
+
+// Checks the arguments to copy() against the capacity, returning the end 
+// of the destination range
+pub fn check_copy(
+  from: usize, 
+  to: usize, 
+  len: usize, 
+  capacity: usize
+) -> Result<usize, Error> {
+  let from_end = try!(util::usize_add(from, len));
+  try!(AssertionError::assert(from_end <= capacity, ERR_READ_PAST_END));
+  Ok(try!(util::usize_add(to, len)))
+}
 
+// Checks the arguments to find_bytes() against the capacity
+pub fn check_find_bytes(
+  pattern: &[u8], 
+  start: usize, 
+  capacity: usize
+) -> Result<(), Error> {
+  try!(AssertionError::assert(pattern.len() > 0, ERR_READ_NOTHING));
+  try!(AssertionError::assert(start < capacity, ERR_READ_PAST_END));
+  Ok(())
+}
+
+// Position of the first occurrence of pattern in data
+pub fn find_in(data: &[u8], pattern: &[u8]) -> Option<usize> {
+  data.windows(pattern.len()).position(|w| w == pattern)
+}
 
 pub trait BinaryStorage {
 
@@ -43,6 +153,9 @@ pub trait BinaryStorage {
   fn w_u32(&mut self, offset: usize, data: u32) -> Result<(), Error>;
   fn w_u64(&mut self, offset: usize, data: u64) -> Result<(), Error>;
 
+  fn w_i128(&mut self, offset: usize, data: i128) -> Result<(), Error>;
+  fn w_u128(&mut self, offset: usize, data: u128) -> Result<(), Error>;
+
   fn w_f32(&mut self, offset: usize, data: f32) -> Result<(), Error>;
   fn w_f64(&mut self, offset: usize, data: f64) -> Result<(), Error>;
 
@@ -51,6 +164,24 @@ pub trait BinaryStorage {
   fn w_bytes(&mut self, offset: usize, data: &[u8]) -> Result<(), Error>;
   fn w_str(&mut self, offset: usize, data: &str) -> Result<(), Error>;
 
+  // Like w_bytes(), but reports whether the write expanded the storage
+  fn w_bytes_reporting(
+    &mut self, 
+    offset: usize, 
+    data: &[u8]
+  ) -> Result<WriteOutcome, Error>;
+
+  fn w_uuid(&mut self, offset: usize, data: Uuid) -> Result<(), Error>;
+
+  // Writes a u32 length followed by the data. Returns the total number of 
+  // bytes written, including the length.
+  fn w_blob(&mut self, offset: usize, data: &[u8]) -> Result<usize, Error>;
+
+  // Write the values consecutively starting at offset in a single write, 
+  // expanding the storage at most once
+  fn w_u32_array(&mut self, offset: usize, values: &[u32]) -> Result<(), Error>;
+  fn w_u64_array(&mut self, offset: usize, values: &[u64]) -> Result<(), Error>;
+
 
   fn r_i8(&self, offset: usize) -> Result<i8, Error>;
   fn r_i16(&self, offset: usize) -> Result<i16, Error>;
@@ -62,14 +193,45 @@ pub trait BinaryStorage {
   fn r_u32(&self, offset: usize) -> Result<u32, Error>;
   fn r_u64(&self, offset: usize) -> Result<u64, Error>;
 
+  fn r_i128(&self, offset: usize) -> Result<i128, Error>;
+  fn r_u128(&self, offset: usize) -> Result<u128, Error>;
+
+  // Read count consecutive values starting at offset in a single read, 
+  // rather than one read per value
+  fn r_u32_array(&self, offset: usize, count: usize) -> Result<Vec<u32>, Error>;
+  fn r_u64_array(&self, offset: usize, count: usize) -> Result<Vec<u64>, Error>;
+
+  // None if none of the 8 bytes at offset have been written and they all 
+  // read as zero, so a field explicitly written as 0 is Some(0). File 
+  // storage only knows about writes made since it was opened
+  fn r_u64_opt(&self, offset: usize) -> Result<Option<u64>, Error>;
+
   fn r_f32(&self, offset: usize) -> Result<f32, Error>;
   fn r_f64(&self, offset: usize) -> Result<f64, Error>;
 
   fn r_bool(&self, offset: usize) -> Result<bool, Error>;
 
   fn r_bytes(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error>;
+  // Like r_bytes(), but fills buf instead of allocating, and returns the 
+  // number of bytes read
+  fn r_bytes_into(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error>;
   fn r_str(&self, offset: usize, len: usize) -> Result<String, Error>;
 
+  fn r_uuid(&self, offset: usize) -> Result<Uuid, Error>;
+
+  // Reads data written by w_blob()
+  fn r_blob(&self, offset: usize) -> Result<Vec<u8>, Error>;
+
+  fn read_all(&self) -> Result<Vec<u8>, Error>;
+
+  // Cursorless positional access, equivalent to r_bytes() and w_bytes()
+  fn pread(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error>;
+  fn pwrite(&mut self, offset: usize, data: &[u8]) -> Result<(), Error>;
+
+  // Length of the data from offset up to and including the last non-zero 
+  // byte, i.e. the extent of meaningful data ahead of any trailing zero-fill
+  fn valid_len_from(&self, offset: usize) -> Result<usize, Error>;
+
   fn fill(
     &mut self, 
     start: Option<usize>, 
@@ -84,6 +246,23 @@ pub trait BinaryStorage {
     val: u8
   ) -> Result<bool, Error>;
 
+  // CRC32 of the bytes from start to end, taking the same bounds as 
+  // is_filled()
+  fn crc32(&self, start: Option<usize>, end: Option<usize>) -> Result<u32, Error>;
+
+  // Offset of the first occurrence of pattern at or after start, or None if
+  // it doesn't occur before the end of capacity
+  fn find_bytes(&self, pattern: &[u8], start: usize) -> Result<Option<usize>, Error>;
+
+  // Exchanges the len bytes at a with the len bytes at b. Both ranges must 
+  // already be within capacity, and they must not overlap.
+  fn swap_ranges(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error>;
+
+  // Copies the len bytes at from to to, which may overlap them. The source 
+  // must be within capacity, and the storage expands to hold the 
+  // destination if needed.
+  fn copy(&mut self, from: usize, to: usize, len: usize) -> Result<(), Error>;
+
   fn get_expand_size(&self) -> usize;
   fn set_expand_size(&mut self, expand_size: usize) -> Result<(), Error>;
 
@@ -91,5 +270,18 @@ pub trait BinaryStorage {
 
   fn expand(&mut self, min_capacity: usize) -> Result<(), Error>;
 
+  // Reduces the capacity to the smallest multiple of expand_size (but at 
+  // least expand_size) that holds max_capacity bytes. Data past the new 
+  // capacity is lost. Does nothing if the capacity is already that small.
+  fn shrink(&mut self, max_capacity: usize) -> Result<(), Error>;
+
+  // Like shrink(), but fails if new_capacity is more than the capacity 
+  // rather than doing nothing
+  fn truncate(&mut self, new_capacity: usize) -> Result<(), Error>;
+
+  // Makes everything written so far durable. Does nothing for storage that
+  // isn't backed by anything persistent.
+  fn sync(&mut self) -> Result<(), Error>;
+
 }
 
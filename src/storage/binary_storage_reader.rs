@@ -0,0 +1,91 @@
+use std::io;
+use std::io::{ Read, Seek, SeekFrom };
+
+use error::Error;
+use storage::binary_storage::BinaryStorage;
+
+
+// Adapts a storage to std::io::Read and std::io::Seek, so it can be handed
+// to anything that consumes a reader without first copying it all into a 
+// Vec. Reads stop at the capacity, and reading at or past the end returns 
+// Ok(0). Seeking past the end is allowed, as with files.
+pub struct BinaryStorageReader<'a, T: BinaryStorage + 'a> {
+  storage: &'a T,
+  position: usize
+}
+impl<'a, T: BinaryStorage + 'a> BinaryStorageReader<'a, T> {
+
+  pub fn new(storage: &'a T) -> BinaryStorageReader<'a, T> {
+    BinaryStorageReader {
+      storage: storage,
+      position: 0
+    }
+  }
+
+  pub fn get_position(&self) -> usize {
+    self.position
+  }
+
+}
+
+// base moved by delta, or None if that would go below 0 or overflow a u64
+fn offset_by(base: u64, delta: i64) -> Option<u64> {
+  if delta >= 0 {
+    base.checked_add(delta as u64)
+  } else {
+    base.checked_sub(delta.wrapping_neg() as u64)
+  }
+}
+
+fn to_io_err(err: Error) -> io::Error {
+  match err {
+    Error::Io(e) => e,
+    e => io::Error::new(io::ErrorKind::Other, e)
+  }
+}
+
+impl<'a, T: BinaryStorage + 'a> Read for BinaryStorageReader<'a, T> {
+
+  fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+    let capacity = try!(self.storage.get_capacity().map_err(to_io_err));
+    if self.position >= capacity || buf.len() == 0 { return Ok(0) }
+
+    let len = if buf.len() < capacity - self.position { 
+      buf.len() 
+    } else { 
+      capacity - self.position 
+    };
+    let read_len = try!(
+      self.storage.r_bytes_into(self.position, &mut buf[..len]).map_err(to_io_err)
+    );
+    self.position += read_len;
+    Ok(read_len)
+  }
+
+}
+
+impl<'a, T: BinaryStorage + 'a> Seek for BinaryStorageReader<'a, T> {
+
+  fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+    let new_position = match pos {
+      SeekFrom::Start(n) => Some(n),
+      SeekFrom::End(n) => {
+        let capacity = try!(self.storage.get_capacity().map_err(to_io_err));
+        offset_by(capacity as u64, n)
+      },
+      SeekFrom::Current(n) => offset_by(self.position as u64, n)
+    };
+
+    let new_position = match new_position {
+      Some(p) if p <= usize::max_value() as u64 => p,
+      _ => return Err(io::Error::new(
+        io::ErrorKind::InvalidInput, 
+        "Cannot seek outside the addressable range of the storage"
+      ))
+    };
+
+    self.position = new_position as usize;
+    Ok(self.position as u64)
+  }
+
+}
@@ -0,0 +1,105 @@
+use error::{ Error, AssertionError };
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+use storage::util;
+
+pub static ERR_NUM_SLOTS_TOO_SMALL: & 'static str =
+  "Number of slots must be greater than zero";
+pub static ERR_SLOT_OUT_OF_RANGE: & 'static str =
+  "Slot index is past the last slot";
+
+
+// One bit per fixed-size slot, set when the slot is in use. Slot i is bit
+// i % 8 (least significant first) of byte i / 8.
+pub struct Bitmap<T: BinaryStorage + Sized> {
+  storage: T,
+  num_slots: u64
+}
+impl<T: BinaryStorage + Sized> Bitmap<T> {
+
+  pub fn new(storage: T, num_slots: u64) -> Result<Bitmap<T>, Error> {
+    try!(AssertionError::assert(num_slots > 0, ERR_NUM_SLOTS_TOO_SMALL));
+    Ok(Bitmap {
+      storage: storage,
+      num_slots: num_slots
+    })
+  }
+
+  pub fn open(&mut self) -> Result<(), Error> {
+    try!(self.storage.open());
+    let len = try!(self.byte_len());
+    self.storage.expand(len)
+  }
+
+  pub fn close(&mut self) -> Result<(), Error> {
+    self.storage.close()
+  }
+
+  pub fn is_open(&self) -> bool {
+    self.storage.is_open()
+  }
+
+  // Marks the lowest free slot as used and returns its index, or None if
+  // every slot is in use
+  pub fn allocate(&mut self) -> Result<Option<u64>, Error> {
+    try!(AssertionError::assert(
+      self.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let len = try!(self.byte_len());
+    for byte_idx in 0..len {
+      let byte = try!(self.storage.r_u8(byte_idx));
+      if byte == 0xff { continue; }
+
+      let bit = (!byte).trailing_zeros() as u64;
+      let index = byte_idx as u64 * 8 + bit;
+      if index >= self.num_slots { break; }
+
+      try!(self.w_bit(index, true));
+      return Ok(Some(index));
+    }
+
+    Ok(None)
+  }
+
+  pub fn free(&mut self, index: u64) -> Result<(), Error> {
+    try!(self.assert_in_range(index));
+    self.w_bit(index, false)
+  }
+
+  pub fn is_set(&self, index: u64) -> Result<bool, Error> {
+    try!(self.assert_in_range(index));
+    self.r_bit(index)
+  }
+
+  pub fn get_num_slots(&self) -> u64 { self.num_slots }
+
+  fn assert_in_range(&self, index: u64) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert(index < self.num_slots, ERR_SLOT_OUT_OF_RANGE));
+    Ok(())
+  }
+
+  fn byte_len(&self) -> Result<usize, Error> {
+    let len = self.num_slots / 8 + if self.num_slots % 8 > 0 { 1 } else { 0 };
+    Ok(try!(util::u64_as_usize(len)))
+  }
+
+  fn r_bit(&self, index: u64) -> Result<bool, Error> {
+    let byte_idx = try!(util::u64_as_usize(index / 8));
+    let byte = try!(self.storage.r_u8(byte_idx));
+    Ok(byte & (1 << (index % 8)) != 0)
+  }
+
+  fn w_bit(&mut self, index: u64, val: bool) -> Result<(), Error> {
+    let byte_idx = try!(util::u64_as_usize(index / 8));
+    let byte = try!(self.storage.r_u8(byte_idx));
+    let mask = 1 << (index % 8);
+    self.storage.w_u8(byte_idx, if val { byte | mask } else { byte & !mask })
+  }
+
+}
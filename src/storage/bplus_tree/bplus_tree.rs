@@ -1,8 +1,7 @@
-use core::clone::Clone;
-use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
-
 use error::{ Error, AssertionError };
 use storage::binary_storage::BinaryStorage;
+use storage::journal::Journal;
+use storage::util;
 
 pub static ERR_USE_LEAF_WHERE_NONE: & 'static str = 
   "Tried to read leaf node from file location where none exists";
@@ -10,6 +9,8 @@ pub static ERR_USE_INNER_WHERE_NONE: & 'static str =
   "Tried to read inner node from file location where none exists";
 pub static ERR_KEY_WRONG_SIZE: & 'static str = 
   "Key is the wrong nubmer of bytes";
+pub static ERR_VAL_WRONG_SIZE: & 'static str = 
+  "Value is the wrong number of bytes";
 pub static ERR_READ_PAST_INNER_NODE: & 'static str = 
   "Tried to read more records from inner node than exist in the node";
 pub static ERR_INNER_NODE_EMPTY: & 'static str = 
@@ -18,6 +19,12 @@ pub static ERR_SEARCH_NO_LEAF_FOR_KEY: & 'static str =
   "Search could not find a leaf node for the key";
 pub static ERR_INVALID_NODE_TYPE: & 'static str = 
   "Node is not marked as either an inner node or a leaf node";
+pub static ERR_JOURNAL_RECORD_TOO_SHORT: & 'static str = 
+  "Journal record is shorter than the key length";
+pub static ERR_LEAF_ALIGN_INVALID: & 'static str = 
+  "Leaf record alignment must be a power of two";
+pub static ERR_KEYS_OUT_OF_ORDER: & 'static str = 
+  "Keys are not in strictly increasing order";
 
 const INNER_NODE_REC_OFFSET: u32 = 13;
 const LEAF_NODE_REC_OFFSET: u32 = 29;
@@ -29,29 +36,37 @@ struct LeafRecord {
 }
 
 struct InnerRecord {
+  pub inner_idx: u32,
   pub min_key: Option<Vec<u8>>,
-  pub ptr: u64,
+  pub ptr: usize,
   pub max_key: Option<Vec<u8>>
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 struct InnerState {
-  pub ptr: u64,
-  pub parent_ptr: u64,
+  pub ptr: usize,
+  pub parent_ptr: usize,
   pub num_recs: u32,
   pub cur_rec_idx: u32
 }
 
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 struct LeafState {
-  pub ptr: u64,
-  pub parent_ptr: u64,
-  pub prev_ptr: u64,
-  pub next_ptr: u64,
+  pub ptr: usize,
+  pub parent_ptr: usize,
+  pub prev_ptr: usize,
+  pub next_ptr: usize,
   pub num_recs: u32,
   pub cur_rec_idx: u32,
 }
 
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum NodeKind {
+  Inner,
+  Leaf,
+  Invalid
+}
+
 enum State {
   Nothing(),
   Inner(InnerState),
@@ -65,7 +80,12 @@ pub struct BPlusTree<T: BinaryStorage + Sized> {
   val_len: u8,
   node_size: u32,
   state: State,
-  num_nodes: u64
+  num_nodes: usize,
+  leaf_align: u32,
+  max_descent_depth: Option<usize>,
+  min_leaf_recs: u32,
+  inline_value_max: Option<u8>,
+  leaf_reads: u64
 }
 impl<T: BinaryStorage + Sized> BPlusTree<T> {
 
@@ -81,32 +101,281 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
       val_len: val_len,
       node_size: node_size,
       state: State::Nothing(),
-      num_nodes: 0
+      num_nodes: 0,
+      leaf_align: 1,
+      max_descent_depth: None,
+      min_leaf_recs: 0,
+      inline_value_max: None,
+      leaf_reads: 0
+    }
+  }
+
+  // Pads leaf records so each one starts on a multiple of align bytes, and
+  // rounds node_size up to match so every node starts aligned too. Costs
+  // space in exchange for records that don't straddle cache lines. Must be
+  // set before any nodes are written, since it changes the leaf layout.
+  pub fn set_leaf_align(&mut self, align: u32) -> Result<(), Error> {
+    try!(AssertionError::assert(align.is_power_of_two(), ERR_LEAF_ALIGN_INVALID));
+    self.leaf_align = align;
+    self.node_size = Self::align_up(self.node_size, align);
+    Ok(())
+  }
+
+  pub fn get_leaf_align(&self) -> u32 { self.leaf_align }
+
+  pub fn get_node_size(&self) -> u32 { self.node_size }
+
+  // Limits how many inner nodes a search may pass through before giving up
+  // with ERR_SEARCH_NO_LEAF_FOR_KEY. None uses the number of nodes, since a
+  // well-formed tree never visits a node twice on the way to a leaf.
+  pub fn set_max_descent_depth(&mut self, depth: Option<usize>) {
+    self.max_descent_depth = depth;
+  }
+
+  pub fn get_max_descent_depth(&self) -> usize {
+    match self.max_descent_depth {
+      Some(d) => d,
+      None => self.num_nodes
+    }
+  }
+
+  // A leaf left holding fewer than min_recs records by delete() is merged
+  // with a sibling, if they fit in one leaf together. 0 turns merging off.
+  pub fn set_min_leaf_occupancy(&mut self, min_recs: u32) {
+    self.min_leaf_recs = min_recs;
+  }
+
+  pub fn get_min_leaf_occupancy(&self) -> u32 { self.min_leaf_recs }
+
+  // When values are at most max bytes long, each separator key in an inner
+  // node is followed by a copy of that key's value (behind a flag byte that
+  // is 0 while there is no copy), so search() can return it without reading
+  // the leaf. Costs inner node fan-out. Must be set before any nodes are 
+  // written, since it changes the inner layout.
+  pub fn set_inline_value_max(&mut self, max: Option<u8>) {
+    self.inline_value_max = max;
+  }
+
+  pub fn get_inline_value_max(&self) -> Option<u8> { self.inline_value_max }
+
+  // Number of times a leaf node has been read since the tree was created
+  pub fn get_leaf_reads(&self) -> u64 { self.leaf_reads }
+
+  // Bytes taken up by an inlined value (and its flag) after each separator
+  // key, or 0 if values aren't inlined
+  fn inline_len(&self) -> u32 {
+    match self.inline_value_max {
+      Some(max) if self.val_len <= max => 1 + self.val_len as u32,
+      _ => 0
     }
   }
 
+  // Bytes between consecutive child pointers in an inner node
+  fn inner_sep_len(&self) -> u32 {
+    self.key_len as u32 + self.inline_len()
+  }
+
+  // Offset of a record from the start of its leaf node under the current
+  // layout
+  pub fn leaf_record_offset(&self, rec_idx: u32) -> u32 {
+    Self::leaf_rec_offset(rec_idx, self.key_len, self.val_len, self.leaf_align)
+  }
+
   pub fn open(&mut self) -> Result<(), Error> {
-    self.storage.open()
+    try!(self.storage.open());
+    self.num_nodes = try!(self.count_nodes());
+    Ok(())
     // TODO: Ensure object properties match saved file data
   }
 
+  // The node area ends after the last node slot holding a node marker
+  fn count_nodes(&self) -> Result<usize, Error> {
+    let capacity = try!(self.storage.get_capacity());
+    let node_size = self.node_size as usize;
+    let mut num_nodes = 0;
+    let mut i = 0;
+
+    while (i + 1) * node_size <= capacity {
+      match try!(self.storage.r_u8(i * node_size)) {
+        0x01 | 0x02 => num_nodes = i + 1,
+        _ => ()
+      };
+      i += 1;
+    }
+
+    Ok(num_nodes)
+  }
+
   pub fn close(&mut self) -> Result<(), Error> {
     self.storage.close()
   }
 
   pub fn insert(&mut self, key: &[u8], val: &[u8]) -> Result<(), Error> {
-    try!(self.search_node(key));
+    try!(AssertionError::assert(val.len() == self.val_len as usize, ERR_VAL_WRONG_SIZE)); 
+    try!(AssertionError::assert(key.len() == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+    if self.num_nodes == 0 { try!(self.alloc_leaf(0, 0)); }
+    try!(self.search_node(key, false));
 
-    println!("LIF {0}", try!(self.leaf_is_full()));
+    try!(self.insert_in_leaf(key, val));
+    try!(self.write_inline_value(key, Some(val)));
 
-    match try!(self.leaf_is_full()) {
-      true => try!(self.split_leaf(key, val)),
-      false => try!(self.insert_in_leaf(key, val))
-    };
+    self.state = State::Nothing();
+    Ok(())
+  }
+
+  // Splits the leaf that key would land in if it is more than half full,
+  // so a run of inserts into that leaf doesn't have to split it midway.
+  // Only a performance hint; the records in the tree are unchanged.
+  pub fn hint_split(&mut self, key: &[u8]) -> Result<(), Error> {
+    try!(self.search_node(key, false));
+    let l = try!(self.get_leaf_state());
+
+    let max_recs = 
+      Self::leaf_max_records(self.node_size, self.key_len, self.val_len, self.leaf_align);
+    if l.num_recs * 2 <= max_recs { return Ok(()); }
 
+    let recs = try!(self.read_leaf_recs(&l));
+    try!(self.split_leaf(&l, recs));
+
+    self.state = State::Nothing();
     Ok(())
   }
 
+  pub fn key_buffer(&self) -> Vec<u8> {
+    vec![0x0; self.key_len as usize]
+  }
+
+  pub fn insert_buf(&mut self, key: &Vec<u8>, val: &[u8]) -> Result<(), Error> {
+    self.insert(key.as_slice(), val)
+  }
+
+  // Inserts every committed journal record into the tree, treating the first
+  // key_len bytes as the key and the rest as the value. Later records for
+  // the same key overwrite earlier ones. Returns the number of records read.
+  pub fn build_from_journal<J: BinaryStorage + Sized>(
+    &mut self, 
+    journal: &mut Journal<J>, 
+    key_len: usize
+  ) -> Result<u64, Error> {
+    try!(AssertionError::assert(key_len == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+
+    let mut count: u64 = 0;
+    for (_, rec) in journal.enumerate_records() {
+      try!(AssertionError::assert(key_len <= rec.len(), ERR_JOURNAL_RECORD_TOO_SHORT));
+      let (key, val) = rec.split_at(key_len);
+      try!(self.insert(key, val));
+      count += 1;
+    }
+
+    Ok(count)
+  }
+
+  // Lists the offset and kind of every allocated node, in storage order,
+  // based on each node's marker byte. Does not change the tree.
+  pub fn nodes(&mut self) -> Result<Vec<(usize, NodeKind)>, Error> {
+    let mut nodes = Vec::new();
+
+    for i in 0..self.num_nodes {
+      let ptr = i * self.node_size as usize;
+      let kind = match try!(self.storage.r_u8(ptr)) {
+        0x01 => NodeKind::Inner,
+        0x02 => NodeKind::Leaf,
+        _ => NodeKind::Invalid
+      };
+      nodes.push((ptr, kind));
+    }
+
+    Ok(nodes)
+  }
+
+  // Exports every record in key order by walking the leaf chain from the
+  // leftmost leaf, checking along the way that each key is strictly greater
+  // than the one before it. Meant for tests and debugging.
+  pub fn to_sorted_vec(&mut self) -> Result<Vec<(Vec<u8>, Vec<u8>)>, Error> {
+    let mut recs: Vec<(Vec<u8>, Vec<u8>)> = Vec::new();
+    if self.num_nodes == 0 { return Ok(recs); }
+
+    let mut leaf_ptr = try!(self.leftmost_leaf());
+
+    loop {
+      let num_recs = try!(self.storage.r_u32(leaf_ptr + 25));
+
+      for idx in 0..num_recs {
+        let rec_offset = leaf_ptr + 
+          Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+        let key = try!(self.storage.r_bytes(rec_offset, self.key_len as usize));
+        let val = try!(self.storage.r_bytes(
+          rec_offset + self.key_len as usize, 
+          self.val_len as usize
+        ));
+
+        if let Some(&(ref prev_key, _)) = recs.last() {
+          try!(AssertionError::assert(prev_key < &key, ERR_KEYS_OUT_OF_ORDER));
+        }
+
+        recs.push((key, val));
+      }
+
+      let next_ptr = try!(self.r_ptr(leaf_ptr + 17));
+      if next_ptr == 0 { break; }
+      leaf_ptr = next_ptr;
+    }
+
+    Ok(recs)
+  }
+
+  // Lists the offset of every leaf in key order by walking the leaf chain
+  // from the leftmost leaf. Does not change the tree.
+  pub fn leaves(&mut self) -> Result<Vec<usize>, Error> {
+    let mut leaves = Vec::new();
+    if self.num_nodes == 0 { return Ok(leaves); }
+
+    let mut leaf_ptr = try!(self.leftmost_leaf());
+    loop {
+      leaves.push(leaf_ptr);
+      let next_ptr = try!(self.r_ptr(leaf_ptr + 17));
+      if next_ptr == 0 { break; }
+      leaf_ptr = next_ptr;
+    }
+
+    Ok(leaves)
+  }
+
+  // Number of levels from the root down to the leaves, counted along the 
+  // leftmost path: 0 for an empty tree and 1 for a lone root leaf. The 
+  // walk is bounded like search(), so a cyclic pointer gives 
+  // ERR_SEARCH_NO_LEAF_FOR_KEY instead of looping.
+  pub fn height(&self) -> Result<usize, Error> {
+    if self.num_nodes == 0 { return Ok(0); }
+
+    let max_depth = self.get_max_descent_depth();
+    let mut ptr = 0;
+    let mut height = 1;
+    loop {
+      match try!(self.storage.r_u8(ptr)) {
+        0x01 => {
+          try!(AssertionError::assert(height <= max_depth, ERR_SEARCH_NO_LEAF_FOR_KEY));
+          ptr = try!(self.r_ptr(ptr + INNER_NODE_REC_OFFSET as usize));
+          height += 1;
+        },
+        0x02 => return Ok(height),
+        _ => return Err(Error::Assertion(AssertionError::new(ERR_INVALID_NODE_TYPE)))
+      }
+    }
+  }
+
+  // Follows the first child pointer of each inner node down from the root
+  fn leftmost_leaf(&self) -> Result<usize, Error> {
+    let mut ptr = 0;
+    loop {
+      match try!(self.storage.r_u8(ptr)) {
+        0x01 => ptr = try!(self.r_ptr(ptr + INNER_NODE_REC_OFFSET as usize)),
+        0x02 => return Ok(ptr),
+        _ => return Err(Error::Assertion(AssertionError::new(ERR_INVALID_NODE_TYPE)))
+      }
+    }
+  }
+
   fn get_leaf_state(&self) -> Result<LeafState, Error> {
     match self.state {
       State::Leaf(s) => Ok(s),
@@ -121,275 +390,635 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
     }
   }
 
-  fn alloc_leaf(&mut self, prev_ptr: u64, parent_ptr: u64) -> Result<u64, Error> {
+  fn r_ptr(&self, offset: usize) -> Result<usize, Error> {
+    Ok(try!(util::u64_as_usize(try!(self.storage.r_u64(offset)))))
+  }
+
+  fn w_ptr(&mut self, offset: usize, ptr: usize) -> Result<(), Error> {
+    self.storage.w_u64(offset, ptr as u64)
+  }
+
+  fn alloc_leaf(&mut self, prev_ptr: usize, parent_ptr: usize) -> Result<usize, Error> {
 
-    let ptr = self.num_nodes * self.node_size as u64;
+    let ptr = self.num_nodes * self.node_size as usize;
 
+    try!(self.storage.expand(ptr + self.node_size as usize));
     try!(self.storage.w_u8(ptr, 0x02)); // Leaf node marker
-    try!(self.storage.w_u64(ptr + 1, parent_ptr)); // Pointer to parent node
-    try!(self.storage.w_u64(ptr + 9, prev_ptr)); // Pointer to previous leaf node
-    try!(self.storage.w_u64(ptr + 17, 0)); // Pointer to next leaf node
-    try!(self.storage.w_u32(ptr + 25, 0)); // Number of records in this node 
+    try!(self.w_ptr(ptr + 1, parent_ptr)); // Pointer to parent node
+    try!(self.w_ptr(ptr + 9, prev_ptr)); // Pointer to previous leaf node
+    try!(self.w_ptr(ptr + 17, 0)); // Pointer to next leaf node
+    try!(self.storage.w_u32(ptr + 25, 0)); // Number of records in this node
 
     self.num_nodes += 1;
 
     Ok(ptr)
   }
 
-  fn split_leaf(&mut self, key: &[u8], val: &[u8]) -> Result<(), Error> {
-    println!("SPLIT LEAF");
-    let l = try!(self.get_leaf_state());
-
-    let split_idx = l.num_recs / 2;
-    let new_leaf_ptr = try!(self.alloc_leaf(l.ptr, l.parent_ptr));
+  // Reads the key and value of every record in a leaf, in order
+  fn read_leaf_recs(&self, l: &LeafState) -> Result<Vec<Vec<u8>>, Error> {
+    let rec_size = Self::leaf_rec_size(self.key_len, self.val_len) as usize;
+    let mut recs = Vec::with_capacity(l.num_recs as usize + 1);
+    for idx in 0..l.num_recs {
+      let rec_offset =
+        l.ptr + Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+      recs.push(try!(self.storage.r_bytes(rec_offset, rec_size)));
+    }
+    Ok(recs)
+  }
 
-    let split_offset = Self::leaf_rec_offset(split_idx, self.key_len, self.val_len) as u64;
-    let len_to_copy = self.node_size as u64 - split_offset;
+  // Replaces the records of the leaf at ptr, zeroing whatever part of the
+  // node they don't use
+  fn write_leaf_recs(&mut self, ptr: usize, recs: &[Vec<u8>]) -> Result<(), Error> {
+    let first_rec_offset =
+      Self::leaf_rec_offset(0, self.key_len, self.val_len, self.leaf_align) as usize;
+    try!(self.storage.fill(
+      Some(ptr + first_rec_offset),
+      Some(ptr + self.node_size as usize),
+      0x0
+    ));
+    for (idx, rec) in recs.iter().enumerate() {
+      let rec_offset = ptr +
+        Self::leaf_rec_offset(idx as u32, self.key_len, self.val_len, self.leaf_align) as usize;
+      try!(self.storage.w_bytes(rec_offset, rec.as_slice()));
+    }
+    try!(self.storage.w_u32(ptr + 25, recs.len() as u32)); // Number of records in this node
+    Ok(())
+  }
 
-    let bytes_to_copy = try!(self.storage.r_bytes(l.ptr + split_offset, len_to_copy as usize)); 
-    try!(self.storage.w_bytes(new_leaf_ptr + LEAF_NODE_REC_OFFSET as u64, bytes_to_copy.as_slice()));
-    try!(self.storage.fill(Some(l.ptr + split_offset), Some(len_to_copy), 0x0));
+  // Splits a leaf's records (which may be one more than fit in it) between
+  // it and a new leaf to its right, and adds the new leaf to the parent.
+  // The root always stays at 0, so a root leaf is split into two new leaves
+  // and becomes an inner node over them.
+  fn split_leaf(&mut self, l: &LeafState, recs: Vec<Vec<u8>>) -> Result<(), Error> {
+    let key_len = self.key_len as usize;
+    let split_idx = recs.len() / 2;
+    let (left_recs, right_recs) = recs.split_at(split_idx);
+    let sep = self.new_sep(&right_recs[0][..key_len]);
+
+    if l.ptr == 0 {
+      let left_ptr = try!(self.alloc_leaf(0, 0));
+      let right_ptr = try!(self.alloc_leaf(left_ptr, 0));
+      try!(self.w_ptr(left_ptr + 17, right_ptr)); // Pointer to next leaf node
+      try!(self.write_leaf_recs(left_ptr, left_recs));
+      try!(self.write_leaf_recs(right_ptr, right_recs));
+      return self.write_inner(0, 0, &[left_ptr, right_ptr], &[sep]);
+    }
 
-    try!(self.enter_node(l.parent_ptr)); 
-    self.insert_in_inner(key, new_leaf_ptr);
+    let right_ptr = try!(self.alloc_leaf(l.ptr, l.parent_ptr));
+    try!(self.w_ptr(right_ptr + 17, l.next_ptr)); // Pointer to next leaf node
+    if l.next_ptr != 0 { try!(self.w_ptr(l.next_ptr + 9, right_ptr)); }
+    try!(self.w_ptr(l.ptr + 17, right_ptr));
+    try!(self.write_leaf_recs(l.ptr, left_recs));
+    try!(self.write_leaf_recs(right_ptr, right_recs));
 
-    Ok(())
+    self.insert_in_inner(l.parent_ptr, sep, right_ptr)
   }
 
-  fn insert_in_inner(&mut self, key: &[u8], ptr: u64) -> Result<(), Error> {
-    let i = try!(self.get_inner_state());
-
-    match try!(self.inner_is_full()) {
-      true => try!(self.split_inner(key, ptr)),
-      false => {
-
-        let mut idx = 0;
-        let mut stop = false;
-
-        while !stop {
-
-          match try!(self.next_inner_rec()) {
-            Some(r) => {
-              match (r.min_key, r.max_key) {
-                (None, Some(max)) => {
-                  if key < max.as_slice() {
-                    try!(self.insert_in_inner_at_idx(idx, key, ptr));
-                  }
-                },
-                (Some(min), Some(max)) => {
-                  if key == min.as_slice() {
-                    try!(self.overwrite_in_inner_at_idx(idx, key, ptr));
-                  } else if min.as_slice() < key && key < max.as_slice() {
-                    try!(self.insert_in_inner_at_idx(idx, key, ptr));
-                  }
-                },
-                (Some(min), None) => {
-                  if key == min.as_slice() {
-                    try!(self.overwrite_in_inner_at_idx(idx, key, ptr));
-                  } else if min.as_slice() < key {
-                    try!(self.insert_in_inner_at_idx(idx, key, ptr));
-                  }
-                },
-                (None, None) => {
-                  if idx == 0 {
-                    try!(self.insert_in_inner_at_idx(idx, key, ptr));
-                  }
-                  stop = true;
-                }
-              }
-            },
-            None => try!(self.insert_in_inner_at_idx(idx, key, ptr))
-          };
-
-          idx += 1;
-        }
+  // A separator for an inner node: the key, followed by an empty inlined
+  // value slot if values are inlined
+  fn new_sep(&self, key: &[u8]) -> Vec<u8> {
+    let mut sep = key.to_vec();
+    sep.resize(self.inner_sep_len() as usize, 0x0);
+    sep
+  }
 
+  // Reads the child pointers of an inner node, and the separators between
+  // them (with any inlined values)
+  fn read_inner(&self, i: &InnerState) -> Result<(Vec<usize>, Vec<Vec<u8>>), Error> {
+    let sep_len = self.inner_sep_len();
+    let mut ptrs = Vec::with_capacity(i.num_recs as usize + 1);
+    let mut seps = Vec::with_capacity(i.num_recs as usize);
+    for idx in 0..i.num_recs {
+      let rec_offset = i.ptr + Self::inner_rec_offset(idx, sep_len) as usize;
+      if idx > 0 {
+        seps.push(try!(self.storage.r_bytes(rec_offset - sep_len as usize, sep_len as usize)));
       }
-    };
+      ptrs.push(try!(self.r_ptr(rec_offset)));
+    }
+    Ok((ptrs, seps))
+  }
 
+  // Rewrites the inner node at ptr with the given child pointers and the
+  // separators between them
+  fn write_inner(
+    &mut self,
+    ptr: usize,
+    parent_ptr: usize,
+    ptrs: &[usize],
+    seps: &[Vec<u8>]
+  ) -> Result<(), Error> {
+    let sep_len = self.inner_sep_len();
+    try!(self.storage.fill(Some(ptr), Some(ptr + self.node_size as usize), 0x0));
+    try!(self.storage.w_u8(ptr, 0x01)); // Inner node marker
+    try!(self.w_ptr(ptr + 1, parent_ptr)); // Pointer to parent node
+    try!(self.storage.w_u32(ptr + 9, ptrs.len() as u32)); // Number of records in this node
+    for (idx, child_ptr) in ptrs.iter().enumerate() {
+      let rec_offset = ptr + Self::inner_rec_offset(idx as u32, sep_len) as usize;
+      if idx > 0 {
+        try!(self.storage.w_bytes(rec_offset - sep_len as usize, seps[idx - 1].as_slice()));
+      }
+      try!(self.w_ptr(rec_offset, *child_ptr));
+    }
     Ok(())
   }
 
-  fn insert_in_inner_at_idx(&mut self, idx: u32, key: &[u8], ptr: u64) -> Result<(), Error> {
-    let rec_size = Self::inner_rec_size(self.key_len) as u64;
-    let rec_offset = Self::inner_rec_offset(idx, self.key_len) as u64;
-    let len_to_move = self.node_size as u64 - rec_offset - rec_size;
-
-    let bytes_to_move = try!(self.storage.r_bytes(rec_offset, len_to_move as usize));
-    try!(self.storage.w_bytes(rec_offset + rec_size, bytes_to_move.as_slice()));
-    try!(self.overwrite_in_inner_at_idx(idx, key, ptr));
+  // Points each of the nodes at ptrs to parent_ptr as their parent
+  fn set_parents(&mut self, ptrs: &[usize], parent_ptr: usize) -> Result<(), Error> {
+    for ptr in ptrs.iter() {
+      try!(self.w_ptr(ptr + 1, parent_ptr)); // Pointer to parent node
+    }
     Ok(())
   }
 
-  fn overwrite_in_inner_at_idx(&mut self, idx: u32, key: &[u8], ptr: u64) -> Result<(), Error> {
-    let rec_offset = Self::inner_rec_offset(idx, self.key_len) as u64;
-    try!(self.storage.w_bytes(rec_offset, key));
-    try!(self.storage.w_u64(rec_offset + self.key_len as u64, ptr));
-    Ok(())
+  // Adds a child to the inner node at ptr, after the separator sep,
+  // splitting the node if it's full
+  fn insert_in_inner(&mut self, ptr: usize, sep: Vec<u8>, child_ptr: usize) -> Result<(), Error> {
+    try!(self.enter_node(ptr));
+    let i = try!(self.get_inner_state());
+    let key_len = self.key_len as usize;
+
+    let (mut ptrs, mut seps) = try!(self.read_inner(&i));
+    let idx = match seps.iter().position(|s| &sep[..key_len] < &s[..key_len]) {
+      Some(idx) => idx,
+      None => seps.len()
+    };
+    seps.insert(idx, sep);
+    ptrs.insert(idx + 1, child_ptr);
+
+    if ptrs.len() as u32 > Self::inner_max_records(self.node_size, self.inner_sep_len()) {
+      return self.split_inner(&i, ptrs, seps);
+    }
+
+    try!(self.write_inner(i.ptr, i.parent_ptr, ptrs.as_slice(), seps.as_slice()));
+    self.set_parents(&[child_ptr], i.ptr)
   }
 
-  fn alloc_inner(&mut self, parent_ptr: u64) -> Result<u64, Error> {
-    let ptr = self.num_nodes * self.node_size as u64;
+  fn alloc_inner(&mut self, parent_ptr: usize) -> Result<usize, Error> {
+    let ptr = self.num_nodes * self.node_size as usize;
 
+    try!(self.storage.expand(ptr + self.node_size as usize));
     try!(self.storage.w_u8(ptr, 0x01)); // Inner node marker
-    try!(self.storage.w_u64(ptr + 1, parent_ptr)); // Pointer to parent node
-    try!(self.storage.w_u32(ptr + 9, 0)); // Number of records in this node 
+    try!(self.w_ptr(ptr + 1, parent_ptr)); // Pointer to parent node
+    try!(self.storage.w_u32(ptr + 9, 0)); // Number of records in this node
 
     self.num_nodes += 1;
 
     Ok(ptr)
   }
 
-  fn split_inner(&mut self, key: &[u8], ptr: u64) -> Result<(), Error> {
-    let i = try!(self.get_inner_state());
+  // Splits an inner node's children (one more than fit in it) between it
+  // and a new inner node to its right, moving the separator between the
+  // two halves up to the parent. Like a root leaf, a root inner node is
+  // split into two new nodes and left at 0 over them.
+  fn split_inner(
+    &mut self,
+    i: &InnerState,
+    ptrs: Vec<usize>,
+    seps: Vec<Vec<u8>>
+  ) -> Result<(), Error> {
+    let split_idx = ptrs.len() / 2;
+    let (left_ptrs, right_ptrs) = ptrs.split_at(split_idx);
+    let left_seps = &seps[..split_idx - 1];
+    let right_seps = &seps[split_idx..];
+    let up_sep = seps[split_idx - 1].clone();
+
+    if i.ptr == 0 {
+      let left_ptr = try!(self.alloc_inner(0));
+      let right_ptr = try!(self.alloc_inner(0));
+      try!(self.write_inner(left_ptr, 0, left_ptrs, left_seps));
+      try!(self.write_inner(right_ptr, 0, right_ptrs, right_seps));
+      try!(self.set_parents(left_ptrs, left_ptr));
+      try!(self.set_parents(right_ptrs, right_ptr));
+      return self.write_inner(0, 0, &[left_ptr, right_ptr], &[up_sep]);
+    }
+
+    let right_ptr = try!(self.alloc_inner(i.parent_ptr));
+    try!(self.write_inner(i.ptr, i.parent_ptr, left_ptrs, left_seps));
+    try!(self.write_inner(right_ptr, i.parent_ptr, right_ptrs, right_seps));
+    try!(self.set_parents(left_ptrs, i.ptr));
+    try!(self.set_parents(right_ptrs, right_ptr));
+
+    self.insert_in_inner(i.parent_ptr, up_sep, right_ptr)
+  }
 
-    let split_idx = i.num_recs / 2;
-    let new_inner_ptr = try!(self.alloc_inner(i.parent_ptr));
-    
-    let split_offset = Self::inner_rec_offset(split_idx, self.key_len) as u64;
-    let len_to_copy = self.node_size as u64 - split_offset;
+  // Adds key to the current leaf, or overwrites its value if it's already
+  // there, splitting the leaf if it's full
+  fn insert_in_leaf(&mut self, key: &[u8], val: &[u8]) -> Result<(), Error> {
+    let l = try!(self.get_leaf_state());
+    let key_len = self.key_len as usize;
 
-    let bytes_to_copy = try!(self.storage.r_bytes(i.ptr + split_offset, len_to_copy as usize));
-    try!(self.storage.w_bytes(new_inner_ptr + INNER_NODE_REC_OFFSET as u64, bytes_to_copy.as_slice()));
-    try!(self.storage.fill(Some(i.ptr + split_offset), Some(len_to_copy), 0x0));
+    let mut recs = try!(self.read_leaf_recs(&l));
+    let idx = match recs.iter().position(|r| key <= &r[..key_len]) {
+      Some(idx) => idx,
+      None => recs.len()
+    };
 
-    try!(self.enter_node(i.parent_ptr));
-    self.insert_in_inner(key, new_inner_ptr);
+    if idx < recs.len() && key == &recs[idx][..key_len] {
+      return self.overwrite_in_leaf_at_idx(l.ptr, idx as u32, key, val);
+    }
 
+    let mut rec = key.to_vec();
+    rec.extend_from_slice(val);
+    recs.insert(idx, rec);
+
+    let max_recs =
+      Self::leaf_max_records(self.node_size, self.key_len, self.val_len, self.leaf_align);
+    if recs.len() as u32 > max_recs {
+      return self.split_leaf(&l, recs);
+    }
+
+    self.write_leaf_recs(l.ptr, recs.as_slice())
+  }
+
+  fn overwrite_in_leaf_at_idx(
+    &mut self,
+    ptr: usize,
+    idx: u32,
+    key: &[u8],
+    val: &[u8]
+  ) -> Result<(), Error> {
+    let rec_offset =
+      ptr + Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+    try!(self.storage.w_bytes(rec_offset, key));
+    try!(self.storage.w_bytes(rec_offset + self.key_len as usize, val));
     Ok(())
   }
 
-  fn insert_in_leaf(&mut self, key: &[u8], val: &[u8]) -> Result<(), Error> {
-    let mut i = 0;
-    let mut min_key: Option<Vec<u8>> = None;
-    let mut max_key: Option<Vec<u8>> = None;
-    let mut stop = false;
+  pub fn search(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
+    // TODO: Implement binary search on leaf node records
+    if self.num_nodes == 0 { return Ok(None); }
+    if let Some(v) = try!(self.search_node(key, true)) { return Ok(Some(v)); }
+    while let Some(r) = try!(self.next_leaf_rec()) {
+      if key == r.key.as_slice() { return Ok(Some(r.val)); }
+    };
+    Ok(None)
+  }
 
-    while !stop {
+  pub fn search_buf(&mut self, key: &Vec<u8>) -> Result<Option<Vec<u8>>, Error> {
+    self.search(key.as_slice())
+  }
 
-      max_key = match try!(self.next_leaf_rec()) {
-        Some(r) => Some(r.key),
-        None => None
-      };
+  // Looks up many keys with a single descent: the keys are sorted, and the
+  // leaf chain is walked once from the leaf holding the smallest, matching 
+  // keys against records as it goes. Results are in the order of keys.
+  pub fn search_batch(&mut self, keys: &[&[u8]]) -> Result<Vec<Option<Vec<u8>>>, Error> {
+    for k in keys.iter() {
+      try!(AssertionError::assert(k.len() == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+    }
 
-      match (min_key.clone(), max_key.clone()) {
-        (None, Some(max)) => {
-          if key < max.as_slice() {
-            try!(self.insert_in_leaf_at_idx(i, key, val));
-            stop = true;
-          }
-        },
-        (Some(min), Some(max)) => {
-          if key == min.as_slice() {
-            try!(self.overwrite_in_leaf_at_idx(i - 1, key, val));
-            stop = true;
-          } else if min.as_slice() < key && key < max.as_slice() {
-            try!(self.insert_in_leaf_at_idx(i, key, val));
-            stop = true;
-          }
-        },
-        (Some(min), None) => {
-          if key == min.as_slice() {
-            try!(self.overwrite_in_leaf_at_idx(i - 1, key, val));
-            stop = true;
-          } else if min.as_slice() < key {
-            try!(self.insert_in_leaf_at_idx(i, key, val));
-            stop = true;
+    let mut results: Vec<Option<Vec<u8>>> = vec![None; keys.len()];
+    if keys.len() == 0 || self.num_nodes == 0 { return Ok(results); }
+
+    let mut order: Vec<usize> = (0..keys.len()).collect();
+    order.sort_by(|a, b| keys[*a].cmp(keys[*b]));
+
+    try!(self.search_node(keys[order[0]], false));
+    let mut leaf_ptr = try!(self.get_leaf_state()).ptr;
+    let mut next = 0;
+
+    while next < order.len() {
+      let num_recs = try!(self.storage.r_u32(leaf_ptr + 25));
+
+      for idx in 0..num_recs {
+        let rec_offset = leaf_ptr + 
+          Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+        let key = try!(self.storage.r_bytes(rec_offset, self.key_len as usize));
+
+        // Keys smaller than this record's weren't in the tree
+        while next < order.len() && keys[order[next]] < key.as_slice() { next += 1; }
+        if next == order.len() { break; }
+
+        if keys[order[next]] == key.as_slice() {
+          let val = try!(self.storage.r_bytes(
+            rec_offset + self.key_len as usize, 
+            self.val_len as usize
+          ));
+          // The same key may have been asked for more than once
+          while next < order.len() && keys[order[next]] == key.as_slice() {
+            results[order[next]] = Some(val.clone());
+            next += 1;
           }
-        },
-        (None, None) => {
-          if i == 0 {
-            try!(self.insert_in_leaf_at_idx(i, key, val));
-          } 
-          stop = true;
         }
       }
 
-      min_key = match max_key {
-        Some(k) => Some(k.clone()),
-        None => None
-      };
-      i += 1;
+      let next_ptr = try!(self.r_ptr(leaf_ptr + 17));
+      if next_ptr == 0 { break; }
+      leaf_ptr = next_ptr;
     }
-    Ok(())
+
+    self.state = State::Nothing();
+    Ok(results)
   }
 
-  fn insert_in_leaf_at_idx(&mut self, idx: u32, key: &[u8], val: &[u8]) -> Result<(), Error> {
-    let rec_size = Self::leaf_rec_size(self.key_len, self.val_len) as u64;
-    let rec_offset = Self::leaf_rec_offset(idx, self.key_len, self.val_len) as u64;
-    let len_to_move = self.node_size as u64 - rec_offset - rec_size;
+  pub fn delete_range(&mut self, start: &[u8], end: &[u8]) -> Result<u64, Error> {
+    try!(AssertionError::assert(end.len() == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+    try!(self.search_node(start, false));
+
+    let rec_size = Self::leaf_rec_size(self.key_len, self.val_len) as usize;
+    let mut leaf_ptr = try!(self.get_leaf_state()).ptr;
+    let mut num_deleted: u64 = 0;
+    let mut deleted_keys: Vec<Vec<u8>> = Vec::new();
+
+    // Walk the leaves from the one containing the start key, compacting
+    // the records that remain in each. Underflowing leaves are left as-is.
+    loop {
+      let num_recs = try!(self.storage.r_u32(leaf_ptr + 25));
+      let mut num_kept: u32 = 0;
+      let mut past_end = false;
+
+      for idx in 0..num_recs {
+        let rec_offset = 
+          leaf_ptr + Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+        let key = try!(self.storage.r_bytes(rec_offset, self.key_len as usize));
+
+        if start <= key.as_slice() && key.as_slice() < end {
+          num_deleted += 1;
+          deleted_keys.push(key);
+          continue;
+        }
 
-    let bytes_to_move = try!(self.storage.r_bytes(rec_offset, len_to_move as usize));
-    try!(self.storage.w_bytes(rec_offset + rec_size, bytes_to_move.as_slice()));
-    try!(self.overwrite_in_leaf_at_idx(idx, key, val));
-    Ok(())
-  }
+        if end <= key.as_slice() { past_end = true; }
 
-  fn overwrite_in_leaf_at_idx(&mut self, idx: u32, key: &[u8], val: &[u8]) -> Result<(), Error> {
-    let rec_offset = Self::leaf_rec_offset(idx, self.key_len, self.val_len) as u64;
-    try!(self.storage.w_bytes(rec_offset, key)); 
-    try!(self.storage.w_bytes(rec_offset + self.key_len as u64, val)); 
-    Ok(())
+        // Shift the record down over any deleted records before it
+        if num_kept != idx {
+          let rec = try!(self.storage.r_bytes(rec_offset, rec_size as usize));
+          let dest_offset = 
+            leaf_ptr + Self::leaf_rec_offset(num_kept, self.key_len, self.val_len, self.leaf_align) as usize;
+          try!(self.storage.w_bytes(dest_offset, rec.as_slice()));
+        }
+
+        num_kept += 1;
+      }
+
+      if num_kept < num_recs {
+        let clear_start = 
+          leaf_ptr + Self::leaf_rec_offset(num_kept, self.key_len, self.val_len, self.leaf_align) as usize;
+        let clear_end = 
+          leaf_ptr + Self::leaf_rec_offset(num_recs, self.key_len, self.val_len, self.leaf_align) as usize;
+        try!(self.storage.fill(Some(clear_start), Some(clear_end), 0x0));
+        try!(self.storage.w_u32(leaf_ptr + 25, num_kept)); // Number of records in this node
+      }
+
+      let next_ptr = try!(self.r_ptr(leaf_ptr + 17));
+      if past_end || next_ptr == 0 { break; }
+      leaf_ptr = next_ptr;
+    }
+
+    // Separators aren't removed, so drop any values inlined behind them
+    for key in deleted_keys.iter() {
+      try!(self.write_inline_value(key.as_slice(), None));
+    }
+
+    self.state = State::Nothing();
+
+    Ok(num_deleted)
   }
 
-  pub fn search(&mut self, key: &[u8]) -> Result<Option<Vec<u8>>, Error> {
-    // TODO: Implement binary search on leaf node records
-    try!(self.search_node(key));
+  // Removes key from the tree, returning whether it was there. Merges the
+  // leaf it was in with a sibling if that leaves it underfull.
+  pub fn delete(&mut self, key: &[u8]) -> Result<bool, Error> {
+    try!(self.search_node(key, false));
+    let l = try!(self.get_leaf_state());
+
+    let mut found: Option<u32> = None;
     while let Some(r) = try!(self.next_leaf_rec()) {
-      if key == r.key.as_slice() { return Ok(Some(r.val)); }
+      if key == r.key.as_slice() { 
+        found = Some(r.leaf_idx);
+        break;
+      }
+    }
+
+    let idx = match found {
+      Some(i) => i,
+      None => {
+        self.state = State::Nothing();
+        return Ok(false);
+      }
     };
-    Ok(None)
+
+    // Shift the later records down over the deleted one
+    let stride = Self::leaf_rec_stride(self.key_len, self.val_len, self.leaf_align) as usize;
+    let rec_offset = 
+      l.ptr + Self::leaf_rec_offset(idx, self.key_len, self.val_len, self.leaf_align) as usize;
+    let end_offset = 
+      l.ptr + Self::leaf_rec_offset(l.num_recs, self.key_len, self.val_len, self.leaf_align) as usize;
+    if idx + 1 < l.num_recs {
+      let bytes_to_move = try!(self.storage.r_bytes(
+        rec_offset + stride, 
+        (end_offset - rec_offset - stride) as usize
+      ));
+      try!(self.storage.w_bytes(rec_offset, bytes_to_move.as_slice()));
+    }
+    try!(self.storage.fill(Some(end_offset - stride), Some(end_offset), 0x0));
+    try!(self.storage.w_u32(l.ptr + 25, l.num_recs - 1)); // Number of records in this node
+
+    if l.num_recs - 1 < self.min_leaf_recs { try!(self.merge_leaf(l.ptr)); }
+    try!(self.write_inline_value(key, None));
+
+    self.state = State::Nothing();
+    Ok(true)
   }
 
-  fn search_node(&mut self, key: &[u8]) -> Result<(), Error> {
-    try!(AssertionError::assert(key.len() == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+  // Merges an underfull leaf with its next sibling, or with its previous
+  // sibling if it is its parent's last child. The right-hand leaf's records
+  // move to the end of the left-hand leaf, and the right-hand leaf is 
+  // unlinked from the leaf chain and its parent and then zeroed. Nothing 
+  // happens if the siblings have different parents or won't fit in one 
+  // leaf. Parents left underfull are not merged.
+  fn merge_leaf(&mut self, ptr: usize) -> Result<(), Error> {
+    let parent_ptr = try!(self.r_ptr(ptr + 1));
+    let prev_ptr = try!(self.r_ptr(ptr + 9));
+    let next_ptr = try!(self.r_ptr(ptr + 17));
+
+    let (left_ptr, right_ptr) = 
+      if next_ptr != 0 && try!(self.r_ptr(next_ptr + 1)) == parent_ptr {
+        (ptr, next_ptr)
+      } else if prev_ptr != 0 && try!(self.r_ptr(prev_ptr + 1)) == parent_ptr {
+        (prev_ptr, ptr)
+      } else {
+        return Ok(());
+      };
+
+    let left_num_recs = try!(self.storage.r_u32(left_ptr + 25));
+    let right_num_recs = try!(self.storage.r_u32(right_ptr + 25));
+    let max_recs = 
+      Self::leaf_max_records(self.node_size, self.key_len, self.val_len, self.leaf_align);
+    if left_num_recs + right_num_recs > max_recs { return Ok(()); }
+
+    // The right-hand leaf's pointer is never the parent's first one, since
+    // the left-hand leaf comes before it
+    try!(self.enter_node(parent_ptr));
+    let i = try!(self.get_inner_state());
+    let mut child_idx: Option<u32> = None;
+    for c in 1..i.num_recs {
+      let child_ptr = try!(self.r_ptr(
+        i.ptr + Self::inner_rec_offset(c, self.inner_sep_len()) as usize
+      ));
+      if child_ptr == right_ptr {
+        child_idx = Some(c);
+        break;
+      }
+    }
+    let child_idx = match child_idx {
+      Some(c) => c,
+      None => return Ok(())
+    };
+
+    // Move the records over
+    if right_num_recs > 0 {
+      let first_rec_offset = 
+        Self::leaf_rec_offset(0, self.key_len, self.val_len, self.leaf_align) as usize;
+      let len_to_copy = 
+        Self::leaf_rec_offset(right_num_recs, self.key_len, self.val_len, self.leaf_align) as usize - 
+          first_rec_offset;
+      let bytes_to_copy = try!(self.storage.r_bytes(
+        right_ptr + first_rec_offset, 
+        len_to_copy as usize
+      ));
+      let dest_offset = left_ptr + 
+        Self::leaf_rec_offset(left_num_recs, self.key_len, self.val_len, self.leaf_align) as usize;
+      try!(self.storage.w_bytes(dest_offset, bytes_to_copy.as_slice()));
+    }
+    try!(self.storage.w_u32(left_ptr + 25, left_num_recs + right_num_recs));
+
+    // Unlink the right-hand leaf from the leaf chain
+    let right_next_ptr = try!(self.r_ptr(right_ptr + 17));
+    try!(self.w_ptr(left_ptr + 17, right_next_ptr));
+    if right_next_ptr != 0 {
+      try!(self.w_ptr(right_next_ptr + 9, left_ptr));
+    }
+
+    // Drop the right-hand leaf's pointer, and the key before it, from the
+    // parent
+    let sep_len = self.inner_sep_len();
+    let rec_size = Self::inner_rec_size(sep_len) as usize;
+    let rec_offset = 
+      i.ptr + Self::inner_rec_offset(child_idx, sep_len) as usize - sep_len as usize;
+    let end_offset = 
+      i.ptr + Self::inner_rec_offset(i.num_recs, sep_len) as usize - sep_len as usize;
+    if rec_offset + rec_size < end_offset {
+      let bytes_to_move = try!(self.storage.r_bytes(
+        rec_offset + rec_size, 
+        (end_offset - rec_offset - rec_size) as usize
+      ));
+      try!(self.storage.w_bytes(rec_offset, bytes_to_move.as_slice()));
+    }
+    try!(self.storage.fill(Some(end_offset - rec_size), Some(end_offset), 0x0));
+    try!(self.storage.w_u32(i.ptr + 9, i.num_recs - 1)); // Number of records in this node
+
+    try!(self.storage.fill(
+      Some(right_ptr), 
+      Some(right_ptr + self.node_size as usize), 
+      0x0
+    ));
+
     self.state = State::Nothing();
+    Ok(())
+  }
+
+  // Descends to the leaf whose key range holds key. With read_inline, 
+  // stops early and returns key's value if an inner node on the way has it
+  // inlined, leaving the state at that inner node.
+  fn search_node(&mut self, key: &[u8], read_inline: bool) -> Result<Option<Vec<u8>>, Error> {
+    try!(AssertionError::assert(key.len() == self.key_len as usize, ERR_KEY_WRONG_SIZE)); 
+    let max_depth = self.get_max_descent_depth();
+    let mut depth: usize = 0;
+
+    try!(self.enter_node(0));
 
+    // Bounding the descent keeps a corrupted pointer that leads back up the
+    // tree from looping forever
     while match self.state { 
       State::Leaf(_) => false, 
       _ => true 
     } {
-      match self.state {
-        State::Nothing() => try!(self.enter_node(0)),
-        State::Leaf(ref l) => (),
-        State::Inner(_) => {
-          // TODO: Implement binary search on inner node
-          while let Some(r) = try!(self.next_inner_rec()) {
-            match (r.min_key, r.max_key) {
-              (None, Some(max)) => if key < max.as_slice() {
-                return self.enter_node(r.ptr);
-              },
-              (Some(min), Some(max)) => if min.as_slice() <= key && key < max.as_slice() {
-                return self.enter_node(r.ptr);
-              },
-              (Some(min), None) => if min.as_slice() <= key {
-                return self.enter_node(r.ptr);
-              },
-              (None, None) => { 
-                return Err(Error::Assertion(AssertionError::new(ERR_INNER_NODE_EMPTY))); 
-              }
-            }
-          }
-        },
-        _ => ()
+      try!(AssertionError::assert(depth < max_depth, ERR_SEARCH_NO_LEAF_FOR_KEY));
+      let r = try!(self.find_child(key));
+      if read_inline && r.min_key.as_ref().map(|k| k.as_slice()) == Some(key) {
+        let ptr = try!(self.get_inner_state()).ptr;
+        if let Some(v) = try!(self.read_inline_value(ptr, r.inner_idx)) { 
+          return Ok(Some(v)); 
+        }
       }
+      try!(self.enter_node(r.ptr));
+      depth += 1;
     }
 
-    match self.state {
-      State::Leaf(_) => Ok(()),
-      _ => Err(Error::Assertion(AssertionError::new(ERR_SEARCH_NO_LEAF_FOR_KEY))) 
+    Ok(None)
+  }
+
+  // Picks the record of the current inner node whose key range holds key
+  fn find_child(&mut self, key: &[u8]) -> Result<InnerRecord, Error> {
+    // TODO: Implement binary search on inner node
+    while let Some(r) = try!(self.next_inner_rec()) {
+      let found = match (&r.min_key, &r.max_key) {
+        (&None, &Some(ref max)) => key < max.as_slice(),
+        (&Some(ref min), &Some(ref max)) => min.as_slice() <= key && key < max.as_slice(),
+        (&Some(ref min), &None) => min.as_slice() <= key,
+        (&None, &None) => true
+      };
+      if found { return Ok(r); }
     }
+    Err(Error::Assertion(AssertionError::new(ERR_SEARCH_NO_LEAF_FOR_KEY)))
+  }
+
+  // Offset of the inlined value slot (flag byte, then value) that follows 
+  // the separator key before record rec_idx of the inner node at ptr
+  fn inline_value_offset(&self, ptr: usize, rec_idx: u32) -> usize {
+    ptr + Self::inner_rec_offset(rec_idx, self.inner_sep_len()) as usize - 
+      self.inline_len() as usize
+  }
 
+  fn read_inline_value(&self, ptr: usize, rec_idx: u32) -> Result<Option<Vec<u8>>, Error> {
+    if self.inline_len() == 0 || rec_idx == 0 { return Ok(None); }
+    let offset = self.inline_value_offset(ptr, rec_idx);
+    if try!(self.storage.r_u8(offset)) == 0 { return Ok(None); }
+    Ok(Some(try!(self.storage.r_bytes(offset + 1, self.val_len as usize))))
   }
 
-  fn enter_node(&mut self, ptr: u64) -> Result<(), Error> {
+  // Writes val into the inlined value slot of every separator on the way 
+  // down that is equal to key, or clears those slots if val is None
+  fn write_inline_value(&mut self, key: &[u8], val: Option<&[u8]>) -> Result<(), Error> {
+    if self.inline_len() == 0 { return Ok(()); }
+    let max_depth = self.get_max_descent_depth();
+    let mut depth: usize = 0;
+    let mut ptr = 0;
+
+    while try!(self.storage.r_u8(ptr)) == 0x01 {
+      try!(AssertionError::assert(depth < max_depth, ERR_SEARCH_NO_LEAF_FOR_KEY));
+      try!(self.enter_node(ptr));
+      let r = try!(self.find_child(key));
+      if r.min_key.as_ref().map(|k| k.as_slice()) == Some(key) {
+        let offset = self.inline_value_offset(ptr, r.inner_idx);
+        match val {
+          Some(v) => {
+            try!(self.storage.w_u8(offset, 0x01));
+            try!(self.storage.w_bytes(offset + 1, v));
+          },
+          None => try!(self.storage.fill(
+            Some(offset), 
+            Some(offset + self.inline_len() as usize), 
+            0x0
+          ))
+        };
+      }
+      ptr = r.ptr;
+      depth += 1;
+    }
+
+    self.state = State::Nothing();
+    Ok(())
+  }
+
+  fn enter_node(&mut self, ptr: usize) -> Result<(), Error> {
     match try!(self.storage.r_u8(ptr)) {
       0x02 => {
+        self.leaf_reads += 1;
         self.state = State::Leaf(LeafState {
           ptr: ptr,
-          parent_ptr: try!(self.storage.r_u64(ptr + 1)),
-          prev_ptr: try!(self.storage.r_u64(ptr + 9)),
-          next_ptr: try!(self.storage.r_u64(ptr + 17)),
+          parent_ptr: try!(self.r_ptr(ptr + 1)),
+          prev_ptr: try!(self.r_ptr(ptr + 9)),
+          next_ptr: try!(self.r_ptr(ptr + 17)),
           num_recs: try!(self.storage.r_u32(ptr + 25)),
           cur_rec_idx: 0
         });
@@ -398,8 +1027,8 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
       0x01 => {
         self.state = State::Inner(InnerState {
           ptr: ptr,
-          parent_ptr: 0,
-          num_recs: 0,
+          parent_ptr: try!(self.r_ptr(ptr + 1)),
+          num_recs: try!(self.storage.r_u32(ptr + 9)),
           cur_rec_idx: 0
         });
         Ok(())
@@ -408,42 +1037,42 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
     }
   }
 
-  fn inner_rec_offset(rec_idx: u32, key_len: u8) -> u32 {
-    INNER_NODE_REC_OFFSET + (8 + key_len as u32) * rec_idx as u32 - key_len as u32
+  // Offset of a record's child pointer, which sits between its min key
+  // (the previous record's max key) and its max key
+  fn inner_rec_offset(rec_idx: u32, sep_len: u32) -> u32 {
+    INNER_NODE_REC_OFFSET + (8 + sep_len) * rec_idx as u32
   }
 
-  fn leaf_rec_offset(rec_idx: u32, key_len: u8, val_len: u8) -> u32 {
-    LEAF_NODE_REC_OFFSET + (key_len as u32 + val_len as u32) * rec_idx as u32
+  fn leaf_rec_offset(rec_idx: u32, key_len: u8, val_len: u8, align: u32) -> u32 {
+    Self::align_up(LEAF_NODE_REC_OFFSET, align) + 
+      Self::leaf_rec_stride(key_len, val_len, align) * rec_idx as u32
   }
 
-  fn inner_max_records(node_size: u32, key_len: u8) -> u32 {
-    (node_size - INNER_NODE_REC_OFFSET - 8) / Self::inner_rec_size(key_len)
+  fn inner_max_records(node_size: u32, sep_len: u32) -> u32 {
+    (node_size - INNER_NODE_REC_OFFSET + sep_len) / Self::inner_rec_size(sep_len)
   }
 
-  fn leaf_max_records(node_size: u32, key_len: u8, val_len: u8) -> u32 {
-    (node_size - LEAF_NODE_REC_OFFSET) / Self::leaf_rec_size(key_len, val_len)
+  fn leaf_max_records(node_size: u32, key_len: u8, val_len: u8, align: u32) -> u32 {
+    (node_size - Self::align_up(LEAF_NODE_REC_OFFSET, align)) / 
+      Self::leaf_rec_stride(key_len, val_len, align)
   }
 
-  fn inner_rec_size(key_len: u8) -> u32 {
-    key_len as u32 + 8
+  fn inner_rec_size(sep_len: u32) -> u32 {
+    sep_len + 8
   }
 
   fn leaf_rec_size(key_len: u8, val_len: u8) -> u32 {
     key_len as u32 + val_len as u32
   }
 
-  fn inner_is_full(&mut self) -> Result<bool, Error> {
-    match self.state {
-      State::Inner(ref i) => Ok(
-        Self::inner_max_records(self.node_size, self.key_len) <= i.num_recs
-      ),
-      _ => Err(Error::Assertion(AssertionError::new(ERR_USE_INNER_WHERE_NONE)))
-    }
+  // Distance between the starts of consecutive leaf records, including
+  // any alignment padding
+  fn leaf_rec_stride(key_len: u8, val_len: u8, align: u32) -> u32 {
+    Self::align_up(Self::leaf_rec_size(key_len, val_len), align)
   }
 
-  fn leaf_is_full(&mut self) -> Result<bool, Error> {
-    let l = try!(self.get_leaf_state());
-    Ok(Self::leaf_max_records(self.node_size, self.key_len, self.val_len) <= l.num_recs)
+  fn align_up(n: u32, align: u32) -> u32 {
+    (n + align - 1) & !(align - 1)
   }
 
   fn next_leaf_rec(&mut self) -> Result<Option<LeafRecord>, Error> {
@@ -451,13 +1080,19 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
     match l.cur_rec_idx < l.num_recs {
       false => Ok(None),
       true => {
-        let rec_offset = Self::leaf_rec_offset(l.cur_rec_idx, self.key_len, self.val_len) as u64;
+        let rec_offset = l.ptr + Self::leaf_rec_offset(
+          l.cur_rec_idx, 
+          self.key_len, 
+          self.val_len, 
+          self.leaf_align
+        ) as usize;
 
         let leaf_idx = l.cur_rec_idx;
         let key = try!(self.storage.r_bytes(rec_offset, self.key_len as usize));
-        let val = try!(self.storage.r_bytes(rec_offset + self.key_len as u64, self.key_len as usize));
+        let val = try!(self.storage.r_bytes(rec_offset + self.key_len as usize, self.val_len as usize));
 
         l.cur_rec_idx += 1;
+        self.state = State::Leaf(l);
 
         Ok(Some(LeafRecord {
           leaf_idx: leaf_idx,
@@ -472,21 +1107,23 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
     let mut i = try!(self.get_inner_state());
 
     if i.cur_rec_idx >= i.num_recs { return Ok(None); }
+    let inner_idx = i.cur_rec_idx;
 
-    let rec_offset = Self::inner_rec_offset(i.cur_rec_idx, self.key_len) as u64;
+    let sep_len = self.inner_sep_len() as usize;
+    let rec_offset = i.ptr + Self::inner_rec_offset(i.cur_rec_idx, sep_len as u32) as usize;
 
     let mut min_key: Option<Vec<u8>> = None;
     if i.cur_rec_idx > 0 {
       min_key = Some(try!(self.storage.r_bytes(
-        rec_offset - self.key_len as u64,
+        rec_offset - sep_len,
         self.key_len as usize
       )));
     }
 
-    let ptr = try!(self.storage.r_u64(rec_offset));
+    let ptr = try!(self.r_ptr(rec_offset));
 
     let mut max_key: Option<Vec<u8>> = None;
-    if i.cur_rec_idx < i.num_recs {
+    if i.cur_rec_idx + 1 < i.num_recs {
       max_key = Some(try!(self.storage.r_bytes(
         rec_offset + 8,
         self.key_len as usize
@@ -494,8 +1131,10 @@ impl<T: BinaryStorage + Sized> BPlusTree<T> {
     }
 
     i.cur_rec_idx += 1;
+    self.state = State::Inner(i);
 
     Ok(Some(InnerRecord {
+      inner_idx: inner_idx,
       min_key: min_key,
       ptr: ptr,
       max_key: max_key,
@@ -1,19 +1,65 @@
+use std::fs;
 use std::fs::{ File, OpenOptions };
 use std::path::Path;
+use std::cmp;
 use std::mem;
-use std::io::{ Cursor, Write, Seek, SeekFrom };
+use std::io;
+use std::io::{ Cursor, Read, Write, Seek, SeekFrom };
 use std::str;
+use std::time::Duration;
 
 use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
+use uuid::Uuid;
 
 use storage::util;
 use storage::binary_storage;
-use storage::binary_storage::BinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WriteOutcome, WorkloadHint };
 use storage::file_synced_buffer::FileSyncedBuffer;
 use error::{ Error, AssertionError };
 
 
 pub static ERR_NO_FILE: &'static str = "File has not been opened";
+pub static ERR_FILE_LOCKED: &'static str = 
+  "File is already opened exclusively by another storage instance";
+pub static ERR_WRITE_VERIFY_FAILED: &'static str = 
+  "Data read back from the file does not match the data written";
+pub static ERR_CHECKSUM_NOT_TRACKED: &'static str = 
+  "Checksum tracking is not turned on for this storage";
+pub static ERR_REBUILD_PATH_EXISTS: &'static str = 
+  "A file already exists at the path to rebuild into";
+
+// The tracked checksum is kept as a CRC32 per block of this many bytes, so 
+// a write only has to rehash the blocks it touches
+const CHECKSUM_BLOCK_LEN: usize = 4096;
+
+// How far reads are allowed to go. Capacity allows reading anything that 
+// has been allocated, including space that has never been written. 
+// HighWater stops at the high-water mark, so reading space that hasn't been
+// written since the storage was opened fails with ERR_READ_PAST_END.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ReadBound {
+  Capacity,
+  HighWater
+}
+
+// Byte order of the multi-byte numeric values read and written by the w_* 
+// and r_* methods. Single bytes, bools, strings and raw bytes are 
+// unaffected.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Endianness {
+  Little,
+  Big
+}
+
+pub struct StorageStats {
+  pub capacity: usize,
+  pub high_water_mark: usize,
+  pub cache_hits: u64,
+  pub cache_misses: u64,
+  pub cache_evictions: u64,
+  pub cached_pages: u64,
+  pub dirty_pages: u64
+}
 
 pub struct FileBinaryStorage {
   path: String,
@@ -25,7 +71,25 @@ pub struct FileBinaryStorage {
   is_open: bool,
   initial_capacity: usize,
   capacity: usize,
+  high_water_mark: usize,
+  written: Vec<(usize, usize)>,
   expand_size: usize,
+  open_retries: u32,
+  open_retry_delay: Duration,
+  zero_on_expand: bool,
+  flush_count: u64,
+  expand_count: u64,
+  unflushed: bool,
+  on_unflushed_drop: Option<Box<FnMut()>>,
+  exclusive: bool,
+  has_lock: bool,
+  verify_writes: bool,
+  write_back: bool,
+  warmup_on_open: u64,
+  track_checksum: bool,
+  block_checksums: Vec<u32>,
+  read_bound: ReadBound,
+  endianness: Endianness,
 }
 impl FileBinaryStorage {
 
@@ -53,11 +117,87 @@ impl FileBinaryStorage {
       is_open: false,
       initial_capacity: initial_capacity,
       capacity: 0,
+      high_water_mark: 0,
+      written: Vec::new(),
       expand_size: expand_size,
+      open_retries: 0,
+      open_retry_delay: Duration::from_millis(0),
+      zero_on_expand: false,
+      flush_count: 0,
+      expand_count: 0,
+      unflushed: false,
+      on_unflushed_drop: None,
+      exclusive: false,
+      has_lock: false,
+      verify_writes: false,
+      write_back: false,
+      warmup_on_open: 0,
+      track_checksum: false,
+      block_checksums: Vec::new(),
+      read_bound: ReadBound::Capacity,
+      endianness: Endianness::Little,
     })
   }
 
+  // Like new(), but takes the buffer parameters and expand_size from the 
+  // hint wherever they aren't given
+  pub fn with_hint(
+    path: String,
+    create: bool,
+    initial_capacity: usize,
+    hint: WorkloadHint,
+    buffer_page_size: Option<usize>,
+    buffer_max_pages: Option<u64>,
+    expand_size: Option<usize>
+  ) -> Result<FileBinaryStorage, Error> {
+    FileBinaryStorage::new(
+      path,
+      create,
+      initial_capacity,
+      buffer_page_size.unwrap_or(hint.default_buffer_page_size()),
+      buffer_max_pages.unwrap_or(hint.default_buffer_max_pages()),
+      expand_size.unwrap_or(hint.default_expand_size())
+    )
+  }
+
   fn write<T>(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    try!(self.prepare_write(offset, mem::size_of::<T>()));
+    self.place_bytes(offset, data)
+  }
+
+  // Writes are done in two phases so that the I/O can be swapped out 
+  // without repeating the validation. prepare_write() checks the range and 
+  // expands the storage to hold it, then place_bytes() puts the bytes in 
+  // the file and the cache.
+  pub(crate) fn prepare_write(&mut self, offset: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let end_offset = try!(util::usize_add(offset, len));
+    self.expand(end_offset)
+  }
+
+  pub(crate) fn place_bytes(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let end_offset = try!(util::usize_add(offset, data.len()));
+    try!(AssertionError::assert(
+      end_offset <= self.capacity, 
+      binary_storage::ERR_WRITE_PAST_END
+    ));
+
+    try!(self.write_through(offset, data));
+    self.raise_high_water_mark(end_offset);
+    util::add_range(&mut self.written, offset, end_offset);
+    Ok(())
+  }
+
+  fn read<T: Copy>(&self, offset: usize) -> Result<Vec<u8>, Error> {
     try!(AssertionError::assert(
       self.is_open, 
       binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
@@ -65,36 +205,525 @@ impl FileBinaryStorage {
 
     let end_offset = try!(util::usize_add(offset, mem::size_of::<T>()));
 
-    try!(self.expand(end_offset));
+    try!(AssertionError::assert_not(
+      end_offset > self.read_limit(), 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    let buffer = try!(self.buffer());
+    Ok(try!(buffer.read(offset as u64, mem::size_of::<T>())))
+  }
+
+  // Reads up to capacity regardless of the read bound, for reads the 
+  // storage makes on its own behalf
+  fn read_range(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let end_offset = try!(util::usize_add(offset, len));
+
+    try!(AssertionError::assert_not(
+      end_offset > self.capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    let buffer = try!(self.buffer());
+    let data = try!(buffer.read(offset as u64, len));
+    Ok(data)
+  }
+
+  fn read_limit(&self) -> usize {
+    match self.read_bound {
+      ReadBound::Capacity => self.capacity,
+      ReadBound::HighWater => self.high_water_mark
+    }
+  }
+
+  pub fn get_read_bound(&self) -> ReadBound {
+    self.read_bound
+  }
+
+  pub fn set_read_bound(&mut self, read_bound: ReadBound) {
+    self.read_bound = read_bound;
+  }
+
+  pub fn get_endianness(&self) -> Endianness {
+    self.endianness
+  }
+
+  pub fn set_endianness(&mut self, endianness: Endianness) {
+    self.endianness = endianness;
+  }
+
+  // Values are always encoded little-endian, so for big-endian storage 
+  // each value of the given width just has its bytes reversed
+  fn to_byte_order(&self, bytes: &mut [u8], width: usize) {
+    if self.endianness == Endianness::Big {
+      for value in bytes.chunks_mut(width) { value.reverse(); }
+    }
+  }
+
+  pub fn set_open_retries(&mut self, count: u32, delay: Duration) {
+    self.open_retries = count;
+    self.open_retry_delay = delay;
+  }
+
+  pub fn get_buffer_page_size(&self) -> usize {
+    self.buffer_page_size
+  }
+
+  pub fn get_buffer_max_pages(&self) -> u64 {
+    self.buffer_max_pages
+  }
+
+  pub fn get_warmup_on_open(&self) -> u64 {
+    self.warmup_on_open
+  }
+
+  // Number of pages from the start of the file to load into the buffer 
+  // when the storage is opened (limited by the buffer's max pages)
+  pub fn set_warmup_on_open(&mut self, pages: u64) {
+    self.warmup_on_open = pages;
+  }
+
+  pub fn get_zero_on_expand(&self) -> bool {
+    self.zero_on_expand
+  }
+
+  pub fn set_zero_on_expand(&mut self, zero_on_expand: bool) {
+    self.zero_on_expand = zero_on_expand;
+  }
+
+  pub fn remove_range(&mut self, start: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    try!(AssertionError::assert(len > 0, binary_storage::ERR_WRITE_NOTHING));
+
+    let end = try!(util::usize_add(start, len));
+
+    try!(AssertionError::assert_not(
+      end > self.capacity, 
+      binary_storage::ERR_WRITE_PAST_END
+    ));
+
+    // Shift everything after the removed range down, a page at a time
+    let mut src = end;
+    while src < self.capacity {
+      let chunk_len = cmp::min(self.buffer_page_size, self.capacity - src);
+      let chunk = try!(self.read_range(src, chunk_len));
+      try!(self.write_through(src - len, chunk.as_slice()));
+      src += chunk_len;
+    }
+
+    // Zero out the space vacated at the end
+    let tail_start = self.capacity - len;
+    try!(self.write_through(tail_start, vec![0x0; len].as_slice()));
+
+    if self.high_water_mark >= end {
+      self.high_water_mark -= len;
+    } else if self.high_water_mark > start {
+      self.high_water_mark = start;
+    }
+    util::cut_range(&mut self.written, start, len);
+
+    Ok(())
+  }
+
+  // Copies everything in this storage into a new file at new_path and 
+  // returns that storage open, with its own buffer and expansion settings.
+  // The file format doesn't depend on any of them, so this is only needed 
+  // to lay the data out fresh, e.g. alongside a change of page size. Won't
+  // overwrite an existing file.
+  pub fn rebuild_with(
+    &self,
+    new_path: String,
+    buffer_page_size: usize,
+    buffer_max_pages: u64,
+    expand_size: usize
+  ) -> Result<FileBinaryStorage, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert_not(
+      Path::new(new_path.as_str()).exists(), 
+      ERR_REBUILD_PATH_EXISTS
+    ));
+
+    let mut rebuilt = try!(FileBinaryStorage::new(
+      new_path,
+      true,
+      self.initial_capacity,
+      buffer_page_size,
+      buffer_max_pages,
+      expand_size
+    ));
+    try!(rebuilt.open());
+    try!(rebuilt.expand(self.capacity));
+
+    // Copy a page at a time so the whole file is never held in memory
+    let mut offset = 0;
+    while offset < self.capacity {
+      let len = cmp::min(self.buffer_page_size, self.capacity - offset);
+      let chunk = try!(self.read_range(offset, len));
+      try!(rebuilt.w_bytes(offset, chunk.as_slice()));
+      offset += len;
+    }
+
+    Ok(rebuilt)
+  }
+
+  // Closes the storage without syncing the file to disk. Writes are passed
+  // straight through to the file, so they will have reached the OS, but
+  // unlike close() there is no guarantee they survive a crash. In 
+  // write-back mode, dirty pages are dropped without being written at all.
+  // Intended for scratch stores whose contents are about to be discarded.
+  pub fn abandon(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    self.file = None;
+    self.buffer = None;
+    self.unflushed = false;
+    self.release_lock();
+
+    self.is_open = false;
+    Ok(())
+  }
+
+  // Called if the storage is dropped while open with writes that haven't
+  // been flushed by close()
+  pub fn set_on_unflushed_drop(&mut self, f: Box<FnMut()>) {
+    self.on_unflushed_drop = Some(f);
+  }
+
+  pub fn get_exclusive(&self) -> bool {
+    self.exclusive
+  }
+
+  // When set, open() takes an advisory lock on the path (a lockfile next to
+  // it) and fails with ERR_FILE_LOCKED if another exclusive instance holds 
+  // it. The lock is released on close(), abandon(), or drop. A lockfile 
+  // left behind by a crashed process has to be removed manually.
+  pub fn set_exclusive(&mut self, exclusive: bool) {
+    self.exclusive = exclusive;
+  }
+
+  fn open_files(&mut self) -> Result<(), Error> {
+    let preexisting = Path::new(self.path.as_str()).exists();
+
+    let write_file = try!(util::retry_io(
+      self.open_retries,
+      self.open_retry_delay,
+      || OpenOptions::new()
+        .write(true)
+        .create(self.create)
+        .open(self.path.clone())
+    ));
+
+    if !preexisting && self.create {
+      try!(write_file.set_len(self.initial_capacity as u64));
+      try!(write_file.sync_all());
+    }
+
+    self.capacity = try!(util::u64_as_usize(try!(write_file.metadata()).len()));
+
+    // The buffer writes its dirty pages back through this handle in 
+    // write-back mode, so it's opened for writing as well
+    let read_file = try!(util::retry_io(
+      self.open_retries,
+      self.open_retry_delay,
+      || OpenOptions::new()
+        .read(true)
+        .write(true)
+        .open(self.path.clone())
+    ));
+
+    let mut buffer = FileSyncedBuffer::new(
+      read_file, 
+      self.buffer_page_size, 
+      self.buffer_max_pages 
+    );
+    try!(buffer.set_write_back(self.write_back));
+    try!(buffer.warm_up(self.warmup_on_open));
+
+    self.file = Some(write_file);
+    self.buffer = Some(buffer);
+    self.high_water_mark = 0;
+    self.written = Vec::new();
+    self.unflushed = false;
+
+    if self.track_checksum {
+      self.block_checksums = try!(self.scan_block_checksums());
+    }
+
+    self.is_open = true;
+    Ok(())
+  }
+
+  fn lock_path(&self) -> String {
+    self.path.clone() + ".lock"
+  }
+
+  fn acquire_lock(&mut self) -> Result<(), Error> {
+    match OpenOptions::new().write(true).create_new(true).open(self.lock_path()) {
+      Ok(_) => {
+        self.has_lock = true;
+        Ok(())
+      },
+      Err(ref e) if e.kind() == io::ErrorKind::AlreadyExists => {
+        Err(Error::Assertion(AssertionError::new(ERR_FILE_LOCKED)))
+      },
+      Err(e) => Err(Error::from(e))
+    }
+  }
 
-    {
+  fn release_lock(&mut self) {
+    if !self.has_lock { return }
+    let _ = fs::remove_file(self.lock_path());
+    self.has_lock = false;
+  }
+
+  pub fn get_flush_count(&self) -> u64 {
+    self.flush_count
+  }
+
+  pub fn get_expand_count(&self) -> u64 {
+    self.expand_count
+  }
+
+  fn write_through(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    if self.track_checksum { try!(self.replace_in_checksum(offset, data)); }
+
+    if self.write_back {
+      let mut buffer = try!(self.buffer_mut());
+      try!(buffer.write(offset as u64, data));
+    } else {
       let mut file = try!(self.file());
       try!(file.seek(SeekFrom::Start(offset as u64)));
       try!(file.write(data)); 
     }
+    self.unflushed = true;
+
+    if self.verify_writes { 
+      // The check reads the file, so dirty pages have to get there first
+      if self.write_back { try!(self.flush_buffer()); }
+      try!(self.verify_write(offset, data)) 
+    }
+
+    if !self.write_back {
+      let mut buffer = try!(self.buffer_mut());
+      try!(buffer.update(offset as u64, data));
+    }
+
+    Ok(())
+  }
 
+  // Writes any dirty pages held in write-back mode to the file
+  fn flush_buffer(&mut self) -> Result<(), Error> {
     let mut buffer = try!(self.buffer_mut());
+    buffer.flush()
+  }
 
-    try!(buffer.update(offset as u64, data));
+  // Reads the written bytes back through a new handle on the path, so 
+  // neither the cache nor the write handle is involved.
+  fn verify_write(&self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    let mut file = try!(OpenOptions::new().read(true).open(self.path.clone()));
+    try!(file.seek(SeekFrom::Start(offset as u64)));
+
+    let mut buf = vec![0x0; data.len()];
+    let mut read_len = 0;
+    while read_len < buf.len() {
+      let n = try!(file.read(&mut buf[read_len..]));
+      if n == 0 { break }
+      read_len += n;
+    }
 
+    try!(AssertionError::assert(
+      read_len == data.len() && buf.as_slice() == data, 
+      ERR_WRITE_VERIFY_FAILED
+    ));
     Ok(())
   }
 
-  fn read<T: Copy>(&self, offset: usize) -> Result<Vec<u8>, Error> {
+  pub fn get_verify_writes(&self) -> bool {
+    self.verify_writes
+  }
+
+  // When set, every write is read back from the file and compared with 
+  // what was written, failing with ERR_WRITE_VERIFY_FAILED on a mismatch. 
+  // This costs an extra open and read per write, so it is off by default.
+  pub fn set_verify_writes(&mut self, verify_writes: bool) {
+    self.verify_writes = verify_writes;
+  }
+
+  pub fn get_write_back(&self) -> bool {
+    self.write_back
+  }
+
+  // When set, writes only go into the buffer's cached pages, which reach 
+  // the file on sync() or close(). Needs buffer_max_pages above 0; with no
+  // cache, writes still go straight to the file. Turning it off while open
+  // flushes the dirty pages.
+  pub fn set_write_back(&mut self, write_back: bool) -> Result<(), Error> {
+    if self.is_open {
+      let mut buffer = try!(self.buffer_mut());
+      try!(buffer.set_write_back(write_back));
+    }
+    self.write_back = write_back;
+    Ok(())
+  }
+
+  pub fn get_track_checksum(&self) -> bool {
+    self.track_checksum
+  }
+
+  // When set, a checksum of the whole file is kept up to date on every 
+  // write, so current_checksum() doesn't have to rescan the file. Each 
+  // write reads and rehashes the blocks it touches, and turning this on (or
+  // opening with it on) scans the whole file once, so it is off by default.
+  pub fn set_track_checksum(&mut self, track_checksum: bool) -> Result<(), Error> {
+    if track_checksum && !self.track_checksum && self.is_open {
+      self.block_checksums = try!(self.scan_block_checksums());
+    }
+    self.track_checksum = track_checksum;
+    Ok(())
+  }
+
+  // The tracked checksum of the whole file, as of the last write
+  pub fn current_checksum(&self) -> Result<u32, Error> {
     try!(AssertionError::assert(
       self.is_open, 
       binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
     ));
+    try!(AssertionError::assert(self.track_checksum, ERR_CHECKSUM_NOT_TRACKED));
+    FileBinaryStorage::combine_checksums(self.block_checksums.as_slice())
+  }
 
-    let end_offset = try!(util::usize_add(offset, mem::size_of::<T>()));
+  // Recomputes the checksum of the whole file from scratch, whether or not
+  // it is being tracked
+  pub fn compute_checksum(&self) -> Result<u32, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    self.scan_checksum()
+  }
 
-    try!(AssertionError::assert_not(
-      end_offset > self.capacity, 
-      binary_storage::ERR_READ_PAST_END
+  fn scan_checksum(&self) -> Result<u32, Error> {
+    let block_checksums = try!(self.scan_block_checksums());
+    FileBinaryStorage::combine_checksums(block_checksums.as_slice())
+  }
+
+  fn scan_block_checksums(&self) -> Result<Vec<u32>, Error> {
+    let buffer = try!(self.buffer());
+    let mut block_checksums = Vec::new();
+    let mut offset = 0;
+    while offset < self.capacity {
+      let len = cmp::min(CHECKSUM_BLOCK_LEN, self.capacity - offset);
+      let data = try!(buffer.read(offset as u64, len));
+      block_checksums.push(FileBinaryStorage::block_checksum(data.as_slice()));
+      offset += len;
+    }
+    Ok(block_checksums)
+  }
+
+  // Rehashes the blocks that data is about to be written over at offset 
+  // in the tracked checksum. Must be called before the write.
+  fn replace_in_checksum(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    if data.len() == 0 { return Ok(()) }
+    let end = try!(util::usize_add(offset, data.len()));
+    let first_block = offset / CHECKSUM_BLOCK_LEN;
+    let last_block = (end - 1) / CHECKSUM_BLOCK_LEN;
+
+    for block in first_block..(last_block + 1) {
+      let block_start = block * CHECKSUM_BLOCK_LEN;
+      let block_end = cmp::min(block_start + CHECKSUM_BLOCK_LEN, self.capacity);
+      let mut bytes = if block_start < block_end {
+        try!(try!(self.buffer()).read(block_start as u64, block_end - block_start))
+      } else {
+        Vec::new()
+      };
+
+      let write_start = cmp::max(offset, block_start);
+      let write_end = cmp::min(end, block_start + CHECKSUM_BLOCK_LEN);
+      if bytes.len() < write_end - block_start { bytes.resize(write_end - block_start, 0x0) }
+      bytes[(write_start - block_start)..(write_end - block_start)]
+        .copy_from_slice(&data[(write_start - offset)..(write_end - offset)]);
+
+      if self.block_checksums.len() <= block { self.block_checksums.resize(block + 1, 0) }
+      self.block_checksums[block] = FileBinaryStorage::block_checksum(bytes.as_slice());
+    }
+    Ok(())
+  }
+
+  // The CRC32 of a block, as if it were padded out to CHECKSUM_BLOCK_LEN 
+  // with zeros. A block of nothing but zeros counts as 0, so growing or 
+  // shrinking the file by zeros doesn't change the checksum.
+  fn block_checksum(data: &[u8]) -> u32 {
+    if data.iter().all(|b| *b == 0x0) { return 0 }
+    let mut block = data.to_vec();
+    block.resize(CHECKSUM_BLOCK_LEN, 0x0);
+    util::crc32(block.as_slice())
+  }
+
+  // The CRC32 of the block checksums in order, leaving out trailing zero 
+  // blocks. A file of nothing but zeros has a checksum of 0.
+  fn combine_checksums(block_checksums: &[u32]) -> Result<u32, Error> {
+    let len = match block_checksums.iter().rposition(|c| *c != 0) {
+      Some(idx) => idx + 1,
+      None => return Ok(0)
+    };
+    let mut bytes = Vec::with_capacity(len * 4);
+    for c in &block_checksums[..len] {
+      try!(bytes.write_u32::<LittleEndian>(*c));
+    }
+    Ok(util::crc32(bytes.as_slice()))
+  }
+
+  fn raise_high_water_mark(&mut self, end_offset: usize) {
+    if end_offset > self.high_water_mark { self.high_water_mark = end_offset }
+  }
+
+  pub fn get_high_water_mark(&self) -> Result<usize, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
     ));
+    Ok(self.high_water_mark)
+  }
 
+  // Like read_all(), but stops at the high-water mark whatever the read
+  // bound, so space that hasn't been written since opening is left out
+  pub fn read_written(&self) -> Result<Vec<u8>, Error> {
+    let high_water_mark = try!(self.get_high_water_mark());
+    if high_water_mark == 0 { return Ok(Vec::new()) }
     let buffer = try!(self.buffer());
-    Ok(try!(buffer.read(offset as u64, mem::size_of::<T>())))
+    Ok(try!(buffer.read(0, high_water_mark)))
+  }
+
+  pub fn stats(&self) -> Result<StorageStats, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    let buffer = try!(self.buffer());
+    Ok(StorageStats {
+      capacity: self.capacity,
+      high_water_mark: self.high_water_mark,
+      cache_hits: buffer.get_cache_hits(),
+      cache_misses: buffer.get_cache_misses(),
+      cache_evictions: buffer.get_cache_evictions(),
+      cached_pages: buffer.get_num_current_pages(),
+      dirty_pages: buffer.get_num_dirty_pages()
+    })
   }
 
   fn file(&self) -> Result<&File, AssertionError> {
@@ -156,39 +785,15 @@ impl BinaryStorage for FileBinaryStorage {
         binary_storage::ERR_OPERATION_INVALID_WHEN_OPEN
       ));
 
-      let preexisting = Path::new(self.path.as_str()).exists();
+      if self.exclusive { try!(self.acquire_lock()) }
 
-      let write_file = try!(
-        OpenOptions::new()
-          .write(true)
-          .create(self.create)
-          .open(self.path.clone())
-      );
-
-      if !preexisting && self.create {
-        try!(write_file.set_len(self.initial_capacity as u64));
-        try!(write_file.sync_all());
+      match self.open_files() {
+        Ok(()) => Ok(()),
+        Err(e) => {
+          self.release_lock();
+          Err(e)
+        }
       }
-
-      self.capacity = try!(util::u64_as_usize(try!(write_file.metadata()).len()));
-
-      let read_file = try!(
-        OpenOptions::new()
-          .read(true)
-          .open(self.path.clone())
-      );
-
-      let buffer = FileSyncedBuffer::new(
-        read_file, 
-        self.buffer_page_size, 
-        self.buffer_max_pages 
-      );
-
-      self.file = Some(write_file);
-      self.buffer = Some(buffer);
-
-      self.is_open = true;
-      Ok(())
     }
 
     fn close(&mut self) -> Result<(), Error> {
@@ -197,32 +802,45 @@ impl BinaryStorage for FileBinaryStorage {
         binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
       ));
 
+      // Sync everything written so far before letting go of the file, so
+      // that a successful close() means the data is durable
+      try!(self.flush_buffer());
+      try!(try!(self.file()).sync_all());
+      self.flush_count += 1;
+      self.unflushed = false;
+
       self.file = None;
       self.buffer = None;
+      self.release_lock();
 
       self.is_open = false;
       Ok(())
     }
 
     fn w_i8(&mut self, offset: usize, data: i8) -> Result<(), Error> { 
-      self.write::<i8>(offset, vec!(data as u8).as_slice())
+      let mut buf = vec![];
+      try!(buf.write_i8(data));
+      self.write::<i8>(offset, buf.as_slice())
     }
 
     fn w_i16(&mut self, offset: usize, data: i16) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_i16::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<i16>());
       self.write::<i16>(offset, buf.as_slice())
     }
 
     fn w_i32(&mut self, offset: usize, data: i32) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_i32::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<i32>());
       self.write::<i32>(offset, buf.as_slice())
     }
 
     fn w_i64(&mut self, offset: usize, data: i64) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_i64::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<i64>());
       self.write::<i64>(offset, buf.as_slice())
     }
 
@@ -233,30 +851,45 @@ impl BinaryStorage for FileBinaryStorage {
     fn w_u16(&mut self, offset: usize, data: u16) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_u16::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<u16>());
       self.write::<u16>(offset, buf.as_slice())
     }
 
     fn w_u32(&mut self, offset: usize, data: u32) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_u32::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<u32>());
       self.write::<u32>(offset, buf.as_slice())
     }
 
     fn w_u64(&mut self, offset: usize, data: u64) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_u64::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<u64>());
       self.write::<u64>(offset, buf.as_slice())
     }
 
+    fn w_i128(&mut self, offset: usize, data: i128) -> Result<(), Error> { 
+      self.w_u128(offset, data as u128)
+    }
+
+    fn w_u128(&mut self, offset: usize, data: u128) -> Result<(), Error> { 
+      let mut buf = util::u128_to_le_bytes(data);
+      self.to_byte_order(&mut buf, mem::size_of::<u128>());
+      self.write::<u128>(offset, &buf)
+    }
+
     fn w_f32(&mut self, offset: usize, data: f32) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_f32::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<f32>());
       self.write::<f32>(offset, buf.as_slice())
     }
 
     fn w_f64(&mut self, offset: usize, data: f64) -> Result<(), Error> { 
       let mut buf = vec![];
       try!(buf.write_f64::<LittleEndian>(data));
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<f64>());
       self.write::<f64>(offset, buf.as_slice())
     }
 
@@ -265,50 +898,91 @@ impl BinaryStorage for FileBinaryStorage {
     }
 
     fn w_bytes(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
-      try!(AssertionError::assert(
-        self.is_open, 
-        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
-      ));
+      try!(self.prepare_write(offset, data.len()));
+      self.place_bytes(offset, data)
+    }
 
-      let end_offset = try!(util::usize_add(offset, data.len()));
+    fn w_str(&mut self, offset: usize, data: &str) -> Result<(), Error> { 
+      self.w_bytes(offset, data.as_bytes()) 
+    }
 
-      try!(self.expand(end_offset));
+    fn w_bytes_reporting(
+      &mut self, 
+      offset: usize, 
+      data: &[u8]
+    ) -> Result<WriteOutcome, Error> {
+      let old_capacity = try!(self.get_capacity());
+      try!(self.w_bytes(offset, data));
+      Ok(WriteOutcome {
+        bytes_written: data.len(),
+        expanded: self.capacity > old_capacity,
+        capacity: self.capacity
+      })
+    }
 
-      {
-        let mut file = try!(self.file());
-        try!(file.seek(SeekFrom::Start(offset as u64)));
-        try!(file.write(data)); 
-      }
+    fn w_uuid(&mut self, offset: usize, data: Uuid) -> Result<(), Error> {
+      self.w_bytes(offset, data.as_bytes())
+    }
 
-      let mut buffer = try!(self.buffer_mut());
-      try!(buffer.update(offset as u64, data));
+    fn w_blob(&mut self, offset: usize, data: &[u8]) -> Result<usize, Error> {
+      try!(AssertionError::assert(
+        data.len() <= u32::max_value() as usize, 
+        binary_storage::ERR_BLOB_TOO_LONG
+      ));
+      let data_offset = try!(util::usize_add(offset, mem::size_of::<u32>()));
+      try!(self.w_u32(offset, data.len() as u32));
+      if data.len() > 0 { try!(self.w_bytes(data_offset, data)); }
+      Ok(mem::size_of::<u32>() + data.len())
+    }
 
-      Ok(())
+    fn w_u32_array(&mut self, offset: usize, values: &[u32]) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      if values.len() == 0 { return Ok(()) }
+      let mut buf = Vec::with_capacity(try!(util::usize_mul(values.len(), 4)));
+      for v in values { try!(buf.write_u32::<LittleEndian>(*v)) }
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<u32>());
+      self.w_bytes(offset, buf.as_slice())
     }
 
-    fn w_str(&mut self, offset: usize, data: &str) -> Result<(), Error> { 
-      self.w_bytes(offset, data.as_bytes()) 
+    fn w_u64_array(&mut self, offset: usize, values: &[u64]) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      if values.len() == 0 { return Ok(()) }
+      let mut buf = Vec::with_capacity(try!(util::usize_mul(values.len(), 8)));
+      for v in values { try!(buf.write_u64::<LittleEndian>(*v)) }
+      self.to_byte_order(buf.as_mut_slice(), mem::size_of::<u64>());
+      self.w_bytes(offset, buf.as_slice())
     }
 
 
     fn r_i8(&self, offset: usize) -> Result<i8, Error> { 
-      Ok(*(try!(self.read::<i8>(offset)).first().unwrap()) as i8)
+      let data = try!(self.read::<i8>(offset));
+      let mut rdr = Cursor::new(data);
+      Ok(try!(rdr.read_i8()))
     }
 
     fn r_i16(&self, offset: usize) -> Result<i16, Error> { 
-      let data = try!(self.read::<i16>(offset));
+      let mut data = try!(self.read::<i16>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<i16>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_i16::<LittleEndian>()))
     }
 
     fn r_i32(&self, offset: usize) -> Result<i32, Error> { 
-      let data = try!(self.read::<i32>(offset));
+      let mut data = try!(self.read::<i32>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<i32>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_i32::<LittleEndian>()))
     }
 
     fn r_i64(&self, offset: usize) -> Result<i64, Error> { 
-      let data = try!(self.read::<i64>(offset));
+      let mut data = try!(self.read::<i64>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<i64>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_i64::<LittleEndian>()))
     }
@@ -318,31 +992,86 @@ impl BinaryStorage for FileBinaryStorage {
     }
 
     fn r_u16(&self, offset: usize) -> Result<u16, Error> { 
-      let data = try!(self.read::<u16>(offset));
+      let mut data = try!(self.read::<u16>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u16>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_u16::<LittleEndian>()))
     }
 
     fn r_u32(&self, offset: usize) -> Result<u32, Error> { 
-      let data = try!(self.read::<u32>(offset));
+      let mut data = try!(self.read::<u32>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u32>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_u32::<LittleEndian>()))
     }
 
     fn r_u64(&self, offset: usize) -> Result<u64, Error> { 
-      let data = try!(self.read::<u64>(offset));
+      let mut data = try!(self.read::<u64>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u64>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_u64::<LittleEndian>()))
     }
 
+    fn r_i128(&self, offset: usize) -> Result<i128, Error> { 
+      Ok(try!(self.r_u128(offset)) as i128)
+    }
+
+    fn r_u128(&self, offset: usize) -> Result<u128, Error> { 
+      let mut data = try!(self.read::<u128>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u128>());
+      Ok(util::u128_from_le_bytes(data.as_slice()))
+    }
+
+    fn r_u32_array(&self, offset: usize, count: usize) -> Result<Vec<u32>, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      let len = try!(util::usize_mul(count, mem::size_of::<u32>()));
+      if len == 0 { return Ok(Vec::new()) }
+      let mut data = try!(self.r_bytes(offset, len));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u32>());
+      let mut rdr = Cursor::new(data);
+      let mut res = Vec::with_capacity(count);
+      for _ in 0..count { res.push(try!(rdr.read_u32::<LittleEndian>())) }
+      Ok(res)
+    }
+
+    fn r_u64_array(&self, offset: usize, count: usize) -> Result<Vec<u64>, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      let len = try!(util::usize_mul(count, mem::size_of::<u64>()));
+      if len == 0 { return Ok(Vec::new()) }
+      let mut data = try!(self.r_bytes(offset, len));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<u64>());
+      let mut rdr = Cursor::new(data);
+      let mut res = Vec::with_capacity(count);
+      for _ in 0..count { res.push(try!(rdr.read_u64::<LittleEndian>())) }
+      Ok(res)
+    }
+
+    fn r_u64_opt(&self, offset: usize) -> Result<Option<u64>, Error> {
+      let end_offset = try!(util::usize_add(offset, mem::size_of::<u64>()));
+      if !util::overlaps_range(self.written.as_slice(), offset, end_offset) &&
+        try!(self.is_filled(Some(offset), Some(end_offset), 0x0)) {
+        Ok(None)
+      } else {
+        Ok(Some(try!(self.r_u64(offset))))
+      }
+    }
+
     fn r_f32(&self, offset: usize) -> Result<f32, Error> { 
-      let data = try!(self.read::<f32>(offset));
+      let mut data = try!(self.read::<f32>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<f32>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_f32::<LittleEndian>()))
     }
 
     fn r_f64(&self, offset: usize) -> Result<f64, Error> { 
-      let data = try!(self.read::<f64>(offset));
+      let mut data = try!(self.read::<f64>(offset));
+      self.to_byte_order(data.as_mut_slice(), mem::size_of::<f64>());
       let mut rdr = Cursor::new(data);
       Ok(try!(rdr.read_f64::<LittleEndian>()))
     }
@@ -356,21 +1085,27 @@ impl BinaryStorage for FileBinaryStorage {
     }
 
     fn r_bytes(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+      let mut data = vec![0; len];
+      let read_len = try!(self.r_bytes_into(offset, data.as_mut_slice()));
+      data.truncate(read_len);
+      Ok(data)
+    }
+
+    fn r_bytes_into(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
       try!(AssertionError::assert(
         self.is_open, 
         binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
       ));
 
-      let end_offset = try!(util::usize_add(offset, len));
+      let end_offset = try!(util::usize_add(offset, buf.len()));
 
       try!(AssertionError::assert_not(
-        end_offset > self.capacity, 
+        end_offset > self.read_limit(), 
         binary_storage::ERR_READ_PAST_END
       ));
 
       let buffer = try!(self.buffer());
-      let data = try!(buffer.read(offset as u64, len));
-      Ok(data)
+      Ok(try!(buffer.read_into(offset as u64, buf)))
     }
 
     fn r_str(&self, offset: usize, len: usize) -> Result<String, Error> {
@@ -378,6 +1113,55 @@ impl BinaryStorage for FileBinaryStorage {
       Ok(try!(str::from_utf8(b.as_slice())).to_string())
     }
 
+    fn r_uuid(&self, offset: usize) -> Result<Uuid, Error> {
+      let b = try!(self.r_bytes(offset, mem::size_of::<Uuid>()));
+      // r_bytes() returns exactly the requested length, so this can't fail
+      Ok(Uuid::from_bytes(b.as_slice()).unwrap())
+    }
+
+    fn r_blob(&self, offset: usize) -> Result<Vec<u8>, Error> {
+      let len = try!(self.r_u32(offset)) as usize;
+      let data_offset = try!(util::usize_add(offset, mem::size_of::<u32>()));
+      if len == 0 { return Ok(Vec::new()) }
+      self.r_bytes(data_offset, len)
+    }
+
+    fn read_all(&self) -> Result<Vec<u8>, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      let limit = self.read_limit();
+      if limit == 0 { return Ok(Vec::new()) }
+      self.r_bytes(0, limit)
+    }
+
+    fn pread(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+      self.r_bytes(offset, len)
+    }
+
+    fn pwrite(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+      self.w_bytes(offset, data)
+    }
+
+    fn valid_len_from(&self, offset: usize) -> Result<usize, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+
+      try!(AssertionError::assert(
+        offset < self.capacity, 
+        binary_storage::ERR_READ_PAST_END
+      ));
+
+      let data = try!(self.read_range(offset, self.capacity - offset));
+      match data.iter().rposition(|b| *b != 0x0) {
+        Some(i) => Ok(i + 1),
+        None => Ok(0)
+      }
+    }
+
 
     fn fill(
       &mut self, 
@@ -416,14 +1200,9 @@ impl BinaryStorage for FileBinaryStorage {
       let len = end_offset - start_offset;
       let buf = vec![val; len];
 
-      {
-        let mut file = try!(self.file());
-        try!(file.seek(SeekFrom::Start(start_offset as u64)));
-        try!(file.write(buf.as_slice())); 
-      }
-
-      let mut buffer = try!(self.buffer_mut());
-      try!(buffer.update(start_offset as u64, buf.as_slice()));
+      try!(self.write_through(start_offset, buf.as_slice()));
+      self.raise_high_water_mark(end_offset);
+      util::add_range(&mut self.written, start_offset, end_offset);
 
       Ok(())
     }
@@ -475,6 +1254,94 @@ impl BinaryStorage for FileBinaryStorage {
       Ok(true)
     }
 
+    fn crc32(&self, start: Option<usize>, end: Option<usize>) -> Result<u32, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+
+      let start_offset = match start {
+        Some(s) => s,
+        None => 0
+      };
+      let end_offset = match end {
+        Some(e) => e,
+        None => self.capacity
+      };
+
+      try!(AssertionError::assert(
+        start_offset < self.capacity, 
+        binary_storage::ERR_READ_PAST_END
+      ));
+
+      try!(AssertionError::assert(
+        end_offset <= self.capacity,
+        binary_storage::ERR_READ_PAST_END
+      ));
+
+      try!(AssertionError::assert(
+        end_offset > start_offset,
+        binary_storage::ERR_READ_NOTHING
+      ));
+
+      let data = try!(self.read_range(start_offset, end_offset - start_offset));
+      Ok(util::crc32(data.as_slice()))
+    }
+
+    // Scans a page at a time. Each read overlaps the one before by one byte 
+    // less than the pattern, so matches that straddle pages are still found.
+    fn find_bytes(&self, pattern: &[u8], start: usize) -> Result<Option<usize>, Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      try!(binary_storage::check_find_bytes(pattern, start, self.capacity));
+
+      let overlap = pattern.len() - 1;
+      let step = cmp::max(self.buffer_page_size, 1);
+      let mut offset = start;
+
+      while offset + pattern.len() <= self.capacity {
+        let len = cmp::min(step + overlap, self.capacity - offset);
+        let data = try!(self.read_range(offset, len));
+        match binary_storage::find_in(data.as_slice(), pattern) {
+          Some(pos) => return Ok(Some(offset + pos)),
+          None => offset += step
+        }
+      }
+
+      Ok(None)
+    }
+
+    fn copy(&mut self, from: usize, to: usize, len: usize) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      try!(binary_storage::check_copy(from, to, len, self.capacity));
+
+      // The whole source is read before anything is written, so overlapping
+      // ranges copy correctly
+      let data = try!(self.read_range(from, len));
+      try!(self.prepare_write(to, len));
+      self.place_bytes(to, data.as_slice())
+    }
+
+    // Files can't swap in place, so both ranges are read into memory and 
+    // written back to each other's offsets
+    fn swap_ranges(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      try!(binary_storage::check_swap_ranges(a, b, len, self.capacity));
+
+      let a_data = try!(self.read_range(a, len));
+      let b_data = try!(self.read_range(b, len));
+      try!(self.place_bytes(a, b_data.as_slice()));
+      self.place_bytes(b, a_data.as_slice())
+    }
+
     fn get_expand_size(&self) -> usize {
       self.expand_size
     }
@@ -511,7 +1378,8 @@ impl BinaryStorage for FileBinaryStorage {
       // already have enough room.
       if new_capacity <= self.capacity { return Ok(()) }
 
-      // Allocate more disk space
+      // Allocate more disk space in a single step, however many increments
+      // of expand_size are needed
       {
         let file = try!(self.file());
         match file.set_len(new_capacity as u64) {
@@ -524,12 +1392,78 @@ impl BinaryStorage for FileBinaryStorage {
         };
       }
 
+      // Make sure the cache knows about the new space
+      {
+        let old_capacity = self.capacity;
+        let mut buffer = try!(self.buffer_mut());
+        try!(buffer.extend(old_capacity, new_capacity));
+      }
+
+      // Explicitly zero the new space if requested, since some filesystems
+      // don't guarantee that extended regions read back as zeros
+      if self.zero_on_expand {
+        let old_capacity = self.capacity;
+        let buf = vec![0x0; new_capacity - old_capacity];
+        try!(self.write_through(old_capacity, buf.as_slice()));
+      }
+
       // Set the new capacity 
       self.capacity = new_capacity;
+      self.expand_count += 1;
       // Return Ok to indicate that allocation was successful
       Ok(())
     }
 
+    fn truncate(&mut self, new_capacity: usize) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      try!(AssertionError::assert(
+        new_capacity <= self.capacity, 
+        binary_storage::ERR_TRUNCATE_PAST_END
+      ));
+      self.shrink(new_capacity)
+    }
+  
+    fn shrink(&mut self, max_capacity: usize) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+
+      let shrink_increments = cmp::max(
+        (try!(util::usize_add(max_capacity, self.expand_size)) - 1) / self.expand_size,
+        1
+      );
+      let new_capacity = shrink_increments * self.expand_size;
+
+      if new_capacity >= self.capacity { return Ok(()) }
+
+      // Drop the truncated bytes from the checksum
+      if self.track_checksum {
+        let zeros = vec![0x0; self.capacity - new_capacity];
+        try!(self.replace_in_checksum(new_capacity, zeros.as_slice()));
+      }
+
+      {
+        let file = try!(self.file());
+        try!(file.set_len(new_capacity as u64));
+      }
+      self.unflushed = true;
+
+      {
+        let mut buffer = try!(self.buffer_mut());
+        buffer.truncate(new_capacity);
+      }
+
+      let old_capacity = self.capacity;
+      self.capacity = new_capacity;
+      if self.high_water_mark > new_capacity { self.high_water_mark = new_capacity }
+      util::cut_range(&mut self.written, new_capacity, old_capacity - new_capacity);
+      Ok(())
+    }
+
     fn get_capacity(&self) -> Result<usize, Error> {
       try!(AssertionError::assert(
         self.is_open, 
@@ -538,9 +1472,42 @@ impl BinaryStorage for FileBinaryStorage {
       Ok(self.capacity)
     }
 
+    fn sync(&mut self) -> Result<(), Error> {
+      try!(AssertionError::assert(
+        self.is_open, 
+        binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+      ));
+      try!(self.flush_buffer());
+      try!(try!(self.file()).sync_all());
+      self.flush_count += 1;
+      self.unflushed = false;
+      Ok(())
+    }
+
     fn is_open(&self) -> bool {
         self.is_open
     }
 
 }
+impl Drop for FileBinaryStorage {
+
+  fn drop(&mut self) {
+    self.release_lock();
+
+    if !self.is_open || !self.unflushed { return }
 
+    if cfg!(all(debug_assertions, feature = "drop-warnings")) {
+      let _ = writeln!(
+        io::stderr(), 
+        "warning: FileBinaryStorage for {} dropped with unflushed writes", 
+        self.path
+      );
+    }
+
+    match self.on_unflushed_drop {
+      Some(ref mut f) => f(),
+      None => ()
+    };
+  }
+
+}
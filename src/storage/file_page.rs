@@ -81,6 +81,16 @@ impl FilePage {
     dst
   }
 
+  // Borrows the bytes in place, or None if the range runs past the data 
+  // held by the page
+  pub fn read_ref(&self, offset: usize, len: usize) -> Option<&[u8]> {
+    match offset.checked_add(len) {
+      Some(end_offset) if end_offset <= self.actual_size => 
+        Some(unsafe { slice::from_raw_parts(self.ptr(offset), len) }),
+      _ => None
+    }
+  }
+
   pub fn truncate(&mut self, len: usize) {
     if len >= self.actual_size { return }
     if len >= self.max_size { return }
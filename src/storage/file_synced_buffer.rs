@@ -1,23 +1,44 @@
 extern crate alloc;
 extern crate core;
 
-use std::cell::RefCell;
+use std::cmp;
+use std::cell::{ Cell, RefCell };
 use std::fs::File;
-use std::io::{Read, Seek, SeekFrom};
+use std::io::{Read, Write, Seek, SeekFrom};
 use std::iter::FromIterator;
-use std::collections::{ HashMap, VecDeque };
+use std::collections::{ BTreeSet, HashMap, VecDeque };
 
-use error::{ Error };
+use error::{ Error, AssertionError };
 use storage::util;
 use storage::file_page::FilePage;
 
+pub static ERR_PAGE_SIZE_TOO_SMALL: & 'static str = 
+  "Page size must be greater than zero";
+pub static ERR_MAX_PAGES_NOT_POWER_OF_2: & 'static str = 
+  "Maximum number of pages must be a power of 2";
+pub static ERR_MAX_PAGES_TOO_BIG: & 'static str = 
+  "Maximum number of pages cannot be more than 1048576";
+
+pub const MAX_PAGES_LIMIT: u64 = 1 << 20;
 
 pub struct FileSyncedBuffer {
   file: RefCell<File>,
   page_size: usize,
   max_pages: u64,
   pages: RefCell<HashMap<u64, FilePage>>,
-  page_insertions: RefCell<VecDeque<u64>>
+  page_insertions: RefCell<VecDeque<u64>>,
+  cache_hits: Cell<u64>,
+  cache_misses: Cell<u64>,
+  cache_evictions: Cell<u64>,
+  small_read_direct: usize,
+  auto_tune: bool,
+  last_read_end: Cell<Option<u64>>,
+  sequential_reads: Cell<u64>,
+  read_ahead_pages: Cell<u64>,
+  disk_reads: Cell<u64>,
+  write_back: bool,
+  dirty: RefCell<BTreeSet<u64>>,
+  disk_writes: Cell<u64>
 }
 impl FileSyncedBuffer {
 
@@ -31,11 +52,51 @@ impl FileSyncedBuffer {
       page_size: page_size,
       max_pages: max_pages,
       pages: RefCell::new(HashMap::new()),
-      page_insertions: RefCell::new(VecDeque::new())
+      page_insertions: RefCell::new(VecDeque::new()),
+      cache_hits: Cell::new(0),
+      cache_misses: Cell::new(0),
+      cache_evictions: Cell::new(0),
+      small_read_direct: 0,
+      auto_tune: false,
+      last_read_end: Cell::new(None),
+      sequential_reads: Cell::new(0),
+      read_ahead_pages: Cell::new(1),
+      disk_reads: Cell::new(0),
+      write_back: false,
+      dirty: RefCell::new(BTreeSet::new()),
+      disk_writes: Cell::new(0)
     }
   }
 
 
+  // Same as new(), but rejects configurations the buffer doesn't handle 
+  // well: page_size must be non-zero and max_pages must be a power of 2 no 
+  // bigger than MAX_PAGES_LIMIT
+  pub fn new_checked(
+    file: File,
+    page_size: usize,
+    max_pages: u64,
+  ) -> Result<FileSyncedBuffer, Error> {
+    try!(FileSyncedBuffer::check_params(page_size, max_pages));
+    Ok(FileSyncedBuffer::new(file, page_size, max_pages))
+  }
+
+  fn check_params(page_size: usize, max_pages: u64) -> Result<(), Error> {
+    // Page size must be greater than zero
+    try!(AssertionError::assert(page_size > 0, ERR_PAGE_SIZE_TOO_SMALL));
+    // Max pages must be a power of 2
+    try!(AssertionError::assert(
+      max_pages.is_power_of_two(), 
+      ERR_MAX_PAGES_NOT_POWER_OF_2
+    ));
+    // Max pages can't exceed the limit
+    try!(AssertionError::assert(
+      max_pages <= MAX_PAGES_LIMIT, 
+      ERR_MAX_PAGES_TOO_BIG
+    ));
+    Ok(())
+  }
+
   fn calc_page_range(&self, offset: u64, length: u64) -> (u64, u64) {
     let page_size = self.page_size as u64;
     let start = offset / page_size; 
@@ -75,16 +136,22 @@ impl FileSyncedBuffer {
     Ok((start_offset_in_page, (end_offset_in_page - start_offset_in_page)))
   }
 
+  // Dirty pages are never evicted, since their data isn't in the file yet. 
+  // If every page is dirty, nothing is removed and the cache stays over 
+  // max_pages until the next flush().
   fn remove_oldest_page(&self) {
-    if self.page_insertions.borrow().len() == 0 { return }
-    match self.page_insertions.borrow_mut().pop_front() {
-      Some(i) => { 
+    let pos = {
+      let dirty = self.dirty.borrow();
+      self.page_insertions.borrow().iter().position(|i| !dirty.contains(i))
+    };
+    match pos {
+      Some(pos) => {
+        let i = self.page_insertions.borrow_mut().remove(pos).unwrap();
         self.pages.borrow_mut().remove(&i); 
-        return;
+        self.cache_evictions.set(self.cache_evictions.get() + 1);
       },
-      _ => ()
+      None => ()
     };
-    self.remove_oldest_page();
   }
 
   fn remove_oldest_pages(&self, room_for: u64) {
@@ -96,6 +163,7 @@ impl FileSyncedBuffer {
   }
 
   fn remove_page(&mut self, index: u64) {
+    if self.dirty.borrow().contains(&index) { return }
     self.pages.borrow_mut().remove(&index);
 
     let mut ins = self.page_insertions.borrow_mut();
@@ -121,10 +189,19 @@ impl FileSyncedBuffer {
   ) -> Result<Vec<u8>, Error> {
 
     match self.pages.borrow().get(&index) {
-      Some(p) => return Ok(p.read(start, len)),
+      Some(p) => {
+        self.cache_hits.set(self.cache_hits.get() + 1);
+        return Ok(p.read(start, len));
+      },
       None => ()
     };
 
+    self.cache_misses.set(self.cache_misses.get() + 1);
+
+    let read_ahead = if self.auto_tune { self.read_ahead_pages.get() } else { 1 };
+
+    if read_ahead > 1 { return self.read_ahead_from_page(index, start, len, read_ahead) }
+
     let seek_pos = index * self.page_size as u64;
 
     try!(self.file.borrow_mut().seek(SeekFrom::Start(seek_pos)));
@@ -133,6 +210,7 @@ impl FileSyncedBuffer {
     
     let read_len = try!(self.file.borrow_mut().read(buf.as_mut_slice()));
     buf.truncate(read_len);
+    self.disk_reads.set(self.disk_reads.get() + 1);
 
     let mut page = FilePage::new(self.page_size).unwrap();
     page.write(0, buf.as_slice());
@@ -143,28 +221,190 @@ impl FileSyncedBuffer {
     Ok(data)
   }
 
+  // Loads the requested page along with the pages that follow it in a 
+  // single read from the file. Following pages are only cached if they are
+  // complete and not already cached.
+  fn read_ahead_from_page(
+    &self, 
+    index: u64, 
+    start: usize, 
+    len: usize,
+    num_pages: u64
+  ) -> Result<Vec<u8>, Error> {
+    let page_size = self.page_size;
+    let total_len = try!(util::u64_as_usize(num_pages * page_size as u64));
+
+    let buf = try!(self.read_direct(index * page_size as u64, total_len));
+
+    let mut data = Vec::new();
+    for (i, chunk) in buf.chunks(page_size).enumerate() {
+      let page_index = index + i as u64;
+      if i > 0 && 
+        (chunk.len() < page_size || self.pages.borrow().contains_key(&page_index)) {
+        continue;
+      }
+      let mut page = FilePage::new(page_size).unwrap();
+      page.write(0, chunk);
+      if i == 0 { data = page.read(start, len) }
+      self.insert_page(page_index, page);
+    }
+
+    // Nothing was read, so the requested page is past the end of the file
+    if buf.len() == 0 {
+      let page = FilePage::new(page_size).unwrap();
+      data = page.read(start, len);
+      self.insert_page(index, page);
+    }
+
+    Ok(data)
+  }
+
+  // Tracks whether reads are sequential and, if so, grows the number of 
+  // pages loaded per read from the file (up to max_pages). Any 
+  // non-sequential read drops back to one page at a time.
+  fn tune_read_ahead(&self, offset: u64, len: usize) {
+    if Some(offset) == self.last_read_end.get() {
+      self.sequential_reads.set(self.sequential_reads.get() + 1);
+    } else {
+      self.sequential_reads.set(0);
+    }
+    self.last_read_end.set(Some(offset + len as u64));
+
+    let shift = if self.sequential_reads.get() > 4 { 4 } else { self.sequential_reads.get() };
+    let mut pages = 1 << shift;
+    if pages > self.max_pages { pages = self.max_pages }
+    if pages < 1 { pages = 1 }
+    self.read_ahead_pages.set(pages);
+  }
+
+  fn read_direct(&self, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = vec![0; len];
+    let read_len = try!(self.read_direct_into(offset, buf.as_mut_slice()));
+    buf.truncate(read_len);
+    Ok(buf)
+  }
+
+  fn read_direct_into(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+    let mut file = self.file.borrow_mut();
+    try!(file.seek(SeekFrom::Start(offset)));
+
+    let mut read_len = 0;
+
+    while read_len < buf.len() {
+      let n = try!(file.read(&mut buf[read_len..]));
+      if n == 0 { break }
+      read_len += n;
+    }
+
+    self.disk_reads.set(self.disk_reads.get() + 1);
+    Ok(read_len)
+  }
+
   pub fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>, Error> {
+    let mut data = vec![0; len];
+    let read_len = try!(self.read_into(offset, data.as_mut_slice()));
+    data.truncate(read_len);
+    Ok(data)
+  }
+
+  // Fills buf from offset onwards and returns the number of bytes read, 
+  // which is short of buf.len() only when the file ends first
+  pub fn read_into(&self, offset: u64, buf: &mut [u8]) -> Result<usize, Error> {
+    let len = buf.len();
+
+    // Small reads go straight to the file so they don't pull in whole 
+    // pages, unless the file may be missing writes that haven't been flushed
+    if len < self.small_read_direct && self.dirty.borrow().is_empty() { 
+      return self.read_direct_into(offset, buf) 
+    }
+
+    if self.auto_tune { self.tune_read_ahead(offset, len) }
+
     let (start, end) = self.calc_page_range(offset, len as u64);
 
-    let mut data = Vec::new();
     let mut total_len: usize = 0;
 
     for i in start..(end + 1) {
       let (start_in_page, len_in_page) = try!(self.calc_page_section(i, offset, len));
       let partial_data = try!(self.read_from_page(i, start_in_page, len_in_page));
       let partial_len = partial_data.len();
-      total_len += partial_data.len();
 
-      data.extend(partial_data);
+      buf[total_len..(total_len + partial_len)].copy_from_slice(partial_data.as_slice());
+      total_len += partial_len;
 
       if partial_len < len_in_page as usize { break };
     }
 
-    data.truncate(total_len);
-    Ok(data)
+    Ok(total_len)
+
+  }
+
+  // Borrows the data straight from the cache when the range falls inside a 
+  // single cached page, saving the copy read() makes. Returns None when the
+  // range crosses a page boundary, isn't cached, or runs past the data in 
+  // the page, in which case the caller should fall back to read().
+  pub fn read_ref(&mut self, offset: u64, len: usize) -> Option<&[u8]> {
+    if len == 0 { return None }
+
+    let (start, end) = self.calc_page_range(offset, len as u64);
+    if start != end { return None }
+
+    let start_in_page = (offset - start * self.page_size as u64) as usize;
+    match self.pages.get_mut().get(&start) {
+      Some(p) => {
+        let data = p.read_ref(start_in_page, len);
+        if data.is_some() { self.cache_hits.set(self.cache_hits.get() + 1) }
+        data
+      },
+      None => None
+    }
+  }
+
+  // Like read(), but also returns the offset to resume reading from. If the
+  // read stops short at the end of the file, the page holding the end of 
+  // the file is dropped from the cache so that data appended to the file 
+  // later is picked up when the read is resumed.
+  pub fn read_resumable(
+    &mut self, 
+    offset: u64, 
+    len: usize
+  ) -> Result<(Vec<u8>, u64), Error> {
+    let data = try!(self.read(offset, len));
+    let next_offset = offset + data.len() as u64;
+
+    if data.len() < len {
+      let page_index = next_offset / self.page_size as u64;
+      self.remove_page(page_index);
+    }
+
+    Ok((data, next_offset))
+  }
 
+  // Loads the first pages of the file (up to max_pages, and only as far as 
+  // the end of the file) into the cache in a single read, so that the 
+  // first reads don't have to go to the file. Doesn't count as cache misses.
+  pub fn warm_up(&self, pages: u64) -> Result<(), Error> {
+    let num_pages = cmp::min(pages, self.max_pages);
+    if num_pages == 0 { return Ok(()) }
+
+    let page_size = self.page_size;
+    let total_len = try!(util::u64_as_usize(num_pages * page_size as u64));
+    let buf = try!(self.read_direct(0, total_len));
+
+    for (i, chunk) in buf.chunks(page_size).enumerate() {
+      let page_index = i as u64;
+      if self.pages.borrow().contains_key(&page_index) { continue }
+      let mut page = FilePage::new(page_size).unwrap();
+      page.write(0, chunk);
+      self.insert_page(page_index, page);
+    }
+
+    Ok(())
   }
 
+  // Keeps cached pages in sync with data the caller has already written to 
+  // the file. Uncached pages are skipped, so with max_pages == 0 this does
+  // nothing and every read goes to the file.
   pub fn update(&mut self, offset: u64, data: &[u8]) -> Result<(), Error> {
     let (start, end) = self.calc_page_range(offset, data.len() as u64);
 
@@ -198,16 +438,117 @@ impl FileSyncedBuffer {
     Ok(())
   }
 
+  // Writes data at offset. In write-back mode (with a cache to hold it) the
+  // data only goes into the cached pages, which are marked dirty and 
+  // written to the file by flush(). Otherwise it goes straight to the file 
+  // and any cached pages are updated to match.
+  pub fn write(&mut self, offset: u64, data: &[u8]) -> Result<(), Error> {
+    if data.len() == 0 { return Ok(()) }
+
+    if !self.write_back || self.max_pages == 0 {
+      try!(self.write_direct(offset, data));
+      return self.update(offset, data);
+    }
+
+    let (start, end) = self.calc_page_range(offset, data.len() as u64);
+    let mut written: usize = 0;
+
+    for i in start..(end + 1) {
+      let (start_in_page, len_in_page) = try!(self.calc_page_section(
+        i, 
+        offset, 
+        data.len()
+      ));
+      if !self.pages.borrow().contains_key(&i) {
+        let existing = try!(self.read_direct(i * self.page_size as u64, self.page_size));
+        let mut page = FilePage::new(self.page_size).unwrap();
+        page.write(0, existing.as_slice());
+        self.insert_page(i, page);
+      }
+      match self.pages.borrow_mut().get_mut(&i) {
+        Some(p) => p.write(start_in_page, &data[written..written + len_in_page]),
+        None => ()
+      };
+      self.dirty.borrow_mut().insert(i);
+      written += len_in_page;
+    }
+    Ok(())
+  }
+
+  fn write_direct(&self, offset: u64, data: &[u8]) -> Result<(), Error> {
+    let mut file = self.file.borrow_mut();
+    try!(file.seek(SeekFrom::Start(offset)));
+    try!(file.write_all(data));
+    self.disk_writes.set(self.disk_writes.get() + 1);
+    Ok(())
+  }
+
+  // Writes the dirty pages to the file in offset order. Runs of adjacent 
+  // dirty pages go out in a single write. A run ends after a page that 
+  // doesn't hold a full page of data, so the gap after it isn't written.
+  pub fn flush(&mut self) -> Result<(), Error> {
+    let dirty = Vec::from_iter(self.dirty.borrow().iter().map(|&p| p));
+    let page_size = self.page_size as u64;
+
+    let mut run_start: Option<u64> = None;
+    let mut run_data: Vec<u8> = Vec::new();
+    let mut next_index = 0;
+
+    for i in dirty {
+      let data = match self.pages.borrow().get(&i) {
+        Some(p) => p.read(0, self.page_size),
+        None => continue
+      };
+
+      if run_start.is_some() && i != next_index {
+        try!(self.write_direct(run_start.unwrap() * page_size, run_data.as_slice()));
+        run_start = None;
+        run_data.clear();
+      }
+      if run_start.is_none() { run_start = Some(i) }
+
+      let full = data.len() == self.page_size;
+      run_data.extend(data);
+      next_index = if full { i + 1 } else { i };
+    }
+
+    if let Some(s) = run_start {
+      try!(self.write_direct(s * page_size, run_data.as_slice()));
+    }
+
+    self.dirty.borrow_mut().clear();
+    self.remove_oldest_pages(0);
+    Ok(())
+  }
+
+  // Counterpart to truncate() for when the file grows from len to new_len.
+  // A cached page that ended at the old end of the file is filled out with 
+  // the zeros the file now holds, so reads past the old end aren't cut short.
+  pub fn extend(&mut self, len: usize, new_len: usize) -> Result<(), Error> {
+    let in_page = len % self.page_size;
+    if in_page == 0 || new_len <= len { return Ok(()) }
+
+    let fill_len = cmp::min(self.page_size - in_page, new_len - len);
+    self.update(len as u64, vec![0x0; fill_len].as_slice())
+  }
+
   pub fn truncate(&mut self, len: usize) {
     if len == 0 { 
       self.pages.borrow_mut().clear();
       self.page_insertions.borrow_mut().clear();
+      self.dirty.borrow_mut().clear();
       return;
     }
 
     let page_size = self.page_size;
 
     let last_page = len / page_size;         
+    let truncated_dirty = Vec::from_iter(
+      self.dirty.borrow().iter().filter(|&&p| p > last_page as u64).map(|&p| p)
+    );
+    for p in truncated_dirty {
+      self.dirty.borrow_mut().remove(&p);
+    }
     let last_page_len = len % page_size;
     let to_remove = Vec::from_iter(
       self.page_insertions.borrow().iter()
@@ -250,6 +591,67 @@ impl FileSyncedBuffer {
     Vec::from_iter(self.page_insertions.borrow().iter().map(|&x| x))
   }
 
+  pub fn get_small_read_direct(&self) -> usize {
+    self.small_read_direct
+  }
+
+  pub fn set_small_read_direct(&mut self, threshold: usize) {
+    self.small_read_direct = threshold;
+  }
+
+  pub fn get_auto_tune(&self) -> bool {
+    self.auto_tune
+  }
+
+  // Experimental: batch sequential reads into larger reads from the file. 
+  // Pages are still cached (and reported) at the configured page size.
+  pub fn set_auto_tune(&mut self, auto_tune: bool) {
+    self.auto_tune = auto_tune;
+    self.last_read_end.set(None);
+    self.sequential_reads.set(0);
+    self.read_ahead_pages.set(1);
+  }
+
+  pub fn get_read_ahead_pages(&self) -> u64 {
+    self.read_ahead_pages.get()
+  }
+
+  pub fn get_disk_reads(&self) -> u64 {
+    self.disk_reads.get()
+  }
+
+  pub fn get_write_back(&self) -> bool {
+    self.write_back
+  }
+
+  // Turning write-back off flushes any dirty pages first
+  pub fn set_write_back(&mut self, write_back: bool) -> Result<(), Error> {
+    if !write_back { try!(self.flush()) }
+    self.write_back = write_back;
+    Ok(())
+  }
+
+  pub fn get_num_dirty_pages(&self) -> u64 {
+    self.dirty.borrow().len() as u64
+  }
+
+  // Number of write calls made to the file by write() and flush()
+  pub fn get_disk_writes(&self) -> u64 {
+    self.disk_writes.get()
+  }
+
+  pub fn get_cache_hits(&self) -> u64 {
+    self.cache_hits.get()
+  }
+
+  pub fn get_cache_misses(&self) -> u64 {
+    self.cache_misses.get()
+  }
+
+  pub fn get_cache_evictions(&self) -> u64 {
+    self.cache_evictions.get()
+  }
+
 }
 
 
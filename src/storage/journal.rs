@@ -1,9 +1,16 @@
 use std::mem;
+use std::io;
+use std::io::Write;
+use std::borrow::Cow;
+
+use byteorder::{ ByteOrder, BigEndian, LittleEndian, WriteBytesExt };
 
 use error::{ Error, AssertionError };
 use storage::binary_storage::BinaryStorage;
 use storage::transactional_storage::TransactionalStorage;
-use storage::util::xor_checksum;
+use storage::file_binary_storage::Endianness;
+use storage::util;
+use storage::util::ChecksumAlgo;
 use storage::binary_storage;
 
 pub static ERR_WRITE_IN_PROGRESS: & 'static str =
@@ -18,11 +25,69 @@ pub static ERR_NO_RECORD_DATA: & 'static str =
   "Record contains no data";
 pub static ERR_CHECKSUM_MISMATCH: & 'static str =
   "Checksum mismatch, record data may be corrupted";
-pub static ERR_WRITE_TOO_BIG: & 'static str =
-  "Data cannot be more than 4294967295 bytes long";
+pub static ERR_WRITE_TOO_LARGE: & 'static str =
+  "Data cannot be more than 16777215 bytes long";
+pub static ERR_UNKNOWN_CHECKSUM_ALGO: & 'static str =
+  "Record header specifies an unknown checksum algorithm";
+pub static ERR_MARKERS_NOT_DISTINCT: & 'static str =
+  "Record start and end markers must be different";
+pub static ERR_NOTHING_RESERVED: & 'static str =
+  "No record has been reserved";
+pub static ERR_RESERVED_LENGTH_MISMATCH: & 'static str =
+  "Data length does not match the reserved record length";
+pub static ERR_RESERVED_NOT_FILLED: & 'static str =
+  "Reserved record must be filled before it can be committed";
+pub static ERR_RECORD_LENGTH_MISMATCH: & 'static str =
+  "Data length does not match the length of the existing record";
+pub static ERR_ENCODED_LENGTH_MISMATCH: & 'static str =
+  "Data does not compress to the same length as the existing record";
+pub static ERR_CORRUPT_RECORD_HEADER: & 'static str =
+  "Data after the last record is neither a record header nor empty";
+pub static ERR_RECORD_COUNT_MISMATCH: & 'static str =
+  "Cached record count does not match the number of committed records";
+pub static ERR_SLOT_SIZE_TOO_SMALL: & 'static str =
+  "Slot size must leave room for a record header, data, and end marker";
+pub static ERR_RECORD_TOO_LARGE: & 'static str =
+  "Record does not fit in a fixed-size slot";
+
+pub const DEFAULT_START_MARKER: u16 = 514;
+pub const DEFAULT_END_MARKER: u16 = 771;
 
+// Bytes before a record's data: the start marker and the length field
 pub const PRE_DATA_LEN: usize = 6;
-pub const POST_DATA_LEN: usize = 3;
+
+// Bytes after a record's data: the checksum, whose size depends on the 
+// algorithm, and the end marker
+pub fn post_data_len(algo: ChecksumAlgo) -> usize {
+  algo.len() + mem::size_of::<u16>()
+}
+
+// The top byte of a record's length field identifies the checksum algorithm
+// used for that record, so records written with different algorithms can
+// be read back by any journal. Xor (id 0) is the original record format.
+const CHECKSUM_ALGO_SHIFT: u32 = 24;
+const RECORD_LEN_MASK: u32 = 0x00FFFFFF;
+
+// The high bit of the checksum algorithm byte marks a record whose data is
+// prefixed by a u64 expiry time. The expiry is covered by the checksum.
+const EXPIRY_FLAG: u8 = 0x80;
+const EXPIRY_LEN: usize = 8;
+
+// The next bit marks a record whose data is run-length encoded. The length
+// field and checksum cover the encoded bytes as stored.
+const COMPRESSED_FLAG: u8 = 0x40;
+
+// What verify() does about a record that fails to read, as chosen by the 
+// on_corruption callback. Abort returns the error (what verify() does when 
+// there is no callback), Truncate drops the record and everything after 
+// it, and Skip drops the record, moving the next valid one and those after
+// it down.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CorruptionAction {
+  Abort,
+  Truncate,
+  Skip
+}
 
 pub struct Journal<T: BinaryStorage + Sized> {
   storage: TransactionalStorage<T>,
@@ -30,7 +95,31 @@ pub struct Journal<T: BinaryStorage + Sized> {
   write_offset: usize,
   is_writing: bool,
   uncommitted_size: usize,
-  record_count: usize
+  record_count: usize,
+  record_offsets: Vec<usize>,
+  skipped: Vec<(usize, usize)>,
+  expiries: Vec<(u64, usize)>,
+  pending_expiry: Option<u64>,
+  dead_bytes: usize,
+  on_commit: Option<Box<FnMut(usize, &[u8])>>,
+  on_uncommitted_drop: Option<Box<FnMut()>>,
+  on_corruption: Option<Box<FnMut(usize, &Error) -> CorruptionAction>>,
+  checksum_algo: ChecksumAlgo,
+  strict_headers: bool,
+  start_marker: u16,
+  end_marker: u16,
+  reserved: Option<(usize, ChecksumAlgo)>,
+  expiry_now: Option<u64>,
+  group_commit_window: Option<u64>,
+  clock: Option<Box<FnMut() -> u64>>,
+  unsynced_since: Option<u64>,
+  sync_every: usize,
+  unsynced_count: usize,
+  on_sync: Option<Box<FnMut()>>,
+  slot_size: Option<usize>,
+  compress_min_size: Option<usize>,
+  trim_incomplete_tail: bool,
+  trimmed_tail_len: usize
 }
 impl<T: BinaryStorage + Sized> Journal<T> {
 
@@ -41,16 +130,58 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       write_offset: 0,
       is_writing: false,
       uncommitted_size: 0,
-      record_count: 0
+      record_count: 0,
+      record_offsets: Vec::new(),
+      skipped: Vec::new(),
+      expiries: Vec::new(),
+      pending_expiry: None,
+      dead_bytes: 0,
+      on_commit: None,
+      on_uncommitted_drop: None,
+      on_corruption: None,
+      checksum_algo: ChecksumAlgo::Xor,
+      strict_headers: false,
+      start_marker: DEFAULT_START_MARKER,
+      end_marker: DEFAULT_END_MARKER,
+      reserved: None,
+      expiry_now: None,
+      group_commit_window: None,
+      clock: None,
+      unsynced_since: None,
+      sync_every: 0,
+      unsynced_count: 0,
+      on_sync: None,
+      slot_size: None,
+      compress_min_size: None,
+      trim_incomplete_tail: false,
+      trimmed_tail_len: 0
     }
   }
 
+  // Uses custom record framing markers instead of the defaults, e.g. to 
+  // avoid collisions when the journal is embedded in a larger format
+  pub fn with_markers(
+    storage: TransactionalStorage<T>,
+    start_marker: u16,
+    end_marker: u16
+  ) -> Result<Journal<T>, Error> {
+    try!(AssertionError::assert(
+      start_marker != end_marker, 
+      ERR_MARKERS_NOT_DISTINCT
+    ));
+    let mut j = Journal::new(storage);
+    j.start_marker = start_marker;
+    j.end_marker = end_marker;
+    Ok(j)
+  }
+
 
   pub fn open(&mut self) -> Result<(), Error> {
     self.storage.open().and(self.verify())
   }
 
   pub fn close(&mut self) -> Result<(), Error> {
+    if self.has_unsynced_commits() { try!(self.flush_commits()); }
     match self.storage.close() {
       Ok(_) => {
         self.read_offset = 0;
@@ -58,6 +189,12 @@ impl<T: BinaryStorage + Sized> Journal<T> {
         self.is_writing = false;
         self.uncommitted_size = 0;
         self.record_count = 0;
+        self.record_offsets.clear();
+        self.skipped.clear();
+        self.expiries.clear();
+        self.pending_expiry = None;
+        self.dead_bytes = 0;
+        self.reserved = None;
         Ok(())
       },
       Err(e) => Err(e)
@@ -72,21 +209,76 @@ impl<T: BinaryStorage + Sized> Journal<T> {
 
     // Start at the beginning of storage
     self.reset();
+    self.skipped.clear();
+
+    // Storage too small to hold even a single record header can't contain
+    // any records, so there's nothing else to check
+    if try!(self.storage.get_capacity()) < PRE_DATA_LEN {
+      self.record_count = 0;
+      self.record_offsets.clear();
+      self.expiries.clear();
+      self.dead_bytes = 0;
+      return Ok(());
+    }
 
     // Turn off transaction checking temporarily since we don't
     // know where the boundary is yet
     self.storage.set_check_on_read(false);
     
-    // Count all the good committed records
-    let mut count = 0;
-    for _ in self.into_iter() {
-      count += 1;
+    // Count all the good committed records, including expired ones, noting
+    // where each one starts for read_nth(). Skipping a corrupt record moves
+    // the read offset on to the next valid record found after it, so 
+    // counting carries on from that record.
+    let expiry_now = self.expiry_now.take();
+    let mut offsets = Vec::new();
+    loop {
+      loop {
+        self.read_offset = self.past_skipped(self.read_offset);
+        let offset = self.read_offset;
+        match self.next() {
+          Some(_) => offsets.push(offset),
+          None => break
+        };
+      }
+      match self.recover_from_corruption() {
+        Ok(true) => (),
+        Ok(false) => break,
+        Err(e) => {
+          self.expiry_now = expiry_now;
+          self.storage.set_check_on_read(true);
+          return Err(e);
+        }
+      };
     }
-    self.record_count = count;
+    self.expiry_now = expiry_now;
+    self.record_count = offsets.len();
+    self.record_offsets = offsets;
+
+    // Writing resumes, and reading is allowed up to, the end of the last 
+    // good record, so a journal opened over existing storage carries on 
+    // from where the one that wrote it left off
+    let end = self.read_offset;
+    self.write_offset = end;
+    match self.storage.set_txn_boundary(end) {
+      Ok(()) => (),
+      Err(e) => {
+        self.storage.set_check_on_read(true);
+        return Err(e);
+      }
+    };
 
-    // check to see if the start marker exists. If an error occurs during the
-    // check, turn transaction checking back on before returning the error 
-    let has_start = match self.has_start() {
+    // check to see if the start marker exists (there's no room for one if 
+    // the last record ends at the end of storage). If an error occurs during
+    // the check, turn transaction checking back on before returning the error 
+    let at_end = match self.storage.get_capacity() {
+      Ok(c) => end + mem::size_of::<u16>() > c,
+      Err(e) => {
+        self.storage.set_check_on_read(true);
+        return Err(e);
+      }
+    };
+    let start_check = if at_end { Ok(false) } else { self.has_start() };
+    let has_start = match start_check {
       Ok(h) => h,
       Err(e) => {
         self.storage.set_check_on_read(true);
@@ -94,9 +286,50 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       }
     };
 
+    // In trim mode, a last record without an end marker (e.g. one cut off 
+    // by the file being truncated) is dropped rather than resumed as an 
+    // uncommitted write
+    self.trimmed_tail_len = 0;
+    let has_start = if has_start && self.trim_incomplete_tail {
+      match self.trim_incomplete_record(end) {
+        Ok(trimmed) => !trimmed,
+        Err(e) => {
+          self.storage.set_check_on_read(true);
+          return Err(e);
+        }
+      }
+    } else {
+      has_start
+    };
+
+    // Anything that follows the last record should either be another record
+    // or an empty (all-zero) slot. In strict mode, anything else is treated
+    // as a corrupted header rather than the end of the log.
+    if !has_start && self.strict_headers {
+      let is_empty = match self.is_empty_slot() {
+        Ok(e) => e,
+        Err(e) => {
+          self.storage.set_check_on_read(true);
+          return Err(e);
+        }
+      };
+      if !is_empty {
+        self.storage.set_check_on_read(true);
+        return Err(Error::from(AssertionError::new(ERR_CORRUPT_RECORD_HEADER)));
+      }
+    }
+
     if has_start {
-      let data = match self.read() {
-        Ok(d) => d,
+      match self.read() {
+        Ok(_) => (),
+        Err(e) => {
+          self.storage.set_check_on_read(true);
+          return Err(e);
+        }
+      };
+      // Use the stored length, since read() strips the expiry and decodes
+      let (algo, len) = match self.read_header(self.read_offset) {
+        Ok(h) => h,
         Err(e) => {
           self.storage.set_check_on_read(true);
           return Err(e);
@@ -105,13 +338,28 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       self.write_offset = self.read_offset + 
         mem::size_of::<u16>() + 
         mem::size_of::<u32>() + 
-        data.len() +
-        mem::size_of::<u8>(); 
+        len +
+        algo.len(); 
+      self.uncommitted_size = self.write_offset - end;
       self.is_writing = true;
     }
 
     self.storage.set_check_on_read(true);
 
+    // Note every expiring record, for fragmentation()
+    let mut expiries = Vec::new();
+    for offset in self.record_offsets.iter() {
+      match try!(self.read_expiry_at(*offset)) {
+        Some(e) => {
+          let (algo, len) = try!(self.read_header(*offset));
+          expiries.push((e, self.stored_size(algo, len)));
+        },
+        None => ()
+      };
+    }
+    self.expiries = expiries;
+    self.count_dead_bytes();
+
     // Reset to the beginning and return Ok
     self.reset();
     Ok(())
@@ -119,18 +367,451 @@ impl<T: BinaryStorage + Sized> Journal<T> {
   }
 
 
+  // Hands a record at the read offset that fails to read to the 
+  // on_corruption callback, if there is one, and carries out its choice. 
+  // Returns whether there may be more records to count.
+  fn recover_from_corruption(&mut self) -> Result<bool, Error> {
+    if self.on_corruption.is_none() { return Ok(false) }
+
+    let offset = self.read_offset;
+    let capacity = try!(self.storage.get_capacity());
+    if offset + mem::size_of::<u16>() > capacity || !try!(self.has_start()) { 
+      return Ok(false) 
+    }
+
+    // An incomplete last record is left for trim mode to deal with
+    if self.trim_incomplete_tail && !self.has_end().unwrap_or(false) { 
+      return Ok(false) 
+    }
+    let err = match self.read() {
+      Ok(_) => return Ok(false),
+      Err(e) => e
+    };
+
+    let action = match self.on_corruption {
+      Some(ref mut f) => f(offset, &err),
+      None => CorruptionAction::Abort
+    };
+    if action == CorruptionAction::Abort { return Err(err) }
+
+    // The corrupt record's header can't be trusted to say where it ends, so
+    // skipping carries on from the next record that reads back whole. The 
+    // corrupt bytes are left where they are and only noted as skipped, so 
+    // dying partway through recovery can't lose or duplicate anything. With
+    // nothing valid after it, the record is truncated instead.
+    let skip_to = if action == CorruptionAction::Skip {
+      try!(self.find_next_record(offset, capacity))
+    } else {
+      None
+    };
+
+    match (action, skip_to) {
+      (CorruptionAction::Skip, Some(next)) => {
+        util::add_range(&mut self.skipped, offset, next);
+        self.read_offset = next;
+        Ok(true)
+      },
+      _ => {
+        try!(self.storage.set_txn_boundary(offset));
+        try!(self.truncate_at(offset));
+        Ok(false)
+      }
+    }
+  }
+
+  // Offset of the first record after the one at offset that has a start 
+  // marker, an end marker and a matching checksum. With fixed-size slots 
+  // only slot starts are tried. Leaves the read offset alone.
+  fn find_next_record(&mut self, offset: usize, capacity: usize) -> Result<Option<usize>, Error> {
+    let step = match self.slot_size { Some(slot) => slot, None => 1 };
+    let read_offset = self.read_offset;
+    let mut found = None;
+    let mut offset = offset + step;
+    while offset + PRE_DATA_LEN <= capacity {
+      self.read_offset = offset;
+      if try!(self.has_start()) && self.has_end().unwrap_or(false) && self.read().is_ok() {
+        found = Some(offset);
+        break;
+      }
+      offset += step;
+    }
+    self.read_offset = read_offset;
+    Ok(found)
+  }
+
+  // Where reading carries on from offset: the end of the corrupt bytes
+  // skipped by verify() if they start there, otherwise offset itself
+  fn past_skipped(&self, offset: usize) -> usize {
+    match self.skipped.iter().find(|r| r.0 == offset) {
+      Some(r) => r.1,
+      None => offset
+    }
+  }
+
+  // Trims the record at offset if it has no end marker, returning whether
+  // it did
+  fn trim_incomplete_record(&mut self, offset: usize) -> Result<bool, Error> {
+    self.read_offset = offset;
+    if self.has_end().unwrap_or(false) { return Ok(false) }
+    try!(self.storage.set_txn_boundary(offset));
+    self.trimmed_tail_len = try!(self.truncate_at(offset));
+    Ok(true)
+  }
+
+  // Zeroes everything from offset to the end of storage, which has to be 
+  // at or past the transaction boundary. Returns the number of bytes of 
+  // data that were cleared, not counting trailing zeros.
+  fn truncate_at(&mut self, offset: usize) -> Result<usize, Error> {
+    let capacity = try!(self.storage.get_capacity());
+    if offset >= capacity { return Ok(0) }
+    let len = try!(self.storage.valid_len_from(offset));
+    if len > 0 { try!(self.storage.fill(Some(offset), Some(capacity), 0x0)); }
+    Ok(len)
+  }
+
+  pub fn recover_scan(&mut self) -> Result<Vec<usize>, Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    // Turn off transaction checking temporarily since we're looking
+    // for records regardless of where the boundary is
+    try!(self.storage.set_check_on_read(false));
+
+    // Hop from record to record using the length fields rather than
+    // searching for markers, since marker values may appear in record data.
+    // Stop at the first record that doesn't check out.
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+    loop {
+      offset = self.past_skipped(offset);
+      match self.check_record_at(offset) {
+        Ok(size) => {
+          offsets.push(offset);
+          offset += size;
+        },
+        Err(_) => break
+      };
+    }
+
+    try!(self.storage.set_check_on_read(true));
+
+    Ok(offsets)
+  }
+
+  fn check_record_at(&self, offset: usize) -> Result<usize, Error> {
+    try!(AssertionError::assert(
+      self.start_marker == try!(self.storage.r_u16(offset)),
+      ERR_NO_COMMITTED_RECORD
+    ));
+
+    let (algo, len) = try!(self.read_header(offset));
+    try!(AssertionError::assert(len > 0, ERR_NO_RECORD_DATA));
+
+    let data = try!(self.storage.r_bytes(offset + PRE_DATA_LEN, len));
+    let checksum = try!(self.read_checksum(algo, offset + PRE_DATA_LEN + len));
+    try!(AssertionError::assert(
+      checksum == algo.checksum(data.as_slice()), 
+      ERR_CHECKSUM_MISMATCH
+    ));
+
+    try!(AssertionError::assert(
+      self.end_marker == try!(self.storage.r_u16(
+        offset + PRE_DATA_LEN + len + algo.len()
+      )),
+      ERR_NO_COMMITTED_RECORD
+    ));
+
+    Ok(self.stored_size(algo, len))
+  }
+
+  fn read_header(&self, offset: usize) -> Result<(ChecksumAlgo, usize), Error> {
+    let len_field = try!(self.storage.r_u32(offset + mem::size_of::<u16>()));
+    let algo_id = 
+      (len_field >> CHECKSUM_ALGO_SHIFT) as u8 & !(EXPIRY_FLAG | COMPRESSED_FLAG);
+    match ChecksumAlgo::from_id(algo_id) {
+      Some(algo) => Ok((algo, (len_field & RECORD_LEN_MASK) as usize)),
+      None => Err(Error::Assertion(AssertionError::new(ERR_UNKNOWN_CHECKSUM_ALGO)))
+    }
+  }
+
+  fn read_expiry_at(&self, offset: usize) -> Result<Option<u64>, Error> {
+    let len_field = try!(self.storage.r_u32(offset + mem::size_of::<u16>()));
+    if (len_field >> CHECKSUM_ALGO_SHIFT) as u8 & EXPIRY_FLAG == 0 { return Ok(None) }
+    // Written as part of the payload, so little-endian whatever the storage
+    let bytes = try!(self.storage.r_bytes(offset + PRE_DATA_LEN, EXPIRY_LEN));
+    Ok(Some(LittleEndian::read_u64(bytes.as_slice())))
+  }
+
+  fn is_compressed_at(&self, offset: usize) -> Result<bool, Error> {
+    let len_field = try!(self.storage.r_u32(offset + mem::size_of::<u16>()));
+    Ok((len_field >> CHECKSUM_ALGO_SHIFT) as u8 & COMPRESSED_FLAG != 0)
+  }
+
+  // The data of the record at offset as it was written, decompressed and 
+  // without its expiry time. The checksum isn't checked.
+  fn read_data_at(&self, offset: usize) -> Result<Vec<u8>, Error> {
+    let (_, len) = try!(self.read_header(offset));
+    let mut data = try!(self.storage.r_bytes(offset + PRE_DATA_LEN, len));
+    if try!(self.is_compressed_at(offset)) { 
+      data = try!(util::rle_decompress(data.as_slice()));
+    }
+    if try!(self.read_expiry_at(offset)).is_some() { data.drain(..EXPIRY_LEN); }
+    Ok(data)
+  }
+
+  fn is_expired_at(&self, offset: usize, now: u64) -> Result<bool, Error> {
+    match try!(self.read_expiry_at(offset)) {
+      Some(expires_at) => Ok(expires_at <= now),
+      None => Ok(false)
+    }
+  }
+
+  fn read_checksum(&self, algo: ChecksumAlgo, offset: usize) -> Result<u32, Error> {
+    match algo {
+      ChecksumAlgo::Xor => Ok(try!(self.storage.r_u8(offset)) as u32),
+      _ => self.storage.r_u32(offset)
+    }
+  }
+
+  fn record_size(algo: ChecksumAlgo, len: usize) -> usize {
+    PRE_DATA_LEN + len + post_data_len(algo)
+  }
+
+  // Bytes the record takes up in storage, including any padding out to the
+  // end of its slot
+  fn stored_size(&self, algo: ChecksumAlgo, len: usize) -> usize {
+    let size = Self::record_size(algo, len);
+    match self.slot_size {
+      Some(slot) => (size + slot - 1) / slot * slot,
+      None => size
+    }
+  }
+
+
+  // Starts a record holding data, which is committed with commit(). The
+  // length field only has 24 bits for the length, since its top byte holds
+  // the checksum algorithm, so a record's stored data can be at most
+  // 16777215 bytes (just under 16 MiB); anything longer fails with
+  // ERR_WRITE_TOO_LARGE. The limit applies after compression, and
+  // write_expiring() uses 8 bytes of it for the expiry time.
   pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
-    // TODO: constrain data size
-    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+    let algo = self.checksum_algo;
+    match self.compress(data) {
+      Some(packed) => {
+        try!(self.write_header(packed.len(), algo, COMPRESSED_FLAG));
+        self.write_payload(packed.as_slice(), algo)
+      },
+      None => {
+        try!(self.write_header(data.len(), algo, 0));
+        self.write_payload(data, algo)
+      }
+    }
+  }
+
+  // The encoded form of data, if compression is on, data is at least 
+  // compress_min_size bytes, and encoding actually makes it smaller
+  fn compress(&self, data: &[u8]) -> Option<Vec<u8>> {
+    match self.compress_min_size {
+      Some(min) if data.len() >= min => {
+        let packed = util::rle_compress(data);
+        if packed.len() < data.len() { Some(packed) } else { None }
+      },
+      _ => None
+    }
+  }
+
+  // Writes a record that is skipped by iteration, and dropped by 
+  // compact_expired(), once the time passed to those is at or past 
+  // expires_at. The journal has no clock of its own, so the units are up 
+  // to the caller.
+  pub fn write_expiring(&mut self, data: &[u8], expires_at: u64) -> Result<(), Error> {
     try!(AssertionError::assert(data.len() > 0, ERR_NOTHING_TO_WRITE));
+    let mut payload = Vec::with_capacity(EXPIRY_LEN + data.len());
+    try!(payload.write_u64::<LittleEndian>(expires_at));
+    payload.extend_from_slice(data);
+
+    let algo = self.checksum_algo;
+    try!(self.write_header(payload.len(), algo, EXPIRY_FLAG));
+    try!(self.write_payload(payload.as_slice(), algo));
+    self.pending_expiry = Some(expires_at);
+    Ok(())
+  }
+
+  // Writes just the header of a record, returning the offset the record 
+  // starts at. The data must then be written with fill_reserved() before 
+  // the record can be committed.
+  pub fn reserve(&mut self, len: usize) -> Result<usize, Error> {
+    let offset = self.write_offset;
+    let algo = self.checksum_algo;
+    try!(self.write_header(len, algo, 0));
+    self.reserved = Some((len, algo));
+    Ok(offset)
+  }
+
+  pub fn fill_reserved(&mut self, data: &[u8]) -> Result<(), Error> {
+    let (len, algo) = match self.reserved {
+      Some(r) => r,
+      None => return Err(Error::Assertion(AssertionError::new(ERR_NOTHING_RESERVED)))
+    };
+    try!(AssertionError::assert(
+      data.len() == len, 
+      ERR_RESERVED_LENGTH_MISMATCH
+    ));
+    try!(self.write_payload(data, algo));
+    self.reserved = None;
+    Ok(())
+  }
+
+  // Pre-expands the backing storage to hold at least the given number of 
+  // bytes, so that writing up to that much won't need to expand it again
+  pub fn reserve_capacity(&mut self, bytes: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+    self.storage.expand(bytes)
+  }
+
+  // Releases storage capacity past the last committed record (rounded up to
+  // the storage's expand_size). Records are always contiguous, since 
+  // discard() doesn't leave a gap, so there is nothing to move first.
+  pub fn compact_and_shrink(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+    let end = try!(self.storage.get_txn_boundary());
+    self.storage.shrink(end)
+  }
+
+  // Drops expired records, along with any corrupt ones verify() skipped, by
+  // copying the live ones, in order, into fresh storage and then switching
+  // the journal over to it. The journal's own 
+  // storage is never written, so if the copy fails partway (or the process
+  // dies) every record is still there. fresh is opened if it isn't already,
+  // and anything in it is overwritten. Returns the number of records 
+  // dropped and the storage the journal is no longer using: the old one 
+  // after a switch, or fresh itself if nothing had expired. Like 
+  // compact_and_shrink(), this doesn't release any storage by itself.
+  pub fn compact_expired(
+    &mut self, 
+    now: u64, 
+    fresh: TransactionalStorage<T>
+  ) -> Result<(usize, TransactionalStorage<T>), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+
+    let boundary = try!(self.storage.get_txn_boundary());
+    let mut live = Vec::new();
+    let mut expiries = Vec::new();
+    let mut dropped = 0;
+    let mut offset = 0;
+
+    while offset < boundary {
+      offset = self.past_skipped(offset);
+      let size = try!(self.check_record_at(offset));
+      if try!(self.is_expired_at(offset, now)) {
+        dropped += 1;
+      } else {
+        match try!(self.read_expiry_at(offset)) {
+          Some(e) => expiries.push((e, size)),
+          None => ()
+        };
+        live.push(try!(self.storage.r_bytes(offset, size)));
+      }
+      offset += size;
+    }
+
+    if dropped == 0 && self.skipped.is_empty() { return Ok((0, fresh)) }
+
+    let mut fresh = fresh;
+    if !fresh.is_open() { try!(fresh.open()); }
+    try!(fresh.set_txn_boundary(0));
+
+    let new_end = live.iter().fold(0, |end, rec| end + rec.len());
+    try!(fresh.expand(new_end));
+    let mut offsets = Vec::with_capacity(live.len());
+    let mut rec_offset = 0;
+    for rec in live.iter() {
+      try!(fresh.w_bytes(rec_offset, rec.as_slice()));
+      offsets.push(rec_offset);
+      rec_offset += rec.len();
+    }
+    if new_end < try!(fresh.get_capacity()) {
+      try!(fresh.fill(Some(new_end), None, 0x0));
+    }
+    try!(fresh.set_txn_boundary(new_end));
+    try!(fresh.sync());
+
+    // The copy is complete, so it's safe to switch over
+    let old = mem::replace(&mut self.storage, fresh);
+    self.record_offsets = offsets;
+    self.skipped.clear();
+    self.expiries = expiries;
+    self.count_dead_bytes();
+    self.write_offset = new_end;
+    self.record_count -= dropped;
+    self.unsynced_since = None;
+    self.unsynced_count = 0;
+    self.reset();
+    Ok((dropped, old))
+  }
+
+  // Share of the committed bytes that are dead space: records that have 
+  // expired as of get_expiry_now() and corrupt records skipped by verify(),
+  // i.e. what compact_expired() would reclaim. discard() never leaves 
+  // gaps, so there is no other dead space. The count is kept up to date by
+  // verify(), commits, compact_expired() and set_expiry_now(), so storage
+  // isn't read. 0 while closed.
+  pub fn fragmentation(&self) -> f64 {
+    let committed = self.write_offset - self.uncommitted_size;
+    if committed == 0 { return 0.0 }
+    self.dead_bytes as f64 / committed as f64
+  }
+
+  // Recounts the dead bytes from the noted expiring records and skipped 
+  // ranges, for when the expiry time or the records have changed
+  fn count_dead_bytes(&mut self) {
+    let skipped = self.skipped.iter().fold(0, |n, r| n + r.1 - r.0);
+    let expired = match self.expiry_now {
+      Some(now) => self.expiries.iter()
+        .filter(|e| e.0 <= now)
+        .fold(0, |n, e| n + e.1),
+      None => 0
+    };
+    self.dead_bytes = skipped + expired;
+  }
+
+  fn write_header(
+    &mut self, 
+    len: usize, 
+    algo: ChecksumAlgo, 
+    flags: u8
+  ) -> Result<(), Error> {
+    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+    try!(AssertionError::assert(len > 0, ERR_NOTHING_TO_WRITE));
     try!(AssertionError::assert_not(
-      data.len() > mem::size_of::<u32>(),
-      ERR_WRITE_TOO_BIG
+      len > RECORD_LEN_MASK as usize,
+      ERR_WRITE_TOO_LARGE
     ));
+    match self.slot_size {
+      Some(slot) => try!(AssertionError::assert(
+        Self::record_size(algo, len) <= slot,
+        ERR_RECORD_TOO_LARGE
+      )),
+      None => ()
+    };
 
     self.is_writing = true;
 
-    match self.storage.w_u16(self.write_offset, 514) {
+    match self.storage.w_u16(self.write_offset, self.start_marker) {
       Ok(()) =>  {
         self.write_offset += mem::size_of::<u16>();
         self.uncommitted_size = mem::size_of::<u16>();
@@ -141,10 +822,11 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       }
     };
 
-    // Length of data plus checksum byte
-    let len = data.len();
+    // Length of data, tagged with the checksum algorithm
+    let algo_byte = algo.id() | flags;
+    let len_field = ((algo_byte as u32) << CHECKSUM_ALGO_SHIFT) | len as u32;
 
-    match self.storage.w_u32(self.write_offset, len as u32) {
+    match self.storage.w_u32(self.write_offset, len_field) {
       Ok(()) => {
         self.write_offset += mem::size_of::<u32>();
         self.uncommitted_size += mem::size_of::<u32>();
@@ -155,6 +837,12 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       }
     };
 
+    Ok(())
+  }
+
+  fn write_payload(&mut self, data: &[u8], algo: ChecksumAlgo) -> Result<(), Error> {
+    let len = data.len();
+
     match self.storage.w_bytes(self.write_offset, data) {
       Ok(()) => {
         self.write_offset += len;
@@ -166,10 +854,16 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       }
     };
 
-    match self.storage.w_u8(self.write_offset, xor_checksum(data)) {
+    let checksum = algo.checksum(data);
+    let checksum_res = match algo {
+      ChecksumAlgo::Xor => self.storage.w_u8(self.write_offset, checksum as u8),
+      _ => self.storage.w_u32(self.write_offset, checksum)
+    };
+
+    match checksum_res {
       Ok(()) =>  {
-        self.write_offset += mem::size_of::<u8>();
-        self.uncommitted_size += mem::size_of::<u8>();
+        self.write_offset += algo.len();
+        self.uncommitted_size += algo.len();
       },
       Err(e) => match self.discard() {
         Ok(()) => return Err(e),
@@ -182,8 +876,107 @@ impl<T: BinaryStorage + Sized> Journal<T> {
 
   pub fn commit(&mut self) -> Result<(), Error> {
     try!(AssertionError::assert(self.is_writing, ERR_WRITE_NOT_IN_PROGRESS));
+    try!(AssertionError::assert(self.reserved.is_none(), ERR_RESERVED_NOT_FILLED));
+
+    let record_offset = self.write_offset - self.uncommitted_size;
+    try!(self.finish_record());
+
+    self.storage.set_txn_boundary(self.write_offset);
+    self.uncommitted_size = 0;
+    self.is_writing = false;
+
+    self.record_count += 1;
+    self.record_offsets.push(record_offset);
+
+    if let Some(e) = self.pending_expiry.take() {
+      let size = self.write_offset - record_offset;
+      self.expiries.push((e, size));
+      if self.expiry_now.map_or(false, |now| e <= now) { self.dead_bytes += size; }
+    }
+
+    if self.group_commit_window.is_some() { try!(self.sync_if_window_elapsed()); }
+    if self.sync_every > 0 { try!(self.sync_if_count_reached()); }
+
+    self.notify_commit(record_offset)
+
+  }
+
+  // Writes all of records and commits them together, moving the transaction
+  // boundary (and syncing, if a sync is due) once for the whole batch rather
+  // than once per record. If any record fails to write, none of the batch 
+  // is committed. End markers are held back until every record's data is in
+  // place, then written last to first, so a crash part way through leaves 
+  // the first record of the batch without one and verify() sees none of it.
+  pub fn write_batch(&mut self, records: &[&[u8]]) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert_not(self.is_writing, ERR_WRITE_IN_PROGRESS));
+    if records.len() == 0 { return Ok(()) }
+
+    let batch_start = self.write_offset;
+    let mut offsets = Vec::with_capacity(records.len());
+    let mut end_offsets = Vec::with_capacity(records.len());
+    for data in records.iter() {
+      let record_offset = self.write_offset;
+      match self.write(data).and_then(|_| self.finish_record_without_end()) {
+        Ok(end_offset) => {
+          offsets.push(record_offset);
+          end_offsets.push(end_offset);
+          self.uncommitted_size = 0;
+          self.is_writing = false;
+        },
+        Err(e) => {
+          try!(self.rollback_batch(batch_start, record_offset));
+          return Err(e);
+        }
+      };
+    }
+
+    for end_offset in end_offsets.iter().rev() {
+      match self.storage.w_u16(*end_offset, self.end_marker) {
+        Ok(()) => (),
+        Err(e) => {
+          let batch_end = self.write_offset;
+          try!(self.rollback_batch(batch_start, batch_end));
+          return Err(e);
+        }
+      };
+    }
 
-    match self.storage.w_u16(self.write_offset, 771) {
+    try!(self.storage.set_txn_boundary(self.write_offset));
+    self.record_count += records.len();
+    self.record_offsets.extend_from_slice(offsets.as_slice());
+
+    if self.group_commit_window.is_some() { try!(self.sync_if_window_elapsed()); }
+    if self.sync_every > 0 { try!(self.sync_if_count_reached()); }
+
+    for offset in offsets {
+      try!(self.notify_commit(offset));
+    }
+    Ok(())
+  }
+
+  // Undoes a batch whose record at failed_offset failed to write. The 
+  // records before it (some of which may have their end markers) are 
+  // zeroed to keep verify() from picking them up on reopen.
+  fn rollback_batch(&mut self, batch_start: usize, failed_offset: usize) -> Result<(), Error> {
+    try!(self.storage.set_txn_boundary(batch_start));
+    self.write_offset = batch_start;
+    self.uncommitted_size = 0;
+    self.is_writing = false;
+    self.reserved = None;
+    if failed_offset > batch_start {
+      try!(self.storage.fill(Some(batch_start), Some(failed_offset), 0x0));
+    }
+    Ok(())
+  }
+
+  // Writes the end marker of the record in progress, and any padding out to
+  // the end of its slot, discarding the record if either fails
+  fn finish_record(&mut self) -> Result<(), Error> {
+    match self.storage.w_u16(self.write_offset, self.end_marker) {
       Ok(()) =>  {
         self.write_offset += mem::size_of::<u16>();
         self.uncommitted_size += mem::size_of::<u16>();
@@ -194,14 +987,108 @@ impl<T: BinaryStorage + Sized> Journal<T> {
       }
     };
 
-    self.storage.set_txn_boundary(self.write_offset);
-    self.uncommitted_size = 0;
-    self.is_writing = false;
+    // Zero the rest of the slot so the next record starts on a slot boundary
+    match self.slot_size {
+      Some(slot) => {
+        let padding = (slot - self.uncommitted_size % slot) % slot;
+        match self.storage.w_bytes(self.write_offset, vec![0x0; padding].as_slice()) {
+          Ok(()) => {
+            self.write_offset += padding;
+            self.uncommitted_size += padding;
+          },
+          Err(e) => match self.discard() {
+            Ok(()) => return Err(e),
+            Err(d) => return Err(d)
+          }
+        };
+      },
+      None => ()
+    };
 
-    self.record_count += 1;
+    Ok(())
+  }
+
+  // Like finish_record(), but zeroes the space for the end marker instead 
+  // of writing it, returning its offset so write_batch() can fill it in 
+  // once the rest of the batch is written
+  fn finish_record_without_end(&mut self) -> Result<usize, Error> {
+    let end_offset = self.write_offset;
+    let len = mem::size_of::<u16>();
+    let padding = match self.slot_size {
+      Some(slot) => (slot - (self.uncommitted_size + len) % slot) % slot,
+      None => 0
+    };
+    match self.storage.w_bytes(self.write_offset, vec![0x0; len + padding].as_slice()) {
+      Ok(()) => {
+        self.write_offset += len + padding;
+        self.uncommitted_size += len + padding;
+      },
+      Err(e) => match self.discard() {
+        Ok(()) => return Err(e),
+        Err(d) => return Err(d)
+      }
+    };
+    Ok(end_offset)
+  }
+
+  // Lets any registered listener know about the newly committed record
+  fn notify_commit(&mut self, record_offset: usize) -> Result<(), Error> {
+    if self.on_commit.is_none() { return Ok(()) }
+
+    let data = try!(self.read_data_at(record_offset));
+    match self.on_commit {
+      Some(ref mut f) => f(record_offset, data.as_slice()),
+      None => ()
+    };
+    Ok(())
+  }
+
+  // Syncs the storage once the oldest unsynced commit is at least a full 
+  // window old, so every commit made within the window shares one sync
+  fn sync_if_window_elapsed(&mut self) -> Result<(), Error> {
+    let window = match self.group_commit_window {
+      Some(w) => w,
+      None => return Ok(())
+    };
+    let now = match self.clock {
+      Some(ref mut f) => f(),
+      None => return Ok(())
+    };
+    let since = match self.unsynced_since {
+      Some(t) => t,
+      None => {
+        self.unsynced_since = Some(now);
+        now
+      }
+    };
+    if now.saturating_sub(since) >= window { try!(self.flush_commits()); }
+    Ok(())
+  }
 
+  // Syncs the storage on every sync_every-th commit since the last sync
+  fn sync_if_count_reached(&mut self) -> Result<(), Error> {
+    self.unsynced_count += 1;
+    if self.unsynced_count >= self.sync_every { try!(self.flush_commits()); }
     Ok(())
+  }
 
+  // Syncs any commits still waiting on their group commit window or 
+  // sync_every count
+  pub fn flush_commits(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    if !self.has_unsynced_commits() { return Ok(()) }
+
+    try!(self.storage.sync());
+    self.unsynced_since = None;
+    self.unsynced_count = 0;
+    match self.on_sync {
+      Some(ref mut f) => f(),
+      None => ()
+    };
+    Ok(())
   }
 
   pub fn discard(&mut self) -> Result<(), Error> {
@@ -212,9 +1099,241 @@ impl<T: BinaryStorage + Sized> Journal<T> {
     self.write_offset -= self.uncommitted_size;
     self.uncommitted_size = 0;
     self.is_writing = false;
+    self.reserved = None;
+    self.pending_expiry = None;
+    Ok(())
+  }
+
+  // Overwrites the data of the committed record at offset, which must be 
+  // exactly the same length as the data it replaces and, if the record is
+  // compressed, compress to the same length too. With fixed-size slots, 
+  // the new data only has to fit in the record's slot.
+  pub fn update_record(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    let size = try!(self.check_record_at(offset));
+    let boundary = try!(self.storage.get_txn_boundary());
+    try!(AssertionError::assert(
+      offset + size <= boundary, 
+      ERR_NO_COMMITTED_RECORD
+    ));
+
+    let (algo, len) = try!(self.read_header(offset));
+    if self.slot_size.is_none() {
+      let old_len = try!(self.read_data_at(offset)).len();
+      try!(AssertionError::assert(data.len() == old_len, ERR_RECORD_LENGTH_MISMATCH));
+    }
+
+    // An expiring record keeps its expiry
+    let mut payload = Vec::with_capacity(len);
+    match try!(self.read_expiry_at(offset)) {
+      Some(expires_at) => try!(payload.write_u64::<LittleEndian>(expires_at)),
+      None => ()
+    };
+    payload.extend_from_slice(data);
+    if try!(self.is_compressed_at(offset)) { payload = util::rle_compress(payload.as_slice()); }
+    match self.slot_size {
+      Some(slot) => try!(AssertionError::assert(
+        data.len() > 0 && Self::record_size(algo, payload.len()) <= slot,
+        ERR_RECORD_TOO_LARGE
+      )),
+      None => try!(AssertionError::assert(
+        payload.len() == len, 
+        ERR_ENCODED_LENGTH_MISMATCH
+      ))
+    };
+
+    // Read before the boundary moves, since nothing past it can be read
+    let tag = try!(self.storage.r_u32(offset + mem::size_of::<u16>())) & !RECORD_LEN_MASK;
+
+    // The record is behind the transaction boundary, so temporarily move 
+    // the boundary back to allow it to be rewritten
+    try!(self.storage.set_txn_boundary(offset));
+    let res = if payload.len() == len {
+      self.rewrite_payload(offset, payload.as_slice(), algo)
+    } else {
+      self.rewrite_in_slot(offset, size, tag, payload.as_slice(), algo)
+    };
+    try!(self.storage.set_txn_boundary(boundary));
+    res
+  }
+
+  // Rewrites a record with a different length inside its slot, keeping the
+  // tag (checksum algorithm and expiry flag) from its old length field, and
+  // zeroes whatever part of the slot it no longer uses
+  fn rewrite_in_slot(
+    &mut self, 
+    offset: usize, 
+    slot_len: usize, 
+    tag: u32, 
+    data: &[u8], 
+    algo: ChecksumAlgo
+  ) -> Result<(), Error> {
+    try!(self.storage.w_u32(
+      offset + mem::size_of::<u16>(), 
+      tag | data.len() as u32
+    ));
+    try!(self.rewrite_payload(offset, data, algo));
+
+    let end = offset + PRE_DATA_LEN + data.len() + algo.len();
+    try!(self.storage.w_u16(end, self.end_marker));
+    self.storage.fill(
+      Some(end + mem::size_of::<u16>()), 
+      Some(offset + slot_len), 
+      0x0
+    )
+  }
+
+  fn rewrite_payload(
+    &mut self, 
+    offset: usize, 
+    data: &[u8], 
+    algo: ChecksumAlgo
+  ) -> Result<(), Error> {
+    try!(self.storage.w_bytes(offset + PRE_DATA_LEN, data));
+    let checksum = algo.checksum(data);
+    let checksum_offset = offset + PRE_DATA_LEN + data.len();
+    match algo {
+      ChecksumAlgo::Xor => self.storage.w_u8(checksum_offset, checksum as u8),
+      _ => self.storage.w_u32(checksum_offset, checksum)
+    }
+  }
+
+  pub fn set_on_commit(&mut self, f: Box<FnMut(usize, &[u8])>) {
+    self.on_commit = Some(f);
+  }
+
+  // Turns on group commit. Commits sync the storage only once the first 
+  // unsynced commit is window ticks old, as measured by now, trading a 
+  // bounded delay in durability for fewer syncs. Syncing on every commit 
+  // is the same as a window of 0.
+  pub fn group_commit(&mut self, window: u64, now: Box<FnMut() -> u64>) {
+    self.group_commit_window = Some(window);
+    self.clock = Some(now);
+  }
+
+  pub fn is_group_commit(&self) -> bool {
+    self.group_commit_window.is_some()
+  }
+
+  pub fn has_unsynced_commits(&self) -> bool {
+    self.unsynced_since.is_some() || self.unsynced_count > 0
+  }
+
+  // Syncs the storage on every nth commit, and on close, so a crash loses 
+  // at most the last n commits. 0 turns it off.
+  pub fn set_sync_every(&mut self, n: usize) {
+    self.sync_every = n;
+  }
+
+  pub fn get_sync_every(&self) -> usize {
+    self.sync_every
+  }
+
+  // Called each time the journal syncs its storage
+  pub fn set_on_sync(&mut self, f: Box<FnMut()>) {
+    self.on_sync = Some(f);
+  }
+
+  // Called if the journal is dropped while open with an uncommitted write
+  pub fn set_on_uncommitted_drop(&mut self, f: Box<FnMut()>) {
+    self.on_uncommitted_drop = Some(f);
+  }
+
+  // Called by verify() with the offset of a record that fails to read (e.g. 
+  // on a checksum mismatch) and the error, to choose how to recover
+  pub fn set_on_corruption(
+    &mut self, 
+    f: Box<FnMut(usize, &Error) -> CorruptionAction>
+  ) {
+    self.on_corruption = Some(f);
+  }
+
+  pub fn get_checksum_algo(&self) -> ChecksumAlgo {
+    self.checksum_algo
+  }
+
+  pub fn set_checksum_algo(&mut self, algo: ChecksumAlgo) {
+    self.checksum_algo = algo;
+  }
+
+  pub fn get_start_marker(&self) -> u16 {
+    self.start_marker
+  }
+
+  pub fn get_end_marker(&self) -> u16 {
+    self.end_marker
+  }
+
+  pub fn get_expiry_now(&self) -> Option<u64> {
+    self.expiry_now
+  }
+
+  // When set, iteration skips records that expired at or before this time
+  pub fn set_expiry_now(&mut self, now: Option<u64>) {
+    self.expiry_now = now;
+    self.count_dead_bytes();
+  }
+
+  pub fn get_compress_min_size(&self) -> Option<usize> {
+    self.compress_min_size
+  }
+
+  // Run-length encodes the data of records written with write() that are 
+  // at least min_size bytes, when that makes them smaller. Smaller records,
+  // and ones that don't shrink, are stored as-is, so tiny records don't 
+  // pay for the encoding. Each record is flagged, so records can be read 
+  // back whatever this is set to. None, the default, turns it off.
+  pub fn set_compress_min_size(&mut self, min_size: Option<usize>) {
+    self.compress_min_size = min_size;
+  }
+
+  pub fn get_fixed_slot_size(&self) -> Option<usize> {
+    self.slot_size
+  }
+
+  // Pads every record out to a whole slot of the given size, so the nth 
+  // record always starts at n * slot_size and can be found without walking
+  // the journal. Records that don't fit in one slot are rejected. The slot
+  // size isn't stored, so it has to be set the same way before reopening.
+  pub fn set_fixed_slot_size(&mut self, slot_size: Option<usize>) -> Result<(), Error> {
+    try!(AssertionError::assert_not(
+      self.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_OPEN
+    ));
+    match slot_size {
+      Some(slot) => try!(AssertionError::assert(
+        slot > Self::record_size(ChecksumAlgo::Xor, 0),
+        ERR_SLOT_SIZE_TOO_SMALL
+      )),
+      None => ()
+    };
+    self.slot_size = slot_size;
     Ok(())
   }
 
+  pub fn get_trim_incomplete_tail(&self) -> bool {
+    self.trim_incomplete_tail
+  }
+
+  // When set, verify() drops a last record that has no end marker, instead
+  // of treating it as an uncommitted write to resume. Either way, the 
+  // records before it are kept.
+  pub fn set_trim_incomplete_tail(&mut self, trim: bool) {
+    self.trim_incomplete_tail = trim;
+  }
+
+  // Bytes of an incomplete last record dropped by the last verify()
+  pub fn trimmed_tail_len(&self) -> usize {
+    self.trimmed_tail_len
+  }
+
+  pub fn get_strict_headers(&self) -> bool {
+    self.strict_headers
+  }
+
+  pub fn set_strict_headers(&mut self, strict_headers: bool) {
+    self.strict_headers = strict_headers;
+  }
+
   pub fn is_writing(&self) -> bool {
     self.is_writing
   }
@@ -225,48 +1344,69 @@ impl<T: BinaryStorage + Sized> Journal<T> {
 
   pub fn has_start(&mut self) -> Result<bool, Error> {
     Ok(
-      514 == try!(self.storage.r_u16(self.read_offset)) 
+      self.start_marker == try!(self.storage.r_u16(self.read_offset)) 
     )
   }
 
+  // An all-zero header region (or the end of storage) marks the definitive
+  // end of the log, as opposed to a header that just fails to validate
+  pub fn is_empty_slot(&mut self) -> Result<bool, Error> {
+    let capacity = try!(self.storage.get_capacity());
+    if self.read_offset >= capacity { return Ok(true) }
+
+    let end = if self.read_offset + PRE_DATA_LEN > capacity {
+      capacity
+    } else {
+      self.read_offset + PRE_DATA_LEN
+    };
+
+    self.storage.is_filled(Some(self.read_offset), Some(end), 0x0)
+  }
+
   pub fn has_end(&mut self) -> Result<bool, Error> {
-    let len = try!
-      (self.storage.r_u32(self.read_offset + mem::size_of::<u16>())
-    ) as usize;
+    let (algo, len) = try!(self.read_header(self.read_offset));
     Ok(
-      771 == try!(self.storage.r_u16(
+      self.end_marker == try!(self.storage.r_u16(
         self.read_offset + 
           PRE_DATA_LEN +
           len +
-          mem::size_of::<u8>() 
+          algo.len() 
       ))
     )
   }
 
   pub fn read(&mut self) -> Result<Vec<u8>, Error> {
 
-    let len = try!(
-      self.storage.r_u32(self.read_offset + mem::size_of::<u16>())
-    ) as usize;
-    try!(AssertionError::assert(len > 1, ERR_NO_RECORD_DATA));
-    let mut bytes = try!(self.storage.r_bytes(
+    let (algo, len) = try!(self.read_header(self.read_offset));
+    try!(AssertionError::assert(len > 0, ERR_NO_RECORD_DATA));
+    let bytes = try!(self.storage.r_bytes(
       self.read_offset + PRE_DATA_LEN,
-      len + mem::size_of::<u8>(),
+      len
     ));
 
-    let checksum = match bytes.pop() {
-      Some(s) => s,
-      None => return Err(Error::Assertion(AssertionError::new(ERR_NO_RECORD_DATA)))
-    };
+    let checksum = try!(self.read_checksum(
+      algo, 
+      self.read_offset + PRE_DATA_LEN + len
+    ));
 
     try!(AssertionError::assert(
-      checksum == xor_checksum(bytes.as_slice()), 
+      checksum == algo.checksum(bytes.as_slice()), 
       ERR_CHECKSUM_MISMATCH
     ));
 
+    let mut bytes = bytes;
+    if try!(self.is_compressed_at(self.read_offset)) { 
+      bytes = try!(util::rle_decompress(bytes.as_slice()));
+    }
+    if try!(self.read_expiry_at(self.read_offset)).is_some() { bytes.drain(..EXPIRY_LEN); }
     Ok(bytes)
   }
 
+  // Expiry time of the record at the read offset, if it has one
+  pub fn read_expiry(&mut self) -> Result<Option<u64>, Error> {
+    self.read_expiry_at(self.read_offset)
+  }
+
   pub fn jump_to(&mut self, offset: usize) -> Result<(), Error> {
     self.read_offset = offset;
 
@@ -296,6 +1436,65 @@ impl<T: BinaryStorage + Sized> Journal<T> {
   }
 
 
+  // Moves the read offset to the start of the idx'th committed record. With 
+  // fixed-size slots this is computed; otherwise it comes from the table of 
+  // record offsets built by verify() and kept up to date by each commit.
+  pub fn seek_to_record(&mut self, idx: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert(idx < self.record_count, ERR_NO_COMMITTED_RECORD));
+
+    // Skipped slots throw off the computed offsets
+    let offset = match self.slot_size {
+      Some(slot) if self.skipped.is_empty() => idx * slot,
+      _ => match self.record_offsets.get(idx) {
+        Some(o) => *o,
+        None => return Err(Error::from(AssertionError::new(ERR_NO_COMMITTED_RECORD)))
+      }
+    };
+    self.jump_to(offset)
+  }
+
+  // Reads the nth committed record (counting from 0, and including expired
+  // ones) without walking the records before it. Leaves the read offset at 
+  // the start of the record, as jump_to() does.
+  pub fn read_nth(&mut self, n: usize) -> Result<Vec<u8>, Error> {
+    try!(self.seek_to_record(n));
+    self.read()
+  }
+
+  pub fn enumerate_records(&mut self) -> RecordEnumerator<T> {
+    self.reset();
+    RecordEnumerator { journal: self }
+  }
+
+  // Reads every committed record from the start and returns the ones pred
+  // accepts, in order. Leaves the read offset past the last record.
+  pub fn filter_records<F: FnMut(&[u8]) -> bool>(&mut self, mut pred: F) -> Vec<Vec<u8>> {
+    self.reset();
+    self.filter(|r| pred(r.as_slice())).collect()
+  }
+
+
+  // Walks the records in bytes holding this journal's format (e.g. a journal
+  // file read or mapped into memory) without going through storage. Records
+  // are borrowed straight out of bytes rather than copied; only compressed 
+  // records have to be decoded into new buffers. endianness is the byte 
+  // order of the storage the bytes came from.
+  pub fn record_slices<'a>(&self, bytes: &'a [u8], endianness: Endianness) -> RecordSlices<'a> {
+    RecordSlices {
+      bytes: bytes,
+      offset: 0,
+      endianness: endianness,
+      start_marker: self.start_marker,
+      end_marker: self.end_marker,
+      slot_size: self.slot_size,
+      expiry_now: self.expiry_now
+    }
+  }
+
   pub fn read_offset(&self) -> usize { self.read_offset }
 
   pub fn write_offset(&self) -> usize { self.write_offset }
@@ -304,10 +1503,60 @@ impl<T: BinaryStorage + Sized> Journal<T> {
 
   pub fn record_count(&self) -> usize { self.record_count }
 
+  // Recounts the committed records with a full scan and checks that the 
+  // cached record_count() agrees, returning ERR_RECORD_COUNT_MISMATCH if 
+  // it doesn't. The read position is left unchanged.
+  pub fn audit(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    let read_offset = self.read_offset;
+    let expiry_now = self.expiry_now.take();
+    let count = self.enumerate_records().count();
+    self.expiry_now = expiry_now;
+    self.read_offset = read_offset;
+    try!(AssertionError::assert(count == self.record_count, ERR_RECORD_COUNT_MISMATCH));
+    Ok(())
+  }
+
+  // Same as audit(), but only in debug builds, for catching paths that let
+  // the counter drift
+  #[cfg(debug_assertions)]
+  pub fn assert_count_consistent(&mut self) -> Result<(), Error> {
+    self.audit()
+  }
+
+  // Lets tests knock the cached count out of step with the records
+  #[cfg(test)]
+  pub(crate) fn set_record_count(&mut self, count: usize) {
+    self.record_count = count;
+  }
+
   pub fn txn_boundary(&self) -> Result<usize, Error> {
     self.storage.get_txn_boundary()
   }
 
+}
+impl<T: BinaryStorage + Sized> Drop for Journal<T> {
+
+  fn drop(&mut self) {
+    if !self.is_open() || !self.is_writing { return }
+
+    if cfg!(all(debug_assertions, feature = "drop-warnings")) {
+      let _ = writeln!(
+        io::stderr(), 
+        "warning: Journal dropped with an uncommitted write of {} bytes", 
+        self.uncommitted_size
+      );
+    }
+
+    match self.on_uncommitted_drop {
+      Some(ref mut f) => f(),
+      None => ()
+    };
+  }
+
 }
 impl<T: BinaryStorage + Sized> Iterator for Journal<T> {
 
@@ -315,29 +1564,168 @@ impl<T: BinaryStorage + Sized> Iterator for Journal<T> {
 
   fn next(&mut self) -> Option<Vec<u8>> {
 
-    match self.has_start().and(self.has_end()) {
-      Ok(h) => if !h { return None },
-      Err(_) => return None
-    };
+    loop {
 
-    match self.read() {
-      Ok(v) => {
+      self.read_offset = self.past_skipped(self.read_offset);
 
-        let new_offset = self.read_offset + 
-          PRE_DATA_LEN + 
-          v.len() +
-          POST_DATA_LEN;
+      match self.has_start().and(self.has_end()) {
+        Ok(h) => if !h { return None },
+        Err(_) => return None
+      };
 
-        match self.jump_to(new_offset) {
-          Ok(_) => {},
-          Err(_) => {}
-        };
+      let v = match self.read() {
+        Ok(v) => v,
+        Err(_) => return None
+      };
 
-        Some(v)
+      let (algo, len) = match self.read_header(self.read_offset) {
+        Ok(h) => h,
+        Err(_) => return None
+      };
 
-      },
-      Err(_) => None
+      let expired = match self.expiry_now {
+        Some(now) => match self.is_expired_at(self.read_offset, now) {
+          Ok(e) => e,
+          Err(_) => return None
+        },
+        None => false
+      };
+
+      let new_offset = self.read_offset + self.stored_size(algo, len);
+
+      match self.jump_to(new_offset) {
+        Ok(_) => {},
+        Err(_) => {}
+      };
+
+      if !expired { return Some(v) }
+
+    }
+
+  }
+}
+
+pub struct RecordEnumerator<'a, T: BinaryStorage + Sized + 'a> {
+  journal: &'a mut Journal<T>
+}
+impl<'a, T: BinaryStorage + Sized> Iterator for RecordEnumerator<'a, T> {
+
+  type Item = (usize, Vec<u8>);
+
+  fn next(&mut self) -> Option<(usize, Vec<u8>)> {
+    self.journal.read_offset = self.journal.past_skipped(self.journal.read_offset);
+    let offset = self.journal.read_offset();
+    match self.journal.next() {
+      Some(v) => Some((offset, v)),
+      None => None
     }
+  }
+}
+
+// Iterator returned by Journal::record_slices(). Stops at the first thing 
+// that isn't a complete, valid committed record, like Journal's own 
+// iterator. Header fields are read in the given byte order; the expiry time
+// is part of the payload, so it's always little-endian.
+pub struct RecordSlices<'a> {
+  bytes: &'a [u8],
+  offset: usize,
+  endianness: Endianness,
+  start_marker: u16,
+  end_marker: u16,
+  slot_size: Option<usize>,
+  expiry_now: Option<u64>
+}
+impl<'a> RecordSlices<'a> {
+
+  // Offset of the next record to be returned
+  pub fn offset(&self) -> usize { self.offset }
 
+  fn read_u16(&self, offset: usize) -> u16 {
+    match self.endianness {
+      Endianness::Little => LittleEndian::read_u16(&self.bytes[offset..]),
+      Endianness::Big => BigEndian::read_u16(&self.bytes[offset..])
+    }
+  }
+
+  fn read_u32(&self, offset: usize) -> u32 {
+    match self.endianness {
+      Endianness::Little => LittleEndian::read_u32(&self.bytes[offset..]),
+      Endianness::Big => BigEndian::read_u32(&self.bytes[offset..])
+    }
+  }
+
+  // The record at offset and the offset of the one after it, or None if 
+  // there isn't a valid committed record there. The second value is true
+  // if the record has expired.
+  fn record_at(&self, offset: usize) -> Option<(Cow<'a, [u8]>, usize, bool)> {
+    let bytes = self.bytes;
+    if offset + PRE_DATA_LEN > bytes.len() { return None }
+    if self.read_u16(offset) != self.start_marker { return None }
+
+    let len_field = self.read_u32(offset + mem::size_of::<u16>());
+    let flags = (len_field >> CHECKSUM_ALGO_SHIFT) as u8;
+    let algo = match ChecksumAlgo::from_id(flags & !(EXPIRY_FLAG | COMPRESSED_FLAG)) {
+      Some(a) => a,
+      None => return None
+    };
+    let len = (len_field & RECORD_LEN_MASK) as usize;
+    if len == 0 { return None }
+
+    let size = PRE_DATA_LEN + len + post_data_len(algo);
+    if offset + size > bytes.len() { return None }
+
+    let data_start = offset + PRE_DATA_LEN;
+    let data = &bytes[data_start..data_start + len];
+    let checksum_start = data_start + len;
+    let checksum = match algo {
+      ChecksumAlgo::Xor => bytes[checksum_start] as u32,
+      _ => self.read_u32(checksum_start)
+    };
+    if checksum != algo.checksum(data) { return None }
+    if self.read_u16(checksum_start + algo.len()) != self.end_marker {
+      return None
+    }
+
+    let mut record = if flags & COMPRESSED_FLAG != 0 {
+      match util::rle_decompress(data) {
+        Ok(d) => Cow::Owned(d),
+        Err(_) => return None
+      }
+    } else {
+      Cow::Borrowed(data)
+    };
+
+    let mut expired = false;
+    if flags & EXPIRY_FLAG != 0 {
+      if record.len() < EXPIRY_LEN { return None }
+      let expires_at = LittleEndian::read_u64(&record[..EXPIRY_LEN]);
+      expired = match self.expiry_now { Some(now) => expires_at <= now, None => false };
+      record = match record {
+        Cow::Borrowed(b) => Cow::Borrowed(&b[EXPIRY_LEN..]),
+        Cow::Owned(mut v) => { v.drain(..EXPIRY_LEN); Cow::Owned(v) }
+      };
+    }
+
+    let stored = match self.slot_size {
+      Some(slot) => (size + slot - 1) / slot * slot,
+      None => size
+    };
+    Some((record, offset + stored, expired))
+  }
+
+}
+impl<'a> Iterator for RecordSlices<'a> {
+
+  type Item = Cow<'a, [u8]>;
+
+  fn next(&mut self) -> Option<Cow<'a, [u8]>> {
+    loop {
+      let (record, next_offset, expired) = match self.record_at(self.offset) {
+        Some(r) => r,
+        None => return None
+      };
+      self.offset = next_offset;
+      if !expired { return Some(record) }
+    }
   }
 }
@@ -0,0 +1,60 @@
+use error::{ Error, AssertionError };
+use storage::util;
+
+pub static ERR_RANGE_LOCKED: & 'static str = 
+  "Range overlaps a range that is already locked";
+pub static ERR_RANGE_NOT_LOCKED: & 'static str = 
+  "Range is not locked";
+pub static ERR_LOCK_NOTHING: & 'static str = 
+  "Cannot lock or unlock a range of 0 bytes";
+
+pub struct LockTable {
+  ranges: Vec<(usize, usize)>
+}
+impl LockTable {
+
+  pub fn new() -> LockTable {
+    LockTable {
+      ranges: Vec::new()
+    }
+  }
+
+  pub fn lock_range(&mut self, offset: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(len > 0, ERR_LOCK_NOTHING));
+    let end = try!(util::usize_add(offset, len));
+    try!(AssertionError::assert_not(
+      self.overlaps(offset, end), 
+      ERR_RANGE_LOCKED
+    ));
+    self.ranges.push((offset, end));
+    Ok(())
+  }
+
+  pub fn unlock_range(&mut self, offset: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(len > 0, ERR_LOCK_NOTHING));
+    let end = try!(util::usize_add(offset, len));
+    match self.ranges.iter().position(|&r| r == (offset, end)) {
+      Some(i) => {
+        self.ranges.remove(i);
+        Ok(())
+      },
+      None => Err(Error::Assertion(AssertionError::new(ERR_RANGE_NOT_LOCKED)))
+    }
+  }
+
+  pub fn is_locked(&self, offset: usize, len: usize) -> bool {
+    match offset.checked_add(len) {
+      Some(end) => self.overlaps(offset, end),
+      None => self.overlaps(offset, usize::max_value())
+    }
+  }
+
+  pub fn get_num_locks(&self) -> usize {
+    self.ranges.len()
+  }
+
+  fn overlaps(&self, start: usize, end: usize) -> bool {
+    self.ranges.iter().any(|&(s, e)| start < e && s < end)
+  }
+
+}
@@ -1,19 +1,35 @@
 use std::vec::Vec;
 use std::str;
 use alloc::heap;
-use std::{mem, ptr, slice};
+use std::{cmp, mem, ptr, slice, thread};
+use std::time::Duration;
+use uuid::Uuid;
 use storage::util;
 use error::{ Error, MemoryError, AssertionError };
 use storage::binary_storage;
-use storage::binary_storage::BinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WriteOutcome, WorkloadHint };
+
+pub static ERR_STALE_GENERATION: & 'static str = 
+  "Storage was reallocated after the slice was taken";
+
+// Smallest page size on the platforms we run on. Touching more often than
+// needed on systems with bigger pages is harmless.
+const PAGE_SIZE: usize = 4096;
 
 #[derive(Debug)]
 pub struct MemoryBinaryStorage {
   origin: *const u8,
   is_open: bool,
+  initial_capacity: usize,
   capacity: usize,
   expand_size: usize,
-  align: usize
+  align: usize,
+  alloc_retries: u32,
+  alloc_retry_delay: Duration,
+  generation: u64,
+  written: Vec<(usize, usize)>,
+  #[cfg(test)]
+  failing_allocs: u32
 }
 impl MemoryBinaryStorage {
 
@@ -40,13 +56,152 @@ impl MemoryBinaryStorage {
     Ok(MemoryBinaryStorage {
       origin: origin as *const u8,
       is_open: false,
+      initial_capacity: initial_capacity,
       capacity: initial_capacity,
       expand_size: expand_size,
-      align: align
+      align: align,
+      alloc_retries: 0,
+      alloc_retry_delay: Duration::from_millis(0),
+      generation: 0,
+      written: Vec::new(),
+      #[cfg(test)]
+      failing_allocs: 0
     })
 
   }
 
+  // Like new(), but takes expand_size from the hint unless one is given
+  pub fn with_hint(
+    initial_capacity: usize,
+    hint: WorkloadHint,
+    expand_size: Option<usize>
+  ) -> Result<MemoryBinaryStorage, Error> {
+    MemoryBinaryStorage::new(
+      initial_capacity,
+      expand_size.unwrap_or(hint.default_expand_size())
+    )
+  }
+
+  // Returns an open, independent copy of the current contents, giving a 
+  // point-in-time image that later writes to this storage won't affect
+  pub fn snapshot_reader(&self) -> Result<MemoryBinaryStorage, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let origin = unsafe { heap::allocate(self.capacity, self.align) };
+
+    if origin.is_null() { 
+      return Err(Error::Memory(MemoryError::new(binary_storage::ERR_STORAGE_ALLOC)));
+    }
+
+    unsafe { ptr::copy_nonoverlapping::<u8>(self.origin, origin, self.capacity) };
+
+    Ok(MemoryBinaryStorage {
+      origin: origin as *const u8,
+      is_open: true,
+      initial_capacity: self.initial_capacity,
+      capacity: self.capacity,
+      expand_size: self.expand_size,
+      align: self.align,
+      alloc_retries: self.alloc_retries,
+      alloc_retry_delay: self.alloc_retry_delay,
+      generation: 0,
+      written: self.written.clone(),
+      #[cfg(test)]
+      failing_allocs: 0
+    })
+  }
+
+  // Number of times expand() will retry a failed reallocation, waiting 
+  // for the given delay between attempts, before giving up
+  pub fn set_alloc_retries(&mut self, count: u32, delay: Duration) {
+    self.alloc_retries = count;
+    self.alloc_retry_delay = delay;
+  }
+
+  pub fn get_alloc_retries(&self) -> u32 {
+    self.alloc_retries
+  }
+
+  // Lets tests make the next n reallocations in expand() fail
+  #[cfg(test)]
+  pub(crate) fn fail_next_allocs(&mut self, n: u32) {
+    self.failing_allocs = n;
+  }
+
+  #[cfg(test)]
+  fn take_failing_alloc(&mut self) -> bool {
+    if self.failing_allocs == 0 { return false }
+    self.failing_allocs -= 1;
+    true
+  }
+
+  #[cfg(not(test))]
+  fn take_failing_alloc(&mut self) -> bool { false }
+
+  // Borrows bytes straight out of storage without copying. The slice only 
+  // lives as long as the borrow of self, so expand() and shrink(), which 
+  // can move the memory, can't run while it is held. Don't turn it into a 
+  // raw pointer that outlives the borrow; if something has to remember a 
+  // location across writes, keep the offset and check generation() instead.
+  pub fn r_slice(&self, offset: usize, len: usize) -> Result<&[u8], Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let end_offset = try!(util::usize_add(offset, len));
+    try!(util::usize_add(self.origin as usize, end_offset));
+
+    try!(AssertionError::assert_not(
+      end_offset > self.capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    Ok(unsafe { slice::from_raw_parts::<u8>(self.ptr(offset), len) })
+  }
+
+  // Counts reallocations. Memory taken before the count changed may have
+  // moved.
+  pub fn generation(&self) -> u64 {
+    self.generation
+  }
+
+  // Debug builds only. Errs if the storage has been reallocated since 
+  // generation was read, for catching code that holds on to memory from 
+  // r_slice() across an expand() or shrink().
+  #[cfg(debug_assertions)]
+  pub fn assert_generation(&self, generation: u64) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      generation == self.generation, 
+      ERR_STALE_GENERATION
+    ));
+    Ok(())
+  }
+
+  // Touches one byte in every page of the allocated capacity so the OS maps
+  // them all in now instead of faulting them in on first use, trading the
+  // time up front for steadier latency later. Each byte is read and written
+  // back as-is, so the contents don't change.
+  pub fn prefault(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let mut offset = 0;
+    while offset < self.capacity {
+      unsafe {
+        let p = self.ptr_mut::<u8>(offset);
+        ptr::write_volatile(p, ptr::read_volatile(p));
+      }
+      offset += PAGE_SIZE;
+    }
+    Ok(())
+  }
+
   fn ptr<T>(&self, offset: usize) -> *const T {
     (self.origin as usize + offset) as *const T
   }
@@ -66,6 +221,27 @@ impl MemoryBinaryStorage {
 
     try!(self.expand(end_offset));
     unsafe { ptr::write(self.ptr_mut(offset), data) }
+    util::add_range(&mut self.written, offset, end_offset);
+    Ok(())
+  }
+
+  fn write_array<T: Copy>(&mut self, offset: usize, values: &[T]) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    if values.len() == 0 { return Ok(()) }
+
+    let len = try!(util::usize_mul(values.len(), mem::size_of::<T>()));
+    let end_offset = try!(util::usize_add(offset, len));
+    try!(util::usize_add(self.origin as usize, end_offset));
+
+    try!(self.expand(end_offset));
+    for (i, v) in values.iter().enumerate() {
+      unsafe { ptr::write(self.ptr_mut(offset + i * mem::size_of::<T>()), *v) }
+    }
+    util::add_range(&mut self.written, offset, end_offset);
     Ok(())
   }
 
@@ -86,6 +262,28 @@ impl MemoryBinaryStorage {
     unsafe { Ok(ptr::read(self.ptr(offset))) }
   }
 
+  fn read_array<T: Copy>(&self, offset: usize, count: usize) -> Result<Vec<T>, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let len = try!(util::usize_mul(count, mem::size_of::<T>()));
+    let end_offset = try!(util::usize_add(offset, len));
+    try!(util::usize_add(self.origin as usize, end_offset));
+
+    try!(AssertionError::assert_not(
+      end_offset > self.capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    let mut res = Vec::with_capacity(count);
+    for i in 0..count {
+      res.push(unsafe { ptr::read(self.ptr(offset + i * mem::size_of::<T>())) });
+    }
+    Ok(res)
+  }
+
   fn check_params(
     expand_size: usize,
     initial_capacity: usize,
@@ -184,6 +382,18 @@ impl BinaryStorage for MemoryBinaryStorage {
     data: u64
   ) -> Result<(), Error> { self.write(offset, data) }
 
+  fn w_i128(
+    &mut self, 
+    offset: usize, 
+    data: i128
+  ) -> Result<(), Error> { self.write(offset, data) }
+
+  fn w_u128(
+    &mut self, 
+    offset: usize, 
+    data: u128
+  ) -> Result<(), Error> { self.write(offset, data) }
+
   fn w_f32(
     &mut self, 
     offset: usize, 
@@ -217,6 +427,7 @@ impl BinaryStorage for MemoryBinaryStorage {
       slice::from_raw_parts_mut(self.ptr_mut(offset), data.len()) 
     };
     dest.clone_from_slice(data);
+    util::add_range(&mut self.written, offset, end_offset);
     Ok(())
   }
 
@@ -224,6 +435,43 @@ impl BinaryStorage for MemoryBinaryStorage {
     self.w_bytes(offset, data.as_bytes()) 
   }
 
+  fn w_bytes_reporting(
+    &mut self, 
+    offset: usize, 
+    data: &[u8]
+  ) -> Result<WriteOutcome, Error> {
+    let old_capacity = try!(self.get_capacity());
+    try!(self.w_bytes(offset, data));
+    Ok(WriteOutcome {
+      bytes_written: data.len(),
+      expanded: self.capacity > old_capacity,
+      capacity: self.capacity
+    })
+  }
+
+  fn w_uuid(&mut self, offset: usize, data: Uuid) -> Result<(), Error> {
+    self.w_bytes(offset, data.as_bytes())
+  }
+
+  fn w_blob(&mut self, offset: usize, data: &[u8]) -> Result<usize, Error> {
+    try!(AssertionError::assert(
+      data.len() <= u32::max_value() as usize, 
+      binary_storage::ERR_BLOB_TOO_LONG
+    ));
+    let data_offset = try!(util::usize_add(offset, mem::size_of::<u32>()));
+    try!(self.w_u32(offset, data.len() as u32));
+    if data.len() > 0 { try!(self.w_bytes(data_offset, data)); }
+    Ok(mem::size_of::<u32>() + data.len())
+  }
+
+  fn w_u32_array(&mut self, offset: usize, values: &[u32]) -> Result<(), Error> {
+    self.write_array(offset, values)
+  }
+
+  fn w_u64_array(&mut self, offset: usize, values: &[u64]) -> Result<(), Error> {
+    self.write_array(offset, values)
+  }
+
 
   fn r_i8(&self, offset: usize) -> Result<i8, Error> { self.read(offset) }
   fn r_i16(&self, offset: usize) -> Result<i16, Error> { self.read(offset) }
@@ -235,18 +483,45 @@ impl BinaryStorage for MemoryBinaryStorage {
   fn r_u32(&self, offset: usize) -> Result<u32, Error> { self.read(offset) }
   fn r_u64(&self, offset: usize) -> Result<u64, Error> { self.read(offset) }
 
+  fn r_i128(&self, offset: usize) -> Result<i128, Error> { self.read(offset) }
+  fn r_u128(&self, offset: usize) -> Result<u128, Error> { self.read(offset) }
+
+  fn r_u32_array(&self, offset: usize, count: usize) -> Result<Vec<u32>, Error> {
+    self.read_array(offset, count)
+  }
+
+  fn r_u64_array(&self, offset: usize, count: usize) -> Result<Vec<u64>, Error> {
+    self.read_array(offset, count)
+  }
+
+  fn r_u64_opt(&self, offset: usize) -> Result<Option<u64>, Error> {
+    let end_offset = try!(util::usize_add(offset, mem::size_of::<u64>()));
+    if !util::overlaps_range(self.written.as_slice(), offset, end_offset) &&
+      try!(self.is_filled(Some(offset), Some(end_offset), 0x0)) {
+      Ok(None)
+    } else {
+      Ok(Some(try!(self.r_u64(offset))))
+    }
+  }
+
   fn r_f32(&self, offset: usize) -> Result<f32, Error> { self.read(offset) }
   fn r_f64(&self, offset: usize) -> Result<f64, Error> { self.read(offset) }
 
   fn r_bool(&self, offset: usize) -> Result<bool, Error> { self.read(offset) }
 
   fn r_bytes(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mut dst = vec![0; len];
+    try!(self.r_bytes_into(offset, dst.as_mut_slice()));
+    Ok(dst)
+  }
+
+  fn r_bytes_into(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
     try!(AssertionError::assert(
       self.is_open, 
       binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
     ));
 
-    let end_offset = try!(util::usize_add(offset, len));
+    let end_offset = try!(util::usize_add(offset, buf.len()));
     try!(util::usize_add(self.origin as usize, end_offset));
 
     try!(AssertionError::assert_not(
@@ -254,10 +529,9 @@ impl BinaryStorage for MemoryBinaryStorage {
       binary_storage::ERR_READ_PAST_END
     ));
 
-    let src = unsafe { slice::from_raw_parts::<u8>(self.ptr(offset), len) };
-    let mut dst = vec![0; len];
-    dst.copy_from_slice(src);
-    Ok(dst)
+    let src = unsafe { slice::from_raw_parts::<u8>(self.ptr(offset), buf.len()) };
+    buf.copy_from_slice(src);
+    Ok(buf.len())
   }
 
   fn r_str(&self, offset: usize, len: usize) -> Result<String, Error> {
@@ -265,6 +539,53 @@ impl BinaryStorage for MemoryBinaryStorage {
     Ok(try!(str::from_utf8(b.as_slice())).to_string())
   }
 
+  fn r_uuid(&self, offset: usize) -> Result<Uuid, Error> {
+    let b = try!(self.r_bytes(offset, mem::size_of::<Uuid>()));
+    // r_bytes() returns exactly the requested length, so this can't fail
+    Ok(Uuid::from_bytes(b.as_slice()).unwrap())
+  }
+
+  fn r_blob(&self, offset: usize) -> Result<Vec<u8>, Error> {
+    let len = try!(self.r_u32(offset)) as usize;
+    let data_offset = try!(util::usize_add(offset, mem::size_of::<u32>()));
+    if len == 0 { return Ok(Vec::new()) }
+    self.r_bytes(data_offset, len)
+  }
+
+  fn read_all(&self) -> Result<Vec<u8>, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    self.r_bytes(0, self.capacity)
+  }
+
+  fn pread(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    self.r_bytes(offset, len)
+  }
+
+  fn pwrite(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    self.w_bytes(offset, data)
+  }
+
+  fn valid_len_from(&self, offset: usize) -> Result<usize, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    try!(AssertionError::assert(
+      offset < self.capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    let data = try!(self.r_bytes(offset, self.capacity - offset));
+    match data.iter().rposition(|b| *b != 0x0) {
+      Some(i) => Ok(i + 1),
+      None => Ok(0)
+    }
+  }
+
 
   fn fill(
     &mut self, 
@@ -302,6 +623,7 @@ impl BinaryStorage for MemoryBinaryStorage {
         end_offset - start_offset
       ) 
     }
+    util::add_range(&mut self.written, start_offset, end_offset);
     Ok(())
   }
 
@@ -345,14 +667,91 @@ impl BinaryStorage for MemoryBinaryStorage {
     Ok(true)
   }
 
+  fn crc32(&self, start: Option<usize>, end: Option<usize>) -> Result<u32, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let start_offset = match start { Some(s) => s, None => 0 };
+    let end_offset = match end { Some(end) => end, None => self.capacity };
+
+    try!(AssertionError::assert(
+      start_offset < self.capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    try!(AssertionError::assert(
+      end_offset <= self.capacity,
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    try!(AssertionError::assert(
+      end_offset > start_offset,
+      binary_storage::ERR_READ_NOTHING
+    ));
+
+    let data = unsafe {
+      slice::from_raw_parts::<u8>(self.ptr(start_offset), end_offset - start_offset)
+    };
+
+    Ok(util::crc32(data))
+  }
+
+  fn find_bytes(&self, pattern: &[u8], start: usize) -> Result<Option<usize>, Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(binary_storage::check_find_bytes(pattern, start, self.capacity));
+
+    let data = unsafe {
+      slice::from_raw_parts::<u8>(self.ptr(start), self.capacity - start)
+    };
+
+    Ok(binary_storage::find_in(data, pattern).map(|pos| start + pos))
+  }
+
+  fn copy(&mut self, from: usize, to: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    let end_offset = try!(binary_storage::check_copy(from, to, len, self.capacity));
+    try!(util::usize_add(self.origin as usize, end_offset));
+
+    try!(self.expand(end_offset));
+
+    unsafe { ptr::copy::<u8>(self.ptr(from), self.ptr_mut(to), len) };
+    util::add_range(&mut self.written, to, end_offset);
+    Ok(())
+  }
+
+  fn swap_ranges(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(binary_storage::check_swap_ranges(a, b, len, self.capacity));
+
+    for i in 0..len {
+      unsafe { ptr::swap::<u8>(self.ptr_mut(a + i), self.ptr_mut(b + i)) };
+    }
+    util::add_range(&mut self.written, a, a + len);
+    util::add_range(&mut self.written, b, b + len);
+    Ok(())
+  }
+
   fn get_expand_size(&self) -> usize {
     self.expand_size
   }
 
   fn set_expand_size(&mut self, expand_size: usize) -> Result<(), Error> {
+    // The capacity stops being a power of 2 once it grows by a small 
+    // expand_size, so check against the initial capacity as new() did
     try!(MemoryBinaryStorage::check_params(
       expand_size,
-      self.capacity
+      self.initial_capacity
     ));
 
     self.expand_size = expand_size;
@@ -381,7 +780,75 @@ impl BinaryStorage for MemoryBinaryStorage {
     // already have enough room.
     if new_capacity <= self.capacity { return Ok(()) }
 
-    // Allocate new memory
+    // Allocate new memory, retrying up to alloc_retries times on failure
+    let mut attempts = 0;
+    let mut ptr;
+    loop {
+      ptr = if self.take_failing_alloc() { ptr::null_mut() } else { 
+        unsafe { 
+          heap::reallocate(
+            self.origin as *mut u8,
+            self.capacity,
+            new_capacity,
+            self.align
+          )
+        }
+      };
+      if !ptr.is_null() || attempts >= self.alloc_retries { break }
+      attempts += 1;
+      thread::sleep(self.alloc_retry_delay);
+    }
+
+    if ptr.is_null() {
+      return Err(
+        Error::Assertion(AssertionError::new(binary_storage::ERR_STORAGE_ALLOC))
+      );
+    } else {
+      // Set the new capacity and pointer, remembering the old capacity
+      let old_capacity = self.capacity;
+      self.origin = ptr as *const u8;
+      self.capacity = new_capacity;
+      self.generation += 1;
+      // Initialize the new storage (set all bytes to 0x00). Not done with
+      // fill(), since that would count it as written.
+      unsafe { 
+        ptr::write_bytes::<u8>(
+          self.ptr_mut(old_capacity), 
+          0x0, 
+          new_capacity - old_capacity
+        ) 
+      }
+      // Return Ok to indicate that allocation was successful
+      Ok(())
+    }
+  }
+
+  fn truncate(&mut self, new_capacity: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert(
+      new_capacity <= self.capacity, 
+      binary_storage::ERR_TRUNCATE_PAST_END
+    ));
+    self.shrink(new_capacity)
+  }
+
+  fn shrink(&mut self, max_capacity: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let shrink_increments = cmp::max(
+      (try!(util::usize_add(max_capacity, self.expand_size)) - 1) / self.expand_size,
+      1
+    );
+    let new_capacity = shrink_increments * self.expand_size;
+
+    if new_capacity >= self.capacity { return Ok(()) }
+
     let ptr = unsafe { 
       heap::reallocate(
         self.origin as *mut u8,
@@ -395,16 +862,14 @@ impl BinaryStorage for MemoryBinaryStorage {
       return Err(
         Error::Assertion(AssertionError::new(binary_storage::ERR_STORAGE_ALLOC))
       );
-    } else {
-      // Set the new capacity and pointer, remembering the old capacity
-      let old_capacity = self.capacity;
-      self.origin = ptr as *const u8;
-      self.capacity = new_capacity;
-      // Initialize the new storage (set all bytes to 0x00)
-      try!(self.fill(Some(old_capacity), Some(new_capacity), 0x0));
-      // Return Ok to indicate that allocation was successful
-      Ok(())
     }
+
+    let old_capacity = self.capacity;
+    self.origin = ptr as *const u8;
+    self.capacity = new_capacity;
+    self.generation += 1;
+    util::cut_range(&mut self.written, new_capacity, old_capacity - new_capacity);
+    Ok(())
   }
 
   fn get_capacity(&self) -> Result<usize, Error> {
@@ -415,6 +880,14 @@ impl BinaryStorage for MemoryBinaryStorage {
     Ok(self.capacity)
   }
 
+  fn sync(&mut self) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open, 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    Ok(())
+  }
+
   fn is_open(&self) -> bool {
     self.is_open
   }
@@ -6,7 +6,15 @@ pub mod memory_binary_storage;
 pub mod file_binary_storage;
 pub mod transactional_storage;
 pub mod journal;
+pub mod lock_table;
+pub mod replication;
+pub mod record_schema;
+pub mod ring_journal;
+pub mod bitmap;
+pub mod segment_pool;
+pub mod xor_storage;
+pub mod binary_storage_reader;
 //pub mod ptr_index;
 //pub mod bp_tree;
-//pub mod bplus_tree;
+pub mod bplus_tree;
 //pub mod b_plus_tree;
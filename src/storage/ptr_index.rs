@@ -4,9 +4,10 @@ use std::ops::Index;
 use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
 
 use error::{ Error, AssertionError };
-use storage::journal::{ PRE_DATA_LEN, POST_DATA_LEN };
+use storage::journal::{ PRE_DATA_LEN, post_data_len };
 use storage::journal::Journal;
 use storage::binary_storage::BinaryStorage;
+use storage::util::ChecksumAlgo;
 
 pub static ERR_NOT_INDEXED: &'static str = "Record is not present in index";
 
@@ -47,7 +48,7 @@ impl<T: BinaryStorage + Sized> PtrIndex<T> {
     try!(AssertionError::assert(self.record_count > n, ERR_NOT_INDEXED));
 
     try!(self.journal.jump_to(
-      (PRE_DATA_LEN + 16 + POST_DATA_LEN) * n
+      (PRE_DATA_LEN + 16 + post_data_len(ChecksumAlgo::Xor)) * n
     ));
 
     match self.journal.read() {
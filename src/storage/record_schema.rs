@@ -0,0 +1,185 @@
+use error::{ Error, AssertionError };
+use storage::binary_storage::BinaryStorage;
+use storage::util;
+
+pub static ERR_DUPLICATE_FIELD: & 'static str = 
+  "A field with this name already exists in the schema";
+pub static ERR_NO_SUCH_FIELD: & 'static str = 
+  "No field with this name exists in the schema";
+pub static ERR_FIELD_TYPE_MISMATCH: & 'static str = 
+  "Field is not of the requested type";
+pub static ERR_FIELD_WIDTH_MISMATCH: & 'static str = 
+  "Data length does not match the width of the field";
+pub static ERR_FIELD_TOO_SMALL: & 'static str = 
+  "Field width must be greater than zero";
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum FieldType {
+  U8,
+  U16,
+  U32,
+  U64,
+  Bytes(usize)
+}
+impl FieldType {
+
+  pub fn width(&self) -> usize {
+    match *self {
+      FieldType::U8 => 1,
+      FieldType::U16 => 2,
+      FieldType::U32 => 4,
+      FieldType::U64 => 8,
+      FieldType::Bytes(n) => n
+    }
+  }
+
+}
+
+struct Field {
+  name: String,
+  field_type: FieldType,
+  offset: usize
+}
+
+// Describes a fixed-layout record as a list of named fields laid out back
+// to back in the order they were added, so callers can read and write 
+// fields by name instead of by hand-computed offset
+pub struct RecordSchema {
+  fields: Vec<Field>,
+  len: usize
+}
+impl RecordSchema {
+
+  pub fn new() -> RecordSchema {
+    RecordSchema {
+      fields: Vec::new(),
+      len: 0
+    }
+  }
+
+  pub fn add_field(&mut self, name: &str, field_type: FieldType) -> Result<(), Error> {
+    try!(AssertionError::assert(field_type.width() > 0, ERR_FIELD_TOO_SMALL));
+    try!(AssertionError::assert_not(
+      self.fields.iter().any(|f| f.name == name),
+      ERR_DUPLICATE_FIELD
+    ));
+
+    let offset = self.len;
+    self.len = try!(util::usize_add(offset, field_type.width()));
+    self.fields.push(Field {
+      name: name.to_string(),
+      field_type: field_type,
+      offset: offset
+    });
+    Ok(())
+  }
+
+  pub fn offset_of(&self, name: &str) -> Result<usize, Error> {
+    Ok(try!(self.field(name)).offset)
+  }
+
+  pub fn type_of(&self, name: &str) -> Result<FieldType, Error> {
+    Ok(try!(self.field(name)).field_type)
+  }
+
+  pub fn len(&self) -> usize {
+    self.len
+  }
+
+  pub fn get_num_fields(&self) -> usize {
+    self.fields.len()
+  }
+
+  fn field(&self, name: &str) -> Result<&Field, Error> {
+    match self.fields.iter().find(|f| f.name == name) {
+      Some(f) => Ok(f),
+      None => Err(Error::Assertion(AssertionError::new(ERR_NO_SUCH_FIELD)))
+    }
+  }
+
+  fn typed_offset(
+    &self, 
+    base: usize, 
+    name: &str, 
+    field_type: FieldType
+  ) -> Result<usize, Error> {
+    let field = try!(self.field(name));
+    try!(AssertionError::assert(
+      field.field_type == field_type, 
+      ERR_FIELD_TYPE_MISMATCH
+    ));
+    Ok(try!(util::usize_add(base, field.offset)))
+  }
+
+  pub fn get_u8<T: BinaryStorage>(
+    &self, storage: &T, base: usize, name: &str
+  ) -> Result<u8, Error> {
+    storage.r_u8(try!(self.typed_offset(base, name, FieldType::U8)))
+  }
+
+  pub fn get_u16<T: BinaryStorage>(
+    &self, storage: &T, base: usize, name: &str
+  ) -> Result<u16, Error> {
+    storage.r_u16(try!(self.typed_offset(base, name, FieldType::U16)))
+  }
+
+  pub fn get_u32<T: BinaryStorage>(
+    &self, storage: &T, base: usize, name: &str
+  ) -> Result<u32, Error> {
+    storage.r_u32(try!(self.typed_offset(base, name, FieldType::U32)))
+  }
+
+  pub fn get_u64<T: BinaryStorage>(
+    &self, storage: &T, base: usize, name: &str
+  ) -> Result<u64, Error> {
+    storage.r_u64(try!(self.typed_offset(base, name, FieldType::U64)))
+  }
+
+  pub fn get_bytes<T: BinaryStorage>(
+    &self, storage: &T, base: usize, name: &str
+  ) -> Result<Vec<u8>, Error> {
+    let field_type = try!(self.type_of(name));
+    let width = field_type.width();
+    storage.r_bytes(
+      try!(self.typed_offset(base, name, FieldType::Bytes(width))),
+      width
+    )
+  }
+
+  pub fn set_u8<T: BinaryStorage>(
+    &self, storage: &mut T, base: usize, name: &str, data: u8
+  ) -> Result<(), Error> {
+    storage.w_u8(try!(self.typed_offset(base, name, FieldType::U8)), data)
+  }
+
+  pub fn set_u16<T: BinaryStorage>(
+    &self, storage: &mut T, base: usize, name: &str, data: u16
+  ) -> Result<(), Error> {
+    storage.w_u16(try!(self.typed_offset(base, name, FieldType::U16)), data)
+  }
+
+  pub fn set_u32<T: BinaryStorage>(
+    &self, storage: &mut T, base: usize, name: &str, data: u32
+  ) -> Result<(), Error> {
+    storage.w_u32(try!(self.typed_offset(base, name, FieldType::U32)), data)
+  }
+
+  pub fn set_u64<T: BinaryStorage>(
+    &self, storage: &mut T, base: usize, name: &str, data: u64
+  ) -> Result<(), Error> {
+    storage.w_u64(try!(self.typed_offset(base, name, FieldType::U64)), data)
+  }
+
+  pub fn set_bytes<T: BinaryStorage>(
+    &self, storage: &mut T, base: usize, name: &str, data: &[u8]
+  ) -> Result<(), Error> {
+    let width = try!(self.type_of(name)).width();
+    let offset = try!(self.typed_offset(base, name, FieldType::Bytes(width)));
+    try!(AssertionError::assert(
+      data.len() == width,
+      ERR_FIELD_WIDTH_MISMATCH
+    ));
+    storage.w_bytes(offset, data)
+  }
+
+}
@@ -0,0 +1,76 @@
+use std::cmp;
+use std::io::Cursor;
+
+use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
+
+use error::{ Error, AssertionError };
+use storage::binary_storage::BinaryStorage;
+use storage::journal::Journal;
+use storage::util;
+
+pub static ERR_CHUNK_SIZE_TOO_SMALL: & 'static str = 
+  "Chunk size must be greater than zero";
+pub static ERR_BAD_PATCH_RECORD: & 'static str = 
+  "Record is too short to be an (offset, bytes) patch";
+
+// Each patch record is the u64 offset of the patch followed by the bytes
+// to write at that offset
+const OFFSET_LEN: usize = 8;
+
+
+// Copies the entire contents of src into the journal as a series of 
+// committed patch records of at most chunk bytes each, returning the number 
+// of records written
+pub fn to_journal<S: BinaryStorage, T: BinaryStorage + Sized>(
+  src: &S,
+  journal: &mut Journal<T>,
+  chunk: usize
+) -> Result<u64, Error> {
+  try!(AssertionError::assert(chunk > 0, ERR_CHUNK_SIZE_TOO_SMALL));
+
+  let capacity = try!(src.get_capacity());
+  let mut offset = 0;
+  let mut count = 0;
+
+  while offset < capacity {
+    let len = cmp::min(chunk, capacity - offset);
+    let data = try!(src.r_bytes(offset, len));
+
+    let mut record = Vec::with_capacity(OFFSET_LEN + len);
+    try!(record.write_u64::<LittleEndian>(offset as u64));
+    record.extend_from_slice(data.as_slice());
+
+    try!(journal.write(record.as_slice()));
+    try!(journal.commit());
+
+    offset += len;
+    count += 1;
+  }
+
+  Ok(count)
+}
+
+// Applies every committed patch record in the journal to dst, in order, 
+// returning the number of records applied
+pub fn replay_into<T: BinaryStorage + Sized, S: BinaryStorage>(
+  journal: &mut Journal<T>,
+  dst: &mut S
+) -> Result<u64, Error> {
+  let mut count = 0;
+
+  journal.reset();
+  for record in journal {
+    try!(AssertionError::assert(
+      record.len() > OFFSET_LEN, 
+      ERR_BAD_PATCH_RECORD
+    ));
+
+    let mut rdr = Cursor::new(&record[0..OFFSET_LEN]);
+    let offset = try!(util::u64_as_usize(try!(rdr.read_u64::<LittleEndian>())));
+    try!(dst.w_bytes(offset, &record[OFFSET_LEN..]));
+
+    count += 1;
+  }
+
+  Ok(count)
+}
@@ -0,0 +1,252 @@
+use std::cmp;
+use std::io::Cursor;
+
+use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
+
+use error::{ Error, AssertionError };
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+use storage::util;
+
+pub static ERR_RING_SIZE_TOO_SMALL: & 'static str =
+  "Ring size must be greater than zero";
+pub static ERR_NOTHING_TO_WRITE: & 'static str =
+  "Cannot write a record with no data";
+pub static ERR_RECORD_TOO_BIG_FOR_RING: & 'static str =
+  "Record is too big to fit in the ring";
+pub static ERR_RING_HEADER_INVALID: & 'static str =
+  "Stored ring header does not fit the ring size";
+pub static ERR_RING_CORRUPT: & 'static str =
+  "Record lengths in the ring do not match the stored header";
+
+// The head (offset of the oldest record in the ring), the number of bytes
+// used, and the number of records are kept at the start of storage so the
+// valid window survives a close and reopen
+const HEAD_OFFSET: usize = 0;
+const USED_OFFSET: usize = 8;
+const COUNT_OFFSET: usize = 16;
+const HEADER_LEN: usize = 24;
+
+// Each record is a u32 length followed by at least one byte of data
+const LEN_LEN: usize = 4;
+const MIN_REC_LEN: usize = LEN_LEN + 1;
+
+
+// A fixed-size log for data that can be thrown away once it gets old. The
+// ring never grows; once it is full, writing a record drops as many of the
+// oldest records as it takes to make room. Records may wrap around the end
+// of the ring.
+pub struct RingJournal<T: BinaryStorage + Sized> {
+  storage: T,
+  ring_size: usize,
+  head: usize,
+  used: usize,
+  record_count: usize,
+  read_offset: usize,
+  read_idx: usize
+}
+impl<T: BinaryStorage + Sized> RingJournal<T> {
+
+  pub fn new(storage: T, ring_size: usize) -> Result<RingJournal<T>, Error> {
+    try!(AssertionError::assert(ring_size > 0, ERR_RING_SIZE_TOO_SMALL));
+    Ok(RingJournal {
+      storage: storage,
+      ring_size: ring_size,
+      head: 0,
+      used: 0,
+      record_count: 0,
+      read_offset: 0,
+      read_idx: 0
+    })
+  }
+
+  pub fn open(&mut self) -> Result<(), Error> {
+    try!(self.storage.open());
+    let end = try!(util::usize_add(HEADER_LEN, self.ring_size));
+    try!(self.storage.expand(end));
+
+    match self.read_header() {
+      Ok(()) => {
+        self.reset();
+        Ok(())
+      },
+      Err(e) => {
+        try!(self.storage.close());
+        Err(e)
+      }
+    }
+  }
+
+  // Loads the stored header, checking it could have been written by a ring
+  // of this size: the head is inside the ring, the used space fits, and 
+  // there are as many records as the used space could hold, but no more
+  fn read_header(&mut self) -> Result<(), Error> {
+    let head = try!(util::u64_as_usize(try!(self.storage.r_u64(HEAD_OFFSET))));
+    let used = try!(util::u64_as_usize(try!(self.storage.r_u64(USED_OFFSET))));
+    let record_count =
+      try!(util::u64_as_usize(try!(self.storage.r_u64(COUNT_OFFSET))));
+
+    try!(AssertionError::assert(
+      head < self.ring_size && 
+        used <= self.ring_size &&
+        (record_count == 0) == (used == 0) &&
+        record_count <= used / MIN_REC_LEN,
+      ERR_RING_HEADER_INVALID
+    ));
+
+    self.head = head;
+    self.used = used;
+    self.record_count = record_count;
+    Ok(())
+  }
+
+  pub fn close(&mut self) -> Result<(), Error> {
+    self.storage.close()
+  }
+
+  pub fn is_open(&self) -> bool {
+    self.storage.is_open()
+  }
+
+  // Appends a record, overwriting the oldest records if the ring is full.
+  // Resets the read position, since the records it pointed into may be gone.
+  pub fn write(&mut self, data: &[u8]) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    try!(AssertionError::assert(data.len() > 0, ERR_NOTHING_TO_WRITE));
+
+    let rec_len = try!(util::usize_add(LEN_LEN, data.len()));
+    try!(AssertionError::assert(
+      rec_len <= self.ring_size && data.len() <= u32::max_value() as usize,
+      ERR_RECORD_TOO_BIG_FOR_RING
+    ));
+
+    // Nothing changes if a dropped record turns out to be corrupt
+    let (head, used, record_count) = (self.head, self.used, self.record_count);
+    while self.ring_size - self.used < rec_len {
+      if let Err(e) = self.drop_oldest() {
+        self.head = head;
+        self.used = used;
+        self.record_count = record_count;
+        return Err(e);
+      }
+    }
+
+    let tail = (self.head + self.used) % self.ring_size;
+    let mut record = Vec::with_capacity(rec_len);
+    try!(record.write_u32::<LittleEndian>(data.len() as u32));
+    record.extend_from_slice(data);
+    try!(self.w_ring(tail, record.as_slice()));
+
+    self.used += rec_len;
+    self.record_count += 1;
+    try!(self.write_header());
+
+    self.reset();
+    Ok(())
+  }
+
+  fn drop_oldest(&mut self) -> Result<(), Error> {
+    let len = try!(self.r_len(self.head));
+    let rec_len = try!(util::usize_add(LEN_LEN, len));
+    let used = match self.used.checked_sub(rec_len) {
+      Some(u) => u,
+      None => return Err(Error::Assertion(AssertionError::new(ERR_RING_CORRUPT)))
+    };
+    let record_count = match self.record_count.checked_sub(1) {
+      Some(c) => c,
+      None => return Err(Error::Assertion(AssertionError::new(ERR_RING_CORRUPT)))
+    };
+    self.head = (self.head + rec_len) % self.ring_size;
+    self.used = used;
+    self.record_count = record_count;
+    Ok(())
+  }
+
+  // The header goes out in a single write, after the record data, so the 
+  // stored head, used space and count always change together
+  fn write_header(&mut self) -> Result<(), Error> {
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    try!(header.write_u64::<LittleEndian>(self.head as u64));
+    try!(header.write_u64::<LittleEndian>(self.used as u64));
+    try!(header.write_u64::<LittleEndian>(self.record_count as u64));
+    self.storage.w_bytes(HEAD_OFFSET, header.as_slice())
+  }
+
+  // Writes bytes starting at a position in the ring, wrapping around to the
+  // start of the ring if they run past the end
+  fn w_ring(&mut self, pos: usize, data: &[u8]) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      pos < self.ring_size && data.len() <= self.ring_size,
+      ERR_RING_CORRUPT
+    ));
+    let first_len = cmp::min(data.len(), self.ring_size - pos);
+    try!(self.storage.w_bytes(HEADER_LEN + pos, &data[..first_len]));
+    if first_len < data.len() {
+      try!(self.storage.w_bytes(HEADER_LEN, &data[first_len..]));
+    }
+    Ok(())
+  }
+
+  fn r_ring(&self, pos: usize, len: usize) -> Result<Vec<u8>, Error> {
+    try!(AssertionError::assert(
+      pos < self.ring_size && len <= self.ring_size,
+      ERR_RING_CORRUPT
+    ));
+    let first_len = cmp::min(len, self.ring_size - pos);
+    let mut data = try!(self.storage.r_bytes(HEADER_LEN + pos, first_len));
+    if first_len < len {
+      data.extend(try!(self.storage.r_bytes(HEADER_LEN, len - first_len)));
+    }
+    Ok(data)
+  }
+
+  fn r_len(&self, pos: usize) -> Result<usize, Error> {
+    let buf = try!(self.r_ring(pos, LEN_LEN));
+    let len = try!(Cursor::new(buf).read_u32::<LittleEndian>());
+    Ok(len as usize)
+  }
+
+  // Moves the read position back to the oldest record in the ring
+  pub fn reset(&mut self) {
+    self.read_offset = self.head;
+    self.read_idx = 0;
+  }
+
+  pub fn read(&self) -> Result<Option<Vec<u8>>, Error> {
+    try!(AssertionError::assert(
+      self.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    if self.read_idx >= self.record_count { return Ok(None) }
+
+    let len = try!(self.r_len(self.read_offset));
+    let data_pos = (self.read_offset + LEN_LEN) % self.ring_size;
+    Ok(Some(try!(self.r_ring(data_pos, len))))
+  }
+
+  pub fn record_count(&self) -> usize { self.record_count }
+
+  pub fn used(&self) -> usize { self.used }
+
+  pub fn get_ring_size(&self) -> usize { self.ring_size }
+
+}
+impl<T: BinaryStorage + Sized> Iterator for RingJournal<T> {
+
+  type Item = Vec<u8>;
+
+  fn next(&mut self) -> Option<Vec<u8>> {
+    match self.read() {
+      Ok(Some(v)) => {
+        self.read_offset =
+          (self.read_offset + LEN_LEN + v.len()) % self.ring_size;
+        self.read_idx += 1;
+        Some(v)
+      },
+      _ => None
+    }
+  }
+}
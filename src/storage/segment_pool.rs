@@ -0,0 +1,93 @@
+use std::collections::VecDeque;
+
+use error::{ Error, AssertionError };
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+
+pub static ERR_MAX_OPEN_TOO_SMALL: & 'static str =
+  "Maximum number of open segments must be greater than zero";
+pub static ERR_NO_SUCH_SEGMENT: & 'static str =
+  "No segment exists at that index";
+
+
+// Holds the storage for every segment of a segmented log but keeps at most
+// max_open of them open at once, so a log with thousands of segment files
+// doesn't run out of file descriptors. Asking for a closed segment opens
+// it, closing whichever open segment was used least recently.
+pub struct SegmentPool<T: BinaryStorage + Sized> {
+  segments: Vec<T>,
+  max_open: usize,
+  // Indices of the open segments, least recently used first
+  open: VecDeque<usize>
+}
+impl<T: BinaryStorage + Sized> SegmentPool<T> {
+
+  pub fn new(max_open: usize) -> Result<SegmentPool<T>, Error> {
+    try!(AssertionError::assert(max_open > 0, ERR_MAX_OPEN_TOO_SMALL));
+    Ok(SegmentPool {
+      segments: Vec::new(),
+      max_open: max_open,
+      open: VecDeque::new()
+    })
+  }
+
+  // Adds a closed segment to the end of the pool and returns its index
+  pub fn add_segment(&mut self, storage: T) -> Result<usize, Error> {
+    try!(AssertionError::assert_not(
+      storage.is_open(),
+      binary_storage::ERR_OPERATION_INVALID_WHEN_OPEN
+    ));
+    self.segments.push(storage);
+    Ok(self.segments.len() - 1)
+  }
+
+  // Returns the segment at idx, opening it first if it isn't open
+  pub fn segment(&mut self, idx: usize) -> Result<&mut T, Error> {
+    try!(AssertionError::assert(idx < self.segments.len(), ERR_NO_SUCH_SEGMENT));
+
+    match self.open.iter().position(|&i| i == idx) {
+      Some(pos) => {
+        self.open.remove(pos);
+      },
+      None => {
+        if self.open.len() >= self.max_open {
+          try!(self.close_least_recent());
+        }
+        try!(self.segments[idx].open());
+      }
+    };
+    self.open.push_back(idx);
+
+    Ok(&mut self.segments[idx])
+  }
+
+  // The segment only leaves the open list once it has actually closed, so
+  // a failed close leaves the pool still tracking it
+  fn close_least_recent(&mut self) -> Result<(), Error> {
+    let i = match self.open.front() {
+      Some(&i) => i,
+      None => return Ok(())
+    };
+    try!(self.segments[i].close());
+    self.open.pop_front();
+    Ok(())
+  }
+
+  pub fn close_all(&mut self) -> Result<(), Error> {
+    while !self.open.is_empty() {
+      try!(self.close_least_recent());
+    }
+    Ok(())
+  }
+
+  pub fn is_segment_open(&self, idx: usize) -> bool {
+    self.open.contains(&idx)
+  }
+
+  pub fn open_count(&self) -> usize { self.open.len() }
+
+  pub fn get_max_open(&self) -> usize { self.max_open }
+
+  pub fn num_segments(&self) -> usize { self.segments.len() }
+
+}
@@ -1,7 +1,9 @@
 use std::mem::size_of;
+use std::collections::HashMap;
+use uuid::Uuid;
 use error::{ Error, AssertionError };
 use storage::binary_storage;
-use storage::binary_storage::BinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WriteOutcome };
 
 pub static ERR_WRITE_BEFORE_TXN_BOUNDARY: & 'static str = 
   "Cannot write before transaction boundary";
@@ -9,11 +11,20 @@ pub static ERR_READ_AFTER_TXN_BOUNDARY: & 'static str =
   "Cannot read after transaction boundary";
 pub static ERR_SET_TXN_BOUNDARY_PAST_END: & 'static str = 
   "Cannot set transaction boundary past end of allocated storage";
+pub static ERR_NO_SUCH_CHECKPOINT: & 'static str = 
+  "No checkpoint exists with the given name";
+pub static ERR_SHRINK_BEFORE_TXN_BOUNDARY: & 'static str = 
+  "Cannot shrink storage to less than the transaction boundary";
+pub static ERR_NON_APPEND_WRITE: & 'static str = 
+  "Cannot write before the end of the last write in strict append mode";
 
 pub struct TransactionalStorage<T: BinaryStorage + Sized> {
   storage: T,
   txn_boundary: usize,
-  check_on_read: bool
+  check_on_read: bool,
+  checkpoints: HashMap<String, usize>,
+  strict_append: bool,
+  append_cursor: usize
 }
 impl<T: BinaryStorage + Sized> TransactionalStorage<T> {
 
@@ -21,10 +32,24 @@ impl<T: BinaryStorage + Sized> TransactionalStorage<T> {
     TransactionalStorage {
       storage: storage,
       txn_boundary: 0,
-      check_on_read: true
+      check_on_read: true,
+      checkpoints: HashMap::new(),
+      strict_append: false,
+      append_cursor: 0
     }
   }
 
+  // Same as new(), but allows reads past the transaction boundary to be 
+  // permitted from the start, before the storage has been opened
+  pub fn with_check_on_read(
+    storage: T, 
+    check_on_read: bool
+  ) -> TransactionalStorage<T> {
+    let mut s = TransactionalStorage::new(storage);
+    s.check_on_read = check_on_read;
+    s
+  }
+
   fn check_boundary_for_read(
     &self, 
     offset: usize, 
@@ -39,13 +64,32 @@ impl<T: BinaryStorage + Sized> TransactionalStorage<T> {
   }
 
   fn check_boundary_for_write(&self, offset: usize) -> Result<(), Error> {
-    Ok(try!(AssertionError::assert(
+    try!(AssertionError::assert(
       !self.storage.is_open() || 
       offset >= self.txn_boundary,
       ERR_WRITE_BEFORE_TXN_BOUNDARY
+    ));
+    Ok(try!(AssertionError::assert(
+      !self.storage.is_open() || 
+      !self.strict_append || 
+      offset >= self.append_cursor,
+      ERR_NON_APPEND_WRITE
     )))
   }
 
+  // Moves the append cursor to the end of a write that succeeded
+  fn advance_append_cursor<R>(
+    &mut self, 
+    res: Result<R, Error>, 
+    offset: usize, 
+    len: usize
+  ) -> Result<R, Error> {
+    if res.is_ok() && offset + len > self.append_cursor {
+      self.append_cursor = offset + len;
+    }
+    res
+  }
+
   pub fn get_txn_boundary(&self) -> Result<usize, Error> {
     try!(AssertionError::assert(
       self.is_open(), 
@@ -64,9 +108,64 @@ impl<T: BinaryStorage + Sized> TransactionalStorage<T> {
       ERR_SET_TXN_BOUNDARY_PAST_END
     ));
     self.txn_boundary = offset;
+    self.append_cursor = offset;
+    Ok(())
+  }
+
+  pub fn checkpoint(&mut self, name: &str) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+    self.checkpoints.insert(name.to_string(), self.txn_boundary);
+    Ok(())
+  }
+
+  pub fn restore(&mut self, name: &str) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      self.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    ));
+
+    let boundary = match self.checkpoints.get(name) {
+      Some(b) => *b,
+      None => return Err(Error::Assertion(AssertionError::new(ERR_NO_SUCH_CHECKPOINT)))
+    };
+
+    // Clear everything written after the checkpoint
+    if boundary < try!(self.storage.get_capacity()) {
+      try!(self.storage.fill(Some(boundary), None, 0x0));
+    }
+
+    // Checkpoints past the boundary point at data that is now gone
+    self.checkpoints.retain(|_, b| *b <= boundary);
+
+    self.txn_boundary = boundary;
+    self.append_cursor = boundary;
     Ok(())
   }
 
+  pub fn has_checkpoint(&self, name: &str) -> bool {
+    self.checkpoints.contains_key(name)
+  }
+
+  pub fn get_strict_append(&self) -> bool {
+    self.strict_append
+  }
+
+  // When set, every write has to start at or after the end of the furthest
+  // write since the transaction boundary was last set (the append cursor),
+  // and anything else fails with ERR_NON_APPEND_WRITE. Moving the boundary
+  // moves the cursor with it, so rewriting uncommitted data still works by
+  // setting the boundary back first.
+  pub fn set_strict_append(&mut self, strict_append: bool) {
+    self.strict_append = strict_append;
+  }
+
+  pub fn get_append_cursor(&self) -> usize {
+    self.append_cursor
+  }
+
   pub fn get_check_on_read(&self) -> Result<bool, Error> {
     try!(AssertionError::assert(
       self.is_open(), 
@@ -103,73 +202,131 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
 
   fn w_i8(&mut self, offset: usize, data: i8) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_i8(offset, data)
+    let res = self.storage.w_i8(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<i8>())
   }
 
   fn w_i16(&mut self, offset: usize, data: i16) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_i16(offset, data)
+    let res = self.storage.w_i16(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<i16>())
   }
 
   fn w_i32(&mut self, offset: usize, data: i32) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_i32(offset, data)
+    let res = self.storage.w_i32(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<i32>())
   }
 
   fn w_i64(&mut self, offset: usize, data: i64) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_i64(offset, data)
+    let res = self.storage.w_i64(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<i64>())
   }
 
 
   fn w_u8(&mut self, offset: usize, data: u8) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_u8(offset, data)
+    let res = self.storage.w_u8(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u8>())
   }
 
   fn w_u16(&mut self, offset: usize, data: u16) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_u16(offset, data)
+    let res = self.storage.w_u16(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u16>())
   }
 
   fn w_u32(&mut self, offset: usize, data: u32) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_u32(offset, data)
+    let res = self.storage.w_u32(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u32>())
   }
 
   fn w_u64(&mut self, offset: usize, data: u64) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_u64(offset, data)
+    let res = self.storage.w_u64(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u64>())
+  }
+
+  fn w_i128(&mut self, offset: usize, data: i128) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_i128(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<i128>())
+  }
+
+  fn w_u128(&mut self, offset: usize, data: u128) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_u128(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u128>())
   }
 
 
   fn w_f32(&mut self, offset: usize, data: f32) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_f32(offset, data)
+    let res = self.storage.w_f32(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<f32>())
   }
 
   fn w_f64(&mut self, offset: usize, data: f64) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_f64(offset, data)
+    let res = self.storage.w_f64(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<f64>())
   }
 
 
   fn w_bool(&mut self, offset: usize, data: bool) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_bool(offset, data)
+    let res = self.storage.w_bool(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<bool>())
   }
 
 
   fn w_bytes(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_bytes(offset, data)
+    let res = self.storage.w_bytes(offset, data);
+    self.advance_append_cursor(res, offset, data.len())
   }
 
   fn w_str(&mut self, offset: usize, data: &str) -> Result<(), Error> {
     try!(self.check_boundary_for_write(offset));
-    self.storage.w_str(offset, data)
+    let res = self.storage.w_str(offset, data);
+    self.advance_append_cursor(res, offset, data.len())
+  }
+
+  fn w_bytes_reporting(
+    &mut self, 
+    offset: usize, 
+    data: &[u8]
+  ) -> Result<WriteOutcome, Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_bytes_reporting(offset, data);
+    self.advance_append_cursor(res, offset, data.len())
+  }
+
+  fn w_uuid(&mut self, offset: usize, data: Uuid) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_uuid(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<Uuid>())
+  }
+
+  fn w_blob(&mut self, offset: usize, data: &[u8]) -> Result<usize, Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_blob(offset, data);
+    self.advance_append_cursor(res, offset, size_of::<u32>() + data.len())
   }
 
+  fn w_u32_array(&mut self, offset: usize, values: &[u32]) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_u32_array(offset, values);
+    self.advance_append_cursor(res, offset, values.len() * size_of::<u32>())
+  }
+
+  fn w_u64_array(&mut self, offset: usize, values: &[u64]) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.w_u64_array(offset, values);
+    self.advance_append_cursor(res, offset, values.len() * size_of::<u64>())
+  }
 
 
   fn r_i8(&self, offset: usize) -> Result<i8, Error> {
@@ -213,6 +370,31 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
     self.storage.r_u64(offset)
   }
 
+  fn r_i128(&self, offset: usize) -> Result<i128, Error> {
+    try!(self.check_boundary_for_read(offset, size_of::<i128>()));
+    self.storage.r_i128(offset)
+  }
+
+  fn r_u128(&self, offset: usize) -> Result<u128, Error> {
+    try!(self.check_boundary_for_read(offset, size_of::<u128>()));
+    self.storage.r_u128(offset)
+  }
+
+  fn r_u32_array(&self, offset: usize, count: usize) -> Result<Vec<u32>, Error> {
+    try!(self.check_boundary_for_read(offset, count.saturating_mul(size_of::<u32>())));
+    self.storage.r_u32_array(offset, count)
+  }
+
+  fn r_u64_array(&self, offset: usize, count: usize) -> Result<Vec<u64>, Error> {
+    try!(self.check_boundary_for_read(offset, count.saturating_mul(size_of::<u64>())));
+    self.storage.r_u64_array(offset, count)
+  }
+
+  fn r_u64_opt(&self, offset: usize) -> Result<Option<u64>, Error> {
+    try!(self.check_boundary_for_read(offset, size_of::<u64>()));
+    self.storage.r_u64_opt(offset)
+  }
+
 
   fn r_f32(&self, offset: usize) -> Result<f32, Error> {
     try!(self.check_boundary_for_read(offset, size_of::<f32>()));
@@ -236,11 +418,51 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
     self.storage.r_bytes(offset, len)
   }
 
+  fn r_bytes_into(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+    try!(self.check_boundary_for_read(offset, buf.len()));
+    self.storage.r_bytes_into(offset, buf)
+  }
+
   fn r_str(&self, offset: usize, len: usize) -> Result<String, Error> {
     try!(self.check_boundary_for_read(offset, len));
     self.storage.r_str(offset, len)
   }
 
+  fn r_uuid(&self, offset: usize) -> Result<Uuid, Error> {
+    try!(self.check_boundary_for_read(offset, size_of::<Uuid>()));
+    self.storage.r_uuid(offset)
+  }
+
+  fn r_blob(&self, offset: usize) -> Result<Vec<u8>, Error> {
+    let len = try!(self.r_u32(offset)) as usize;
+    if len == 0 { return Ok(Vec::new()) }
+    self.r_bytes(offset + size_of::<u32>(), len)
+  }
+
+  fn read_all(&self) -> Result<Vec<u8>, Error> {
+    try!(self.check_boundary_for_read(0, try!(self.storage.get_capacity())));
+    self.storage.read_all()
+  }
+
+  fn pread(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    try!(self.check_boundary_for_read(offset, len));
+    self.storage.pread(offset, len)
+  }
+
+  fn pwrite(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(offset));
+    let res = self.storage.pwrite(offset, data);
+    self.advance_append_cursor(res, offset, data.len())
+  }
+
+  fn valid_len_from(&self, offset: usize) -> Result<usize, Error> {
+    let capacity = try!(self.storage.get_capacity());
+    if offset < capacity {
+      try!(self.check_boundary_for_read(offset, capacity - offset));
+    }
+    self.storage.valid_len_from(offset)
+  }
+
 
   fn fill(
     &mut self, 
@@ -258,7 +480,17 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
       Some(e) => try!(self.check_boundary_for_write(e))
     };
 
-    self.storage.fill(start, end, val)
+    let start_offset = match start { Some(s) => s, None => 0 };
+    let end_offset = match end { 
+      Some(e) => e, 
+      None => try!(self.storage.get_capacity()) 
+    };
+    let res = self.storage.fill(start, end, val);
+    self.advance_append_cursor(
+      res, 
+      start_offset, 
+      end_offset.saturating_sub(start_offset)
+    )
   }
 
   fn is_filled(
@@ -270,6 +502,41 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
     self.storage.is_filled(start, end, val)
   }
 
+  fn crc32(&self, start: Option<usize>, end: Option<usize>) -> Result<u32, Error> {
+    let start_offset = match start { Some(s) => s, None => 0 };
+    let end_offset = match end { 
+      Some(e) => e, 
+      None => try!(self.storage.get_capacity()) 
+    };
+    try!(self.check_boundary_for_read(
+      start_offset, 
+      end_offset.saturating_sub(start_offset)
+    ));
+    self.storage.crc32(start, end)
+  }
+
+  // The search reads everything from start to the end of capacity
+  fn find_bytes(&self, pattern: &[u8], start: usize) -> Result<Option<usize>, Error> {
+    let capacity = try!(self.storage.get_capacity());
+    try!(self.check_boundary_for_read(start, capacity.saturating_sub(start)));
+    self.storage.find_bytes(pattern, start)
+  }
+
+  fn copy(&mut self, from: usize, to: usize, len: usize) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(to));
+    try!(self.check_boundary_for_read(from, len));
+    let res = self.storage.copy(from, to, len);
+    self.advance_append_cursor(res, to, len)
+  }
+
+  fn swap_ranges(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+    try!(self.check_boundary_for_write(a));
+    try!(self.check_boundary_for_write(b));
+    let res = self.storage.swap_ranges(a, b, len);
+    let end = if a > b { a } else { b };
+    self.advance_append_cursor(res, end, len)
+  }
+
 
   fn get_expand_size(&self) -> usize {
     self.storage.get_expand_size()
@@ -289,5 +556,25 @@ impl<T: BinaryStorage + Sized> BinaryStorage for TransactionalStorage<T> {
     self.storage.expand(min_capacity)
   }
 
+  fn shrink(&mut self, max_capacity: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      max_capacity >= self.txn_boundary, 
+      ERR_SHRINK_BEFORE_TXN_BOUNDARY
+    ));
+    self.storage.shrink(max_capacity)
+  }
+
+  fn truncate(&mut self, new_capacity: usize) -> Result<(), Error> {
+    try!(AssertionError::assert(
+      new_capacity >= self.txn_boundary, 
+      ERR_SHRINK_BEFORE_TXN_BOUNDARY
+    ));
+    self.storage.truncate(new_capacity)
+  }
+
+  fn sync(&mut self) -> Result<(), Error> {
+    self.storage.sync()
+  }
+
 
 }
@@ -1,6 +1,14 @@
+use std::cmp;
+use std::io;
+use std::thread;
+use std::time::Duration;
+
 use error::AssertionError;
 use storage::binary_storage;
 
+pub static ERR_RLE_DATA_TRUNCATED: & 'static str = 
+  "Run-length encoded data ends partway through a run";
+
 pub fn u64_as_usize(n: u64) -> Result<usize, AssertionError> {
   try!(AssertionError::assert_not(
     n > usize::max_value() as u64, 
@@ -16,6 +24,24 @@ pub fn usize_add(a: usize, b: usize) -> Result<usize, AssertionError> {
   }
 }
 
+pub fn usize_mul(a: usize, b: usize) -> Result<usize, AssertionError> {
+  match a.checked_mul(b) {
+    Some(n) => Ok(n),
+    None => Err(AssertionError::new(binary_storage::ERR_ARITHMETIC_OVERFLOW))
+  }
+}
+
+// Capacity needed for a set of planned (offset, len) writes, i.e. the 
+// furthest any of them reaches, so storage can be expanded once up front
+pub fn required_capacity(writes: &[(usize, usize)]) -> Result<usize, AssertionError> {
+  let mut capacity = 0;
+  for &(offset, len) in writes {
+    let end = try!(usize_add(offset, len));
+    if end > capacity { capacity = end; }
+  }
+  Ok(capacity)
+}
+
 pub fn u64_add(a: u64, b: u64) -> Result<u64, AssertionError> {
   match a.checked_add(b) {
     Some(n) => Ok(n),
@@ -23,6 +49,18 @@ pub fn u64_add(a: u64, b: u64) -> Result<u64, AssertionError> {
   }
 }
 
+// byteorder has no 128-bit support, so these do the little-endian 
+// conversion by hand
+pub fn u128_to_le_bytes(n: u128) -> [u8; 16] {
+  let mut bytes = [0x0; 16];
+  for (i, b) in bytes.iter_mut().enumerate() { *b = (n >> (i * 8)) as u8; }
+  bytes
+}
+
+pub fn u128_from_le_bytes(bytes: &[u8]) -> u128 {
+  bytes.iter().take(16).enumerate().fold(0, |n, (i, &b)| n | (b as u128) << (i * 8))
+}
+
 pub fn xor_checksum(bytes: &[u8]) -> u8 {
   let mut res = 0x0;
   for byte in bytes {
@@ -31,3 +69,165 @@ pub fn xor_checksum(bytes: &[u8]) -> u8 {
   res
 }
 
+pub fn is_retryable_io_err(err: &io::Error) -> bool {
+  match err.kind() {
+    io::ErrorKind::Interrupted => true,
+    io::ErrorKind::TimedOut => true,
+    io::ErrorKind::WouldBlock => true,
+    _ => false
+  }
+}
+
+pub fn retry_io<T, F>(
+  retries: u32, 
+  delay: Duration, 
+  mut f: F
+) -> Result<T, io::Error> where F: FnMut() -> Result<T, io::Error> {
+  let mut attempt = 0;
+  loop {
+    match f() {
+      Ok(v) => return Ok(v),
+      Err(e) => {
+        if attempt >= retries || !is_retryable_io_err(&e) { return Err(e) }
+        attempt += 1;
+        thread::sleep(delay);
+      }
+    }
+  }
+}
+
+pub fn crc32(bytes: &[u8]) -> u32 {
+  crc32_with_poly(bytes, 0xEDB88320)
+}
+
+pub fn crc32c(bytes: &[u8]) -> u32 {
+  crc32_with_poly(bytes, 0x82F63B78)
+}
+
+fn crc32_with_poly(bytes: &[u8], poly: u32) -> u32 {
+  let mut crc = 0xFFFFFFFF;
+  for byte in bytes {
+    crc = crc ^ (*byte as u32);
+    for _ in 0..8 {
+      crc = if crc & 1 == 1 { (crc >> 1) ^ poly } else { crc >> 1 };
+    }
+  }
+  !crc
+}
+
+pub fn fnv1a(bytes: &[u8]) -> u32 {
+  let mut hash: u32 = 0x811C9DC5;
+  for byte in bytes {
+    hash = hash ^ (*byte as u32);
+    hash = hash.wrapping_mul(0x01000193);
+  }
+  hash
+}
+
+// Run-length encodes bytes as (count, byte) pairs, with counts of 1 to 255.
+// Only worth it for data with long runs; anything else doubles in size.
+pub fn rle_compress(bytes: &[u8]) -> Vec<u8> {
+  let mut res = Vec::new();
+  let mut i = 0;
+  while i < bytes.len() {
+    let byte = bytes[i];
+    let mut run = 1;
+    while i + run < bytes.len() && bytes[i + run] == byte && run < 255 {
+      run += 1;
+    }
+    res.push(run as u8);
+    res.push(byte);
+    i += run;
+  }
+  res
+}
+
+pub fn rle_decompress(bytes: &[u8]) -> Result<Vec<u8>, AssertionError> {
+  try!(AssertionError::assert(bytes.len() % 2 == 0, ERR_RLE_DATA_TRUNCATED));
+  let mut res = Vec::new();
+  for pair in bytes.chunks(2) {
+    for _ in 0..pair[0] {
+      res.push(pair[1]);
+    }
+  }
+  Ok(res)
+}
+
+// Adds [start, end) to a sorted list of disjoint (start, end) ranges, 
+// merging it with any ranges it overlaps or touches
+pub fn add_range(ranges: &mut Vec<(usize, usize)>, start: usize, end: usize) {
+  if start >= end { return }
+  let first = ranges.iter().position(|r| r.1 >= start).unwrap_or(ranges.len());
+  let mut last = first;
+  let mut merged = (start, end);
+  while last < ranges.len() && ranges[last].0 <= end {
+    merged = (cmp::min(merged.0, ranges[last].0), cmp::max(merged.1, ranges[last].1));
+    last += 1;
+  }
+  ranges.drain(first..last);
+  ranges.insert(first, merged);
+}
+
+// Whether any of a list of (start, end) ranges overlaps [start, end)
+pub fn overlaps_range(ranges: &[(usize, usize)], start: usize, end: usize) -> bool {
+  ranges.iter().any(|r| r.0 < end && start < r.1)
+}
+
+// Removes [start, start + len) from a sorted list of disjoint ranges, 
+// moving the parts after it down by len, as when bytes are cut out of the
+// middle of storage
+pub fn cut_range(ranges: &mut Vec<(usize, usize)>, start: usize, len: usize) {
+  let end = start.saturating_add(len);
+  let old: Vec<(usize, usize)> = ranges.drain(..).collect();
+  for (s, e) in old {
+    if s < start { add_range(ranges, s, cmp::min(e, start)); }
+    if e > end { add_range(ranges, cmp::max(s, end) - len, e - len); }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChecksumAlgo {
+  Xor,
+  Crc32,
+  Crc32c,
+  Fnv
+}
+impl ChecksumAlgo {
+
+  pub fn from_id(id: u8) -> Option<ChecksumAlgo> {
+    match id {
+      0 => Some(ChecksumAlgo::Xor),
+      1 => Some(ChecksumAlgo::Crc32),
+      2 => Some(ChecksumAlgo::Crc32c),
+      3 => Some(ChecksumAlgo::Fnv),
+      _ => None
+    }
+  }
+
+  pub fn id(&self) -> u8 {
+    match *self {
+      ChecksumAlgo::Xor => 0,
+      ChecksumAlgo::Crc32 => 1,
+      ChecksumAlgo::Crc32c => 2,
+      ChecksumAlgo::Fnv => 3
+    }
+  }
+
+  // Number of bytes the checksum occupies when stored
+  pub fn len(&self) -> usize {
+    match *self {
+      ChecksumAlgo::Xor => 1,
+      _ => 4
+    }
+  }
+
+  pub fn checksum(&self, bytes: &[u8]) -> u32 {
+    match *self {
+      ChecksumAlgo::Xor => xor_checksum(bytes) as u32,
+      ChecksumAlgo::Crc32 => crc32(bytes),
+      ChecksumAlgo::Crc32c => crc32c(bytes),
+      ChecksumAlgo::Fnv => fnv1a(bytes)
+    }
+  }
+
+}
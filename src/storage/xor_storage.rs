@@ -0,0 +1,514 @@
+use std::mem::size_of;
+use std::io::Cursor;
+use std::str;
+
+use byteorder::{ LittleEndian, ReadBytesExt, WriteBytesExt };
+use uuid::Uuid;
+
+use error::{ Error, AssertionError };
+use storage::util;
+use storage::binary_storage;
+use storage::binary_storage::{ BinaryStorage, WriteOutcome };
+
+pub static ERR_EMPTY_XOR_KEY: & 'static str = 
+  "XOR key must contain at least one byte";
+
+
+// Wraps another storage and XORs every byte with a repeating key on the way
+// in and out, so plaintext never reaches the underlying storage. This is
+// obfuscation only, NOT encryption: a repeating XOR key is trivially
+// recovered from any known plaintext (including runs of zeros), so it only
+// keeps data from being readable at a casual glance.
+//
+// The byte at offset n is XORed with key[n % key.len()], so the encoding
+// depends only on position and reads and writes can start anywhere. Values
+// are stored little-endian regardless of the wrapped storage. Space that
+// was never written through the wrapper (e.g. the initial capacity of a new
+// store, or space added by expansion) holds zeros underneath, so it reads
+// back as the key rather than as zeros. fill() it through the wrapper first
+// if something depends on zeroed space.
+pub struct XorStorage<T: BinaryStorage + Sized> {
+  storage: T, 
+  key: Vec<u8>
+}
+impl<T: BinaryStorage + Sized> XorStorage<T> {
+
+  pub fn new(storage: T, key: &[u8]) -> Result<XorStorage<T>, Error> {
+    try!(AssertionError::assert(key.len() > 0, ERR_EMPTY_XOR_KEY));
+    Ok(XorStorage {
+      storage: storage, 
+      key: key.to_vec()
+    })
+  }
+
+  // The wrapped storage, whose contents are the XORed bytes
+  pub fn get_storage(&self) -> &T {
+    &self.storage
+  }
+
+  // XORs data in place as if it started at offset. Applying it twice
+  // restores the original bytes, so it both encodes and decodes.
+  fn apply_key(&self, offset: usize, data: &mut [u8]) {
+    let start = offset % self.key.len();
+    for (i, b) in data.iter_mut().enumerate() {
+      *b ^= self.key[(start + i) % self.key.len()];
+    }
+  }
+
+  fn check_open(&self) -> Result<(), Error> {
+    Ok(try!(AssertionError::assert(
+      self.storage.is_open(), 
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED
+    )))
+  }
+
+  fn write_encoded(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    let mut buf = data.to_vec();
+    self.apply_key(offset, buf.as_mut_slice());
+    self.storage.w_bytes(offset, buf.as_slice())
+  }
+
+  fn read_decoded(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = try!(self.storage.r_bytes(offset, len));
+    self.apply_key(offset, buf.as_mut_slice());
+    Ok(buf)
+  }
+
+  // Resolves the optional bounds taken by fill() and is_filled(), failing
+  // with err if the range is out of bounds and with nothing_err if it's empty
+  fn fill_range(
+    &self, 
+    start: Option<usize>, 
+    end: Option<usize>, 
+    err: & 'static str, 
+    nothing_err: & 'static str
+  ) -> Result<(usize, usize), Error> {
+    try!(self.check_open());
+    let capacity = try!(self.storage.get_capacity());
+    let start_offset = match start { Some(s) => s, None => 0 };
+    let end_offset = match end { Some(e) => e, None => capacity };
+    try!(AssertionError::assert(start_offset < capacity, err));
+    try!(AssertionError::assert(end_offset <= capacity, err));
+    try!(AssertionError::assert(end_offset > start_offset, nothing_err));
+    Ok((start_offset, end_offset))
+  }
+
+}
+impl<T: BinaryStorage + Sized> BinaryStorage for XorStorage<T> {
+
+  fn open(&mut self) -> Result<(), Error> {
+    self.storage.open()
+  }
+
+  fn close(&mut self) -> Result<(), Error> {
+    self.storage.close()
+  }
+
+
+  fn is_open(&self) -> bool {
+    self.storage.is_open()
+  }
+
+
+  fn w_i8(&mut self, offset: usize, data: i8) -> Result<(), Error> {
+    self.write_encoded(offset, &[data as u8])
+  }
+
+  fn w_i16(&mut self, offset: usize, data: i16) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_i16::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_i32(&mut self, offset: usize, data: i32) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_i32::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_i64(&mut self, offset: usize, data: i64) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_i64::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+
+  fn w_u8(&mut self, offset: usize, data: u8) -> Result<(), Error> {
+    self.write_encoded(offset, &[data])
+  }
+
+  fn w_u16(&mut self, offset: usize, data: u16) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_u16::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_u32(&mut self, offset: usize, data: u32) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_u32::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_u64(&mut self, offset: usize, data: u64) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_u64::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_i128(&mut self, offset: usize, data: i128) -> Result<(), Error> {
+    self.w_u128(offset, data as u128)
+  }
+
+  fn w_u128(&mut self, offset: usize, data: u128) -> Result<(), Error> {
+    self.write_encoded(offset, &util::u128_to_le_bytes(data))
+  }
+
+
+  fn w_f32(&mut self, offset: usize, data: f32) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_f32::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_f64(&mut self, offset: usize, data: f64) -> Result<(), Error> {
+    let mut buf = vec![];
+    try!(buf.write_f64::<LittleEndian>(data));
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+
+  fn w_bool(&mut self, offset: usize, data: bool) -> Result<(), Error> {
+    self.write_encoded(offset, &[data as u8])
+  }
+
+
+  fn w_bytes(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    self.write_encoded(offset, data)
+  }
+
+  fn w_str(&mut self, offset: usize, data: &str) -> Result<(), Error> {
+    self.write_encoded(offset, data.as_bytes())
+  }
+
+  fn w_bytes_reporting(
+    &mut self, 
+    offset: usize, 
+    data: &[u8]
+  ) -> Result<WriteOutcome, Error> {
+    let mut buf = data.to_vec();
+    self.apply_key(offset, buf.as_mut_slice());
+    self.storage.w_bytes_reporting(offset, buf.as_slice())
+  }
+
+  fn w_uuid(&mut self, offset: usize, data: Uuid) -> Result<(), Error> {
+    self.write_encoded(offset, data.as_bytes())
+  }
+
+  fn w_blob(&mut self, offset: usize, data: &[u8]) -> Result<usize, Error> {
+    try!(AssertionError::assert(
+      data.len() <= u32::max_value() as usize, 
+      binary_storage::ERR_BLOB_TOO_LONG
+    ));
+    let data_offset = try!(util::usize_add(offset, size_of::<u32>()));
+    try!(self.w_u32(offset, data.len() as u32));
+    if data.len() > 0 { try!(self.write_encoded(data_offset, data)); }
+    Ok(size_of::<u32>() + data.len())
+  }
+
+  fn w_u32_array(&mut self, offset: usize, values: &[u32]) -> Result<(), Error> {
+    try!(self.check_open());
+    if values.len() == 0 { return Ok(()) }
+    let mut buf = Vec::with_capacity(try!(util::usize_mul(values.len(), 4)));
+    for v in values { try!(buf.write_u32::<LittleEndian>(*v)) }
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+  fn w_u64_array(&mut self, offset: usize, values: &[u64]) -> Result<(), Error> {
+    try!(self.check_open());
+    if values.len() == 0 { return Ok(()) }
+    let mut buf = Vec::with_capacity(try!(util::usize_mul(values.len(), 8)));
+    for v in values { try!(buf.write_u64::<LittleEndian>(*v)) }
+    self.write_encoded(offset, buf.as_slice())
+  }
+
+
+  fn r_i8(&self, offset: usize) -> Result<i8, Error> {
+    Ok(try!(self.read_decoded(offset, size_of::<i8>()))[0] as i8)
+  }
+
+  fn r_i16(&self, offset: usize) -> Result<i16, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<i16>())));
+    Ok(try!(rdr.read_i16::<LittleEndian>()))
+  }
+
+  fn r_i32(&self, offset: usize) -> Result<i32, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<i32>())));
+    Ok(try!(rdr.read_i32::<LittleEndian>()))
+  }
+
+  fn r_i64(&self, offset: usize) -> Result<i64, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<i64>())));
+    Ok(try!(rdr.read_i64::<LittleEndian>()))
+  }
+
+
+  fn r_u8(&self, offset: usize) -> Result<u8, Error> {
+    Ok(try!(self.read_decoded(offset, size_of::<u8>()))[0])
+  }
+
+  fn r_u16(&self, offset: usize) -> Result<u16, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<u16>())));
+    Ok(try!(rdr.read_u16::<LittleEndian>()))
+  }
+
+  fn r_u32(&self, offset: usize) -> Result<u32, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<u32>())));
+    Ok(try!(rdr.read_u32::<LittleEndian>()))
+  }
+
+  fn r_u64(&self, offset: usize) -> Result<u64, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<u64>())));
+    Ok(try!(rdr.read_u64::<LittleEndian>()))
+  }
+
+  fn r_i128(&self, offset: usize) -> Result<i128, Error> {
+    Ok(try!(self.r_u128(offset)) as i128)
+  }
+
+  fn r_u128(&self, offset: usize) -> Result<u128, Error> {
+    let data = try!(self.read_decoded(offset, size_of::<u128>()));
+    Ok(util::u128_from_le_bytes(data.as_slice()))
+  }
+
+  fn r_u32_array(&self, offset: usize, count: usize) -> Result<Vec<u32>, Error> {
+    try!(self.check_open());
+    let len = try!(util::usize_mul(count, size_of::<u32>()));
+    if len == 0 { return Ok(Vec::new()) }
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, len)));
+    let mut res = Vec::with_capacity(count);
+    for _ in 0..count { res.push(try!(rdr.read_u32::<LittleEndian>())) }
+    Ok(res)
+  }
+
+  fn r_u64_array(&self, offset: usize, count: usize) -> Result<Vec<u64>, Error> {
+    try!(self.check_open());
+    let len = try!(util::usize_mul(count, size_of::<u64>()));
+    if len == 0 { return Ok(Vec::new()) }
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, len)));
+    let mut res = Vec::with_capacity(count);
+    for _ in 0..count { res.push(try!(rdr.read_u64::<LittleEndian>())) }
+    Ok(res)
+  }
+
+  // Whether the field was written is up to the underlying storage, since
+  // encoded bytes are only zero where they were never written
+  fn r_u64_opt(&self, offset: usize) -> Result<Option<u64>, Error> {
+    match try!(self.storage.r_u64_opt(offset)) {
+      None => Ok(None),
+      Some(_) => {
+        let data = try!(self.read_decoded(offset, size_of::<u64>()));
+        let mut rdr = Cursor::new(data);
+        Ok(Some(try!(rdr.read_u64::<LittleEndian>())))
+      }
+    }
+  }
+
+
+  fn r_f32(&self, offset: usize) -> Result<f32, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<f32>())));
+    Ok(try!(rdr.read_f32::<LittleEndian>()))
+  }
+
+  fn r_f64(&self, offset: usize) -> Result<f64, Error> {
+    let mut rdr = Cursor::new(try!(self.read_decoded(offset, size_of::<f64>())));
+    Ok(try!(rdr.read_f64::<LittleEndian>()))
+  }
+
+
+  fn r_bool(&self, offset: usize) -> Result<bool, Error> {
+    Ok(try!(self.read_decoded(offset, size_of::<bool>()))[0] != 0)
+  }
+
+
+  fn r_bytes(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    self.read_decoded(offset, len)
+  }
+
+  fn r_bytes_into(&self, offset: usize, buf: &mut [u8]) -> Result<usize, Error> {
+    let read_len = try!(self.storage.r_bytes_into(offset, buf));
+    self.apply_key(offset, &mut buf[..read_len]);
+    Ok(read_len)
+  }
+
+  fn r_str(&self, offset: usize, len: usize) -> Result<String, Error> {
+    let b = try!(self.read_decoded(offset, len));
+    Ok(try!(str::from_utf8(b.as_slice())).to_string())
+  }
+
+  fn r_uuid(&self, offset: usize) -> Result<Uuid, Error> {
+    let b = try!(self.read_decoded(offset, size_of::<Uuid>()));
+    // read_decoded() returns exactly the requested length, so this can't fail
+    Ok(Uuid::from_bytes(b.as_slice()).unwrap())
+  }
+
+  fn r_blob(&self, offset: usize) -> Result<Vec<u8>, Error> {
+    let len = try!(self.r_u32(offset)) as usize;
+    let data_offset = try!(util::usize_add(offset, size_of::<u32>()));
+    if len == 0 { return Ok(Vec::new()) }
+    self.read_decoded(data_offset, len)
+  }
+
+  fn read_all(&self) -> Result<Vec<u8>, Error> {
+    let mut buf = try!(self.storage.read_all());
+    self.apply_key(0, buf.as_mut_slice());
+    Ok(buf)
+  }
+
+  fn pread(&self, offset: usize, len: usize) -> Result<Vec<u8>, Error> {
+    let mut buf = try!(self.storage.pread(offset, len));
+    self.apply_key(offset, buf.as_mut_slice());
+    Ok(buf)
+  }
+
+  fn pwrite(&mut self, offset: usize, data: &[u8]) -> Result<(), Error> {
+    let mut buf = data.to_vec();
+    self.apply_key(offset, buf.as_mut_slice());
+    self.storage.pwrite(offset, buf.as_slice())
+  }
+
+  fn valid_len_from(&self, offset: usize) -> Result<usize, Error> {
+    try!(self.check_open());
+    let capacity = try!(self.storage.get_capacity());
+    try!(AssertionError::assert(
+      offset < capacity, 
+      binary_storage::ERR_READ_PAST_END
+    ));
+
+    let data = try!(self.read_decoded(offset, capacity - offset));
+    match data.iter().rposition(|b| *b != 0x0) {
+      Some(i) => Ok(i + 1), 
+      None => Ok(0)
+    }
+  }
+
+
+  // The key varies by position, so the fill value has to be encoded byte by
+  // byte rather than passed through to the wrapped storage's fill()
+  fn fill(
+    &mut self, 
+    start: Option<usize>, 
+    end: Option<usize>, 
+    val: u8
+  ) -> Result<(), Error> {
+    let (start_offset, end_offset) = try!(self.fill_range(
+      start, 
+      end, 
+      binary_storage::ERR_WRITE_PAST_END, 
+      binary_storage::ERR_WRITE_NOTHING
+    ));
+    self.write_encoded(start_offset, vec![val; end_offset - start_offset].as_slice())
+  }
+
+  fn is_filled(
+    &self, 
+    start: Option<usize>, 
+    end: Option<usize>, 
+    val: u8
+  ) -> Result<bool, Error> {
+    let (start_offset, end_offset) = try!(self.fill_range(
+      start, 
+      end, 
+      binary_storage::ERR_READ_PAST_END, 
+      binary_storage::ERR_READ_NOTHING
+    ));
+    let data = try!(self.read_decoded(start_offset, end_offset - start_offset));
+    Ok(data.iter().all(|b| *b == val))
+  }
+
+  fn crc32(&self, start: Option<usize>, end: Option<usize>) -> Result<u32, Error> {
+    let (start_offset, end_offset) = try!(self.fill_range(
+      start, 
+      end, 
+      binary_storage::ERR_READ_PAST_END, 
+      binary_storage::ERR_READ_NOTHING
+    ));
+    let data = try!(self.read_decoded(start_offset, end_offset - start_offset));
+    Ok(util::crc32(data.as_slice()))
+  }
+
+  // The pattern is searched for in the decoded data, since its encoded form
+  // depends on where it occurs
+  fn find_bytes(&self, pattern: &[u8], start: usize) -> Result<Option<usize>, Error> {
+    try!(self.check_open());
+    let capacity = try!(self.storage.get_capacity());
+    try!(binary_storage::check_find_bytes(pattern, start, capacity));
+
+    let data = try!(self.read_decoded(start, capacity - start));
+    Ok(binary_storage::find_in(data.as_slice(), pattern).map(|pos| start + pos))
+  }
+
+  fn copy(&mut self, from: usize, to: usize, len: usize) -> Result<(), Error> {
+    try!(self.check_open());
+    try!(binary_storage::check_copy(
+      from, 
+      to, 
+      len, 
+      try!(self.storage.get_capacity())
+    ));
+
+    // The key depends on the offset, so the data has to be re-encoded for 
+    // its new position
+    let data = try!(self.read_decoded(from, len));
+    self.write_encoded(to, data.as_slice())
+  }
+
+  // Swapping the stored bytes would leave each range encoded for the other's
+  // position, so the ranges are decoded and written back re-encoded
+  fn swap_ranges(&mut self, a: usize, b: usize, len: usize) -> Result<(), Error> {
+    try!(self.check_open());
+    try!(binary_storage::check_swap_ranges(
+      a, 
+      b, 
+      len, 
+      try!(self.storage.get_capacity())
+    ));
+
+    let a_data = try!(self.read_decoded(a, len));
+    let b_data = try!(self.read_decoded(b, len));
+    try!(self.write_encoded(a, b_data.as_slice()));
+    self.write_encoded(b, a_data.as_slice())
+  }
+
+
+  fn get_expand_size(&self) -> usize {
+    self.storage.get_expand_size()
+  }
+
+  fn set_expand_size(&mut self, expand_size: usize) -> Result<(), Error> {
+    self.storage.set_expand_size(expand_size)
+  }
+
+
+  fn get_capacity(&self) -> Result<usize, Error> {
+    self.storage.get_capacity()
+  }
+
+
+  fn expand(&mut self, min_capacity: usize) -> Result<(), Error> {
+    self.storage.expand(min_capacity)
+  }
+
+  fn shrink(&mut self, max_capacity: usize) -> Result<(), Error> {
+    self.storage.shrink(max_capacity)
+  }
+
+  fn truncate(&mut self, new_capacity: usize) -> Result<(), Error> {
+    self.storage.truncate(new_capacity)
+  }
+
+  fn sync(&mut self) -> Result<(), Error> {
+    self.storage.sync()
+  }
+
+
+}
@@ -0,0 +1,201 @@
+use std::fs;
+use std::io;
+use std::io::{ Read, Seek, SeekFrom };
+
+use uuid::Uuid;
+
+use storage::binary_storage::BinaryStorage;
+use storage::binary_storage_reader::BinaryStorageReader;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+use storage::file_binary_storage::FileBinaryStorage;
+
+pub static BASE_PATH: &'static str = "./test_data/storage/file_binary_storage/";
+
+fn rnd_path() -> String {
+  BASE_PATH.to_string() 
+    + Uuid::new_v4().simple().to_string().as_str()
+    + ".tmp"
+}
+
+fn rm_tmp(filename: String) {
+  fs::remove_file(filename).unwrap()
+}
+
+// Small pages so that reads have to span several of them
+fn get_storage() -> (FileBinaryStorage, String) {
+  let path = rnd_path();
+  let s = FileBinaryStorage::new(
+    path.clone(),
+    true,
+    256,
+    16, 
+    16,
+    256
+  ).unwrap();
+  (s, path)
+}
+
+fn get_filled_storage() -> (FileBinaryStorage, String) {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  let bytes: Vec<u8> = (0..100).map(|b| b as u8).collect();
+  s.w_bytes(0, bytes.as_slice()).unwrap();
+  (s, p)
+}
+
+
+// read() tests
+
+#[test]
+fn read_spans_page_boundaries() {
+  let (s, p) = get_filled_storage();
+  {
+    let mut r = BinaryStorageReader::new(&s);
+    r.seek(SeekFrom::Start(10)).unwrap();
+    let mut buf = [0u8; 40];
+    r.read_exact(&mut buf).unwrap();
+    let expected: Vec<u8> = (10..50).map(|b| b as u8).collect();
+    assert_eq!(expected.as_slice(), &buf[..]);
+    assert_eq!(50, r.get_position());
+  }
+  rm_tmp(p);
+}
+
+#[test]
+fn read_advances_position_across_calls() {
+  let (s, p) = get_filled_storage();
+  {
+    let mut r = BinaryStorageReader::new(&s);
+    let mut buf = [0u8; 7];
+    for i in 0..10 {
+      assert_eq!(7, r.read(&mut buf).unwrap());
+      let expected: Vec<u8> = (i * 7..i * 7 + 7).map(|b| b as u8).collect();
+      assert_eq!(expected.as_slice(), &buf[..]);
+    }
+  }
+  rm_tmp(p);
+}
+
+#[test]
+fn read_stops_at_capacity() {
+  let (s, p) = get_filled_storage();
+  {
+    let cap = s.get_capacity().unwrap();
+    let mut r = BinaryStorageReader::new(&s);
+    r.seek(SeekFrom::Start(cap as u64 - 3)).unwrap();
+    let mut buf = [0u8; 10];
+    assert_eq!(3, r.read(&mut buf).unwrap());
+    assert_eq!(0, r.read(&mut buf).unwrap());
+  }
+  rm_tmp(p);
+}
+
+#[test]
+fn read_to_end_returns_whole_storage() {
+  let (s, p) = get_filled_storage();
+  {
+    let mut r = BinaryStorageReader::new(&s);
+    let mut v = Vec::new();
+    r.read_to_end(&mut v).unwrap();
+    assert_eq!(s.get_capacity().unwrap(), v.len());
+    assert_eq!(s.r_bytes(0, v.len()).unwrap(), v);
+  }
+  rm_tmp(p);
+}
+
+#[test]
+fn read_returns_zero_past_end() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let mut r = BinaryStorageReader::new(&s);
+  r.seek(SeekFrom::Start(300)).unwrap();
+  let mut buf = [0u8; 4];
+  assert_eq!(0, r.read(&mut buf).unwrap());
+}
+
+#[test]
+fn read_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  let mut r = BinaryStorageReader::new(&s);
+  let mut buf = [0u8; 4];
+  assert!(r.read(&mut buf).is_err());
+}
+
+
+// seek() tests
+
+#[test]
+fn seek_from_end_reads_last_bytes() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_bytes(252, &[1, 2, 3, 4]).unwrap();
+  let mut r = BinaryStorageReader::new(&s);
+  assert_eq!(252, r.seek(SeekFrom::End(-4)).unwrap());
+  let mut buf = [0u8; 8];
+  assert_eq!(4, r.read(&mut buf).unwrap());
+  assert_eq!(&[1, 2, 3, 4], &buf[..4]);
+}
+
+#[test]
+fn seek_from_end_spans_file_pages() {
+  let (s, p) = get_filled_storage();
+  {
+    let cap = s.get_capacity().unwrap();
+    let mut r = BinaryStorageReader::new(&s);
+    let pos = r.seek(SeekFrom::End(-(cap as i64) + 60)).unwrap();
+    assert_eq!(60, pos);
+    let mut buf = [0u8; 20];
+    r.read_exact(&mut buf).unwrap();
+    let expected: Vec<u8> = (60..80).map(|b| b as u8).collect();
+    assert_eq!(expected.as_slice(), &buf[..]);
+  }
+  rm_tmp(p);
+}
+
+#[test]
+fn seek_from_current_moves_relative() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let mut r = BinaryStorageReader::new(&s);
+  assert_eq!(10, r.seek(SeekFrom::Current(10)).unwrap());
+  assert_eq!(6, r.seek(SeekFrom::Current(-4)).unwrap());
+  assert_eq!(6, r.get_position());
+}
+
+#[test]
+fn seek_returns_err_before_start() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let mut r = BinaryStorageReader::new(&s);
+  assert!(r.seek(SeekFrom::End(-257)).is_err());
+  assert!(r.seek(SeekFrom::Current(-1)).is_err());
+  assert_eq!(0, r.get_position());
+}
+
+#[test]
+fn seek_returns_invalid_input_on_overflow() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let mut r = BinaryStorageReader::new(&s);
+  let far = i64::max_value() as u64 + 10;
+  assert_eq!(far, r.seek(SeekFrom::Start(far)).unwrap());
+  assert_eq!(far - 4, r.seek(SeekFrom::Current(-4)).unwrap());
+
+  r.seek(SeekFrom::Start(usize::max_value() as u64)).unwrap();
+  assert_eq!(
+    io::ErrorKind::InvalidInput,
+    r.seek(SeekFrom::Current(1)).unwrap_err().kind()
+  );
+  assert_eq!(usize::max_value(), r.get_position());
+
+  r.seek(SeekFrom::Start(0)).unwrap();
+  assert_eq!(
+    io::ErrorKind::InvalidInput,
+    r.seek(SeekFrom::Current(i64::min_value())).unwrap_err().kind()
+  );
+  assert_eq!(
+    io::ErrorKind::InvalidInput,
+    r.seek(SeekFrom::End(i64::min_value())).unwrap_err().kind()
+  );
+  assert_eq!(0, r.get_position());
+}
@@ -1,8 +1,9 @@
 use std::str;
 
 use std::error::Error;
+use uuid::Uuid;
 use storage::binary_storage;
-use storage::binary_storage::BinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WriteOutcome };
 
 
 // open(), close(), and is_open() tests 
@@ -287,6 +288,42 @@ pub fn w_u64_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
   assert_eq!(u64::max_value(), s.r_u64(256).unwrap());
 }
 
+// w_i128() tests
+pub fn w_i128_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  let res = s.w_i128(0, i128::min_value());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    res.unwrap_err().description()
+  );
+}
+
+pub fn w_i128_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert!(s.w_i128(256, i128::min_value()).is_ok());
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(i128::min_value(), s.r_i128(256).unwrap());
+}
+
+// w_u128() tests
+pub fn w_u128_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  let res = s.w_u128(0, u128::max_value());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    res.unwrap_err().description()
+  );
+}
+
+pub fn w_u128_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert!(s.w_u128(256, u128::max_value()).is_ok());
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(u128::max_value(), s.r_u128(256).unwrap());
+}
+
 // w_f32() tests
 pub fn w_f32_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
   assert!(!s.is_open());
@@ -834,6 +871,231 @@ pub fn r_u64_result_is_not_mutated_on_subsequent_write<T: BinaryStorage>(mut s:
   assert_eq!(u64::max_value() - 10, res2);
 }
 
+// w_u32_array() and w_u64_array() tests
+pub fn w_u32_array_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.w_u32_array(0, &[0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+pub fn w_u32_array_writes_values_read_back_one_at_a_time<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let values: Vec<u32> = (0..40).map(|i| i * 0x01010101 + 3).collect();
+  s.w_u32_array(5, values.as_slice()).unwrap();
+  for (i, v) in values.iter().enumerate() {
+    assert_eq!(*v, s.r_u32(5 + i * 4).unwrap());
+  }
+  assert!(s.is_filled(None, Some(5), 0x0).unwrap());
+  assert!(s.is_filled(Some(165), None, 0x0).unwrap());
+}
+
+pub fn w_u32_array_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u32_array(510, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_u32_array(510, 3).unwrap());
+}
+
+pub fn w_u32_array_writes_nothing_for_empty_slice<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u32_array(1000, &[]).unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert!(s.is_filled(None, None, 0x0).unwrap());
+}
+
+pub fn w_u64_array_writes_values_read_back_one_at_a_time<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let values: Vec<u64> = (0..20).map(|i| u64::max_value() - i * 0x100000001).collect();
+  s.w_u64_array(8, values.as_slice()).unwrap();
+  for (i, v) in values.iter().enumerate() {
+    assert_eq!(*v, s.r_u64(8 + i * 8).unwrap());
+  }
+}
+
+// r_u32_array() and r_u64_array() tests
+pub fn r_u32_array_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_u32_array(0, 4).unwrap_err().description()
+  );
+}
+
+pub fn r_u32_array_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_u32_array(244, 4).unwrap_err().description()
+  );
+  assert_eq!(4, s.r_u32_array(240, 4).unwrap().len());
+}
+
+pub fn r_u32_array_reads_values_written_one_at_a_time<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let values: Vec<u32> = (0..50).map(|i| i * 0x01010101 + 7).collect();
+  for (i, v) in values.iter().enumerate() {
+    s.w_u32(3 + i * 4, *v).unwrap();
+  }
+  assert_eq!(values, s.r_u32_array(3, 50).unwrap());
+  assert_eq!(&values[10..20], s.r_u32_array(43, 10).unwrap().as_slice());
+}
+
+pub fn r_u32_array_returns_empty_for_0_count<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(Vec::<u32>::new(), s.r_u32_array(0, 0).unwrap());
+}
+
+pub fn r_u64_array_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_u64_array(232, 4).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_ARITHMETIC_OVERFLOW,
+    s.r_u64_array(0, usize::max_value()).unwrap_err().description()
+  );
+}
+
+pub fn r_u64_array_reads_values_written_one_at_a_time<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let values: Vec<u64> = (0..20).map(|i| u64::max_value() - i * 0x100000001).collect();
+  for (i, v) in values.iter().enumerate() {
+    s.w_u64(16 + i * 8, *v).unwrap();
+  }
+  assert_eq!(values, s.r_u64_array(16, 20).unwrap());
+}
+
+// r_u64_opt() tests
+pub fn r_u64_opt_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.r_u64_opt(0).unwrap_err().description()
+  );
+}
+
+pub fn r_u64_opt_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END, 
+    s.r_u64_opt(250).unwrap_err().description()
+  );
+}
+
+pub fn r_u64_opt_returns_none_when_unwritten<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(None, s.r_u64_opt(0).unwrap());
+  assert_eq!(None, s.r_u64_opt(248).unwrap());
+}
+
+pub fn r_u64_opt_returns_some_when_written<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u64(0, u64::max_value()).unwrap();
+  s.w_u64(16, 1).unwrap();
+  assert_eq!(Some(u64::max_value()), s.r_u64_opt(0).unwrap());
+  assert_eq!(Some(1), s.r_u64_opt(16).unwrap());
+  assert_eq!(Some(1 << 8), s.r_u64_opt(15).unwrap());
+}
+
+pub fn r_u64_opt_returns_some_after_writing_zero<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u64(8, 0).unwrap();
+  assert_eq!(Some(0), s.r_u64_opt(8).unwrap());
+  assert_eq!(None, s.r_u64_opt(16).unwrap());
+}
+
+pub fn r_u64_opt_returns_some_after_filling_with_zero<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.fill(Some(32), Some(40), 0x0).unwrap();
+  assert_eq!(Some(0), s.r_u64_opt(32).unwrap());
+  assert_eq!(None, s.r_u64_opt(40).unwrap());
+}
+
+pub fn r_u64_opt_returns_some_when_one_byte_written<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u8(7, 0x0).unwrap();
+  assert_eq!(Some(0), s.r_u64_opt(0).unwrap());
+  assert_eq!(Some(0), s.r_u64_opt(7).unwrap());
+  assert_eq!(None, s.r_u64_opt(8).unwrap());
+}
+
+// r_i128() tests
+pub fn r_i128_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_i128(0).unwrap_err().description()
+  );
+}
+
+pub fn r_i128_reads_written_data<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_i128(0, i128::min_value()).unwrap();
+  assert_eq!(i128::min_value(), s.r_i128(0).unwrap());
+  s.w_i128(32, i128::max_value()).unwrap();
+  assert_eq!(i128::max_value(), s.r_i128(32).unwrap());
+}
+
+pub fn r_i128_does_not_read_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert!(s.r_i128(240).is_ok());
+  assert_eq!(
+      binary_storage::ERR_READ_PAST_END,
+      s.r_i128(256).unwrap_err().description()
+  );
+}
+
+pub fn r_i128_result_is_not_mutated_on_subsequent_write<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_i128(0, i128::min_value()).unwrap();
+  let res1 = s.r_i128(0).unwrap();
+  assert_eq!(i128::min_value(), res1);
+  s.w_i128(0, i128::max_value()).unwrap();
+  let res2 = s.r_i128(0).unwrap();
+  assert_eq!(i128::min_value(), res1);
+  assert_eq!(i128::max_value(), res2);
+}
+
+// r_u128() tests
+pub fn r_u128_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_u128(0).unwrap_err().description()
+  );
+}
+
+pub fn r_u128_reads_written_data<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u128(0, u128::max_value()).unwrap();
+  assert_eq!(u128::max_value(), s.r_u128(0).unwrap());
+  s.w_u128(32, u128::max_value() - 10).unwrap();
+  assert_eq!(u128::max_value() - 10, s.r_u128(32).unwrap());
+}
+
+pub fn r_u128_does_not_read_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert!(s.r_u128(240).is_ok());
+  assert_eq!(
+      binary_storage::ERR_READ_PAST_END,
+      s.r_u128(256).unwrap_err().description()
+  );
+}
+
+pub fn r_u128_result_is_not_mutated_on_subsequent_write<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_u128(0, u128::max_value()).unwrap();
+  let res1 = s.r_u128(0).unwrap();
+  assert_eq!(u128::max_value(), res1);
+  s.w_u128(0, u128::max_value() - 10).unwrap();
+  let res2 = s.r_u128(0).unwrap();
+  assert_eq!(u128::max_value(), res1);
+  assert_eq!(u128::max_value() - 10, res2);
+}
+
 // r_f32() tests
 pub fn r_f32_returns_err_when_closed<T: BinaryStorage>(s: T) {
   assert!(!s.is_open());
@@ -1024,6 +1286,60 @@ pub fn r_bytes_result_is_not_mutated_on_subsequent_write<T: BinaryStorage>(
   assert_eq!(vec!(0x4, 0x5, 0x6), res2);
 }
 
+// r_bytes_into() tests
+pub fn r_bytes_into_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  let mut buf = [0x0; 5];
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_bytes_into(0, &mut buf).unwrap_err().description()
+  );
+}
+
+pub fn r_bytes_into_fills_buffer_with_written_data<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(32, &[0x5, 0x6, 0x7, 0x8, 0x9]).unwrap();
+  let mut buf = [0xFF; 5];
+  assert_eq!(5, s.r_bytes_into(32, &mut buf).unwrap());
+  assert_eq!([0x5, 0x6, 0x7, 0x8, 0x9], buf);
+}
+
+pub fn r_bytes_into_reads_part_of_written_data<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(0, &[0x0, 0x1, 0x2, 0x3, 0x4]).unwrap();
+  let mut buf = [0xFF; 4];
+  assert_eq!(2, s.r_bytes_into(2, &mut buf[1..3]).unwrap());
+  assert_eq!([0xFF, 0x2, 0x3, 0xFF], buf);
+}
+
+pub fn r_bytes_into_matches_r_bytes<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  let mut buf = [0x0; 8];
+  s.r_bytes_into(9, &mut buf).unwrap();
+  assert_eq!(s.r_bytes(9, 8).unwrap(), buf.to_vec());
+}
+
+pub fn r_bytes_into_with_empty_buffer_reads_nothing<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let mut buf = [];
+  assert_eq!(0, s.r_bytes_into(16, &mut buf).unwrap());
+}
+
+pub fn r_bytes_into_does_not_read_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let mut buf = [0x0; 2];
+  assert!(s.r_bytes_into(254, &mut buf).is_ok());
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes_into(255, &mut buf).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes(255, 2).unwrap_err().description()
+  );
+}
+
 // r_str() tests
 pub fn r_str_returns_err_when_closed<T: BinaryStorage>(s: T) {
   assert!(!s.is_open());
@@ -1074,6 +1390,296 @@ pub fn r_str_result_is_not_mutated_on_subsequent_write<T: BinaryStorage>(mut s:
   assert_eq!("barbaz", res2);
 }
 
+// w_uuid() and r_uuid() tests
+pub fn w_uuid_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.w_uuid(0, Uuid::nil()).unwrap_err().description()
+  );
+}
+
+pub fn r_uuid_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.r_uuid(0).unwrap_err().description()
+  );
+}
+
+pub fn r_uuid_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END, 
+    s.r_uuid(250).unwrap_err().description()
+  );
+}
+
+pub fn w_uuid_and_r_uuid_round_trip<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let ids = vec!(
+    Uuid::nil(),
+    Uuid::new_v4(),
+    Uuid::new_v4(),
+    Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap()
+  );
+  for (i, id) in ids.iter().enumerate() {
+    s.w_uuid(i * 16 + 1, *id).unwrap();
+  }
+  for (i, id) in ids.iter().enumerate() {
+    assert_eq!(*id, s.r_uuid(i * 16 + 1).unwrap());
+  }
+}
+
+pub fn w_uuid_writes_raw_bytes<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let id = Uuid::parse_str("936da01f-9abd-4d9d-80c7-02af85c822a8").unwrap();
+  s.w_uuid(4, id).unwrap();
+  assert_eq!(
+    vec!(
+      0x93, 0x6d, 0xa0, 0x1f, 0x9a, 0xbd, 0x4d, 0x9d, 
+      0x80, 0xc7, 0x02, 0xaf, 0x85, 0xc8, 0x22, 0xa8
+    ),
+    s.r_bytes(4, 16).unwrap()
+  );
+  assert_eq!(0x0, s.r_u8(3).unwrap());
+  assert_eq!(0x0, s.r_u8(20).unwrap());
+}
+
+pub fn w_uuid_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let id = Uuid::new_v4();
+  s.w_uuid(250, id).unwrap();
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(id, s.r_uuid(250).unwrap());
+}
+
+pub fn w_uuid_returns_err_when_end_overflows<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_ARITHMETIC_OVERFLOW,
+    s.w_uuid(usize::max_value() - 8, Uuid::new_v4()).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+}
+
+// read_all() tests
+pub fn read_all_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.read_all().unwrap_err().description()
+  );
+}
+
+pub fn read_all_returns_capacity_length_bytes<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(256, s.read_all().unwrap().len());
+  s.w_u8(256, 0x1).unwrap();
+  assert_eq!(512, s.read_all().unwrap().len());
+}
+
+pub fn read_all_returns_written_data_and_zero_fill<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  let mut expected = vec![0x0; s.get_capacity().unwrap()];
+  expected[10] = 0x1;
+  expected[11] = 0x2;
+  expected[12] = 0x3;
+  expected[13] = 0x4;
+  assert_eq!(expected, s.read_all().unwrap());
+}
+
+// pread() and pwrite() tests
+pub fn pread_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.pread(0, 4).unwrap_err().description()
+  );
+}
+
+pub fn pwrite_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.pwrite(0, &[0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+pub fn pread_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END, 
+    s.pread(250, 10).unwrap_err().description()
+  );
+}
+
+pub fn pwrite_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.pwrite(510, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.pread(510, 4).unwrap());
+}
+
+pub fn pread_and_pwrite_are_positional<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.pwrite(20, &[0x5, 0x6]).unwrap();
+  s.pwrite(10, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(vec!(0x2, 0x3), s.pread(11, 2).unwrap());
+  assert_eq!(vec!(0x5, 0x6), s.pread(20, 2).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.pread(10, 3).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(10, 3).unwrap());
+  s.w_bytes(10, &[0x7]).unwrap();
+  assert_eq!(vec!(0x7, 0x2, 0x3), s.pread(10, 3).unwrap());
+}
+
+// valid_len_from() tests
+pub fn valid_len_from_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED, 
+    s.valid_len_from(0).unwrap_err().description()
+  );
+}
+
+pub fn valid_len_from_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END, 
+    s.valid_len_from(256).unwrap_err().description()
+  );
+}
+
+pub fn valid_len_from_returns_0_when_zero_filled<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(0, s.valid_len_from(0).unwrap());
+  assert_eq!(0, s.valid_len_from(100).unwrap());
+}
+
+pub fn valid_len_from_returns_written_extent<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x0, 0x0, 0x2]).unwrap();
+  assert_eq!(14, s.valid_len_from(0).unwrap());
+  assert_eq!(4, s.valid_len_from(10).unwrap());
+  assert_eq!(1, s.valid_len_from(13).unwrap());
+  assert_eq!(0, s.valid_len_from(14).unwrap());
+  s.fill(Some(12), None, 0x0).unwrap();
+  assert_eq!(1, s.valid_len_from(10).unwrap());
+}
+
+// swap_ranges() tests
+pub fn swap_ranges_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.swap_ranges(0, 8, 4).unwrap_err().description()
+  );
+}
+
+pub fn swap_ranges_returns_err_when_ranges_overlap<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_RANGES_OVERLAP,
+    s.swap_ranges(0, 3, 4).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_RANGES_OVERLAP,
+    s.swap_ranges(10, 7, 4).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_RANGES_OVERLAP,
+    s.swap_ranges(5, 5, 1).unwrap_err().description()
+  );
+}
+
+pub fn swap_ranges_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_WRITE_PAST_END,
+    s.swap_ranges(0, 253, 4).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+}
+
+pub fn swap_ranges_exchanges_contents<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x9, 0x1, 0x2, 0x3, 0x4, 0x9]).unwrap();
+  s.w_bytes(100, &[0x8, 0x5, 0x6, 0x7, 0x8, 0x8]).unwrap();
+  s.swap_ranges(11, 101, 4).unwrap();
+  assert_eq!(vec!(0x9, 0x5, 0x6, 0x7, 0x8, 0x9), s.r_bytes(10, 6).unwrap());
+  assert_eq!(vec!(0x8, 0x1, 0x2, 0x3, 0x4, 0x8), s.r_bytes(100, 6).unwrap());
+  assert!(s.is_filled(None, Some(10), 0x0).unwrap());
+  assert!(s.is_filled(Some(16), Some(100), 0x0).unwrap());
+  assert!(s.is_filled(Some(106), None, 0x0).unwrap());
+}
+
+pub fn swap_ranges_exchanges_adjacent_ranges<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(252, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.swap_ranges(254, 252, 2).unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x1, 0x2), s.r_bytes(252, 4).unwrap());
+}
+
+// copy() tests
+pub fn copy_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.copy(0, 8, 4).unwrap_err().description()
+  );
+}
+
+pub fn copy_returns_err_when_source_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.copy(253, 0, 4).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+}
+
+pub fn copy_copies_separate_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.w_bytes(99, &[0x9, 0x9, 0x9, 0x9, 0x9, 0x9]).unwrap();
+  s.copy(10, 100, 4).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(10, 4).unwrap());
+  assert_eq!(vec!(0x9, 0x1, 0x2, 0x3, 0x4, 0x9), s.r_bytes(99, 6).unwrap());
+}
+
+pub fn copy_copies_forward_over_overlapping_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  s.copy(10, 12, 4).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x1, 0x2, 0x3, 0x4), s.r_bytes(10, 6).unwrap());
+}
+
+pub fn copy_copies_backward_over_overlapping_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  s.copy(12, 10, 4).unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x5, 0x6, 0x5, 0x6), s.r_bytes(10, 6).unwrap());
+}
+
+pub fn copy_expands_to_hold_destination<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(250, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  s.copy(250, 254, 6).unwrap();
+  assert!(s.get_capacity().unwrap() >= 260);
+  assert_eq!(
+    vec!(0x1, 0x2, 0x3, 0x4, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6), 
+    s.r_bytes(250, 10).unwrap()
+  );
+}
+
+pub fn copy_of_nothing_changes_nothing<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2]).unwrap();
+  s.copy(10, 11, 0).unwrap();
+  assert_eq!(vec!(0x1, 0x2), s.r_bytes(10, 2).unwrap());
+}
+
 // fill() tests
 pub fn fill_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
   assert!(!s.is_open());
@@ -1249,6 +1855,119 @@ pub fn is_filled_goes_to_end_when_end_offset_is_none<T: BinaryStorage>(mut s: T)
   assert!(!s.is_filled(None, None, 0x0).unwrap());
 }
 
+// crc32() tests
+pub fn crc32_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.crc32(None, None).unwrap_err().description()
+  );
+}
+
+pub fn crc32_returns_err_when_range_is_bad<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.crc32(Some(256), None).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.crc32(Some(0), Some(257)).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_NOTHING,
+    s.crc32(Some(10), Some(10)).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_NOTHING,
+    s.crc32(Some(10), Some(9)).unwrap_err().description()
+  );
+}
+
+pub fn crc32_matches_known_pattern<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_str(10, "123456789").unwrap();
+  assert_eq!(0xCBF43926, s.crc32(Some(10), Some(19)).unwrap());
+  s.w_bytes(0, &[0x0; 10]).unwrap();
+  assert_eq!(0xE38A6876, s.crc32(None, Some(10)).unwrap());
+}
+
+pub fn crc32_changes_after_single_byte_mutation<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_str(10, "123456789").unwrap();
+  let before = s.crc32(Some(10), Some(19)).unwrap();
+  s.w_u8(14, b'x').unwrap();
+  assert!(before != s.crc32(Some(10), Some(19)).unwrap());
+  s.w_u8(14, b'5').unwrap();
+  assert_eq!(before, s.crc32(Some(10), Some(19)).unwrap());
+}
+
+// find_bytes() tests
+pub fn find_bytes_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.find_bytes(&[0x2, 0x3], 0).unwrap_err().description()
+  );
+}
+
+pub fn find_bytes_returns_err_when_start_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.find_bytes(&[0x2, 0x3], 256).unwrap_err().description()
+  );
+}
+
+pub fn find_bytes_returns_err_when_pattern_empty<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_NOTHING,
+    s.find_bytes(&[], 0).unwrap_err().description()
+  );
+}
+
+pub fn find_bytes_finds_pattern_at_start<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(0, &[0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(Some(0), s.find_bytes(&[0x2, 0x3], 0).unwrap());
+  s.w_bytes(100, &[0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(Some(100), s.find_bytes(&[0x2, 0x3], 100).unwrap());
+}
+
+pub fn find_bytes_finds_first_occurrence_after_start<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x2, 0x3]).unwrap();
+  s.w_bytes(50, &[0x2, 0x3]).unwrap();
+  s.w_bytes(90, &[0x2, 0x3]).unwrap();
+  assert_eq!(Some(10), s.find_bytes(&[0x2, 0x3], 0).unwrap());
+  assert_eq!(Some(50), s.find_bytes(&[0x2, 0x3], 11).unwrap());
+  assert_eq!(Some(90), s.find_bytes(&[0x2, 0x3], 51).unwrap());
+}
+
+// With 16-byte pages, as in the file storage tests, the pattern starts in 
+// one page and ends in the next
+pub fn find_bytes_finds_pattern_across_page_boundary<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(29, &[0x2, 0x3, 0x2, 0x3, 0x4, 0x5]).unwrap();
+  assert_eq!(Some(30), s.find_bytes(&[0x3, 0x2, 0x3, 0x4, 0x5], 0).unwrap());
+}
+
+pub fn find_bytes_finds_pattern_at_end_of_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(253, &[0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(Some(253), s.find_bytes(&[0x2, 0x3, 0x4], 0).unwrap());
+}
+
+pub fn find_bytes_returns_none_when_pattern_absent<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x2, 0x3, 0x4]).unwrap();
+  s.w_bytes(254, &[0x2, 0x3]).unwrap();
+  assert_eq!(None, s.find_bytes(&[0x2, 0x3, 0x5], 0).unwrap());
+  assert_eq!(None, s.find_bytes(&[0x2, 0x3, 0x4], 11).unwrap());
+  assert_eq!(None, s.find_bytes(&[0x2, 0x3, 0x4], 254).unwrap());
+}
+
 // get_expand_size() and set_expand_size() tests
 pub fn get_expand_size_returns_initial_expand_size<T: BinaryStorage>(s: T) {
   assert_eq!(512, s.get_expand_size());
@@ -1301,12 +2020,24 @@ pub fn capacity_increases_to_increments_of_last_set_expand_size<T: BinaryStorage
 ) {
   s.open().unwrap();
   s.w_u8(256, 0x1).unwrap();
-  /*
   assert_eq!(512, s.get_capacity().unwrap());
   s.set_expand_size(8).unwrap();
   s.w_u8(512, 0x1).unwrap();
   assert_eq!(520, s.get_capacity().unwrap());
-  */
+}
+
+pub fn capacity_increases_to_increments_of_larger_expand_size<T: BinaryStorage>(
+  mut s: T
+) {
+  s.open().unwrap();
+  s.set_expand_size(8).unwrap();
+  s.w_u8(256, 0x1).unwrap();
+  assert_eq!(264, s.get_capacity().unwrap());
+  s.set_expand_size(1024).unwrap();
+  s.w_u8(264, 0x1).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  s.w_u8(1024, 0x1).unwrap();
+  assert_eq!(2048, s.get_capacity().unwrap());
 }
 
 // get_capacity() tests
@@ -1419,3 +2150,272 @@ pub fn expand_changes_capacity_by_multiples_of_expand_size_when_successful
   s.expand(3000).unwrap();
   assert_eq!(3072, s.get_capacity().unwrap());
 }
+
+// shrink() tests
+pub fn shrink_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+      s.shrink(0).unwrap_err().description()
+  );
+}
+
+pub fn shrink_does_nothing_when_capacity_already_small<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.shrink(300).unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+}
+
+pub fn shrink_reduces_capacity_to_multiple_of_expand_size<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.expand(1000).unwrap();
+  assert_eq!(1008, s.get_capacity().unwrap());
+  s.shrink(100).unwrap();
+  assert_eq!(112, s.get_capacity().unwrap());
+}
+
+pub fn shrink_does_not_reduce_capacity_below_expand_size<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.shrink(0).unwrap();
+  assert_eq!(16, s.get_capacity().unwrap());
+}
+
+pub fn shrink_keeps_data_below_new_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.w_bytes(20, &[0x1, 0x2, 0x3]).unwrap();
+  s.w_bytes(900, &[0x4, 0x5, 0x6]).unwrap();
+  s.shrink(30).unwrap();
+  assert_eq!(32, s.get_capacity().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(20, 3).unwrap());
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes(900, 3).unwrap_err().description()
+  );
+}
+
+pub fn shrink_then_expand_reads_zeros_past_old_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.w_bytes(40, &[0x1, 0x2, 0x3]).unwrap();
+  s.shrink(16).unwrap();
+  s.expand(64).unwrap();
+  assert_eq!(vec!(0x0, 0x0, 0x0), s.r_bytes(40, 3).unwrap());
+}
+
+// truncate() tests
+pub fn truncate_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert!(!s.is_open());
+  assert_eq!(
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+      s.truncate(0).unwrap_err().description()
+  );
+}
+
+pub fn truncate_returns_err_when_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+      binary_storage::ERR_TRUNCATE_PAST_END,
+      s.truncate(257).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+  s.truncate(256).unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+}
+
+pub fn truncate_reduces_capacity_to_multiple_of_expand_size<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.expand(1000).unwrap();
+  s.truncate(100).unwrap();
+  assert_eq!(112, s.get_capacity().unwrap());
+}
+
+pub fn truncate_drops_data_past_new_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(16).unwrap();
+  s.w_bytes(20, &[0x1, 0x2, 0x3]).unwrap();
+  s.w_bytes(900, &[0x4, 0x5, 0x6]).unwrap();
+  s.truncate(32).unwrap();
+  assert_eq!(32, s.get_capacity().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(20, 3).unwrap());
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes(30, 3).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes(900, 3).unwrap_err().description()
+  );
+}
+
+// w_bytes_reporting() tests
+pub fn w_bytes_reporting_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.w_bytes_reporting(0, &[0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+pub fn w_bytes_reporting_does_not_report_expansion_within_capacity<T: BinaryStorage>(
+  mut s: T
+) {
+  s.open().unwrap();
+  s.set_expand_size(256).unwrap();
+  assert_eq!(
+    WriteOutcome { bytes_written: 3, expanded: false, capacity: 256 },
+    s.w_bytes_reporting(100, &[0x1, 0x2, 0x3]).unwrap()
+  );
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(100, 3).unwrap());
+}
+
+pub fn w_bytes_reporting_reports_expansion_past_capacity<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(
+    WriteOutcome { bytes_written: 4, expanded: true, capacity: 512 },
+    s.w_bytes_reporting(254, &[0x1, 0x2, 0x3, 0x4]).unwrap()
+  );
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(254, 4).unwrap());
+}
+
+pub fn w_bytes_reporting_does_not_report_expansion_after_expanding<T: BinaryStorage>(
+  mut s: T
+) {
+  s.open().unwrap();
+  s.w_bytes_reporting(254, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  assert!(!s.w_bytes_reporting(300, &[0x1, 0x2, 0x3, 0x4]).unwrap().expanded);
+}
+
+// Signed round-trip tests
+pub fn w_i8_r_i8_round_trip_signed_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let vals = [i8::min_value(), i8::min_value() + 1, -1, 0, 1, i8::max_value()];
+  for (i, v) in vals.iter().enumerate() {
+    s.w_i8(3 + i * 5, *v).unwrap();
+  }
+  for (i, v) in vals.iter().enumerate() {
+    assert_eq!(*v, s.r_i8(3 + i * 5).unwrap());
+  }
+  assert_eq!(0x80, s.r_u8(3).unwrap());
+  assert_eq!(0xff, s.r_u8(13).unwrap());
+}
+
+pub fn w_i16_r_i16_round_trip_signed_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let vals = [i16::min_value(), i16::min_value() + 1, -1, 0, 1, i16::max_value()];
+  for (i, v) in vals.iter().enumerate() {
+    s.w_i16(3 + i * 5, *v).unwrap();
+  }
+  for (i, v) in vals.iter().enumerate() {
+    assert_eq!(*v, s.r_i16(3 + i * 5).unwrap());
+  }
+  assert_eq!(vec!(0x0, 0x80), s.r_bytes(3, 2).unwrap());
+  assert_eq!(vec!(0xff, 0xff), s.r_bytes(13, 2).unwrap());
+}
+
+pub fn w_i32_r_i32_round_trip_signed_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let vals = [i32::min_value(), i32::min_value() + 1, -1, 0, 1, i32::max_value()];
+  for (i, v) in vals.iter().enumerate() {
+    s.w_i32(3 + i * 5, *v).unwrap();
+  }
+  for (i, v) in vals.iter().enumerate() {
+    assert_eq!(*v, s.r_i32(3 + i * 5).unwrap());
+  }
+  assert_eq!(vec!(0x0, 0x0, 0x0, 0x80), s.r_bytes(3, 4).unwrap());
+  assert_eq!(vec!(0xff, 0xff, 0xff, 0xff), s.r_bytes(13, 4).unwrap());
+}
+
+pub fn w_i64_r_i64_round_trip_signed_range<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let vals = [i64::min_value(), i64::min_value() + 1, -1, 0, 1, i64::max_value()];
+  for (i, v) in vals.iter().enumerate() {
+    s.w_i64(3 + i * 9, *v).unwrap();
+  }
+  for (i, v) in vals.iter().enumerate() {
+    assert_eq!(*v, s.r_i64(3 + i * 9).unwrap());
+  }
+  assert_eq!(vec!(0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x80), s.r_bytes(3, 8).unwrap());
+  assert_eq!(vec![0xff; 8], s.r_bytes(21, 8).unwrap());
+}
+
+pub fn sync_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.sync().unwrap_err().description()
+  );
+}
+
+pub fn sync_keeps_written_data<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  s.sync().unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(10, 3).unwrap());
+  s.w_bytes(13, &[0x4]).unwrap();
+  s.sync().unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(10, 4).unwrap());
+}
+
+pub fn w_blob_returns_err_when_closed<T: BinaryStorage>(mut s: T) {
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.w_blob(0, &[0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+pub fn r_blob_returns_err_when_closed<T: BinaryStorage>(s: T) {
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_blob(0).unwrap_err().description()
+  );
+}
+
+pub fn w_blob_writes_length_prefix<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  assert_eq!(7, s.w_blob(10, &[0x1, 0x2, 0x3]).unwrap());
+  assert_eq!(3, s.r_u32(10).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(14, 3).unwrap());
+}
+
+pub fn w_blob_r_blob_round_trip_varying_sizes<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  let blobs: Vec<Vec<u8>> = vec!(
+    vec!(),
+    vec!(0x1),
+    vec![0x2; 17],
+    vec!(),
+    (0..200).map(|i| i as u8).collect()
+  );
+
+  let mut offset = 3;
+  let mut offsets = Vec::new();
+  for b in blobs.iter() {
+    offsets.push(offset);
+    offset += s.w_blob(offset, b.as_slice()).unwrap();
+  }
+
+  for (b, o) in blobs.iter().zip(offsets.iter()) {
+    assert_eq!(*b, s.r_blob(*o).unwrap());
+  }
+}
+
+pub fn w_blob_over_capacity_expands_storage<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.w_blob(250, &[0x5; 20]).unwrap();
+  assert!(s.get_capacity().unwrap() >= 274);
+  assert_eq!(vec![0x5; 20], s.r_blob(250).unwrap());
+}
+
+pub fn r_blob_returns_err_when_length_runs_past_end<T: BinaryStorage>(mut s: T) {
+  s.open().unwrap();
+  s.set_expand_size(256).unwrap();
+  let capacity = s.get_capacity().unwrap();
+  s.w_u32(capacity - 8, 100).unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_blob(capacity - 8).unwrap_err().description()
+  );
+}
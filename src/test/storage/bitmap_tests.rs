@@ -0,0 +1,145 @@
+use std::error::Error;
+use storage::bitmap;
+use storage::bitmap::Bitmap;
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+
+fn new_bitmap(num_slots: u64) -> Bitmap<MemoryBinaryStorage> {
+  let mut b = Bitmap::new(MemoryBinaryStorage::new(16, 16).unwrap(), num_slots).unwrap();
+  b.open().unwrap();
+  b
+}
+
+
+// new() and open() tests
+#[test]
+pub fn new_returns_err_when_num_slots_is_0() {
+  let res = Bitmap::new(MemoryBinaryStorage::new(16, 16).unwrap(), 0);
+  assert_eq!(
+    bitmap::ERR_NUM_SLOTS_TOO_SMALL,
+    res.err().unwrap().description()
+  );
+}
+
+#[test]
+pub fn open_starts_with_all_slots_free() {
+  let b = new_bitmap(20);
+  for i in 0..20 {
+    assert!(!b.is_set(i).unwrap());
+  }
+}
+
+#[test]
+pub fn open_expands_storage_to_hold_all_slots() {
+  let mut b = new_bitmap(1000);
+  b.free(999).unwrap();
+  assert!(!b.is_set(999).unwrap());
+}
+
+
+// allocate() tests
+#[test]
+pub fn allocate_returns_err_when_closed() {
+  let mut b = Bitmap::new(MemoryBinaryStorage::new(16, 16).unwrap(), 8).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    b.allocate().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn allocate_returns_slots_in_order() {
+  let mut b = new_bitmap(20);
+  for i in 0..20 {
+    assert_eq!(Some(i), b.allocate().unwrap());
+    assert!(b.is_set(i).unwrap());
+  }
+}
+
+#[test]
+pub fn allocate_returns_none_when_all_slots_used() {
+  let mut b = new_bitmap(20);
+  for _ in 0..20 {
+    b.allocate().unwrap();
+  }
+  assert_eq!(None, b.allocate().unwrap());
+}
+
+#[test]
+pub fn allocate_does_not_use_padding_bits_past_last_slot() {
+  let mut b = new_bitmap(3);
+  b.allocate().unwrap();
+  b.allocate().unwrap();
+  b.allocate().unwrap();
+  assert_eq!(None, b.allocate().unwrap());
+}
+
+#[test]
+pub fn allocate_reuses_freed_slots_lowest_first() {
+  let mut b = new_bitmap(20);
+  for _ in 0..20 {
+    b.allocate().unwrap();
+  }
+  b.free(17).unwrap();
+  b.free(3).unwrap();
+  b.free(9).unwrap();
+  assert_eq!(Some(3), b.allocate().unwrap());
+  assert_eq!(Some(9), b.allocate().unwrap());
+  assert_eq!(Some(17), b.allocate().unwrap());
+  assert_eq!(None, b.allocate().unwrap());
+}
+
+
+// free() and is_set() tests
+#[test]
+pub fn free_clears_only_that_slot() {
+  let mut b = new_bitmap(16);
+  for _ in 0..16 {
+    b.allocate().unwrap();
+  }
+  b.free(8).unwrap();
+  for i in 0..16 {
+    assert_eq!(i != 8, b.is_set(i).unwrap());
+  }
+}
+
+#[test]
+pub fn free_returns_err_when_out_of_range() {
+  let mut b = new_bitmap(16);
+  assert_eq!(
+    bitmap::ERR_SLOT_OUT_OF_RANGE,
+    b.free(16).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn is_set_returns_err_when_out_of_range() {
+  let b = new_bitmap(16);
+  assert_eq!(
+    bitmap::ERR_SLOT_OUT_OF_RANGE,
+    b.is_set(16).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn is_set_returns_err_when_closed() {
+  let b = Bitmap::new(MemoryBinaryStorage::new(16, 16).unwrap(), 8).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    b.is_set(0).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn allocations_survive_reopen() {
+  let mut b = new_bitmap(16);
+  b.allocate().unwrap();
+  b.allocate().unwrap();
+  b.free(0).unwrap();
+  b.close().unwrap();
+  b.open().unwrap();
+  assert!(!b.is_set(0).unwrap());
+  assert!(b.is_set(1).unwrap());
+  assert_eq!(Some(0), b.allocate().unwrap());
+}
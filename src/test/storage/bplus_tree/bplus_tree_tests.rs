@@ -1,7 +1,11 @@
+use std::error::Error;
 
 use storage::binary_storage::BinaryStorage;
 use storage::memory_binary_storage::MemoryBinaryStorage;
-use storage::bplus_tree::bplus_tree::BPlusTree;
+use storage::transactional_storage::TransactionalStorage;
+use storage::journal::Journal;
+use storage::bplus_tree::bplus_tree;
+use storage::bplus_tree::bplus_tree::{ BPlusTree, NodeKind };
 
 #[test]
 pub fn inserts_and_finds() {
@@ -9,13 +13,13 @@ pub fn inserts_and_finds() {
   let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
   s.open().unwrap();
   s.w_u8(0, 0x02).unwrap();
-  s.w_u32(25, 3);
-  s.w_u8(29, 0x01); // 1 => 255
-  s.w_u8(30, 0xff);
-  s.w_u8(31, 0x02); // 2 => 254
-  s.w_u8(32, 0xfe);
-  s.w_u8(33, 0x03); // 3 => 253
-  s.w_u8(34, 0xfd);
+  s.w_u32(25, 3).unwrap();
+  s.w_u8(29, 0x01).unwrap(); // 1 => 255
+  s.w_u8(30, 0xff).unwrap();
+  s.w_u8(31, 0x02).unwrap(); // 2 => 254
+  s.w_u8(32, 0xfe).unwrap();
+  s.w_u8(33, 0x03).unwrap(); // 3 => 253
+  s.w_u8(34, 0xfd).unwrap();
   s.close().unwrap();
 
   let mut t = BPlusTree::new(
@@ -49,3 +53,938 @@ pub fn inserts_and_finds() {
 
 }
 
+#[test]
+pub fn insert_splits_leaves_and_inner_nodes_into_new_levels() {
+  // 40 byte nodes hold 5 leaf records of 2 bytes, or 3 child pointers, so
+  // 100 keys need several levels of inner nodes
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+
+  let keys: Vec<u8> = (0..100).map(|i| (i * 37 % 100) as u8).collect();
+  for k in keys.iter() {
+    t.insert(&[*k], &[0xff - *k]).unwrap();
+  }
+
+  for k in 0..100 {
+    assert_eq!(vec!(0xff - k), t.search(&[k]).unwrap().unwrap());
+  }
+  assert_eq!(None, t.search(&[100]).unwrap());
+}
+
+#[test]
+pub fn insert_overwrites_value_of_existing_key() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+
+  for k in 0..20 { t.insert(&[k], &[k]).unwrap(); }
+  t.insert(&[0x07], &[0xaa]).unwrap();
+
+  assert_eq!(vec!(0xaa), t.search(&[0x07]).unwrap().unwrap());
+  for k in (0..20).filter(|k| *k != 0x07) {
+    assert_eq!(vec!(k), t.search(&[k]).unwrap().unwrap());
+  }
+}
+
+#[test]
+pub fn search_returns_none_for_new_tree() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+
+  assert_eq!(None, t.search(&[0x01]).unwrap());
+}
+
+#[test]
+pub fn delete_range_removes_keys_in_range() {
+
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 5).unwrap();
+  s.w_u8(29, 0x01).unwrap(); // 1 => 255
+  s.w_u8(30, 0xff).unwrap();
+  s.w_u8(31, 0x02).unwrap(); // 2 => 254
+  s.w_u8(32, 0xfe).unwrap();
+  s.w_u8(33, 0x03).unwrap(); // 3 => 253
+  s.w_u8(34, 0xfd).unwrap();
+  s.w_u8(35, 0x04).unwrap(); // 4 => 252
+  s.w_u8(36, 0xfc).unwrap();
+  s.w_u8(37, 0x05).unwrap(); // 5 => 251
+  s.w_u8(38, 0xfb).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(
+    s,
+    1,
+    1,
+    40,
+  );
+
+  t.open().unwrap();
+
+  assert_eq!(2, t.delete_range(&[0x02], &[0x04]).unwrap());
+
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert!(t.search(&[0x02]).unwrap().is_none());
+  assert!(t.search(&[0x03]).unwrap().is_none());
+  assert_eq!(vec!(0xfc), t.search(&[0x04]).unwrap().unwrap());
+  assert_eq!(vec!(0xfb), t.search(&[0x05]).unwrap().unwrap());
+
+}
+
+#[test]
+pub fn key_buffer_is_zeroed_and_key_sized() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let t = BPlusTree::new(s, 4, 1, 64);
+  assert_eq!(vec!(0x0, 0x0, 0x0, 0x0), t.key_buffer());
+}
+
+#[test]
+pub fn reused_key_buffer_finds_same_values_as_slices() {
+
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 3).unwrap();
+  s.w_u8(29, 0x01).unwrap(); // 1 => 255
+  s.w_u8(30, 0xff).unwrap();
+  s.w_u8(31, 0x02).unwrap(); // 2 => 254
+  s.w_u8(32, 0xfe).unwrap();
+  s.w_u8(33, 0x03).unwrap(); // 3 => 253
+  s.w_u8(34, 0xfd).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(
+    s,
+    1,
+    1,
+    40,
+  );
+
+  t.open().unwrap();
+
+  let mut key = t.key_buffer();
+  for k in 1..4 {
+    key[0] = k;
+    assert_eq!(
+      t.search(&[k]).unwrap(),
+      t.search_buf(&key).unwrap()
+    );
+  }
+
+}
+
+fn get_journal() -> Journal<MemoryBinaryStorage> {
+  let mut j = Journal::new(TransactionalStorage::new(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  ));
+  j.open().unwrap();
+  j
+}
+
+fn write_rec(j: &mut Journal<MemoryBinaryStorage>, data: &[u8]) {
+  j.write(data).unwrap();
+  j.commit().unwrap();
+}
+
+fn get_empty_leaf_tree() -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  t
+}
+
+#[test]
+pub fn build_from_journal_inserts_each_record() {
+  let mut j = get_journal();
+  write_rec(&mut j, &[0x01, 0xff]);
+  write_rec(&mut j, &[0x02, 0xfe]);
+  write_rec(&mut j, &[0x03, 0xfd]);
+
+  let mut t = get_empty_leaf_tree();
+  assert_eq!(3, t.build_from_journal(&mut j, 1).unwrap());
+
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfe), t.search(&[0x02]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+}
+
+#[test]
+pub fn build_from_journal_keeps_latest_value_for_key() {
+  let mut j = get_journal();
+  write_rec(&mut j, &[0x01, 0xff]);
+  write_rec(&mut j, &[0x02, 0xfe]);
+  write_rec(&mut j, &[0x01, 0x10]);
+
+  let mut t = get_empty_leaf_tree();
+  assert_eq!(3, t.build_from_journal(&mut j, 1).unwrap());
+
+  assert_eq!(vec!(0x10), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfe), t.search(&[0x02]).unwrap().unwrap());
+}
+
+#[test]
+pub fn build_from_journal_returns_0_for_empty_journal() {
+  let mut j = get_journal();
+  let mut t = get_empty_leaf_tree();
+  assert_eq!(0, t.build_from_journal(&mut j, 1).unwrap());
+}
+
+#[test]
+pub fn build_from_journal_returns_err_when_key_len_mismatched() {
+  let mut j = get_journal();
+  write_rec(&mut j, &[0x01, 0x02, 0xff]);
+  let mut t = get_empty_leaf_tree();
+  let res = t.build_from_journal(&mut j, 2);
+  assert!(res.is_err());
+  assert_eq!(bplus_tree::ERR_KEY_WRONG_SIZE, res.unwrap_err().description());
+}
+
+#[test]
+pub fn nodes_returns_empty_for_new_tree() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(0, t.nodes().unwrap().len());
+}
+
+#[test]
+pub fn nodes_classifies_inner_and_leaf_nodes() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap(); // Inner
+  s.w_u8(40, 0x02).unwrap(); // Leaf
+  s.w_u8(80, 0x02).unwrap(); // Leaf
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+
+  assert_eq!(
+    vec!(
+      (0, NodeKind::Inner), 
+      (40, NodeKind::Leaf), 
+      (80, NodeKind::Leaf)
+    ), 
+    t.nodes().unwrap()
+  );
+}
+
+#[test]
+pub fn nodes_marks_unknown_markers_as_invalid() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap(); // Leaf
+  s.w_u8(40, 0x07).unwrap(); // Not a node marker
+  s.w_u8(80, 0x01).unwrap(); // Inner
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+
+  assert_eq!(
+    vec!((0, NodeKind::Leaf), (40, NodeKind::Invalid), (80, NodeKind::Inner)), 
+    t.nodes().unwrap()
+  );
+}
+
+fn get_three_rec_leaf_tree() -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 3).unwrap();
+  s.w_u8(29, 0x01).unwrap(); // 1 => 255
+  s.w_u8(30, 0xff).unwrap();
+  s.w_u8(31, 0x03).unwrap(); // 3 => 253
+  s.w_u8(32, 0xfd).unwrap();
+  s.w_u8(33, 0x05).unwrap(); // 5 => 251
+  s.w_u8(34, 0xfb).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  t
+}
+
+#[test]
+pub fn insert_accepts_correctly_sized_value() {
+  let mut t = get_three_rec_leaf_tree();
+  t.insert(&[0x02], &[0xfe]).unwrap();
+  assert_eq!(vec!(0xfe), t.search(&[0x02]).unwrap().unwrap());
+}
+
+#[test]
+pub fn insert_returns_err_when_value_too_big() {
+  let mut t = get_three_rec_leaf_tree();
+  assert_eq!(
+    bplus_tree::ERR_VAL_WRONG_SIZE,
+    t.insert(&[0x02], &[0xfe, 0xfe]).unwrap_err().description()
+  );
+  assert!(t.search(&[0x02]).unwrap().is_none());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+}
+
+#[test]
+pub fn insert_returns_err_when_value_too_small() {
+  let mut t = get_three_rec_leaf_tree();
+  assert_eq!(
+    bplus_tree::ERR_VAL_WRONG_SIZE,
+    t.insert(&[0x03], &[]).unwrap_err().description()
+  );
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+  assert_eq!(vec!(0xfb), t.search(&[0x05]).unwrap().unwrap());
+}
+
+// Leaf with three records laid out for 16-byte alignment: records start at
+// 32 (29 rounded up) and are 16 bytes apart
+fn get_aligned_leaf_tree() -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 3).unwrap();
+  s.w_bytes(32, &[0x01, 0xff]).unwrap(); // 1 => 255
+  s.w_bytes(48, &[0x02, 0xfe]).unwrap(); // 2 => 254
+  s.w_bytes(64, &[0x03, 0xfd]).unwrap(); // 3 => 253
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 90);
+  t.set_leaf_align(16).unwrap();
+  t.open().unwrap();
+  t
+}
+
+#[test]
+pub fn leaf_align_defaults_to_packed_layout() {
+  let t = BPlusTree::new(MemoryBinaryStorage::new(256, 256).unwrap(), 1, 1, 90);
+  assert_eq!(1, t.get_leaf_align());
+  assert_eq!(90, t.get_node_size());
+  assert_eq!(29, t.leaf_record_offset(0));
+  assert_eq!(31, t.leaf_record_offset(1));
+}
+
+#[test]
+pub fn set_leaf_align_returns_err_when_not_power_of_two() {
+  let mut t = BPlusTree::new(MemoryBinaryStorage::new(256, 256).unwrap(), 1, 1, 90);
+  assert_eq!(
+    bplus_tree::ERR_LEAF_ALIGN_INVALID,
+    t.set_leaf_align(0).unwrap_err().description()
+  );
+  assert_eq!(
+    bplus_tree::ERR_LEAF_ALIGN_INVALID,
+    t.set_leaf_align(48).unwrap_err().description()
+  );
+  assert_eq!(1, t.get_leaf_align());
+}
+
+#[test]
+pub fn set_leaf_align_pads_node_size() {
+  let mut t = BPlusTree::new(MemoryBinaryStorage::new(256, 256).unwrap(), 4, 8, 200);
+  t.set_leaf_align(64).unwrap();
+  assert_eq!(64, t.get_leaf_align());
+  assert_eq!(256, t.get_node_size());
+}
+
+#[test]
+pub fn leaf_records_land_on_aligned_offsets() {
+  let mut t = BPlusTree::new(MemoryBinaryStorage::new(256, 256).unwrap(), 4, 8, 512);
+  t.set_leaf_align(64).unwrap();
+  assert_eq!(64, t.leaf_record_offset(0));
+  for i in 0..6 {
+    assert_eq!(0, t.leaf_record_offset(i) % 64);
+  }
+  assert_eq!(128, t.leaf_record_offset(1));
+}
+
+#[test]
+pub fn search_finds_values_with_leaf_align() {
+  let mut t = get_aligned_leaf_tree();
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfe), t.search(&[0x02]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+  assert_eq!(None, t.search(&[0x04]).unwrap());
+}
+
+#[test]
+pub fn delete_range_keeps_records_aligned() {
+  let mut t = get_aligned_leaf_tree();
+  assert_eq!(1, t.delete_range(&[0x01], &[0x02]).unwrap());
+  assert_eq!(None, t.search(&[0x01]).unwrap());
+  assert_eq!(vec!(0xfe), t.search(&[0x02]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+}
+
+#[test]
+pub fn to_sorted_vec_returns_empty_for_new_tree() {
+  let mut t = BPlusTree::new(MemoryBinaryStorage::new(256, 256).unwrap(), 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(0, t.to_sorted_vec().unwrap().len());
+}
+
+#[test]
+pub fn to_sorted_vec_exports_leaf_records() {
+  let mut t = get_three_rec_leaf_tree();
+  assert_eq!(
+    vec!(
+      (vec!(0x01), vec!(0xff)),
+      (vec!(0x03), vec!(0xfd)),
+      (vec!(0x05), vec!(0xfb))
+    ),
+    t.to_sorted_vec().unwrap()
+  );
+}
+
+#[test]
+pub fn to_sorted_vec_follows_leaf_chain_from_leftmost_leaf() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap(); // Inner
+  s.w_u64(13, 40).unwrap(); // First child is the leaf at 40
+  s.w_u8(40, 0x02).unwrap(); // Leaf holding 1 and 2
+  s.w_u64(57, 80).unwrap();
+  s.w_u32(65, 2).unwrap();
+  s.w_bytes(69, &[0x01, 0xff, 0x02, 0xfe]).unwrap();
+  s.w_u8(80, 0x02).unwrap(); // Leaf holding 3
+  s.w_u64(89, 40).unwrap();
+  s.w_u32(105, 1).unwrap();
+  s.w_bytes(109, &[0x03, 0xfd]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(
+    vec!(
+      (vec!(0x01), vec!(0xff)),
+      (vec!(0x02), vec!(0xfe)),
+      (vec!(0x03), vec!(0xfd))
+    ),
+    t.to_sorted_vec().unwrap()
+  );
+}
+
+#[test]
+pub fn to_sorted_vec_returns_err_when_keys_out_of_order() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 3).unwrap();
+  s.w_bytes(29, &[0x01, 0xff, 0x05, 0xfb, 0x03, 0xfd]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(
+    bplus_tree::ERR_KEYS_OUT_OF_ORDER,
+    t.to_sorted_vec().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn to_sorted_vec_returns_err_when_keys_repeat() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 2).unwrap();
+  s.w_bytes(29, &[0x01, 0xff, 0x01, 0xfe]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(
+    bplus_tree::ERR_KEYS_OUT_OF_ORDER,
+    t.to_sorted_vec().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn to_sorted_vec_is_sorted_and_complete_after_random_inserts() {
+  let mut s = MemoryBinaryStorage::new(2048, 2048).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 2, 2, 1024);
+  t.open().unwrap();
+
+  // Visit 0..200 in a scrambled order; 73 is coprime with 200
+  let mut keys: Vec<u16> = (0..200).map(|i| (i * 73 % 200) as u16).collect();
+  for k in keys.iter() {
+    let key = [(*k >> 8) as u8, *k as u8];
+    t.insert(&key, &[key[1], key[0]]).unwrap();
+  }
+
+  let recs = t.to_sorted_vec().unwrap();
+  keys.sort();
+  assert_eq!(keys.len(), recs.len());
+  for (k, &(ref key, ref val)) in keys.iter().zip(recs.iter()) {
+    assert_eq!(&vec!((*k >> 8) as u8, *k as u8), key);
+    assert_eq!(&vec!(*k as u8, (*k >> 8) as u8), val);
+  }
+}
+
+#[test]
+pub fn hint_split_returns_err_when_key_wrong_size() {
+  let mut t = get_three_rec_leaf_tree();
+  assert_eq!(
+    bplus_tree::ERR_KEY_WRONG_SIZE,
+    t.hint_split(&[0x01, 0x02]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn hint_split_leaves_half_empty_leaf_alone() {
+  // 40 byte nodes hold 5 records of 2 bytes, so 2 records is under half
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x02).unwrap();
+  s.w_u32(25, 2).unwrap();
+  s.w_bytes(29, &[0x01, 0xff, 0x03, 0xfd]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  t.hint_split(&[0x02]).unwrap();
+  assert_eq!(1, t.nodes().unwrap().len());
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+}
+
+#[test]
+pub fn hint_split_splits_more_than_half_full_leaf() {
+  // Root inner node at 0 pointing at a leaf at 40 holding 3 of 5 records
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap();
+  s.w_u32(9, 1).unwrap();
+  s.w_u64(13, 40).unwrap();
+  s.w_u8(40, 0x02).unwrap();
+  s.w_u32(65, 3).unwrap();
+  s.w_bytes(69, &[0x01, 0xff, 0x03, 0xfd, 0x05, 0xfb]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(2, t.nodes().unwrap().len());
+
+  t.hint_split(&[0x03]).unwrap();
+  assert_eq!(3, t.nodes().unwrap().len());
+
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+  assert_eq!(vec!(0xfd), t.search(&[0x03]).unwrap().unwrap());
+  assert_eq!(vec!(0xfb), t.search(&[0x05]).unwrap().unwrap());
+  assert_eq!(None, t.search(&[0x04]).unwrap());
+}
+
+#[test]
+pub fn max_descent_depth_defaults_to_num_nodes() {
+  let mut t = get_three_rec_leaf_tree();
+  assert_eq!(1, t.get_max_descent_depth());
+  t.set_max_descent_depth(Some(10));
+  assert_eq!(10, t.get_max_descent_depth());
+  t.set_max_descent_depth(None);
+  assert_eq!(1, t.get_max_descent_depth());
+}
+
+// Root inner node at 0 with a single child pointer back to itself
+fn get_self_referential_tree() -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap();
+  s.w_u32(9, 1).unwrap();
+  s.w_u64(13, 0).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  t
+}
+
+#[test]
+pub fn search_returns_err_on_self_referential_inner_node() {
+  let mut t = get_self_referential_tree();
+  assert_eq!(
+    bplus_tree::ERR_SEARCH_NO_LEAF_FOR_KEY,
+    t.search(&[0x01]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn search_returns_err_on_self_referential_inner_node_with_large_limit() {
+  let mut t = get_self_referential_tree();
+  t.set_max_descent_depth(Some(1000));
+  assert_eq!(
+    bplus_tree::ERR_SEARCH_NO_LEAF_FOR_KEY,
+    t.search(&[0x01]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn search_returns_err_on_inner_node_cycle() {
+  // Two inner nodes pointing at each other
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap();
+  s.w_u32(9, 1).unwrap();
+  s.w_u64(13, 40).unwrap();
+  s.w_u8(40, 0x01).unwrap();
+  s.w_u32(49, 1).unwrap();
+  s.w_u64(53, 0).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(
+    bplus_tree::ERR_SEARCH_NO_LEAF_FOR_KEY,
+    t.search(&[0x01]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn search_returns_err_when_descent_deeper_than_limit() {
+  // Inner node at 0 pointing at a leaf at 40 needs one step down
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap();
+  s.w_u32(9, 1).unwrap();
+  s.w_u64(13, 40).unwrap();
+  s.w_u8(40, 0x02).unwrap();
+  s.w_u32(65, 1).unwrap();
+  s.w_bytes(69, &[0x01, 0xff]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(vec!(0xff), t.search(&[0x01]).unwrap().unwrap());
+
+  t.set_max_descent_depth(Some(0));
+  assert_eq!(
+    bplus_tree::ERR_SEARCH_NO_LEAF_FOR_KEY,
+    t.search(&[0x01]).unwrap_err().description()
+  );
+}
+
+// height() tests
+#[test]
+pub fn height_is_0_for_new_tree() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(0, t.height().unwrap());
+}
+
+#[test]
+pub fn height_is_1_for_root_leaf() {
+  let t = get_three_rec_leaf_tree();
+  assert_eq!(1, t.height().unwrap());
+}
+
+#[test]
+pub fn height_counts_inner_levels() {
+  let t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(2, t.height().unwrap());
+
+  // Root inner node at 0 over an inner node at 40 over a leaf at 80
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap();
+  s.w_u32(9, 1).unwrap();
+  s.w_u64(13, 40).unwrap();
+  s.w_u8(40, 0x01).unwrap();
+  s.w_u64(41, 0).unwrap();
+  s.w_u32(49, 1).unwrap();
+  s.w_u64(53, 80).unwrap();
+  s.w_u8(80, 0x02).unwrap();
+  s.w_u64(81, 40).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(3, t.height().unwrap());
+}
+
+#[test]
+pub fn height_returns_err_on_inner_node_cycle() {
+  let t = get_self_referential_tree();
+  assert_eq!(
+    bplus_tree::ERR_SEARCH_NO_LEAF_FOR_KEY,
+    t.height().unwrap_err().description()
+  );
+}
+
+
+// set_inline_value_max() tests
+
+// Same records as get_three_leaf_tree(), but with 48 byte nodes and each
+// separator followed by its inlined value. The value for 0x03 is inlined 
+// and the one for 0x05 isn't.
+fn get_inline_three_leaf_tree() -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap(); // Inner
+  s.w_u32(9, 3).unwrap();
+  s.w_u64(13, 48).unwrap();
+  s.w_bytes(21, &[0x03, 0x01, 0xfc]).unwrap();
+  s.w_u64(24, 96).unwrap();
+  s.w_bytes(32, &[0x05, 0x00, 0x00]).unwrap();
+  s.w_u64(35, 144).unwrap();
+
+  s.w_u8(48, 0x02).unwrap();
+  s.w_u64(65, 96).unwrap();
+  s.w_u32(73, 2).unwrap();
+  s.w_bytes(77, &[0x01, 0xfe, 0x02, 0xfd]).unwrap();
+
+  s.w_u8(96, 0x02).unwrap();
+  s.w_u64(105, 48).unwrap();
+  s.w_u64(113, 144).unwrap();
+  s.w_u32(121, 2).unwrap();
+  s.w_bytes(125, &[0x03, 0xfc, 0x04, 0xfb]).unwrap();
+
+  s.w_u8(144, 0x02).unwrap();
+  s.w_u64(153, 96).unwrap();
+  s.w_u32(169, 2).unwrap();
+  s.w_bytes(173, &[0x05, 0xfa, 0x06, 0xf9]).unwrap();
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 48);
+  t.set_inline_value_max(Some(1));
+  t.open().unwrap();
+  t
+}
+
+#[test]
+pub fn inline_value_max_defaults_to_none() {
+  let t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(None, t.get_inline_value_max());
+}
+
+#[test]
+pub fn search_returns_inlined_value_without_reading_leaf() {
+  let mut t = get_inline_three_leaf_tree();
+  assert_eq!(Some(vec!(0xfc)), t.search(&[0x03]).unwrap());
+  assert_eq!(0, t.get_leaf_reads());
+}
+
+#[test]
+pub fn search_reads_leaf_for_key_that_is_not_a_separator() {
+  let mut t = get_inline_three_leaf_tree();
+  assert_eq!(Some(vec!(0xfb)), t.search(&[0x04]).unwrap());
+  assert_eq!(Some(vec!(0xfe)), t.search(&[0x01]).unwrap());
+  assert_eq!(None, t.search(&[0x07]).unwrap());
+  assert_eq!(3, t.get_leaf_reads());
+}
+
+#[test]
+pub fn search_reads_leaf_when_separator_has_no_inlined_value() {
+  let mut t = get_inline_three_leaf_tree();
+  assert_eq!(Some(vec!(0xfa)), t.search(&[0x05]).unwrap());
+  assert_eq!(1, t.get_leaf_reads());
+}
+
+#[test]
+pub fn values_over_inline_value_max_are_not_inlined() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  t.set_inline_value_max(Some(0));
+  assert_searchable(&mut t, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+  assert_eq!(6, t.get_leaf_reads());
+}
+
+#[test]
+pub fn delete_clears_inlined_value() {
+  let mut t = get_inline_three_leaf_tree();
+  assert!(t.delete(&[0x03]).unwrap());
+  assert_eq!(None, t.search(&[0x03]).unwrap());
+  assert_eq!(Some(vec!(0xfb)), t.search(&[0x04]).unwrap());
+}
+
+
+// search_batch() tests
+#[test]
+pub fn search_batch_returns_err_when_key_wrong_size() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(
+    bplus_tree::ERR_KEY_WRONG_SIZE,
+    t.search_batch(&[&[0x01], &[0x01, 0x02]]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn search_batch_returns_empty_for_no_keys() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(0, t.search_batch(&[]).unwrap().len());
+}
+
+#[test]
+pub fn search_batch_returns_none_for_each_key_in_new_tree() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  assert_eq!(vec!(None, None), t.search_batch(&[&[0x01], &[0x02]]).unwrap());
+}
+
+#[test]
+pub fn search_batch_matches_individual_searches_in_input_order() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  let keys: Vec<&[u8]> = vec!(&[0x06], &[0x00], &[0x03], &[0x09], &[0x01], &[0x04], &[0x03]);
+  let batch = t.search_batch(keys.as_slice()).unwrap();
+  let single: Vec<Option<Vec<u8>>> = keys.iter().map(|k| t.search(k).unwrap()).collect();
+  assert_eq!(single, batch);
+  assert_eq!(
+    vec!(Some(vec!(0xf9)), None, Some(vec!(0xfc)), None, Some(vec!(0xfe)), Some(vec!(0xfb)), Some(vec!(0xfc))),
+    batch
+  );
+}
+
+#[test]
+pub fn search_batch_descends_once() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  t.search_batch(&[&[0x06], &[0x03], &[0x05], &[0x04]]).unwrap();
+  assert_eq!(1, t.get_leaf_reads());
+}
+
+
+// delete(), set_min_leaf_occupancy(), and leaves() tests
+
+// Root inner node at 0 over three chained leaves: 40 holding 1 and 2, 80
+// holding 3 and 4, and 120 holding the keys in last_leaf. Each key k maps
+// to 0xff - k.
+fn get_three_leaf_tree(last_leaf: &[u8]) -> BPlusTree<MemoryBinaryStorage> {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u8(0, 0x01).unwrap(); // Inner
+  s.w_u32(9, 3).unwrap();
+  s.w_u64(13, 40).unwrap();
+  s.w_u8(21, 0x03).unwrap();
+  s.w_u64(22, 80).unwrap();
+  s.w_u8(30, last_leaf[0]).unwrap();
+  s.w_u64(31, 120).unwrap();
+
+  s.w_u8(40, 0x02).unwrap();
+  s.w_u64(57, 80).unwrap();
+  s.w_u32(65, 2).unwrap();
+  s.w_bytes(69, &[0x01, 0xfe, 0x02, 0xfd]).unwrap();
+
+  s.w_u8(80, 0x02).unwrap();
+  s.w_u64(89, 40).unwrap();
+  s.w_u64(97, 120).unwrap();
+  s.w_u32(105, 2).unwrap();
+  s.w_bytes(109, &[0x03, 0xfc, 0x04, 0xfb]).unwrap();
+
+  s.w_u8(120, 0x02).unwrap();
+  s.w_u64(129, 80).unwrap();
+  s.w_u32(145, last_leaf.len() as u32).unwrap();
+  for (i, k) in last_leaf.iter().enumerate() {
+    s.w_bytes(149 + 2 * i, &[*k, 0xff - *k]).unwrap();
+  }
+  s.close().unwrap();
+
+  let mut t = BPlusTree::new(s, 1, 1, 40);
+  t.open().unwrap();
+  t
+}
+
+fn assert_searchable(t: &mut BPlusTree<MemoryBinaryStorage>, keys: &[u8]) {
+  for k in keys.iter() {
+    assert_eq!(Some(vec!(0xff - *k)), t.search(&[*k]).unwrap());
+  }
+}
+
+#[test]
+pub fn leaves_follows_leaf_chain() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(vec!(40, 80, 120), t.leaves().unwrap());
+}
+
+#[test]
+pub fn min_leaf_occupancy_defaults_to_0() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(0, t.get_min_leaf_occupancy());
+  t.set_min_leaf_occupancy(2);
+  assert_eq!(2, t.get_min_leaf_occupancy());
+}
+
+#[test]
+pub fn delete_returns_err_when_key_wrong_size() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert_eq!(
+    bplus_tree::ERR_KEY_WRONG_SIZE,
+    t.delete(&[0x01, 0x02]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn delete_returns_false_when_key_missing() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert!(!t.delete(&[0x07]).unwrap());
+  assert_searchable(&mut t, &[0x01, 0x02, 0x03, 0x04, 0x05, 0x06]);
+}
+
+#[test]
+pub fn delete_does_not_merge_when_occupancy_is_0() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  assert!(t.delete(&[0x03]).unwrap());
+  assert!(t.delete(&[0x04]).unwrap());
+  assert_eq!(vec!(40, 80, 120), t.leaves().unwrap());
+  assert_eq!(None, t.search(&[0x03]).unwrap());
+  assert_searchable(&mut t, &[0x01, 0x02, 0x05, 0x06]);
+}
+
+#[test]
+pub fn delete_merges_underfull_leaf_with_next_leaf() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  t.set_min_leaf_occupancy(2);
+  assert!(t.delete(&[0x03]).unwrap());
+
+  assert_eq!(vec!(40, 80), t.leaves().unwrap());
+  assert_eq!(NodeKind::Invalid, t.nodes().unwrap()[3].1);
+  assert_eq!(None, t.search(&[0x03]).unwrap());
+  assert_searchable(&mut t, &[0x01, 0x02, 0x04, 0x05, 0x06]);
+  assert_eq!(5, t.to_sorted_vec().unwrap().len());
+}
+
+#[test]
+pub fn delete_merges_last_leaf_with_previous_leaf() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  t.set_min_leaf_occupancy(2);
+  assert!(t.delete(&[0x06]).unwrap());
+
+  assert_eq!(vec!(40, 80), t.leaves().unwrap());
+  assert_eq!(None, t.search(&[0x06]).unwrap());
+  assert_searchable(&mut t, &[0x01, 0x02, 0x03, 0x04, 0x05]);
+}
+
+#[test]
+pub fn delete_keeps_chain_consistent_over_repeated_merges() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06]);
+  t.set_min_leaf_occupancy(2);
+  assert!(t.delete(&[0x02]).unwrap());
+  assert_eq!(vec!(40, 120), t.leaves().unwrap());
+  assert!(t.delete(&[0x05]).unwrap());
+  assert!(t.delete(&[0x06]).unwrap());
+
+  assert_eq!(vec!(40), t.leaves().unwrap());
+  assert_searchable(&mut t, &[0x01, 0x03, 0x04]);
+  assert_eq!(
+    vec!(
+      (vec!(0x01), vec!(0xfe)),
+      (vec!(0x03), vec!(0xfc)),
+      (vec!(0x04), vec!(0xfb))
+    ),
+    t.to_sorted_vec().unwrap()
+  );
+}
+
+#[test]
+pub fn delete_does_not_merge_when_leaves_would_overflow() {
+  let mut t = get_three_leaf_tree(&[0x05, 0x06, 0x07, 0x08, 0x09]);
+  t.set_min_leaf_occupancy(2);
+  assert!(t.delete(&[0x04]).unwrap());
+
+  assert_eq!(vec!(40, 80, 120), t.leaves().unwrap());
+  assert_searchable(&mut t, &[0x01, 0x02, 0x03, 0x05, 0x06, 0x07, 0x08, 0x09]);
+}
@@ -1 +1 @@
-//mod bplus_tree_tests;
+mod bplus_tree_tests;
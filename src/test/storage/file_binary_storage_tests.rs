@@ -1,12 +1,19 @@
 use std::fs;
 use std::fs::OpenOptions;
+use std::io::Read;
 use std::path::Path;
+use std::time::Duration;
+use std::rc::Rc;
+use std::cell::Cell;
+use std::error::Error as StdError;
 use uuid::Uuid;
 
 use error::Error;
+use storage::binary_storage;
 use test::storage::binary_storage_tests;
-use storage::binary_storage::BinaryStorage;
-use storage::file_binary_storage::FileBinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WorkloadHint };
+use storage::file_binary_storage;
+use storage::file_binary_storage::{ FileBinaryStorage, ReadBound, Endianness };
 
 
 pub static BASE_PATH: &'static str = "./test_data/storage/file_binary_storage/";
@@ -134,6 +141,35 @@ pub fn open_does_not_open_when_file_does_not_exist_and_creation_not_allowed() {
   assert!(!s.is_open());
 }
 
+#[test]
+pub fn open_fails_immediately_on_not_found_with_retries_set() {
+  let path = rnd_path();
+  let mut s = FileBinaryStorage::new(
+    path.clone(),
+    false,
+    256,
+    16, 
+    16,
+    512
+  ).unwrap();
+  s.set_open_retries(3, Duration::from_secs(10));
+  assert!(
+    match s.open().unwrap_err() {
+      Error::Io(_) => true,
+      _ => false
+    }
+  );
+  assert!(!s.is_open());
+}
+
+#[test]
+pub fn open_succeeds_with_retries_set() {
+  let (mut s, p) = get_storage();
+  s.set_open_retries(3, Duration::from_millis(1));
+  assert!(s.open().is_ok());
+  rm_tmp(p);
+}
+
 #[test]
 pub fn open_does_not_create_file_when_not_allowed_and_file_does_not_exist() {
   let path = rnd_path();
@@ -195,6 +231,55 @@ fn is_closed_after_open_and_close() {
 // new() tests
 // TODO: Write these
 
+// with_hint() tests
+#[test]
+pub fn with_hint_uses_defaults_for_each_hint() {
+  let expected = vec!(
+    (WorkloadHint::Append, 4096, 16, 65536),
+    (WorkloadHint::RandomWrite, 4096, 256, 4096),
+    (WorkloadHint::ReadMostly, 16384, 1024, 4096)
+  );
+  for (hint, page_size, max_pages, expand_size) in expected {
+    let s = FileBinaryStorage::with_hint(
+      rnd_path(), true, 256, hint, None, None, None
+    ).unwrap();
+    assert_eq!(page_size, s.get_buffer_page_size());
+    assert_eq!(max_pages, s.get_buffer_max_pages());
+    assert_eq!(expand_size, s.get_expand_size());
+  }
+}
+
+#[test]
+pub fn with_hint_prefers_explicit_values() {
+  let s = FileBinaryStorage::with_hint(
+    rnd_path(), true, 256, WorkloadHint::ReadMostly, Some(16), None, Some(512)
+  ).unwrap();
+  assert_eq!(16, s.get_buffer_page_size());
+  assert_eq!(1024, s.get_buffer_max_pages());
+  assert_eq!(512, s.get_expand_size());
+
+  let s = FileBinaryStorage::with_hint(
+    rnd_path(), true, 256, WorkloadHint::Append, None, Some(4), None
+  ).unwrap();
+  assert_eq!(4096, s.get_buffer_page_size());
+  assert_eq!(4, s.get_buffer_max_pages());
+  assert_eq!(65536, s.get_expand_size());
+}
+
+#[test]
+pub fn with_hint_store_is_usable() {
+  let path = rnd_path();
+  let mut s = FileBinaryStorage::with_hint(
+    path.clone(), true, 256, WorkloadHint::Append, None, None, None
+  ).unwrap();
+  s.open().unwrap();
+  s.w_u64(300, 0x1234).unwrap();
+  assert_eq!(0x1234, s.r_u64(300).unwrap());
+  assert_eq!(65536, s.get_capacity().unwrap());
+  s.close().unwrap();
+  rm_tmp(path);
+}
+
 // w_i8() tests
 #[test]
 fn w_i8_returns_err_when_closed() {
@@ -419,6 +504,34 @@ fn w_u64_over_capacity_expands_storage() {
   rm_tmp(p);
 }
 
+// w_i128() tests
+#[test]
+fn w_i128_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_i128_returns_err_when_closed(s);
+}
+
+#[test]
+fn w_i128_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_i128_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
+// w_u128() tests
+#[test]
+fn w_u128_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_u128_returns_err_when_closed(s);
+}
+
+#[test]
+fn w_u128_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_u128_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
 // w_f32() tests
 #[test]
 fn w_f32_returns_err_when_closed() {
@@ -909,6 +1022,62 @@ fn r_u64_result_is_not_mutated_on_subsequent_write() {
   rm_tmp(p);
 }
 
+// r_i128() tests
+#[test]
+fn r_i128_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_i128_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_i128_reads_written_data() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_i128_reads_written_data(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_i128_does_not_read_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_i128_does_not_read_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_i128_result_is_not_mutated_on_subsequent_write() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_i128_result_is_not_mutated_on_subsequent_write(s);
+  rm_tmp(p);
+}
+
+// r_u128() tests
+#[test]
+fn r_u128_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_u128_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_u128_reads_written_data() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u128_reads_written_data(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u128_does_not_read_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u128_does_not_read_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u128_result_is_not_mutated_on_subsequent_write() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u128_result_is_not_mutated_on_subsequent_write(s);
+  rm_tmp(p);
+}
+
 // r_f32() tests
 #[test]
 fn r_f32_returns_err_when_closed() {
@@ -1077,6 +1246,48 @@ fn r_bytes_result_is_not_mutated_on_subsequent_write() {
   rm_tmp(p);
 }
 
+// r_bytes_into() tests
+#[test]
+fn r_bytes_into_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_bytes_into_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_bytes_into_fills_buffer_with_written_data() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_bytes_into_fills_buffer_with_written_data(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_bytes_into_reads_part_of_written_data() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_bytes_into_reads_part_of_written_data(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_bytes_into_matches_r_bytes() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_bytes_into_matches_r_bytes(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_bytes_into_with_empty_buffer_reads_nothing() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_bytes_into_with_empty_buffer_reads_nothing(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_bytes_into_does_not_read_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_bytes_into_does_not_read_past_capacity(s);
+  rm_tmp(p);
+}
+
 // r_str() tests
 #[test]
 fn r_str_returns_err_when_closed() {
@@ -1261,6 +1472,90 @@ fn is_filled_goes_to_end_when_end_offset_is_none() {
   rm_tmp(p);
 }
 
+// crc32() tests
+#[test]
+fn crc32_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::crc32_returns_err_when_closed(s);
+}
+
+#[test]
+fn crc32_returns_err_when_range_is_bad() {
+  let (s, p) = get_storage();
+  binary_storage_tests::crc32_returns_err_when_range_is_bad(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn crc32_matches_known_pattern() {
+  let (s, p) = get_storage();
+  binary_storage_tests::crc32_matches_known_pattern(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn crc32_changes_after_single_byte_mutation() {
+  let (s, p) = get_storage();
+  binary_storage_tests::crc32_changes_after_single_byte_mutation(s);
+  rm_tmp(p);
+}
+
+// find_bytes() tests
+#[test]
+fn find_bytes_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::find_bytes_returns_err_when_closed(s);
+}
+
+#[test]
+fn find_bytes_returns_err_when_start_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_returns_err_when_start_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_returns_err_when_pattern_empty() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_returns_err_when_pattern_empty(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_start() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_finds_pattern_at_start(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_finds_first_occurrence_after_start() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_finds_first_occurrence_after_start(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_finds_pattern_across_page_boundary() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_finds_pattern_across_page_boundary(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_end_of_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_finds_pattern_at_end_of_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_returns_none_when_pattern_absent() {
+  let (s, p) = get_storage();
+  binary_storage_tests::find_bytes_returns_none_when_pattern_absent(s);
+  rm_tmp(p);
+}
+
 // get_expand_size() and set_expand_size() tests
 #[test]
 fn get_expand_size_returns_initial_expand_size() {
@@ -1311,6 +1606,13 @@ fn capacity_increases_to_increments_of_last_set_expand_size() {
   rm_tmp(p);
 }
 
+#[test]
+fn capacity_increases_to_increments_of_larger_expand_size() {
+  let (s, p) = get_storage();
+  binary_storage_tests::capacity_increases_to_increments_of_larger_expand_size(s);
+  rm_tmp(p);
+}
+
 // get_capacity() tests
 #[test]
 fn get_capacity_returns_err_when_closed() {
@@ -1409,3 +1711,1801 @@ fn expand_changes_capacity_by_multiples_of_expand_size_when_successful() {
   binary_storage_tests::expand_changes_capacity_by_multiples_of_expand_size_when_successful(s);
   rm_tmp(p);
 }
+
+// read_all() tests
+#[test]
+fn read_all_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::read_all_returns_err_when_closed(s);
+}
+
+#[test]
+fn read_all_returns_capacity_length_bytes() {
+  let (s, p) = get_storage();
+  binary_storage_tests::read_all_returns_capacity_length_bytes(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn read_all_returns_written_data_and_zero_fill() {
+  let (s, p) = get_storage();
+  binary_storage_tests::read_all_returns_written_data_and_zero_fill(s);
+  rm_tmp(p);
+}
+
+// read_written() tests
+#[test]
+fn read_written_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  assert!(s.read_written().is_err());
+}
+
+#[test]
+fn read_written_returns_empty_before_any_writes() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(Vec::<u8>::new(), s.read_written().unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn read_written_stops_at_high_water_mark() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2]).unwrap();
+  s.w_u8(5, 0x3).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x0, 0x0, 0x0, 0x3), s.read_written().unwrap());
+  assert_eq!(s.get_capacity().unwrap(), s.read_all().unwrap().len());
+  rm_tmp(p);
+}
+
+// get_high_water_mark() and stats() tests
+#[test]
+fn get_high_water_mark_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  assert!(s.get_high_water_mark().is_err());
+}
+
+#[test]
+fn get_high_water_mark_tracks_end_of_furthest_write() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.get_high_water_mark().unwrap());
+  s.w_u32(10, 0x1).unwrap();
+  assert_eq!(14, s.get_high_water_mark().unwrap());
+  s.w_u8(2, 0x1).unwrap();
+  assert_eq!(14, s.get_high_water_mark().unwrap());
+  s.w_bytes(20, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(23, s.get_high_water_mark().unwrap());
+  s.fill(Some(30), Some(40), 0x1).unwrap();
+  assert_eq!(40, s.get_high_water_mark().unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn stats_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  assert!(s.stats().is_err());
+}
+
+#[test]
+fn stats_reflects_individual_counters() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1; 64]).unwrap();
+  s.r_bytes(0, 32).unwrap();
+  s.r_bytes(0, 32).unwrap();
+  s.w_u8(600, 0x1).unwrap();
+  s.r_bytes(0, 512).unwrap();
+  let stats = s.stats().unwrap();
+  assert_eq!(s.get_capacity().unwrap(), stats.capacity);
+  assert_eq!(1024, stats.capacity);
+  assert_eq!(s.get_high_water_mark().unwrap(), stats.high_water_mark);
+  assert_eq!(601, stats.high_water_mark);
+  assert_eq!(4, stats.cache_hits);
+  assert_eq!(32, stats.cache_misses);
+  assert_eq!(16, stats.cache_evictions);
+  rm_tmp(p);
+}
+
+#[test]
+fn stats_reports_cached_pages() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.stats().unwrap().cached_pages);
+  s.r_bytes(0, 40).unwrap();
+  assert_eq!(3, s.stats().unwrap().cached_pages);
+  rm_tmp(p);
+}
+
+#[test]
+fn stats_reports_no_dirty_pages_after_write_through_writes() {
+  // Writes go straight to the file as well as the cache, so no page is 
+  // ever left dirty
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.stats().unwrap().dirty_pages);
+  s.r_bytes(0, 40).unwrap();
+  s.w_bytes(0, &[0x1; 40]).unwrap();
+  let stats = s.stats().unwrap();
+  assert_eq!(3, stats.cached_pages);
+  assert_eq!(0, stats.dirty_pages);
+  rm_tmp(p);
+}
+
+#[test]
+fn stats_reports_dirty_pages_in_write_back_mode() {
+  let (mut s, p) = get_storage();
+  s.set_write_back(true).unwrap();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1; 20]).unwrap();
+  s.w_u8(100, 0x1).unwrap();
+  let stats = s.stats().unwrap();
+  assert_eq!(3, stats.cached_pages);
+  assert_eq!(3, stats.dirty_pages);
+  s.sync().unwrap();
+  assert_eq!(0, s.stats().unwrap().dirty_pages);
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// get_read_bound() and set_read_bound() tests
+#[test]
+fn read_bound_defaults_to_capacity() {
+  let (s, _) = get_storage();
+  assert_eq!(ReadBound::Capacity, s.get_read_bound());
+}
+
+#[test]
+fn capacity_bound_reads_unwritten_space_as_zeros() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_u32(0, 0x1).unwrap();
+  assert_eq!(0, s.r_u32(100).unwrap());
+  assert_eq!(vec![0x0; 8], s.r_bytes(200, 8).unwrap());
+  assert_eq!(s.get_capacity().unwrap(), s.read_all().unwrap().len());
+  rm_tmp(p);
+}
+
+#[test]
+fn high_water_bound_rejects_reads_past_high_water_mark() {
+  let (mut s, p) = get_storage();
+  s.set_read_bound(ReadBound::HighWater);
+  s.open().unwrap();
+  s.w_u32(0, 0x1).unwrap();
+  s.w_bytes(4, &[0x2, 0x3]).unwrap();
+
+  assert_eq!(0x1, s.r_u32(0).unwrap());
+  assert_eq!(vec!(0x2, 0x3), s.r_bytes(4, 2).unwrap());
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_u32(100).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_u8(6).unwrap_err().description()
+  );
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_bytes(4, 3).unwrap_err().description()
+  );
+  assert_eq!(vec!(0x1, 0x0, 0x0, 0x0, 0x2, 0x3), s.read_all().unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn high_water_bound_allows_reads_after_later_writes() {
+  let (mut s, p) = get_storage();
+  s.set_read_bound(ReadBound::HighWater);
+  s.open().unwrap();
+  assert_eq!(Vec::<u8>::new(), s.read_all().unwrap());
+  assert!(s.r_u64(40).is_err());
+  s.w_u8(50, 0x1).unwrap();
+  assert_eq!(0, s.r_u64(40).unwrap());
+  s.set_read_bound(ReadBound::Capacity);
+  assert_eq!(0, s.r_u64(100).unwrap());
+  rm_tmp(p);
+}
+
+// get_zero_on_expand() and set_zero_on_expand() tests
+#[test]
+fn zero_on_expand_is_off_by_default() {
+  let (s, _) = get_storage();
+  assert!(!s.get_zero_on_expand());
+}
+
+#[test]
+fn set_zero_on_expand_changes_zero_on_expand() {
+  let (mut s, _) = get_storage();
+  s.set_zero_on_expand(true);
+  assert!(s.get_zero_on_expand());
+  s.set_zero_on_expand(false);
+  assert!(!s.get_zero_on_expand());
+}
+
+#[test]
+fn expand_zero_fills_new_space_when_zero_on_expand_set() {
+  let (mut s, p) = get_storage();
+  s.set_zero_on_expand(true);
+  s.open().unwrap();
+  s.fill(None, None, 0x1).unwrap();
+  s.expand(1000).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert!(s.is_filled(None, Some(256), 0x1).unwrap());
+  assert!(s.is_filled(Some(256), Some(1024), 0x0).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn expand_zero_fills_new_space_in_cached_pages_when_zero_on_expand_set() {
+  let (mut s, p) = get_storage();
+  s.set_zero_on_expand(true);
+  s.open().unwrap();
+  s.r_bytes(0, 256).unwrap();
+  s.expand(512).unwrap();
+  assert!(s.is_filled(Some(256), Some(512), 0x0).unwrap());
+  rm_tmp(p);
+}
+
+// pread() and pwrite() tests
+#[test]
+fn pread_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::pread_returns_err_when_closed(s);
+}
+
+#[test]
+fn pwrite_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::pwrite_returns_err_when_closed(s);
+}
+
+#[test]
+fn pread_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::pread_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn pwrite_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::pwrite_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn pread_and_pwrite_are_positional() {
+  let (s, p) = get_storage();
+  binary_storage_tests::pread_and_pwrite_are_positional(s);
+  rm_tmp(p);
+}
+
+// remove_range() tests
+#[test]
+fn remove_range_returns_err_when_closed() {
+  let (mut s, _) = get_storage();
+  assert!(s.remove_range(0, 4).is_err());
+}
+
+#[test]
+fn remove_range_returns_err_when_len_is_0() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_WRITE_NOTHING,
+    s.remove_range(4, 0).unwrap_err().description()
+  );
+  rm_tmp(p);
+}
+
+#[test]
+fn remove_range_returns_err_when_past_end() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_WRITE_PAST_END,
+    s.remove_range(250, 10).unwrap_err().description()
+  );
+  rm_tmp(p);
+}
+
+#[test]
+fn remove_range_shifts_following_bytes_down() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  let pattern: Vec<u8> = (0..100).map(|x| x as u8).collect();
+  s.w_bytes(0, pattern.as_slice()).unwrap();
+  s.remove_range(10, 20).unwrap();
+  assert_eq!(pattern[0..10].to_vec(), s.r_bytes(0, 10).unwrap());
+  assert_eq!(pattern[30..100].to_vec(), s.r_bytes(10, 70).unwrap());
+  assert!(s.is_filled(Some(80), None, 0x0).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn remove_range_zero_fills_vacated_tail() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.fill(None, None, 0x1).unwrap();
+  let cap = s.get_capacity().unwrap();
+  s.remove_range(100, 40).unwrap();
+  assert!(s.is_filled(None, Some(cap - 40), 0x1).unwrap());
+  assert!(s.is_filled(Some(cap - 40), None, 0x0).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn remove_range_persists_to_file() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  s.remove_range(1, 2).unwrap();
+  s.close().unwrap();
+  s.open().unwrap();
+  assert_eq!(vec!(0x1, 0x4, 0x5, 0x6, 0x0, 0x0), s.r_bytes(0, 6).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn remove_range_reduces_high_water_mark() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1; 100]).unwrap();
+  s.remove_range(10, 20).unwrap();
+  assert_eq!(80, s.get_high_water_mark().unwrap());
+  s.remove_range(70, 20).unwrap();
+  assert_eq!(70, s.get_high_water_mark().unwrap());
+  rm_tmp(p);
+}
+
+// close() and abandon() durability tests
+#[test]
+fn close_flushes_file() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(0, s.get_flush_count());
+  s.close().unwrap();
+  assert_eq!(1, s.get_flush_count());
+  s.open().unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(0, 3).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn abandon_returns_err_when_closed() {
+  let (mut s, _) = get_storage();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.abandon().unwrap_err().description()
+  );
+}
+
+#[test]
+fn abandon_closes_without_flushing() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.abandon().unwrap();
+  assert!(!s.is_open());
+  assert_eq!(0, s.get_flush_count());
+  rm_tmp(p);
+}
+
+// valid_len_from() tests
+#[test]
+fn valid_len_from_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::valid_len_from_returns_err_when_closed(s);
+}
+
+#[test]
+fn valid_len_from_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::valid_len_from_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn valid_len_from_returns_0_when_zero_filled() {
+  let (s, p) = get_storage();
+  binary_storage_tests::valid_len_from_returns_0_when_zero_filled(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn valid_len_from_returns_written_extent() {
+  let (s, p) = get_storage();
+  binary_storage_tests::valid_len_from_returns_written_extent(s);
+  rm_tmp(p);
+}
+
+// w_u32_array() and w_u64_array() tests
+#[test]
+fn w_u32_array_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_u32_array_returns_err_when_closed(s);
+}
+
+#[test]
+fn w_u32_array_writes_values_read_back_one_at_a_time() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_u32_array_writes_values_read_back_one_at_a_time(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u32_array_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_u32_array_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u32_array_writes_nothing_for_empty_slice() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_u32_array_writes_nothing_for_empty_slice(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u64_array_writes_values_read_back_one_at_a_time() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_u64_array_writes_values_read_back_one_at_a_time(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u32_array_expands_once_for_over_capacity_write() {
+  let (mut s, p) = get_storage_expand_size(4);
+  s.open().unwrap();
+  let values = vec![0x7; 200];
+  s.w_u32_array(0, values.as_slice()).unwrap();
+  assert_eq!(800, s.get_capacity().unwrap());
+  assert_eq!(1, s.get_expand_count());
+  assert_eq!(0x7, s.r_u32(796).unwrap());
+  rm_tmp(p);
+}
+
+// r_u32_array() and r_u64_array() tests
+#[test]
+fn r_u32_array_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_u32_array_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_u32_array_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u32_array_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u32_array_reads_values_written_one_at_a_time() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u32_array_reads_values_written_one_at_a_time(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u32_array_returns_empty_for_0_count() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u32_array_returns_empty_for_0_count(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_array_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_array_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_array_reads_values_written_one_at_a_time() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_array_reads_values_written_one_at_a_time(s);
+  rm_tmp(p);
+}
+
+// copy() tests
+#[test]
+fn copy_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::copy_returns_err_when_closed(s);
+}
+
+#[test]
+fn copy_returns_err_when_source_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_returns_err_when_source_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn copy_copies_separate_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_copies_separate_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn copy_copies_forward_over_overlapping_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_copies_forward_over_overlapping_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn copy_copies_backward_over_overlapping_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_copies_backward_over_overlapping_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn copy_expands_to_hold_destination() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_expands_to_hold_destination(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn copy_of_nothing_changes_nothing() {
+  let (s, p) = get_storage();
+  binary_storage_tests::copy_of_nothing_changes_nothing(s);
+  rm_tmp(p);
+}
+
+// swap_ranges() tests
+#[test]
+fn swap_ranges_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::swap_ranges_returns_err_when_closed(s);
+}
+
+#[test]
+fn swap_ranges_returns_err_when_ranges_overlap() {
+  let (s, p) = get_storage();
+  binary_storage_tests::swap_ranges_returns_err_when_ranges_overlap(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn swap_ranges_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::swap_ranges_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn swap_ranges_exchanges_contents() {
+  let (s, p) = get_storage();
+  binary_storage_tests::swap_ranges_exchanges_contents(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn swap_ranges_exchanges_adjacent_ranges() {
+  let (s, p) = get_storage();
+  binary_storage_tests::swap_ranges_exchanges_adjacent_ranges(s);
+  rm_tmp(p);
+}
+
+// expand() increment tests
+#[test]
+fn w_bytes_over_several_increments_expands_once() {
+  let (mut s, p) = get_storage_expand_size(4);
+  s.open().unwrap();
+  s.w_bytes(255, &[0x0, 0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  assert_eq!(264, s.get_capacity().unwrap());
+  assert_eq!(1, s.get_expand_count());
+  rm_tmp(p);
+}
+
+#[test]
+fn expand_does_not_count_when_capacity_sufficient() {
+  let (mut s, p) = get_storage_expand_size(4);
+  s.open().unwrap();
+  s.w_bytes(0, &[0x0, 0x1, 0x2]).unwrap();
+  s.expand(256).unwrap();
+  assert_eq!(0, s.get_expand_count());
+  s.expand(300).unwrap();
+  assert_eq!(300, s.get_capacity().unwrap());
+  assert_eq!(1, s.get_expand_count());
+  rm_tmp(p);
+}
+
+// r_u64_opt() tests
+#[test]
+fn r_u64_opt_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_u64_opt_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_none_when_unwritten() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_none_when_unwritten(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_written() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_some_when_written(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_writing_zero() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_some_after_writing_zero(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_filling_with_zero() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_some_after_filling_with_zero(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_one_byte_written() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_u64_opt_returns_some_when_one_byte_written(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_u64_opt_returns_none_for_zero_written_before_reopen() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_u64(8, 0).unwrap();
+  assert_eq!(Some(0), s.r_u64_opt(8).unwrap());
+  s.close().unwrap();
+  s.open().unwrap();
+  assert_eq!(None, s.r_u64_opt(8).unwrap());
+  rm_tmp(p);
+}
+
+// set_on_unflushed_drop() tests
+#[test]
+fn drop_with_unflushed_writes_calls_hook() {
+  let (mut s, p) = get_storage();
+  let dropped = Rc::new(Cell::new(false));
+  let d = dropped.clone();
+  s.set_on_unflushed_drop(Box::new(move || d.set(true)));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  drop(s);
+  assert!(dropped.get());
+  rm_tmp(p);
+}
+
+#[test]
+fn drop_after_close_does_not_call_hook() {
+  let (mut s, p) = get_storage();
+  let dropped = Rc::new(Cell::new(false));
+  let d = dropped.clone();
+  s.set_on_unflushed_drop(Box::new(move || d.set(true)));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.close().unwrap();
+  drop(s);
+  assert!(!dropped.get());
+  rm_tmp(p);
+}
+
+#[test]
+fn drop_after_abandon_does_not_call_hook() {
+  let (mut s, p) = get_storage();
+  let dropped = Rc::new(Cell::new(false));
+  let d = dropped.clone();
+  s.set_on_unflushed_drop(Box::new(move || d.set(true)));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.abandon().unwrap();
+  drop(s);
+  assert!(!dropped.get());
+  rm_tmp(p);
+}
+
+#[test]
+fn drop_without_writes_does_not_call_hook() {
+  let (mut s, p) = get_storage();
+  let dropped = Rc::new(Cell::new(false));
+  let d = dropped.clone();
+  s.set_on_unflushed_drop(Box::new(move || d.set(true)));
+  s.open().unwrap();
+  s.r_u8(0).unwrap();
+  drop(s);
+  assert!(!dropped.get());
+  rm_tmp(p);
+}
+
+// w_uuid() and r_uuid() tests
+#[test]
+fn w_uuid_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_uuid_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_uuid_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_uuid_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_uuid_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_uuid_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_uuid_and_r_uuid_round_trip() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_uuid_and_r_uuid_round_trip(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_uuid_writes_raw_bytes() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_uuid_writes_raw_bytes(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_uuid_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_uuid_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_uuid_returns_err_when_end_overflows() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_uuid_returns_err_when_end_overflows(s);
+  rm_tmp(p);
+}
+
+// set_exclusive() tests
+fn get_storage_at(path: String) -> FileBinaryStorage {
+  FileBinaryStorage::new(path, true, 256, 16, 16, 512).unwrap()
+}
+
+#[test]
+fn exclusive_defaults_to_false() {
+  let (s, _) = get_storage();
+  assert!(!s.get_exclusive());
+}
+
+#[test]
+fn exclusive_open_returns_err_when_already_locked() {
+  let (mut s1, p) = get_storage();
+  let mut s2 = get_storage_at(p.clone());
+  s1.set_exclusive(true);
+  s2.set_exclusive(true);
+  s1.open().unwrap();
+  let res = s2.open();
+  assert!(res.is_err());
+  assert_eq!(file_binary_storage::ERR_FILE_LOCKED, res.unwrap_err().description());
+  assert!(!s2.is_open());
+  s1.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn exclusive_open_succeeds_after_close() {
+  let (mut s1, p) = get_storage();
+  let mut s2 = get_storage_at(p.clone());
+  s1.set_exclusive(true);
+  s2.set_exclusive(true);
+  s1.open().unwrap();
+  s1.close().unwrap();
+  s2.open().unwrap();
+  assert!(s2.is_open());
+  s2.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn exclusive_open_succeeds_after_abandon() {
+  let (mut s1, p) = get_storage();
+  let mut s2 = get_storage_at(p.clone());
+  s1.set_exclusive(true);
+  s2.set_exclusive(true);
+  s1.open().unwrap();
+  s1.abandon().unwrap();
+  s2.open().unwrap();
+  s2.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn exclusive_open_succeeds_after_drop() {
+  let (mut s1, p) = get_storage();
+  s1.set_exclusive(true);
+  s1.open().unwrap();
+  drop(s1);
+  let mut s2 = get_storage_at(p.clone());
+  s2.set_exclusive(true);
+  s2.open().unwrap();
+  s2.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn exclusive_close_removes_lockfile() {
+  let (mut s, p) = get_storage();
+  s.set_exclusive(true);
+  s.open().unwrap();
+  assert!(Path::new((p.clone() + ".lock").as_str()).exists());
+  s.close().unwrap();
+  assert!(!Path::new((p.clone() + ".lock").as_str()).exists());
+  rm_tmp(p);
+}
+
+#[test]
+fn non_exclusive_open_ignores_lock() {
+  let (mut s1, p) = get_storage();
+  let mut s2 = get_storage_at(p.clone());
+  s1.set_exclusive(true);
+  s1.open().unwrap();
+  s2.open().unwrap();
+  assert!(s2.is_open());
+  s2.close().unwrap();
+  s1.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn non_exclusive_opens_of_same_path_both_succeed() {
+  let (mut s1, p) = get_storage();
+  let mut s2 = get_storage_at(p.clone());
+  s1.open().unwrap();
+  s2.open().unwrap();
+  assert!(!Path::new((p.clone() + ".lock").as_str()).exists());
+  s1.close().unwrap();
+  s2.close().unwrap();
+  rm_tmp(p);
+}
+
+// Uncached buffer tests
+fn get_uncached_storage() -> (FileBinaryStorage, String) {
+  let path = rnd_path();
+  let s = FileBinaryStorage::new(path.clone(), true, 256, 16, 0, 512).unwrap();
+  (s, path)
+}
+
+#[test]
+fn uncached_writes_are_read_back() {
+  let (mut s, p) = get_uncached_storage();
+  s.open().unwrap();
+  s.w_u32(14, 0x01020304).unwrap();
+  s.w_bytes(30, &[0x5, 0x6, 0x7]).unwrap();
+  assert_eq!(0x01020304, s.r_u32(14).unwrap());
+  s.w_u8(15, 0xff).unwrap();
+  assert_eq!(0x0102ff04, s.r_u32(14).unwrap());
+  assert_eq!(vec!(0x5, 0x6, 0x7), s.r_bytes(30, 3).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn uncached_writes_past_capacity_are_read_back() {
+  let (mut s, p) = get_uncached_storage();
+  s.open().unwrap();
+  s.w_u64(254, 0x0102030405060708).unwrap();
+  assert_eq!(0x0102030405060708, s.r_u64(254).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// set_verify_writes() tests
+#[test]
+fn verify_writes_defaults_to_false() {
+  let (s, _) = get_storage();
+  assert!(!s.get_verify_writes());
+}
+
+#[test]
+fn verified_writes_succeed() {
+  let (mut s, p) = get_storage();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.w_u64(8, 0x0102030405060708).unwrap();
+  s.w_bytes(20, &[0x5, 0x6, 0x7]).unwrap();
+  s.w_str(30, "foo").unwrap();
+  assert_eq!(0x0102030405060708, s.r_u64(8).unwrap());
+  assert_eq!(vec!(0x5, 0x6, 0x7), s.r_bytes(20, 3).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn verified_writes_past_capacity_succeed() {
+  let (mut s, p) = get_storage();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  s.w_bytes(254, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(254, 4).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// Swaps the file at the storage's path for a different one, so that 
+// writes go to the original file while reads of the path see the new one
+fn replace_file(path: String) {
+  fs::remove_file(path.clone()).unwrap();
+  let f = OpenOptions::new().write(true).create(true).open(path).unwrap();
+  f.set_len(256).unwrap();
+}
+
+#[test]
+fn verified_write_returns_err_on_mismatch() {
+  let (mut s, p) = get_storage();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  replace_file(p.clone());
+  let res = s.w_u32(0, 0x01020304);
+  assert!(res.is_err());
+  assert_eq!(file_binary_storage::ERR_WRITE_VERIFY_FAILED, res.unwrap_err().description());
+  let res = s.w_bytes(10, &[0x1, 0x2]);
+  assert!(res.is_err());
+  assert_eq!(file_binary_storage::ERR_WRITE_VERIFY_FAILED, res.unwrap_err().description());
+  s.abandon().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn verified_fill_succeeds() {
+  let (mut s, p) = get_storage();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  s.fill(Some(10), Some(20), 0x5).unwrap();
+  assert!(s.is_filled(Some(10), Some(20), 0x5).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn verified_fill_returns_err_on_mismatch() {
+  let (mut s, p) = get_storage();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  replace_file(p.clone());
+  assert_eq!(
+    file_binary_storage::ERR_WRITE_VERIFY_FAILED, 
+    s.fill(Some(10), Some(20), 0x5).unwrap_err().description()
+  );
+  s.abandon().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn unverified_write_ignores_mismatch() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  replace_file(p.clone());
+  s.w_u32(0, 0x01020304).unwrap();
+  s.abandon().unwrap();
+  rm_tmp(p);
+}
+
+// rebuild_with() tests
+#[test]
+fn rebuild_with_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.rebuild_with(rnd_path(), 32, 8, 256).err().unwrap().description()
+  );
+}
+
+#[test]
+fn rebuild_with_returns_err_when_path_exists() {
+  let (mut s, p) = get_storage();
+  let (mut other, other_p) = get_storage();
+  s.open().unwrap();
+  other.open().unwrap();
+  other.close().unwrap();
+  assert_eq!(
+    file_binary_storage::ERR_REBUILD_PATH_EXISTS,
+    s.rebuild_with(other_p.clone(), 32, 8, 256).err().unwrap().description()
+  );
+  s.close().unwrap();
+  rm_tmp(p);
+  rm_tmp(other_p);
+}
+
+#[test]
+fn rebuild_with_copies_all_data() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_u64(0, 0x0102030405060708).unwrap();
+  s.w_str(100, "foo").unwrap();
+  s.w_bytes(700, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+
+  let new_p = rnd_path();
+  let mut r = s.rebuild_with(new_p.clone(), 64, 4, 256).unwrap();
+  assert!(r.is_open());
+  assert_eq!(1024, r.get_capacity().unwrap());
+  assert_eq!(s.read_all().unwrap(), r.read_all().unwrap());
+  assert_eq!(0x0102030405060708, r.r_u64(0).unwrap());
+  assert_eq!("foo", r.r_str(100, 3).unwrap());
+  assert_eq!(256, r.get_expand_size());
+
+  // The copy is independent of the original
+  r.w_u8(0, 0xff).unwrap();
+  assert_eq!(0x08, s.r_u8(0).unwrap());
+
+  r.close().unwrap();
+  s.close().unwrap();
+  rm_tmp(p);
+  rm_tmp(new_p);
+}
+
+#[test]
+fn rebuilt_storage_reopens_with_same_data() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(10, &[0x5, 0x6, 0x7, 0x8]).unwrap();
+
+  let new_p = rnd_path();
+  let mut r = s.rebuild_with(new_p.clone(), 8, 2, 512).unwrap();
+  r.close().unwrap();
+  r.open().unwrap();
+  assert_eq!(s.read_all().unwrap(), r.read_all().unwrap());
+  assert!(r.stats().unwrap().cached_pages <= 2);
+
+  r.close().unwrap();
+  s.close().unwrap();
+  rm_tmp(p);
+  rm_tmp(new_p);
+}
+
+// set_write_back() tests
+fn file_bytes(path: String, len: usize) -> Vec<u8> {
+  let mut buf = Vec::new();
+  fs::File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+  buf.truncate(len);
+  buf
+}
+
+#[test]
+fn write_back_defaults_to_false() {
+  let (s, _) = get_storage();
+  assert!(!s.get_write_back());
+}
+
+#[test]
+fn write_back_writes_reach_file_on_sync() {
+  let (mut s, p) = get_storage();
+  s.set_write_back(true).unwrap();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.w_u8(40, 0x5).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(0, 4).unwrap());
+  assert_eq!(vec![0x0; 41], file_bytes(p.clone(), 41));
+
+  s.sync().unwrap();
+  let mut expected = vec![0x0; 41];
+  expected[0..4].copy_from_slice(&[0x1, 0x2, 0x3, 0x4]);
+  expected[40] = 0x5;
+  assert_eq!(expected, file_bytes(p.clone(), 41));
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn write_back_writes_reach_file_on_close() {
+  let (mut s, p) = get_storage();
+  s.set_write_back(true).unwrap();
+  s.open().unwrap();
+  s.w_u64(8, 0x0102030405060708).unwrap();
+  s.close().unwrap();
+
+  s.set_write_back(false).unwrap();
+  s.open().unwrap();
+  assert_eq!(0x0102030405060708, s.r_u64(8).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn turning_write_back_off_flushes_dirty_pages() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.set_write_back(true).unwrap();
+  s.w_bytes(16, &[0x7, 0x8]).unwrap();
+  assert_eq!(vec![0x0; 2], file_bytes(p.clone(), 18)[16..].to_vec());
+  s.set_write_back(false).unwrap();
+  assert_eq!(vec!(0x7, 0x8), file_bytes(p.clone(), 18)[16..].to_vec());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn write_back_writes_past_capacity_succeed() {
+  let (mut s, p) = get_storage();
+  s.set_write_back(true).unwrap();
+  s.open().unwrap();
+  s.w_bytes(254, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(254, 4).unwrap());
+  s.close().unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), file_bytes(p.clone(), 258)[254..].to_vec());
+  rm_tmp(p);
+}
+
+#[test]
+fn verified_write_back_writes_succeed() {
+  let (mut s, p) = get_storage();
+  s.set_write_back(true).unwrap();
+  s.set_verify_writes(true);
+  s.open().unwrap();
+  s.w_bytes(20, &[0x5, 0x6, 0x7]).unwrap();
+  assert_eq!(vec!(0x5, 0x6, 0x7), file_bytes(p.clone(), 23)[20..].to_vec());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// set_track_checksum(), current_checksum(), and compute_checksum() tests
+#[test]
+fn track_checksum_defaults_to_false() {
+  let (s, _) = get_storage();
+  assert!(!s.get_track_checksum());
+}
+
+#[test]
+fn current_checksum_returns_err_when_closed() {
+  let (mut s, _) = get_storage();
+  s.set_track_checksum(true).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.current_checksum().unwrap_err().description()
+  );
+}
+
+#[test]
+fn current_checksum_returns_err_when_not_tracked() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(
+    file_binary_storage::ERR_CHECKSUM_NOT_TRACKED,
+    s.current_checksum().unwrap_err().description()
+  );
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_is_0_for_empty_file() {
+  let (mut s, p) = get_storage();
+  s.set_track_checksum(true).unwrap();
+  s.open().unwrap();
+  assert_eq!(0, s.current_checksum().unwrap());
+  assert_eq!(0, s.compute_checksum().unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_matches_recomputation_after_writes() {
+  let (mut s, p) = get_storage();
+  s.set_track_checksum(true).unwrap();
+  s.open().unwrap();
+
+  s.w_u8(0, 0x1).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+  s.w_u64(13, 0x0102030405060708).unwrap();
+  s.w_bytes(40, &[0x5, 0x6, 0x7, 0x8, 0x9]).unwrap();
+  s.w_str(100, "foo").unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  // Overwrites, including one that writes the same bytes back
+  s.w_u32(14, 0xaabbccdd).unwrap();
+  s.w_bytes(40, &[0x5, 0x6, 0x7]).unwrap();
+  s.fill(Some(98), Some(102), 0xee).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+  assert!(s.current_checksum().unwrap() != 0);
+
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_tracks_expand_shrink_and_remove_range() {
+  let (mut s, p) = get_storage_expand_size(256);
+  s.set_track_checksum(true).unwrap();
+  s.set_zero_on_expand(true);
+  s.open().unwrap();
+
+  s.w_bytes(250, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+  s.w_u8(600, 0x9).unwrap();
+  assert_eq!(768, s.get_capacity().unwrap());
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  s.remove_range(252, 3).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  s.shrink(256).unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_matches_recomputation_across_blocks() {
+  let (mut s, p) = get_storage_expand_size(4096);
+  s.set_track_checksum(true).unwrap();
+  s.open().unwrap();
+
+  s.w_bytes(4090, &[0x1; 20]).unwrap();
+  s.w_u8(9000, 0x2).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+  s.fill(Some(4000), Some(4200), 0x0).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_changes_when_bytes_move() {
+  let (mut s, p) = get_storage();
+  s.set_track_checksum(true).unwrap();
+  s.open().unwrap();
+
+  s.w_bytes(0, &[0x1, 0x2]).unwrap();
+  let checksum = s.current_checksum().unwrap();
+  s.w_bytes(0, &[0x0, 0x0, 0x0, 0x0, 0x1, 0x2]).unwrap();
+  assert!(s.current_checksum().unwrap() != checksum);
+  s.w_bytes(0, &[0x2, 0x1, 0x0, 0x0, 0x0, 0x0]).unwrap();
+  assert!(s.current_checksum().unwrap() != checksum);
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn current_checksum_is_rebuilt_on_open() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  s.close().unwrap();
+
+  s.set_track_checksum(true).unwrap();
+  s.open().unwrap();
+  let checksum = s.current_checksum().unwrap();
+  assert!(checksum != 0);
+  assert_eq!(s.compute_checksum().unwrap(), checksum);
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+#[test]
+fn set_track_checksum_while_open_scans_file() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  s.set_track_checksum(true).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+  s.w_u8(11, 0x4).unwrap();
+  assert_eq!(s.compute_checksum().unwrap(), s.current_checksum().unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// shrink() tests
+#[test]
+fn shrink_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::shrink_returns_err_when_closed(s);
+}
+
+#[test]
+fn shrink_does_nothing_when_capacity_already_small() {
+  let (s, p) = get_storage();
+  binary_storage_tests::shrink_does_nothing_when_capacity_already_small(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn shrink_reduces_capacity_to_multiple_of_expand_size() {
+  let (s, p) = get_storage();
+  binary_storage_tests::shrink_reduces_capacity_to_multiple_of_expand_size(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn shrink_does_not_reduce_capacity_below_expand_size() {
+  let (s, p) = get_storage();
+  binary_storage_tests::shrink_does_not_reduce_capacity_below_expand_size(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn shrink_keeps_data_below_new_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::shrink_keeps_data_below_new_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn shrink_then_expand_reads_zeros_past_old_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::shrink_then_expand_reads_zeros_past_old_capacity(s);
+  rm_tmp(p);
+}
+
+// truncate() tests
+#[test]
+fn truncate_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::truncate_returns_err_when_closed(s);
+}
+
+#[test]
+fn truncate_returns_err_when_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::truncate_returns_err_when_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn truncate_reduces_capacity_to_multiple_of_expand_size() {
+  let (s, p) = get_storage();
+  binary_storage_tests::truncate_reduces_capacity_to_multiple_of_expand_size(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn truncate_drops_data_past_new_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::truncate_drops_data_past_new_capacity(s);
+  rm_tmp(p);
+}
+
+// w_bytes_reporting() tests
+#[test]
+fn w_bytes_reporting_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_bytes_reporting_returns_err_when_closed(s);
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_within_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_within_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_bytes_reporting_reports_expansion_past_capacity() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_bytes_reporting_reports_expansion_past_capacity(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_after_expanding() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_after_expanding(s);
+  rm_tmp(p);
+}
+
+// prepare_write() and place_bytes() tests
+#[test]
+fn prepare_write_returns_err_when_closed() {
+  let (mut s, _) = get_storage();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.prepare_write(0, 4).unwrap_err().description()
+  );
+}
+
+#[test]
+fn prepare_write_expands_to_hold_range() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.prepare_write(254, 4).unwrap();
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(0, s.get_high_water_mark().unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn place_bytes_returns_err_when_closed() {
+  let (mut s, _) = get_storage();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.place_bytes(0, &[0x1]).unwrap_err().description()
+  );
+}
+
+#[test]
+fn place_bytes_returns_err_when_past_capacity() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_WRITE_PAST_END,
+    s.place_bytes(254, &[0x1, 0x2, 0x3, 0x4]).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn prepare_write_and_place_bytes_match_w_bytes() {
+  let (mut s1, p1) = get_storage();
+  let (mut s2, p2) = get_storage();
+  s1.open().unwrap();
+  s2.open().unwrap();
+
+  let data = [0x1, 0x2, 0x3, 0x4, 0x5];
+  s1.w_bytes(253, &data).unwrap();
+  s2.prepare_write(253, data.len()).unwrap();
+  s2.place_bytes(253, &data).unwrap();
+
+  assert_eq!(s1.get_capacity().unwrap(), s2.get_capacity().unwrap());
+  assert_eq!(s1.get_high_water_mark().unwrap(), s2.get_high_water_mark().unwrap());
+  assert_eq!(s1.read_all().unwrap(), s2.read_all().unwrap());
+
+  s1.close().unwrap();
+  s2.close().unwrap();
+  rm_tmp(p1);
+  rm_tmp(p2);
+}
+
+// Signed round-trip tests
+#[test]
+fn w_i8_r_i8_round_trip_signed_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_i8_r_i8_round_trip_signed_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_i16_r_i16_round_trip_signed_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_i16_r_i16_round_trip_signed_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_i32_r_i32_round_trip_signed_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_i32_r_i32_round_trip_signed_range(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_i64_r_i64_round_trip_signed_range() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_i64_r_i64_round_trip_signed_range(s);
+  rm_tmp(p);
+}
+
+// set_warmup_on_open() tests
+#[test]
+fn warmup_on_open_defaults_to_0() {
+  let (s, _) = get_storage();
+  assert_eq!(0, s.get_warmup_on_open());
+}
+
+#[test]
+fn open_without_warmup_caches_nothing() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.stats().unwrap().cached_pages);
+  rm_tmp(p);
+}
+
+#[test]
+fn open_with_warmup_caches_first_pages() {
+  let (mut s, p) = get_storage();
+  s.set_warmup_on_open(4);
+  s.open().unwrap();
+  let stats = s.stats().unwrap();
+  assert_eq!(4, stats.cached_pages);
+  assert_eq!(0, stats.cache_misses);
+  rm_tmp(p);
+}
+
+#[test]
+fn open_with_warmup_makes_first_reads_cache_hits() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.w_bytes(60, &[0x5, 0x6, 0x7, 0x8]).unwrap();
+  s.close().unwrap();
+
+  s.set_warmup_on_open(4);
+  s.open().unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(0, 4).unwrap());
+  assert_eq!(vec!(0x5, 0x6, 0x7, 0x8), s.r_bytes(60, 4).unwrap());
+  let stats = s.stats().unwrap();
+  assert_eq!(2, stats.cache_hits);
+  assert_eq!(0, stats.cache_misses);
+  rm_tmp(p);
+}
+
+// sync() tests
+#[test]
+fn sync_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::sync_returns_err_when_closed(s);
+}
+
+#[test]
+fn sync_keeps_written_data() {
+  let (s, p) = get_storage();
+  binary_storage_tests::sync_keeps_written_data(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn sync_flushes_file() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.sync().unwrap();
+  assert_eq!(1, s.get_flush_count());
+  s.sync().unwrap();
+  assert_eq!(2, s.get_flush_count());
+  rm_tmp(p);
+}
+
+#[test]
+fn synced_data_survives_reopening_file() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_u64(0, 0x0102030405060708).unwrap();
+  s.w_bytes(300, &[0x9, 0xa, 0xb]).unwrap();
+  s.sync().unwrap();
+  drop(s);
+
+  let mut s = FileBinaryStorage::new(p.clone(), false, 256, 16, 16, 512).unwrap();
+  s.open().unwrap();
+  assert_eq!(0x0102030405060708, s.r_u64(0).unwrap());
+  assert_eq!(vec!(0x9, 0xa, 0xb), s.r_bytes(300, 3).unwrap());
+  s.close().unwrap();
+  rm_tmp(p);
+}
+
+// w_blob() and r_blob() tests
+#[test]
+fn w_blob_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::w_blob_returns_err_when_closed(s);
+}
+
+#[test]
+fn r_blob_returns_err_when_closed() {
+  let (s, _) = get_storage();
+  binary_storage_tests::r_blob_returns_err_when_closed(s);
+}
+
+#[test]
+fn w_blob_writes_length_prefix() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_blob_writes_length_prefix(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_blob_r_blob_round_trip_varying_sizes() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_blob_r_blob_round_trip_varying_sizes(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn w_blob_over_capacity_expands_storage() {
+  let (s, p) = get_storage();
+  binary_storage_tests::w_blob_over_capacity_expands_storage(s);
+  rm_tmp(p);
+}
+
+#[test]
+fn r_blob_returns_err_when_length_runs_past_end() {
+  let (s, p) = get_storage();
+  binary_storage_tests::r_blob_returns_err_when_length_runs_past_end(s);
+  rm_tmp(p);
+}
+
+
+// set_endianness() tests
+#[test]
+fn endianness_defaults_to_little() {
+  let (s, _) = get_storage();
+  assert_eq!(Endianness::Little, s.get_endianness());
+}
+
+#[test]
+fn w_u32_writes_little_endian_bytes_by_default() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  s.w_u32(0, 0x01020304).unwrap();
+  assert_eq!(vec!(0x04, 0x03, 0x02, 0x01), s.r_bytes(0, 4).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u32_writes_big_endian_bytes_when_set() {
+  let (mut s, p) = get_storage();
+  s.set_endianness(Endianness::Big);
+  s.open().unwrap();
+  s.w_u32(0, 0x01020304).unwrap();
+  assert_eq!(vec!(0x01, 0x02, 0x03, 0x04), s.r_bytes(0, 4).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn w_u32_array_writes_big_endian_bytes_when_set() {
+  let (mut s, p) = get_storage();
+  s.set_endianness(Endianness::Big);
+  s.open().unwrap();
+  s.w_u32_array(0, &[0x01020304, 0x05060708]).unwrap();
+  assert_eq!(
+    vec!(0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08), 
+    s.r_bytes(0, 8).unwrap()
+  );
+  rm_tmp(p);
+}
+
+#[test]
+fn single_byte_values_unaffected_by_endianness() {
+  let (mut s, p) = get_storage();
+  s.set_endianness(Endianness::Big);
+  s.open().unwrap();
+  s.w_u8(0, 0xAB).unwrap();
+  s.w_i8(1, -2).unwrap();
+  s.w_bool(2, true).unwrap();
+  assert_eq!(vec!(0xAB, 0xFE, 0x01), s.r_bytes(0, 3).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn values_round_trip_in_either_endianness() {
+  for endianness in vec!(Endianness::Little, Endianness::Big) {
+    let (mut s, p) = get_storage();
+    s.set_endianness(endianness);
+    s.open().unwrap();
+    s.w_i16(0, -12345).unwrap();
+    s.w_u32(2, 0xDEADBEEF).unwrap();
+    s.w_i64(6, -1234567890123).unwrap();
+    s.w_f32(14, 1.5).unwrap();
+    s.w_f64(18, -2.25).unwrap();
+    s.w_u64_array(26, &[1, u64::max_value() - 1]).unwrap();
+    assert_eq!(-12345, s.r_i16(0).unwrap());
+    assert_eq!(0xDEADBEEF, s.r_u32(2).unwrap());
+    assert_eq!(-1234567890123, s.r_i64(6).unwrap());
+    assert_eq!(1.5, s.r_f32(14).unwrap());
+    assert_eq!(-2.25, s.r_f64(18).unwrap());
+    assert_eq!(vec!(1, u64::max_value() - 1), s.r_u64_array(26, 2).unwrap());
+    rm_tmp(p);
+  }
+}
+
+#[test]
+fn big_endian_value_reads_back_reversed_as_little_endian() {
+  let (mut s, p) = get_storage();
+  s.set_endianness(Endianness::Big);
+  s.open().unwrap();
+  s.w_u16(0, 0x0102).unwrap();
+  s.set_endianness(Endianness::Little);
+  assert_eq!(0x0201, s.r_u16(0).unwrap());
+  rm_tmp(p);
+}
+
+
+// Read-your-writes tests
+fn get_storage_page_size(page_size: usize) -> (FileBinaryStorage, String) {
+  let path = rnd_path();
+  let s = FileBinaryStorage::new(
+    path.clone(),
+    true,
+    256,
+    page_size, 
+    16,
+    256
+  ).unwrap();
+  (s, path)
+}
+
+#[test]
+fn cached_reads_see_typed_writes_immediately() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.r_u64(40).unwrap());
+  s.w_u64(40, 0x0102030405060708).unwrap();
+  assert_eq!(0x0102030405060708, s.r_u64(40).unwrap());
+  s.w_u16(44, 0xFFFF).unwrap();
+  assert_eq!(0x0102FFFF05060708, s.r_u64(40).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn cached_reads_see_byte_writes_and_fills_immediately() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(vec!(0x0; 40), s.r_bytes(0, 40).unwrap());
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(10, 3).unwrap());
+  s.fill(Some(11), Some(30), 0x7).unwrap();
+  assert_eq!(vec!(0x1, 0x7, 0x7), s.r_bytes(10, 3).unwrap());
+  assert_eq!(vec!(0x7, 0x0), s.r_bytes(29, 2).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn cached_reads_see_writes_across_expansion() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.r_u32(252).unwrap());
+  s.w_bytes(252, &[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+  assert_eq!(
+    vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8), 
+    s.r_bytes(252, 8).unwrap()
+  );
+  rm_tmp(p);
+}
+
+#[test]
+fn cached_page_larger_than_capacity_reads_expanded_space() {
+  let (mut s, p) = get_storage_page_size(1024);
+  s.open().unwrap();
+  assert_eq!(0, s.r_u32(0).unwrap());
+  s.w_u32(300, 0xFFFFFFFF).unwrap();
+  assert_eq!(512, s.get_capacity().unwrap());
+  assert_eq!(0xFFFFFFFF, s.r_u32(300).unwrap());
+  assert_eq!(0, s.r_u32(400).unwrap());
+  assert_eq!(vec!(0x0; 8), s.r_bytes(504, 8).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn cached_page_reads_zeros_after_shrink_and_expansion() {
+  let (mut s, p) = get_storage_page_size(1024);
+  s.open().unwrap();
+  s.w_u8(600, 0xFF).unwrap();
+  assert_eq!(0xFF, s.r_u8(600).unwrap());
+  s.shrink(0).unwrap();
+  assert_eq!(256, s.get_capacity().unwrap());
+  s.expand(768).unwrap();
+  assert_eq!(0, s.r_u8(600).unwrap());
+  assert_eq!(vec!(0x0; 8), s.r_bytes(760, 8).unwrap());
+  rm_tmp(p);
+}
+
+#[test]
+fn find_bytes_finds_pattern_longer_than_a_page() {
+  let (mut s, p) = get_storage();
+  s.open().unwrap();
+  let pattern: Vec<u8> = (1..41).collect();
+  s.w_bytes(5, pattern.as_slice()).unwrap();
+  s.w_bytes(100, pattern.as_slice()).unwrap();
+  assert_eq!(Some(5), s.find_bytes(pattern.as_slice(), 0).unwrap());
+  assert_eq!(Some(100), s.find_bytes(pattern.as_slice(), 6).unwrap());
+  rm_tmp(p);
+}
@@ -1,10 +1,13 @@
 use std::str;
 use std::fs;
 use std::fs::{ File, OpenOptions };
-use std::io::Write;
+use std::io::{ Read, Write, Seek, SeekFrom };
 
 use uuid::Uuid;
 
+use std::error::Error;
+
+use storage::file_synced_buffer;
 use storage::file_synced_buffer::FileSyncedBuffer;
 
 
@@ -42,6 +45,47 @@ fn rm_tmp(filename: String) {
   fs::remove_file(filename).unwrap()
 }
 
+// new_checked() tests
+#[test]
+fn new_checked_returns_ok_for_valid_params() {
+  let b = FileSyncedBuffer::new_checked(file_r("100.txt"), 16, 16).unwrap();
+  assert_eq!(16, b.get_page_size());
+  assert_eq!(16, b.get_max_pages());
+}
+
+#[test]
+fn new_checked_returns_err_when_page_size_is_0() {
+  match FileSyncedBuffer::new_checked(file_r("100.txt"), 0, 16) {
+    Ok(_) => panic!("expected an error"),
+    Err(e) => assert_eq!(file_synced_buffer::ERR_PAGE_SIZE_TOO_SMALL, e.description())
+  }
+}
+
+#[test]
+fn new_checked_returns_err_when_max_pages_not_power_of_2() {
+  for pages in vec!(0, 3, 12) {
+    match FileSyncedBuffer::new_checked(file_r("100.txt"), 16, pages) {
+      Ok(_) => panic!("expected an error"),
+      Err(e) => assert_eq!(
+        file_synced_buffer::ERR_MAX_PAGES_NOT_POWER_OF_2, 
+        e.description()
+      )
+    }
+  }
+}
+
+#[test]
+fn new_checked_returns_err_when_max_pages_too_big() {
+  match FileSyncedBuffer::new_checked(
+    file_r("100.txt"), 
+    16, 
+    file_synced_buffer::MAX_PAGES_LIMIT * 2
+  ) {
+    Ok(_) => panic!("expected an error"),
+    Err(e) => assert_eq!(file_synced_buffer::ERR_MAX_PAGES_TOO_BIG, e.description())
+  }
+}
+
 // read() tests
 #[test]
 fn read_returns_empty_on_blank_file() {
@@ -96,6 +140,160 @@ fn read_reads_data_across_multiple_page_boundaries() {
   );
 }
 
+// warm_up() tests
+#[test]
+fn warm_up_loads_first_pages() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(3).unwrap();
+  assert_eq!(3, b.get_num_current_pages());
+  assert_eq!(vec!(0, 1, 2), b.get_current_page_insertions());
+}
+
+#[test]
+fn warm_up_does_not_count_cache_misses() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(3).unwrap();
+  assert_eq!(0, b.get_cache_misses());
+  assert_eq!(0, b.get_cache_hits());
+}
+
+#[test]
+fn warm_up_makes_first_reads_cache_hits() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(3).unwrap();
+  let data = b.read(0, 48).unwrap();
+  assert_eq!(
+    "Lorem ipsum dolor sit amet, consectetur adipisci", 
+    str::from_utf8(&data).unwrap()
+  );
+  assert_eq!(3, b.get_cache_hits());
+  assert_eq!(0, b.get_cache_misses());
+}
+
+#[test]
+fn warm_up_is_limited_by_max_pages() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 2);
+  b.warm_up(8).unwrap();
+  assert_eq!(2, b.get_num_current_pages());
+}
+
+#[test]
+fn warm_up_stops_at_eof() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(16).unwrap();
+  assert_eq!(7, b.get_num_current_pages());
+  assert_eq!(4, b.read(96, 16).unwrap().len());
+  assert_eq!(0, b.get_cache_misses());
+}
+
+#[test]
+fn warm_up_with_0_max_pages_does_nothing() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 0);
+  b.warm_up(4).unwrap();
+  assert_eq!(0, b.get_num_current_pages());
+  assert_eq!(0, b.get_disk_reads());
+}
+
+// read_ref() tests
+#[test]
+fn read_ref_returns_none_when_page_not_cached() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  assert_eq!(None, b.read_ref(35, 10));
+  assert_eq!(0, b.get_disk_reads());
+}
+
+#[test]
+fn read_ref_matches_read_within_cached_page() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  let copied = b.read(35, 10).unwrap();
+  let hits = b.get_cache_hits();
+  assert_eq!(Some(copied.as_slice()), b.read_ref(35, 10));
+  assert_eq!("etur adipi", str::from_utf8(b.read_ref(35, 10).unwrap()).unwrap());
+  assert_eq!(hits + 2, b.get_cache_hits());
+}
+
+#[test]
+fn read_ref_matches_read_for_whole_page() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(4).unwrap();
+  for page in 0..4 {
+    let copied = b.read(page * 16, 16).unwrap();
+    assert_eq!(Some(copied.as_slice()), b.read_ref(page * 16, 16));
+  }
+}
+
+#[test]
+fn read_ref_returns_none_across_page_boundary() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(4).unwrap();
+  assert_eq!(None, b.read_ref(25, 10));
+  assert!(b.read_ref(25, 7).is_some());
+}
+
+#[test]
+fn read_ref_returns_none_past_data_in_page() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(16).unwrap();
+  assert_eq!(4, b.read_ref(96, 4).unwrap().len());
+  assert_eq!(None, b.read_ref(96, 5));
+}
+
+#[test]
+fn read_ref_returns_none_for_empty_range() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.warm_up(1).unwrap();
+  assert_eq!(None, b.read_ref(0, 0));
+}
+
+#[test]
+fn read_ref_sees_updated_page_data() {
+  let (mut f, p) = file_tmp_rw();
+  f.write_all(b"0123456789abcdef").unwrap();
+  let mut b = FileSyncedBuffer::new(f, 16, 16);
+  b.warm_up(1).unwrap();
+  b.update(4, b"WXYZ").unwrap();
+  assert_eq!(Some(&b"23WXYZ89"[..]), b.read_ref(2, 8));
+  rm_tmp(p);
+}
+
+// read_resumable() tests
+#[test]
+fn read_resumable_returns_next_offset() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  let (data, next) = b.read_resumable(10, 20).unwrap();
+  assert_eq!(20, data.len());
+  assert_eq!(30, next);
+}
+
+#[test]
+fn read_resumable_stops_at_eof() {
+  let mut b = FileSyncedBuffer::new(file_r("10.txt"), 16, 16);
+  let (data, next) = b.read_resumable(4, 16).unwrap();
+  assert_eq!(6, data.len());
+  assert_eq!(10, next);
+  let (data, next) = b.read_resumable(next, 16).unwrap();
+  assert_eq!(0, data.len());
+  assert_eq!(10, next);
+}
+
+#[test]
+fn read_resumable_reads_appended_data() {
+  let (mut f, p) = file_tmp_rw();
+  f.write(b"Lorem ipsum").unwrap();
+  let mut b = FileSyncedBuffer::new(f, 16, 16);
+  let (data, next) = b.read_resumable(0, 32).unwrap();
+  assert_eq!("Lorem ipsum", str::from_utf8(data.as_slice()).unwrap());
+  assert_eq!(11, next);
+
+  let mut w = OpenOptions::new().append(true).open(p.clone()).unwrap();
+  w.write(b" dolor sit amet").unwrap();
+
+  let (data, next) = b.read_resumable(next, 32).unwrap();
+  assert_eq!(" dolor sit amet", str::from_utf8(data.as_slice()).unwrap());
+  assert_eq!(26, next);
+  rm_tmp(p);
+}
+
 // update() tests
 #[test]
 fn update_writes_to_subset_of_first_page() {
@@ -223,6 +421,209 @@ fn update_only_writes_to_cached_pages() {
   rm_tmp(p);
 }
 
+fn file_w(path: String) -> File {
+  OpenOptions::new()
+    .write(true)
+    .open(path)
+    .unwrap()
+}
+
+#[test]
+fn update_with_0_max_pages_does_not_cache() {
+  let (mut f, p) = file_tmp_rw();
+
+  f.write(&[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+  let mut b = FileSyncedBuffer::new(f, 4, 0);
+  b.update(2, &[0x9, 0x9, 0x9]).unwrap();
+  assert_eq!(0, b.get_num_current_pages());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8), b.read(0, 8).unwrap());
+  assert_eq!(0, b.get_num_current_pages());
+
+  rm_tmp(p);
+}
+
+#[test]
+fn update_with_0_max_pages_reads_stay_consistent_with_file() {
+  let (mut f, p) = file_tmp_rw();
+
+  f.write(&[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+  let mut w = file_w(p.clone());
+  let mut b = FileSyncedBuffer::new(f, 4, 0);
+  assert_eq!(vec!(0x3, 0x4, 0x5), b.read(2, 3).unwrap());
+
+  w.seek(SeekFrom::Start(3)).unwrap();
+  w.write(&[0xa, 0xb, 0xc]).unwrap();
+  b.update(3, &[0xa, 0xb, 0xc]).unwrap();
+
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0xa, 0xb, 0xc, 0x7, 0x8), b.read(0, 8).unwrap());
+  assert_eq!(vec!(0xb, 0xc), b.read(4, 2).unwrap());
+  assert_eq!(0, b.get_num_current_pages());
+
+  rm_tmp(p);
+}
+
+#[test]
+fn update_after_set_max_pages_to_0_reads_stay_consistent_with_file() {
+  let (mut f, p) = file_tmp_rw();
+
+  f.write(&[0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8]).unwrap();
+  let mut w = file_w(p.clone());
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8), b.read(0, 8).unwrap());
+  assert_eq!(2, b.get_num_current_pages());
+
+  b.set_max_pages(0);
+  assert_eq!(0, b.get_num_current_pages());
+
+  w.seek(SeekFrom::Start(0)).unwrap();
+  w.write(&[0xa, 0xb]).unwrap();
+  b.update(0, &[0xa, 0xb]).unwrap();
+
+  assert_eq!(vec!(0xa, 0xb, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8), b.read(0, 8).unwrap());
+  assert_eq!(0, b.get_num_current_pages());
+
+  rm_tmp(p);
+}
+
+#[test]
+fn read_resumable_with_0_max_pages_reads_appended_data() {
+  let (mut f, p) = file_tmp_rw();
+
+  f.write(&[0x1, 0x2, 0x3]).unwrap();
+  let mut w = file_w(p.clone());
+  let mut b = FileSyncedBuffer::new(f, 4, 0);
+  let (data, next) = b.read_resumable(0, 6).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), data);
+
+  w.seek(SeekFrom::Start(3)).unwrap();
+  w.write(&[0x4, 0x5, 0x6]).unwrap();
+
+  let (data, _) = b.read_resumable(next, 3).unwrap();
+  assert_eq!(vec!(0x4, 0x5, 0x6), data);
+
+  rm_tmp(p);
+}
+
+// write(), flush(), and set_write_back() tests
+fn file_contents(path: &str) -> Vec<u8> {
+  let mut f = OpenOptions::new().read(true).open(path).unwrap();
+  let mut buf = Vec::new();
+  f.read_to_end(&mut buf).unwrap();
+  buf
+}
+
+#[test]
+fn write_goes_to_file_when_not_write_back() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.write(2, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(vec!(0x0, 0x0, 0x1, 0x2, 0x3), file_contents(&p));
+  assert_eq!(0, b.get_num_dirty_pages());
+  assert_eq!(1, b.get_disk_writes());
+  rm_tmp(p);
+}
+
+#[test]
+fn write_back_holds_data_until_flush() {
+  let (mut f, p) = file_tmp_rw();
+  f.write(&[0x1, 0x2, 0x3, 0x4, 0x5, 0x6]).unwrap();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.set_write_back(true).unwrap();
+  b.write(3, &[0x7, 0x8]).unwrap();
+  assert_eq!(2, b.get_num_dirty_pages());
+  assert_eq!(0, b.get_disk_writes());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6), file_contents(&p));
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x7, 0x8, 0x6), b.read(0, 6).unwrap());
+
+  b.flush().unwrap();
+  assert_eq!(0, b.get_num_dirty_pages());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x7, 0x8, 0x6), file_contents(&p));
+  rm_tmp(p);
+}
+
+#[test]
+fn flush_coalesces_contiguous_dirty_pages() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.set_write_back(true).unwrap();
+  // Pages 0-2 in one run, written out of order
+  b.write(8, &[0x3; 4]).unwrap();
+  b.write(0, &[0x1; 4]).unwrap();
+  b.write(4, &[0x2; 4]).unwrap();
+  // Pages 5-6 in another run
+  b.write(20, &[0x5; 8]).unwrap();
+  assert_eq!(5, b.get_num_dirty_pages());
+
+  b.flush().unwrap();
+  assert_eq!(2, b.get_disk_writes());
+  let mut expected = Vec::new();
+  expected.extend_from_slice(&[0x1; 4]);
+  expected.extend_from_slice(&[0x2; 4]);
+  expected.extend_from_slice(&[0x3; 4]);
+  expected.extend_from_slice(&[0x0; 8]);
+  expected.extend_from_slice(&[0x5; 8]);
+  assert_eq!(expected, file_contents(&p));
+  rm_tmp(p);
+}
+
+#[test]
+fn flush_writes_each_non_contiguous_page_separately() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.set_write_back(true).unwrap();
+  b.write(0, &[0x1; 4]).unwrap();
+  b.write(8, &[0x2; 4]).unwrap();
+  b.write(16, &[0x3; 4]).unwrap();
+  b.flush().unwrap();
+  assert_eq!(3, b.get_disk_writes());
+  assert_eq!(0x3, file_contents(&p)[19]);
+  rm_tmp(p);
+}
+
+#[test]
+fn flush_ends_run_after_partial_page() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.set_write_back(true).unwrap();
+  b.write(0, &[0x1; 6]).unwrap();
+  b.write(12, &[0x2; 4]).unwrap();
+  b.flush().unwrap();
+  assert_eq!(2, b.get_disk_writes());
+  assert_eq!(
+    vec!(0x1, 0x1, 0x1, 0x1, 0x1, 0x1, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x2, 0x2, 0x2, 0x2),
+    file_contents(&p)
+  );
+  rm_tmp(p);
+}
+
+#[test]
+fn write_back_does_not_evict_dirty_pages() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 2);
+  b.set_write_back(true).unwrap();
+  b.write(0, &[0x1; 12]).unwrap();
+  assert_eq!(3, b.get_num_current_pages());
+  assert_eq!(0, b.get_cache_evictions());
+
+  b.flush().unwrap();
+  assert_eq!(2, b.get_num_current_pages());
+  assert_eq!(vec![0x1; 12], file_contents(&p));
+  rm_tmp(p);
+}
+
+#[test]
+fn set_write_back_false_flushes() {
+  let (f, p) = file_tmp_rw();
+  let mut b = FileSyncedBuffer::new(f, 4, 16);
+  b.set_write_back(true).unwrap();
+  b.write(0, &[0x1, 0x2]).unwrap();
+  assert_eq!(Vec::<u8>::new(), file_contents(&p));
+  b.set_write_back(false).unwrap();
+  assert!(!b.get_write_back());
+  assert_eq!(vec!(0x1, 0x2), file_contents(&p));
+  rm_tmp(p);
+}
+
 // truncate() tests
 #[test]
 fn truncate_to_0_removes_all_pages() {
@@ -261,7 +662,29 @@ fn truncate_truncates_page_at_len() {
   assert_eq!("ectetur adipi", str::from_utf8(res2.as_slice()).unwrap());
 }
 
-// get_page_size() tests
+// extend() tests
+#[test]
+fn extend_fills_out_page_at_old_len_with_zeros() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.read(32, 16).unwrap();
+  b.truncate(45);
+  b.extend(45, 100).unwrap();
+  let res = b.read(32, 16).unwrap();
+  assert_eq!(16, res.len());
+  assert_eq!("ectetur adipi\0\0\0", str::from_utf8(res.as_slice()).unwrap());
+}
+
+#[test]
+fn extend_only_fills_up_to_new_len() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.read(32, 16).unwrap();
+  b.truncate(45);
+  b.extend(45, 47).unwrap();
+  let res = b.read(32, 16).unwrap();
+  assert_eq!(15, res.len());
+  assert_eq!("ectetur adipi\0\0", str::from_utf8(res.as_slice()).unwrap());
+}
+
 #[test]
 fn get_page_size_returns_initialized_page_size() {
   let b = FileSyncedBuffer::new(file_r("100.txt"), 32, 64);
@@ -373,6 +796,172 @@ fn get_current_page_insertions_shows_oldest_pages_removed_first() {
   assert_eq!(vec!(0, 2, 4, 6), b.get_current_page_insertions());
 }
 
+// get_cache_hits(), get_cache_misses(), and get_cache_evictions() tests
+#[test]
+fn cache_counters_start_at_0() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 4);
+  assert_eq!(0, b.get_cache_hits());
+  assert_eq!(0, b.get_cache_misses());
+  assert_eq!(0, b.get_cache_evictions());
+}
+
+#[test]
+fn cache_misses_increase_when_pages_are_loaded() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 4);
+  b.read(0, 16).unwrap();
+  assert_eq!(1, b.get_cache_misses());
+  b.read(16, 32).unwrap();
+  assert_eq!(3, b.get_cache_misses());
+  assert_eq!(0, b.get_cache_hits());
+}
+
+#[test]
+fn cache_hits_increase_when_cached_pages_are_read() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 4);
+  b.read(0, 32).unwrap();
+  assert_eq!(0, b.get_cache_hits());
+  b.read(4, 4).unwrap();
+  assert_eq!(1, b.get_cache_hits());
+  b.read(8, 16).unwrap();
+  assert_eq!(3, b.get_cache_hits());
+  assert_eq!(2, b.get_cache_misses());
+}
+
+#[test]
+fn cache_evictions_increase_when_oldest_pages_are_removed() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 2);
+  b.read(0, 32).unwrap();
+  assert_eq!(0, b.get_cache_evictions());
+  b.read(32, 16).unwrap();
+  assert_eq!(1, b.get_cache_evictions());
+  b.read(48, 32).unwrap();
+  assert_eq!(3, b.get_cache_evictions());
+}
+
+#[test]
+fn cache_evictions_increase_when_max_pages_is_reduced() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 4);
+  b.read(0, 64).unwrap();
+  b.set_max_pages(1);
+  assert_eq!(3, b.get_cache_evictions());
+}
+
+// get_small_read_direct() and set_small_read_direct() tests
+#[test]
+fn get_small_read_direct_starts_at_0() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  assert_eq!(0, b.get_small_read_direct());
+}
+
+#[test]
+fn get_small_read_direct_returns_threshold_after_set() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.set_small_read_direct(8);
+  assert_eq!(8, b.get_small_read_direct());
+}
+
+#[test]
+fn small_read_below_threshold_does_not_cache_page() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.set_small_read_direct(8);
+  let res = b.read(4, 4).unwrap();
+  assert_eq!("m ip", str::from_utf8(res.as_slice()).unwrap());
+  assert_eq!(0, b.get_num_current_pages());
+}
+
+#[test]
+fn read_at_or_above_threshold_caches_pages() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.set_small_read_direct(8);
+  let res = b.read(4, 8).unwrap();
+  assert_eq!("m ipsum ", str::from_utf8(res.as_slice()).unwrap());
+  assert_eq!(1, b.get_num_current_pages());
+}
+
+#[test]
+fn small_read_below_threshold_truncates_at_eof() {
+  let mut b = FileSyncedBuffer::new(file_r("10.txt"), 16, 16);
+  b.set_small_read_direct(8);
+  let res = b.read(6, 6).unwrap();
+  assert_eq!("ips\n", str::from_utf8(res.as_slice()).unwrap());
+  assert_eq!(0, b.read(12, 4).unwrap().len());
+}
+
+#[test]
+fn small_read_below_threshold_reads_when_page_already_cached() {
+  let (mut f, p) = file_tmp_rw();
+  f.write(b"Lorem ipsum dolor sit amet").unwrap();
+  let mut b = FileSyncedBuffer::new(f, 16, 16);
+  b.read(0, 16).unwrap();
+  b.set_small_read_direct(8);
+  assert_eq!("ipsum", str::from_utf8(b.read(6, 5).unwrap().as_slice()).unwrap());
+  rm_tmp(p);
+}
+
+// set_auto_tune() tests
+#[test]
+fn auto_tune_is_off_by_default() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  assert!(!b.get_auto_tune());
+  assert_eq!(1, b.get_read_ahead_pages());
+}
+
+#[test]
+fn disk_reads_match_pages_loaded_without_auto_tune() {
+  let b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  for i in 0..6 {
+    b.read(i * 16, 16).unwrap();
+  }
+  assert_eq!(6, b.get_disk_reads());
+  assert_eq!(6, b.get_cache_misses());
+}
+
+#[test]
+fn auto_tune_coalesces_sequential_reads() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.set_auto_tune(true);
+  for i in 0..6 {
+    b.read(i * 16, 16).unwrap();
+  }
+  assert_eq!(3, b.get_disk_reads());
+  assert_eq!(6, b.get_num_current_pages());
+  assert_eq!(16, b.get_page_size());
+}
+
+#[test]
+fn auto_tune_returns_same_data_as_untuned_reads() {
+  let plain = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  let mut tuned = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  tuned.set_auto_tune(true);
+  for i in 0..8 {
+    assert_eq!(plain.read(i * 13, 13).unwrap(), tuned.read(i * 13, 13).unwrap());
+  }
+  assert_eq!(plain.read(90, 16).unwrap(), tuned.read(90, 16).unwrap());
+}
+
+#[test]
+fn auto_tune_resets_read_ahead_on_random_access() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 16);
+  b.set_auto_tune(true);
+  b.read(0, 16).unwrap();
+  b.read(16, 16).unwrap();
+  b.read(32, 16).unwrap();
+  assert_eq!(4, b.get_read_ahead_pages());
+  b.read(4, 4).unwrap();
+  assert_eq!(1, b.get_read_ahead_pages());
+}
+
+#[test]
+fn auto_tune_read_ahead_is_limited_by_max_pages() {
+  let mut b = FileSyncedBuffer::new(file_r("100.txt"), 16, 2);
+  b.set_auto_tune(true);
+  for i in 0..4 {
+    b.read(i * 16, 16).unwrap();
+  }
+  assert_eq!(2, b.get_read_ahead_pages());
+  assert!(b.get_num_current_pages() <= 2);
+}
+
 // page caching tests
 #[test]
 fn reads_1_page_when_caching_0_pages() {
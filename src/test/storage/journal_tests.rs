@@ -1,13 +1,29 @@
 // TODO: Test for invalid checksums, especially their effects on verify() behavior
 
+use std::fs;
+use std::io::Read;
+use std::borrow::Cow;
 use std::error::Error;
+use std::rc::Rc;
+use std::cell::RefCell;
+use uuid::Uuid;
 use storage::journal;
-use storage::journal::Journal;
+use storage::journal::{ Journal, CorruptionAction };
 use storage::binary_storage;
 use storage::binary_storage::BinaryStorage;
 use storage::transactional_storage;
 use storage::transactional_storage::TransactionalStorage;
 use storage::memory_binary_storage::MemoryBinaryStorage;
+use storage::file_binary_storage::{ FileBinaryStorage, Endianness };
+use storage::util::ChecksumAlgo;
+
+pub static BASE_PATH: &'static str = "./test_data/storage/file_binary_storage/";
+
+fn rnd_path() -> String {
+  BASE_PATH.to_string() 
+    + Uuid::new_v4().simple().to_string().as_str()
+    + ".tmp"
+}
 
 fn new_storage(
   initial_capacity: usize, 
@@ -27,6 +43,60 @@ pub fn mem_new_reads_and_writes_from_0_when_empty_storage() {
 }
 
 
+// with_markers() tests
+#[test]
+pub fn new_uses_default_markers() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(journal::DEFAULT_START_MARKER, j.get_start_marker());
+  assert_eq!(journal::DEFAULT_END_MARKER, j.get_end_marker());
+}
+
+#[test]
+pub fn with_markers_returns_err_when_markers_match() {
+  match Journal::with_markers(new_storage(256, 256), 1000, 1000) {
+    Ok(_) => panic!("expected an error"),
+    Err(e) => assert_eq!(journal::ERR_MARKERS_NOT_DISTINCT, e.description())
+  }
+}
+
+#[test]
+pub fn with_markers_writes_and_reads_records() {
+  let mut j = Journal::with_markers(new_storage(256, 256), 1000, 2000).unwrap();
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  assert_eq!(vec!(0x3, 0x4), j.next().unwrap());
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+}
+
+#[test]
+pub fn default_marker_journal_does_not_read_custom_marker_records() {
+  // A committed record framed with 1000 and 2000 as the markers
+  let record = [0xe8, 0x3, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0xd0, 0x7];
+
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(0, &record).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::with_markers(s, 1000, 2000).unwrap();
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(0, &record).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.open().unwrap();
+  assert_eq!(0, j.record_count());
+  assert_eq!(None, j.next());
+}
+
 // open(), close(), verify(), and is_open() tests
 #[test]
 pub fn is_closed_by_default() {
@@ -104,6 +174,299 @@ pub fn open_and_verify_returns_err_on_bad_checksum() {
   );
 }
 
+// Three records at 0, 12, and 24, where the one at 12 has a bad checksum
+fn storage_with_corrupt_middle_record() -> TransactionalStorage<MemoryBinaryStorage> {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    12, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x4, 0x5, 0x6, 0x8, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    24, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x7, 0x8, 0x9, 0x6, 0x3, 0x3]
+  ).unwrap();
+  s.close().unwrap();
+  s
+}
+
+fn journal_with_corruption_action(
+  action: CorruptionAction,
+  calls: Rc<RefCell<Vec<(usize, String)>>>
+) -> Journal<MemoryBinaryStorage> {
+  let mut j = Journal::new(storage_with_corrupt_middle_record());
+  j.set_on_corruption(Box::new(move |offset, e| {
+    calls.borrow_mut().push((offset, e.description().to_string()));
+    action
+  }));
+  j
+}
+
+#[test]
+pub fn open_and_verify_passes_corrupt_record_to_on_corruption() {
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = journal_with_corruption_action(CorruptionAction::Abort, calls.clone());
+  assert_eq!(
+    journal::ERR_CHECKSUM_MISMATCH,
+    j.open().unwrap_err().description()
+  );
+  assert_eq!(
+    vec!((12, journal::ERR_CHECKSUM_MISMATCH.to_string())),
+    *calls.borrow()
+  );
+}
+
+#[test]
+pub fn open_and_verify_truncates_at_corrupt_record() {
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = journal_with_corruption_action(CorruptionAction::Truncate, calls.clone());
+  j.open().unwrap();
+  assert_eq!(1, calls.borrow().len());
+  assert_eq!(1, j.record_count());
+  assert_eq!(12, j.write_offset());
+  assert_eq!(vec!(vec!(0x1, 0x2, 0x3)), j.by_ref().collect::<Vec<Vec<u8>>>());
+
+  j.write(&[0xa, 0xb]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(1, calls.borrow().len());
+  assert_eq!(
+    vec!(vec!(0x1, 0x2, 0x3), vec!(0xa, 0xb)), 
+    j.by_ref().collect::<Vec<Vec<u8>>>()
+  );
+}
+
+#[test]
+pub fn open_and_verify_skips_corrupt_record() {
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = journal_with_corruption_action(CorruptionAction::Skip, calls.clone());
+  j.open().unwrap();
+  assert_eq!(1, calls.borrow().len());
+  assert_eq!(2, j.record_count());
+  assert_eq!(36, j.write_offset());
+  assert_eq!(
+    vec!((0, vec!(0x1, 0x2, 0x3)), (24, vec!(0x7, 0x8, 0x9))), 
+    j.enumerate_records().collect::<Vec<(usize, Vec<u8>)>>()
+  );
+
+  j.write(&[0xa, 0xb]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  // The corrupt record is still there, so it's reported again
+  assert_eq!(2, calls.borrow().len());
+  assert_eq!(3, j.record_count());
+  j.audit().unwrap();
+}
+
+#[test]
+pub fn open_and_verify_skip_leaves_corrupt_record_in_storage() {
+  let path = rnd_path();
+  {
+    let mut s = TransactionalStorage::new(
+      FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+    );
+    s.open().unwrap();
+    s.w_bytes(
+      0, 
+      &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+    ).unwrap();
+    s.w_bytes(
+      12, 
+      &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x4, 0x5, 0x6, 0x8, 0x3, 0x3]
+    ).unwrap();
+    s.w_bytes(
+      24, 
+      &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x7, 0x8, 0x9, 0x6, 0x3, 0x3]
+    ).unwrap();
+    s.close().unwrap();
+  }
+  let mut before = Vec::new();
+  fs::File::open(path.clone()).unwrap().read_to_end(&mut before).unwrap();
+
+  // Dies without closing right after skipping, as if the process crashed
+  // partway through recovery
+  {
+    let mut j = Journal::new(TransactionalStorage::new(
+      FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+    ));
+    j.set_on_corruption(Box::new(|_, _| CorruptionAction::Skip));
+    j.open().unwrap();
+    assert_eq!(2, j.record_count());
+  }
+  let mut after = Vec::new();
+  fs::File::open(path.clone()).unwrap().read_to_end(&mut after).unwrap();
+  assert_eq!(before, after);
+
+  let mut j = Journal::new(TransactionalStorage::new(
+    FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+  ));
+  j.set_on_corruption(Box::new(|_, _| CorruptionAction::Skip));
+  j.open().unwrap();
+  assert_eq!(
+    vec!(vec!(0x1, 0x2, 0x3), vec!(0x7, 0x8, 0x9)), 
+    j.by_ref().collect::<Vec<Vec<u8>>>()
+  );
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn open_and_verify_skips_consecutive_corrupt_records() {
+  let mut s = storage_with_corrupt_middle_record();
+  s.open().unwrap();
+  s.w_u8(33, 0x0).unwrap();
+  s.close().unwrap();
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = Journal::new(s);
+  let c = calls.clone();
+  j.set_on_corruption(Box::new(move |offset, _| {
+    c.borrow_mut().push(offset);
+    CorruptionAction::Skip
+  }));
+  j.open().unwrap();
+  assert_eq!(vec!(12), *calls.borrow());
+  assert_eq!(1, j.record_count());
+  assert_eq!(12, j.write_offset());
+}
+
+#[test]
+pub fn open_and_verify_skips_to_next_valid_record_past_corrupt_length() {
+  let mut s = storage_with_corrupt_middle_record();
+  s.open().unwrap();
+  s.w_u8(14, 0x1).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.set_on_corruption(Box::new(|_, _| CorruptionAction::Skip));
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(36, j.write_offset());
+  assert_eq!(
+    vec!((0, vec!(0x1, 0x2, 0x3)), (24, vec!(0x7, 0x8, 0x9))), 
+    j.enumerate_records().collect::<Vec<(usize, Vec<u8>)>>()
+  );
+  j.audit().unwrap();
+}
+
+#[test]
+pub fn open_and_verify_skips_whole_slot_holding_corrupt_record() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  // A valid-looking record inside the corrupt slot isn't at a slot start,
+  // so it's passed over
+  s.w_bytes(
+    16, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x4, 0x5, 0x6, 0x8, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    20, 
+    &[0x2, 0x2, 0x1, 0x0, 0x0, 0x0, 0x5, 0x5, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    32, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x7, 0x8, 0x9, 0x6, 0x3, 0x3]
+  ).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.set_fixed_slot_size(Some(16)).unwrap();
+  j.set_on_corruption(Box::new(|_, _| CorruptionAction::Skip));
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(48, j.write_offset());
+  assert_eq!(
+    vec!((0, vec!(0x1, 0x2, 0x3)), (32, vec!(0x7, 0x8, 0x9))), 
+    j.enumerate_records().collect::<Vec<(usize, Vec<u8>)>>()
+  );
+  assert_eq!(vec!(0x7, 0x8, 0x9), j.read_nth(1).unwrap());
+}
+
+#[test]
+pub fn trim_incomplete_tail_defaults_to_false() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert!(!j.get_trim_incomplete_tail());
+  j.set_trim_incomplete_tail(true);
+  assert!(j.get_trim_incomplete_tail());
+}
+
+#[test]
+pub fn open_and_verify_trims_uncommitted_record_in_trim_mode() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(12, &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x4, 0x5]).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.set_trim_incomplete_tail(true);
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+  assert_eq!(8, j.trimmed_tail_len());
+  assert!(!j.is_writing());
+  assert_eq!(12, j.write_offset());
+  assert_eq!(12, j.txn_boundary().unwrap());
+}
+
+#[test]
+pub fn open_and_verify_trims_nothing_from_complete_journal() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_trim_incomplete_tail(true);
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+  assert_eq!(0, j.trimmed_tail_len());
+}
+
+#[test]
+pub fn open_and_verify_trims_record_cut_off_by_file_truncation() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  for r in [vec!(0x1, 0x2, 0x3), vec!(0x4, 0x5), vec!(0x6, 0x7, 0x8, 0x9)].iter() {
+    j.write(r.as_slice()).unwrap();
+    j.commit().unwrap();
+  }
+  j.close().unwrap();
+
+  // Records are at 0, 12, and 23, so this cuts the last one short
+  fs::OpenOptions::new().write(true).open(path.as_str()).unwrap().set_len(30).unwrap();
+
+  let mut j = file_journal(path.clone());
+  assert!(j.open().is_err());
+
+  let mut j = file_journal(path.clone());
+  j.set_trim_incomplete_tail(true);
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(7, j.trimmed_tail_len());
+  assert_eq!(23, j.write_offset());
+  j.write(&[0xa, 0xb]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  assert_eq!(
+    vec!(vec!(0x1, 0x2, 0x3), vec!(0x4, 0x5), vec!(0xa, 0xb)), 
+    j.by_ref().collect::<Vec<Vec<u8>>>()
+  );
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
 #[test]
 pub fn open_and_verify_recognizes_all_committed_records() {
   let mut s = new_storage(256, 256);
@@ -152,661 +515,2891 @@ pub fn open_and_verify_allows_record_commit() {
 }
 
 #[test]
-pub fn close_returns_ok_when_previously_open() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  assert!(j.close().is_ok());
+pub fn open_and_verify_returns_ok_when_storage_smaller_than_header() {
+  for cap in vec!(1, 2, 4) {
+    let mut j = Journal::new(new_storage(cap, cap));
+    assert!(j.open().is_ok());
+    assert_eq!(0, j.record_count());
+    assert!(!j.is_writing());
+    assert_eq!(0, j.write_offset());
+  }
 }
 
 #[test]
-pub fn is_open_after_open() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn open_and_verify_allows_writes_when_storage_smaller_than_header() {
+  let mut j = Journal::new(new_storage(4, 4));
   j.open().unwrap();
-  assert!(j.is_open());
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(1, j.record_count());
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
 }
 
 #[test]
-pub fn is_closed_after_open_and_close() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn open_and_verify_returns_ok_when_storage_is_header_sized() {
+  let mut j = Journal::new(new_storage(8, 8));
+  assert!(j.open().is_ok());
+  assert_eq!(0, j.record_count());
+}
+
+#[test]
+pub fn open_and_verify_returns_ok_on_empty_tail_when_strict() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.set_strict_headers(true);
   j.open().unwrap();
-  j.close().unwrap();
-  assert!(!j.is_open());
+  assert_eq!(1, j.record_count());
 }
 
-// write(), commit(), and discard() tests
 #[test]
-pub fn write_returns_err_when_closed() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn open_and_verify_returns_err_on_corrupt_header_when_strict() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(12, &[0x0, 0x7, 0x3, 0x0, 0x0, 0x0]).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.set_strict_headers(true);
   assert_eq!(
-      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-      j.write(&[0x0, 0x1, 0x2]).unwrap_err().description()
+    journal::ERR_CORRUPT_RECORD_HEADER,
+    j.open().unwrap_err().description()
   );
 }
 
 #[test]
-pub fn write_returns_ok_when_open() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn open_and_verify_ignores_corrupt_header_when_not_strict() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x0, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(12, &[0x0, 0x7, 0x3, 0x0, 0x0, 0x0]).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  assert!(!j.get_strict_headers());
   j.open().unwrap();
-  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
+  assert_eq!(1, j.record_count());
 }
 
 #[test]
-pub fn write_returns_err_when_uncommitted_data() {
+pub fn close_returns_ok_when_previously_open() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(
-    journal::ERR_WRITE_IN_PROGRESS,
-    j.write(&[0x0, 0x1, 0x2]).unwrap_err().description()
-  );
+  assert!(j.close().is_ok());
 }
 
 #[test]
-pub fn write_returns_ok_after_commit() {
+pub fn is_open_after_open() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
+  assert!(j.is_open());
 }
 
 #[test]
-pub fn commit_returns_err_when_closed() {
+pub fn is_closed_after_open_and_close() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.close().unwrap();
-  assert_eq!(
-    journal::ERR_WRITE_NOT_IN_PROGRESS,
-    j.commit().unwrap_err().description()
-  );
+  assert!(!j.is_open());
 }
 
+// is_empty_slot() tests
 #[test]
-pub fn commit_returns_err_when_no_data() {
+pub fn is_empty_slot_returns_true_for_zeroed_header() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  assert_eq!(
-    journal::ERR_WRITE_NOT_IN_PROGRESS,
-    j.commit().unwrap_err().description()
-  );
+  assert!(j.is_empty_slot().unwrap());
 }
 
 #[test]
-pub fn commit_returns_ok_when_uncommitted_data() {
+pub fn is_empty_slot_returns_false_for_record() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert!(j.commit().is_ok());
+  j.write(&[0x1, 0x2, 0x3]).unwrap();
+  j.commit().unwrap();
+  assert!(!j.is_empty_slot().unwrap());
+  j.next().unwrap();
+  assert_eq!(12, j.read_offset());
+  assert!(j.is_empty_slot().unwrap());
 }
 
 #[test]
-pub fn discard_returns_err_when_closed() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn is_empty_slot_returns_false_for_nonzero_garbage() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(5, &[0x1]).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.close().unwrap();
-  assert_eq!(
-    journal::ERR_WRITE_NOT_IN_PROGRESS,
-    j.commit().unwrap_err().description()
-  );
+  assert!(!j.is_empty_slot().unwrap());
 }
 
+// recover_scan() tests
 #[test]
-pub fn discard_returns_ok_when_uncommitted_data() {
+pub fn recover_scan_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert!(j.discard().is_ok());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.recover_scan().unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn discard_returns_err_when_no_data() {
+pub fn recover_scan_returns_nothing_when_no_records() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  assert_eq!(
-    journal::ERR_WRITE_NOT_IN_PROGRESS,
-    j.discard().unwrap_err().description()
-  );
+  assert_eq!(0, j.recover_scan().unwrap().len());
 }
 
 #[test]
-pub fn write_returns_ok_after_discard() {
+pub fn recover_scan_finds_records_containing_marker_values() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.discard().unwrap();
-  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
+  j.write(&[0x2, 0x2, 0x3, 0x3]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x3, 0x2, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x2, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0, 13, 26), j.recover_scan().unwrap());
 }
 
-
-// is_writing() tests
 #[test]
-pub fn is_not_writing_when_new() {
-  let j = Journal::new(new_storage(256, 256));
-  assert!(!j.is_writing());
+pub fn recover_scan_stops_at_first_bad_record() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x2, 0x0, 0x0, 0x0, 0x3, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    11, 
+    &[0x2, 0x2, 0x2, 0x0, 0x0, 0x0, 0x2, 0x2, 0x1, 0x3, 0x3]
+  ).unwrap();
+  s.w_bytes(
+    22, 
+    &[0x2, 0x2, 0x2, 0x0, 0x0, 0x0, 0x2, 0x2, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  j.open().unwrap_err();
+  assert_eq!(vec!(0), j.recover_scan().unwrap());
 }
 
 #[test]
-pub fn is_not_writing_when_newly_opened() {
+pub fn recover_scan_ignores_uncommitted_record() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  assert!(!j.is_writing());
+  j.write(&[0x2, 0x2, 0x3, 0x3]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x3, 0x2, 0x2]).unwrap();
+  assert_eq!(vec!(0), j.recover_scan().unwrap());
 }
 
+// write(), commit(), and discard() tests
 #[test]
-pub fn is_writing_after_write() {
+pub fn write_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert!(j.is_writing());
+  assert_eq!(
+      binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+      j.write(&[0x0, 0x1, 0x2]).unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn is_not_writing_when_closed() {
+pub fn write_returns_ok_when_open() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.close().unwrap();
-  assert!(!j.is_writing());
+  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
 }
 
 #[test]
-pub fn is_writing_when_reopened_before_commit() {
+pub fn write_returns_err_when_uncommitted_data() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.close().unwrap();
-  j.open().unwrap();
-  assert!(j.is_writing());
+  assert_eq!(
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.write(&[0x0, 0x1, 0x2]).unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn is_not_writing_after_commit() {
+pub fn write_returns_err_when_data_too_large() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
+  let data = vec![0x1; 0x01000000];
+  assert_eq!(
+    journal::ERR_WRITE_TOO_LARGE,
+    j.write(data.as_slice()).unwrap_err().description()
+  );
   assert!(!j.is_writing());
+  assert_eq!(0, j.write_offset());
 }
 
 #[test]
-pub fn is_not_writing_after_discard() {
+pub fn write_returns_ok_after_commit() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.discard().unwrap();
-  assert!(!j.is_writing());
+  j.commit().unwrap();
+  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
 }
 
-// has_start() tests
 #[test]
-pub fn has_start_returns_err_when_closed() {
+pub fn commit_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.close().unwrap();
   assert_eq!(
-    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.has_start().unwrap_err().description()
+    journal::ERR_WRITE_NOT_IN_PROGRESS,
+    j.commit().unwrap_err().description()
   );
 }
 
 #[test]
-pub fn has_start_returns_err_when_past_txn_boundary() {
+pub fn commit_returns_err_when_no_data() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.has_start().unwrap_err().description()
+    journal::ERR_WRITE_NOT_IN_PROGRESS,
+    j.commit().unwrap_err().description()
   );
 }
 
 #[test]
-pub fn has_start_returns_ok_when_open() {
+pub fn commit_returns_ok_when_uncommitted_data() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert!(j.has_start().is_ok());
+  assert!(j.commit().is_ok());
 }
 
 #[test]
-pub fn has_start_returns_true_when_record_exists() {
+pub fn discard_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert!(j.has_start().unwrap());
+  j.close().unwrap();
+  assert_eq!(
+    journal::ERR_WRITE_NOT_IN_PROGRESS,
+    j.commit().unwrap_err().description()
+  );
 }
 
-// has_end() tests
 #[test]
-pub fn has_end_returns_err_when_closed() {
+pub fn discard_returns_ok_when_uncommitted_data() {
   let mut j = Journal::new(new_storage(256, 256));
-  assert_eq!(
-    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.has_end().unwrap_err().description()
-  );
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert!(j.discard().is_ok());
 }
 
 #[test]
-pub fn has_end_returns_err_when_past_txn_boundary() {
+pub fn discard_returns_err_when_no_data() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.has_end().unwrap_err().description()
+    journal::ERR_WRITE_NOT_IN_PROGRESS,
+    j.discard().unwrap_err().description()
   );
 }
 
 #[test]
-pub fn has_end_returns_true_when_record_is_committed() {
+pub fn write_returns_ok_after_discard() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.discard().unwrap();
+  assert!(j.write(&[0x0, 0x1, 0x2]).is_ok());
+}
+
+#[test]
+pub fn discard_does_not_leave_gap() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert!(j.has_end().is_ok());
+  for _ in 0..3 {
+    j.write(&[0x3, 0x4, 0x5, 0x6]).unwrap();
+    j.discard().unwrap();
+  }
+  assert_eq!(12, j.write_offset());
+  j.write(&[0x7, 0x8]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(23, j.txn_boundary().unwrap());
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
 }
 
-// read() tests
+
+// write_batch() tests
 #[test]
-pub fn read_returns_err_when_closed() {
+pub fn write_batch_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
   assert_eq!(
     binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.read().unwrap_err().description()
+    j.write_batch(&[&[0x0, 0x1]]).unwrap_err().description()
   );
 }
 
 #[test]
-pub fn read_returns_err_when_no_data() {
+pub fn write_batch_returns_err_when_uncommitted_data() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.read().unwrap_err().description()
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.write_batch(&[&[0x3, 0x4]]).unwrap_err().description()
   );
 }
 
 #[test]
-pub fn read_returns_err_when_uncommitted_data() {
+pub fn write_batch_commits_all_records() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write_batch(&[&[0x3, 0x4], &[0x5, 0x6, 0x7, 0x8], &[0x9, 0xa, 0xb]]).unwrap();
+  assert_eq!(4, j.record_count());
+  assert!(!j.is_writing());
+  assert_eq!(j.write_offset(), j.txn_boundary().unwrap());
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.read().unwrap_err().description()
+    vec!(vec!(0x0, 0x1, 0x2), vec!(0x3, 0x4), vec!(0x5, 0x6, 0x7, 0x8), vec!(0x9, 0xa, 0xb)),
+    all_records(&mut j)
   );
 }
 
 #[test]
-pub fn read_returns_ok_when_committed_data() {
+pub fn write_batch_records_survive_reopen() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert!(j.read().is_ok());
+  j.write_batch(&[&[0x1, 0x2], &[0x3, 0x4, 0x5]]).unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert!(!j.is_writing());
 }
 
 #[test]
-pub fn read_returns_first_record() {
+pub fn write_batch_of_nothing_commits_nothing() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  j.write_batch(&[]).unwrap();
+  assert_eq!(0, j.record_count());
+  assert_eq!(0, j.write_offset());
 }
 
 #[test]
-pub fn read_returns_record_multiple_times() {
+pub fn write_batch_commits_nothing_when_a_record_fails() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  assert_eq!(
+    journal::ERR_NOTHING_TO_WRITE,
+    j.write_batch(&[&[0x3, 0x4], &[0x5, 0x6], &[], &[0x7, 0x8]]).unwrap_err().description()
+  );
+  assert_eq!(1, j.record_count());
+  assert!(!j.is_writing());
+  assert_eq!(12, j.write_offset());
+  assert_eq!(12, j.txn_boundary().unwrap());
+  assert_eq!(vec!(vec!(0x0, 0x1, 0x2)), all_records(&mut j));
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+  assert!(!j.is_writing());
 }
 
-// jump_to() tests
 #[test]
-pub fn jump_to_returns_err_when_closed() {
+pub fn write_batch_leaves_no_records_visible_after_failing_part_way() {
+  let path = rnd_path();
+  {
+    let mut j = Journal::new(TransactionalStorage::new(
+      FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+    ));
+    j.open().unwrap();
+    j.write(&[0x0, 0x1, 0x2]).unwrap();
+    j.commit().unwrap();
+    assert!(j.write_batch(&[&[0x3, 0x4], &[0x5, 0x6, 0x7], &[]]).is_err());
+  }
+
+  let mut j = Journal::new(TransactionalStorage::new(
+    FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+  ));
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+  assert!(!j.is_writing());
+  assert_eq!(12, j.write_offset());
+  assert_eq!(vec!(vec!(0x0, 0x1, 0x2)), j.by_ref().collect::<Vec<Vec<u8>>>());
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn write_batch_commits_nothing_when_a_record_does_not_fit_its_slot() {
   let mut j = Journal::new(new_storage(256, 256));
+  j.set_fixed_slot_size(Some(16)).unwrap();
+  j.open().unwrap();
   assert_eq!(
-    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.jump_to(6).unwrap_err().description()
+    journal::ERR_RECORD_TOO_LARGE,
+    j.write_batch(&[&[0x1, 0x2], &[0x3; 20]]).unwrap_err().description()
   );
+  assert_eq!(0, j.record_count());
+  assert_eq!(0, j.write_offset());
+  j.write_batch(&[&[0x4, 0x5]]).unwrap();
+  assert_eq!(16, j.write_offset());
+  assert_eq!(vec!(vec!(0x4, 0x5)), all_records(&mut j));
 }
 
 #[test]
-pub fn jump_to_returns_err_when_past_txn_boundary() {
+pub fn write_batch_notifies_on_commit_for_each_record() {
+  let committed = Rc::new(RefCell::new(Vec::new()));
+  let c = committed.clone();
   let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_commit(Box::new(move |offset, data| c.borrow_mut().push((offset, data.to_vec()))));
   j.open().unwrap();
+  j.write_batch(&[&[0x1, 0x2], &[0x3, 0x4, 0x5]]).unwrap();
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.jump_to(6).unwrap_err().description()
+    vec!((0, vec!(0x1, 0x2)), (11, vec!(0x3, 0x4, 0x5))),
+    *committed.borrow()
   );
 }
 
 #[test]
-pub fn jump_to_returns_ok_when_at_record_start() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
-  j.commit().unwrap();
-  assert!(j.jump_to(12).is_ok());
+pub fn write_batch_counts_as_one_commit_for_sync_every() {
+  let (mut j, syncs) = sync_every_journal(2);
+  j.write_batch(&[&[0x1, 0x2], &[0x3, 0x4], &[0x5, 0x6]]).unwrap();
+  assert_eq!(0, *syncs.borrow());
+  j.write_batch(&[&[0x7, 0x8]]).unwrap();
+  assert_eq!(1, *syncs.borrow());
 }
 
+
+// reserve_capacity() tests
 #[test]
-pub fn jump_to_returns_err_when_not_at_record_start() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
-  j.commit().unwrap();
+pub fn reserve_capacity_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(16, 16));
   assert_eq!(
-    journal::ERR_NO_COMMITTED_RECORD,
-    j.jump_to(13).unwrap_err().description()
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.reserve_capacity(256).unwrap_err().description()
   );
 }
 
 #[test]
-pub fn jump_to_returns_err_when_at_uncommitted_record_start() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn reserve_capacity_returns_err_when_write_in_progress() {
+  let mut j = Journal::new(new_storage(16, 16));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
   assert_eq!(
-    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
-    j.jump_to(12).unwrap_err().description()
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.reserve_capacity(256).unwrap_err().description()
   );
 }
 
 #[test]
-pub fn jump_to_still_jumps_when_err() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn reserve_capacity_does_not_shrink_storage() {
+  let mut j = Journal::new(new_storage(256, 16));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
-  j.jump_to(11).unwrap_err();
-  assert_eq!(11, j.read_offset());
+  j.reserve_capacity(16).unwrap();
+  assert_eq!(256, j.capacity().unwrap());
 }
 
 #[test]
-pub fn jump_to_jumps_when_complete_record() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn reserve_capacity_prevents_later_expansion() {
+  let mut j = Journal::new(new_storage(16, 16));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
-  j.commit().unwrap();
-  j.jump_to(12).unwrap();
-  assert_eq!(12, j.read_offset());
+  j.reserve_capacity(20 * 12).unwrap();
+  let capacity = j.capacity().unwrap();
+  assert_eq!(240, capacity);
+  for _ in 0..20 {
+    j.write(&[0x0, 0x1, 0x2]).unwrap();
+    j.commit().unwrap();
+    assert_eq!(capacity, j.capacity().unwrap());
+  }
+  assert_eq!(20, j.record_count());
 }
 
+// compact_and_shrink() tests
 #[test]
-pub fn jump_to_allows_record_read_at_jump_location() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn compact_and_shrink_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(16, 16));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.compact_and_shrink().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn compact_and_shrink_returns_err_when_write_in_progress() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.compact_and_shrink().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn compact_and_shrink_reduces_capacity_after_discard() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  for i in 0..3 {
+    j.write(&[i, i, i]).unwrap();
+    j.commit().unwrap();
+  }
+  j.write(vec![0xff; 500].as_slice()).unwrap();
+  j.discard().unwrap();
+  assert!(j.capacity().unwrap() > 500);
+
+  j.compact_and_shrink().unwrap();
+  assert_eq!(48, j.capacity().unwrap());
+  assert_eq!(36, j.txn_boundary().unwrap());
+
+  let recs: Vec<Vec<u8>> = j.enumerate_records().map(|(_, r)| r).collect();
+  assert_eq!(
+    vec!(vec!(0x0, 0x0, 0x0), vec!(0x1, 0x1, 0x1), vec!(0x2, 0x2, 0x2)), 
+    recs
+  );
+}
+
+#[test]
+pub fn compact_and_shrink_allows_writing_afterwards() {
+  let mut j = Journal::new(new_storage(16, 16));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  j.write(vec![0xff; 500].as_slice()).unwrap();
+  j.discard().unwrap();
+  j.compact_and_shrink().unwrap();
+  assert_eq!(16, j.capacity().unwrap());
+
   j.write(&[0x3, 0x4, 0x5]).unwrap();
   j.commit().unwrap();
-  j.jump_to(12).unwrap();
-  assert_eq!(vec!(0x3, 0x4, 0x5), j.read().unwrap());
+  assert_eq!(2, j.record_count());
+
+  let recs: Vec<Vec<u8>> = j.enumerate_records().map(|(_, r)| r).collect();
+  assert_eq!(vec!(vec!(0x0, 0x1, 0x2), vec!(0x3, 0x4, 0x5)), recs);
 }
 
-// reset() tests
 #[test]
-pub fn reset_does_not_change_read_offset_when_already_0() {
-  let mut j = Journal::new(new_storage(256, 256));
+pub fn compact_and_shrink_on_empty_journal_keeps_one_increment() {
+  let mut j = Journal::new(new_storage(256, 16));
   j.open().unwrap();
-  assert_eq!(0, j.read_offset());
-  j.reset();
-  assert_eq!(0, j.read_offset());
+  j.compact_and_shrink().unwrap();
+  assert_eq!(16, j.capacity().unwrap());
 }
 
+// assert_count_consistent() tests
 #[test]
-pub fn reset_changes_read_offset_to_0() {
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.assert_count_consistent().unwrap_err().description()
+  );
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_passes_on_empty_journal() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
+  j.assert_count_consistent().unwrap();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_passes_after_commits_and_discards() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  for i in 0..5 {
+    j.write(&[i, i, i]).unwrap();
+    j.commit().unwrap();
+    j.assert_count_consistent().unwrap();
+    j.write(&[0xff, 0xff]).unwrap();
+    j.discard().unwrap();
+    j.assert_count_consistent().unwrap();
+  }
+  assert_eq!(5, j.record_count());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_passes_after_reserve_and_update() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(0, j.read_offset());
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.reserve(3).unwrap();
+  j.fill_reserved(&[0x3, 0x4, 0x5]).unwrap();
   j.commit().unwrap();
-  j.jump_to(12).unwrap();
-  assert_eq!(12, j.read_offset());
-  j.reset();
-  assert_eq!(0, j.read_offset());
+  j.assert_count_consistent().unwrap();
+  j.update_record(0, &[0x6, 0x7, 0x8]).unwrap();
+  j.assert_count_consistent().unwrap();
+  assert_eq!(2, j.record_count());
 }
 
 #[test]
-pub fn reset_allows_reading_from_first_record() {
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_passes_after_compact_and_shrink() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(vec![0xff; 500].as_slice()).unwrap();
+  j.discard().unwrap();
+  j.compact_and_shrink().unwrap();
+  j.assert_count_consistent().unwrap();
+  assert_eq!(1, j.record_count());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+pub fn assert_count_consistent_does_not_move_read_offset() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
   j.write(&[0x3, 0x4, 0x5]).unwrap();
   j.commit().unwrap();
-  j.jump_to(12).unwrap();
-  assert_eq!(vec!(0x3, 0x4, 0x5), j.read().unwrap());
   j.reset();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  j.next().unwrap();
+  let read_offset = j.read_offset();
+  j.assert_count_consistent().unwrap();
+  assert_eq!(read_offset, j.read_offset());
 }
 
-// next() tests
+// audit() tests
 #[test]
-pub fn next_returns_none_when_closed() {
+pub fn audit_returns_err_when_closed() {
   let mut j = Journal::new(new_storage(256, 256));
-  assert!(j.next().is_none());
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.audit().unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn next_returns_none_when_no_records() {
+pub fn audit_passes_after_normal_operations() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.audit().unwrap();
+  for i in 0..4 {
+    j.write(&[i, i, i]).unwrap();
+    j.commit().unwrap();
+    j.write(&[0xff, 0xff]).unwrap();
+    j.discard().unwrap();
+  }
+  j.audit().unwrap();
+  j.write(vec![0xff; 200].as_slice()).unwrap();
+  j.discard().unwrap();
+  j.compact_and_shrink().unwrap();
+  j.audit().unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  j.audit().unwrap();
+  assert_eq!(4, j.record_count());
+}
+
+#[test]
+pub fn audit_detects_count_above_records() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  assert!(j.next().is_none());
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.set_record_count(2);
+  assert_eq!(
+    journal::ERR_RECORD_COUNT_MISMATCH,
+    j.audit().unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn next_returns_records_in_order() {
+pub fn audit_detects_count_below_records() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
-  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x3, 0x4, 0x5), j.next().unwrap());
+  j.set_record_count(1);
+  assert_eq!(
+    journal::ERR_RECORD_COUNT_MISMATCH,
+    j.audit().unwrap_err().description()
+  );
+  j.set_record_count(2);
+  j.audit().unwrap();
 }
 
 #[test]
-pub fn next_returns_none_when_no_more_records_available() {
+pub fn audit_does_not_move_read_offset() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
-  assert!(j.next().is_none());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.reset();
+  j.next().unwrap();
+  let read_offset = j.read_offset();
+  j.set_record_count(5);
+  j.audit().unwrap_err();
+  assert_eq!(read_offset, j.read_offset());
 }
 
-#[test]
-pub fn next_returns_records_as_they_become_available() {
+// write_expiring(), set_expiry_now(), and compact_expired() tests
+fn expiring_journal() -> Journal<MemoryBinaryStorage> {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.write_expiring(&[0x1, 0x1], 100).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
-  assert!(j.next().is_none());
-  j.write(&[0x4, 0x5, 0x6]).unwrap();
+  j.write(&[0x2, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(vec!(0x4, 0x5, 0x6), j.next().unwrap());
-  assert!(j.next().is_none());
+  j.write_expiring(&[0x3, 0x3], 300).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x4, 0x4], 200).unwrap();
+  j.commit().unwrap();
+  j
 }
 
-// read_offset() tests
-#[test]
-pub fn read_offset_starts_at_0() {
-  let j = Journal::new(new_storage(256, 256));
-  assert_eq!(0, j.read_offset());
+fn all_records(j: &mut Journal<MemoryBinaryStorage>) -> Vec<Vec<u8>> {
+  j.enumerate_records().map(|(_, r)| r).collect()
 }
 
 #[test]
-pub fn read_offset_moves_on_next() {
+pub fn write_expiring_returns_err_when_empty() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert_eq!(0, j.read_offset());
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
-  assert_eq!(12, j.read_offset());
+  assert_eq!(
+    journal::ERR_NOTHING_TO_WRITE,
+    j.write_expiring(&[], 100).unwrap_err().description()
+  );
 }
 
 #[test]
-pub fn read_offset_resets_after_reopening() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  j.commit().unwrap();
-  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
-  assert_eq!(12, j.read_offset());
-  j.close().unwrap();
-  j.open().unwrap();
-  assert_eq!(0, j.read_offset());
+pub fn write_expiring_records_read_back_without_expiry() {
+  let mut j = expiring_journal();
+  j.reset();
+  assert_eq!(vec!(0x1, 0x1), j.read().unwrap());
+  assert_eq!(Some(100), j.read_expiry().unwrap());
+  j.next().unwrap();
+  assert_eq!(vec!(0x2, 0x2), j.read().unwrap());
+  assert_eq!(None, j.read_expiry().unwrap());
 }
 
-// write_offset() tests
 #[test]
-pub fn write_offset_starts_at_0() {
+pub fn expiry_now_defaults_to_none() {
   let j = Journal::new(new_storage(256, 256));
-  assert_eq!(0, j.read_offset());
+  assert_eq!(None, j.get_expiry_now());
 }
 
 #[test]
-pub fn write_offset_increases_on_write() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(10, j.write_offset());
+pub fn iteration_returns_all_records_when_expiry_now_not_set() {
+  let mut j = expiring_journal();
+  assert_eq!(
+    vec!(vec!(0x1, 0x1), vec!(0x2, 0x2), vec!(0x3, 0x3), vec!(0x4, 0x4)),
+    all_records(&mut j)
+  );
 }
 
 #[test]
-pub fn write_offset_increases_on_commit() {
+pub fn iteration_skips_expired_records() {
+  let mut j = expiring_journal();
+  j.set_expiry_now(Some(200));
+  assert_eq!(vec!(vec!(0x2, 0x2), vec!(0x3, 0x3)), all_records(&mut j));
+  j.set_expiry_now(Some(99));
+  assert_eq!(4, all_records(&mut j).len());
+  j.set_expiry_now(Some(1000));
+  assert_eq!(vec!(vec!(0x2, 0x2)), all_records(&mut j));
+}
+
+#[test]
+pub fn iteration_skips_expired_last_record() {
   let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(10, j.write_offset());
+  j.write(&[0x1, 0x1]).unwrap();
   j.commit().unwrap();
-  assert_eq!(12, j.write_offset());
+  j.write_expiring(&[0x2, 0x2], 10).unwrap();
+  j.commit().unwrap();
+  j.set_expiry_now(Some(10));
+  assert_eq!(vec!(vec!(0x1, 0x1)), all_records(&mut j));
 }
 
 #[test]
-pub fn write_offset_resets_on_discard() {
-  let mut j = Journal::new(new_storage(256, 256));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(10, j.write_offset());
-  j.discard().unwrap();
-  assert_eq!(0, j.write_offset());
+pub fn expired_records_are_still_counted() {
+  let mut j = expiring_journal();
+  j.set_expiry_now(Some(1000));
+  assert_eq!(4, j.record_count());
+  j.audit().unwrap();
 }
 
 #[test]
-pub fn write_offset_goes_to_uncommitted_record_end_when_reopened_before_commit() {
+pub fn on_commit_receives_data_without_expiry() {
   let mut j = Journal::new(new_storage(256, 256));
+  let committed = Rc::new(RefCell::new(Vec::new()));
+  let c = committed.clone();
+  j.set_on_commit(Box::new(move |_, data| c.borrow_mut().push(data.to_vec())));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(10, j.write_offset());
-  j.close().unwrap();
-  j.open().unwrap();
-  assert_eq!(10, j.write_offset());
+  j.write_expiring(&[0x1, 0x2], 100).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(vec!(0x1, 0x2)), *committed.borrow());
 }
 
-// capacity() tests
 #[test]
-pub fn capacity_returns_err_when_closed() {
-  let j = Journal::new(new_storage(16, 16));
+pub fn update_record_keeps_expiry() {
+  let mut j = expiring_journal();
+  j.update_record(0, &[0x9, 0x9]).unwrap();
+  j.reset();
+  assert_eq!(vec!(0x9, 0x9), j.read().unwrap());
+  assert_eq!(Some(100), j.read_expiry().unwrap());
+}
+
+#[test]
+pub fn compact_expired_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
   assert_eq!(
     binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.capacity().unwrap_err().description()
+    j.compact_expired(100, new_storage(256, 256)).err().unwrap().description()
   );
 }
 
 #[test]
-pub fn capacity_returns_ok_when_open() {
-  let mut j = Journal::new(new_storage(16, 16));
-  j.open().unwrap();
-  assert!(j.capacity().is_ok());
+pub fn compact_expired_returns_err_when_write_in_progress() {
+  let mut j = expiring_journal();
+  j.write(&[0x5]).unwrap();
+  assert_eq!(
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.compact_expired(100, new_storage(256, 256)).err().unwrap().description()
+  );
 }
 
 #[test]
-pub fn capacity_returns_initial_capacity() {
-  let mut j = Journal::new(new_storage(16, 16));
+pub fn compact_expired_removes_expired_records() {
+  let mut j = expiring_journal();
+  assert_eq!(2, j.compact_expired(200, new_storage(256, 256)).unwrap().0);
+  assert_eq!(2, j.record_count());
+  j.audit().unwrap();
+  assert_eq!(vec!(vec!(0x2, 0x2), vec!(0x3, 0x3)), all_records(&mut j));
+  j.reset();
+  j.next().unwrap();
+  assert_eq!(Some(300), j.read_expiry().unwrap());
+}
+
+#[test]
+pub fn compact_expired_does_nothing_when_nothing_expired() {
+  let mut j = expiring_journal();
+  let boundary = j.txn_boundary().unwrap();
+  assert_eq!(0, j.compact_expired(50, new_storage(256, 256)).unwrap().0);
+  assert_eq!(boundary, j.txn_boundary().unwrap());
+  assert_eq!(4, all_records(&mut j).len());
+}
+
+#[test]
+pub fn compact_expired_returns_fresh_storage_when_nothing_expired() {
+  let mut j = expiring_journal();
+  let mut fresh = new_storage(256, 256);
+  fresh.open().unwrap();
+  fresh.w_u8(0, 0x9).unwrap();
+  let (dropped, mut unused) = j.compact_expired(50, fresh).unwrap();
+  assert_eq!(0, dropped);
+  unused.set_check_on_read(false).unwrap();
+  assert_eq!(0x9, unused.r_u8(0).unwrap());
+}
+
+#[test]
+pub fn compact_expired_leaves_old_storage_untouched() {
+  let mut j = expiring_journal();
+  let boundary = j.txn_boundary().unwrap();
+  let (_, old) = j.compact_expired(200, new_storage(256, 256)).unwrap();
+  assert_eq!(boundary, old.get_txn_boundary().unwrap());
+
+  let mut old_j = Journal::new(old);
+  old_j.verify().unwrap();
+  assert_eq!(4, old_j.record_count());
+  assert_eq!(4, all_records(&mut old_j).len());
+}
+
+#[test]
+pub fn compact_expired_overwrites_data_in_fresh_storage() {
+  let mut j = expiring_journal();
+  let mut fresh = new_storage(256, 256);
+  fresh.open().unwrap();
+  fresh.fill(None, None, 0x3).unwrap();
+  j.compact_expired(200, fresh).unwrap();
+  j.close().unwrap();
   j.open().unwrap();
-  assert_eq!(16, j.capacity().unwrap());
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(vec!(0x2, 0x2), vec!(0x3, 0x3)), all_records(&mut j));
 }
 
 #[test]
-pub fn capacity_returns_expanded_capacity() {
-  let mut j = Journal::new(new_storage(16, 16));
+pub fn compact_expired_keeps_old_records_when_copy_fails() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
   j.open().unwrap();
-  assert_eq!(16, j.capacity().unwrap());
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.write_expiring(&[0x1, 0x1], 100).unwrap();
   j.commit().unwrap();
-  assert_eq!(16, j.capacity().unwrap());
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.write(&[0x2, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(32, j.capacity().unwrap());
-}
 
-// txn_boundary() tests
-#[test]
-pub fn txn_boundary_returns_err_when_closed() {
-  let j = Journal::new(new_storage(16, 16));
+  // Can't be opened, since the directory doesn't exist and create is off
+  let fresh = TransactionalStorage::new(
+    FileBinaryStorage::new(path.clone() + "/missing/journal", false, 256, 16, 16, 256).unwrap()
+  );
+  assert!(j.compact_expired(200, fresh).is_err());
+  assert_eq!(2, j.record_count());
   assert_eq!(
-    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
-    j.txn_boundary().unwrap_err().description()
+    vec!((0, vec!(0x1, 0x1)), (19, vec!(0x2, 0x2))),
+    j.enumerate_records().collect::<Vec<(usize, Vec<u8>)>>()
   );
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
 }
 
 #[test]
-pub fn txn_boundary_starts_at_0() {
-  let mut j = Journal::new(new_storage(16, 16));
-  j.open().unwrap();
-  assert_eq!(0, j.txn_boundary().unwrap());
+pub fn compact_expired_allows_writing_afterwards() {
+  let mut j = expiring_journal();
+  j.compact_expired(1000, new_storage(256, 256)).unwrap();
+  j.write(&[0x5, 0x5]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(vec!(0x2, 0x2), vec!(0x5, 0x5)), all_records(&mut j));
 }
 
 #[test]
-pub fn txn_boundary_does_not_advance_on_write() {
+pub fn compact_expired_then_compact_and_shrink_releases_storage() {
   let mut j = Journal::new(new_storage(16, 16));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(0, j.txn_boundary().unwrap());
+  for i in 0..20 {
+    j.write_expiring(&[i, i, i], i as u64).unwrap();
+    j.commit().unwrap();
+  }
+  let capacity = j.capacity().unwrap();
+  assert_eq!(18, j.compact_expired(17, new_storage(16, 16)).unwrap().0);
+  j.compact_and_shrink().unwrap();
+  assert!(j.capacity().unwrap() < capacity);
+  assert_eq!(vec!(vec!(18, 18, 18), vec!(19, 19, 19)), all_records(&mut j));
 }
 
+// fragmentation() tests
 #[test]
-pub fn txn_boundary_advances_on_commit() {
-  let mut j = Journal::new(new_storage(16, 16));
-  j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(0, j.txn_boundary().unwrap());
-  j.commit().unwrap();
-  assert_eq!(12, j.txn_boundary().unwrap());
+pub fn fragmentation_is_0_when_closed() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(0.0, j.fragmentation());
 }
 
 #[test]
-pub fn txn_boundary_does_not_advance_on_discard() {
-  let mut j = Journal::new(new_storage(16, 16));
+pub fn fragmentation_is_0_for_empty_journal() {
+  let mut j = Journal::new(new_storage(256, 256));
   j.open().unwrap();
-  j.write(&[0x0, 0x1, 0x2]).unwrap();
-  assert_eq!(0, j.txn_boundary().unwrap());
+  j.set_expiry_now(Some(100));
+  assert_eq!(0.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_is_0_after_discards() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.set_expiry_now(Some(100));
+  j.write(&[0x1, 0x1]).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x2, 0x2, 0x2], 50).unwrap();
   j.discard().unwrap();
-  assert_eq!(0, j.txn_boundary().unwrap());
+  j.write(&[0x3, 0x3]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(0.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_reflects_expired_records() {
+  // Expiring records take 19 bytes each and the plain record 11
+  let mut j = expiring_journal();
+  assert_eq!(68, j.write_offset());
+  assert_eq!(0.0, j.fragmentation());
+  j.set_expiry_now(Some(50));
+  assert_eq!(0.0, j.fragmentation());
+  j.set_expiry_now(Some(100));
+  assert_eq!(19.0 / 68.0, j.fragmentation());
+  j.set_expiry_now(Some(200));
+  assert_eq!(38.0 / 68.0, j.fragmentation());
+  j.set_expiry_now(Some(300));
+  assert_eq!(57.0 / 68.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_counts_records_committed_already_expired() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.set_expiry_now(Some(100));
+  j.write(&[0x1, 0x1]).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x2, 0x2], 50).unwrap();
+  j.commit().unwrap();
+  assert_eq!(19.0 / 30.0, j.fragmentation());
+  j.write_expiring(&[0x3, 0x3], 150).unwrap();
+  j.commit().unwrap();
+  assert_eq!(19.0 / 49.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_is_restored_by_verify() {
+  let mut j = expiring_journal();
+  j.close().unwrap();
+  j.set_expiry_now(Some(200));
+  j.open().unwrap();
+  assert_eq!(38.0 / 68.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_counts_corrupt_records_skipped_by_verify() {
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = journal_with_corruption_action(CorruptionAction::Skip, calls);
+  j.open().unwrap();
+  assert_eq!(12.0 / 36.0, j.fragmentation());
+}
+
+#[test]
+pub fn fragmentation_drops_to_0_after_compact_expired() {
+  let mut j = expiring_journal();
+  j.set_expiry_now(Some(200));
+  j.compact_expired(200, new_storage(256, 256)).unwrap();
+  assert_eq!(0.0, j.fragmentation());
+  j.set_expiry_now(Some(300));
+  assert_eq!(19.0 / 30.0, j.fragmentation());
+}
+
+// update_record() tests
+#[test]
+pub fn update_record_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.update_record(0, &[0x0, 0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn update_record_returns_err_when_no_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
   j.write(&[0x0, 0x1, 0x2]).unwrap();
   j.commit().unwrap();
-  assert_eq!(12, j.txn_boundary().unwrap());
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.update_record(2, &[0x0, 0x1, 0x2]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn update_record_returns_err_on_length_mismatch() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(
+    journal::ERR_RECORD_LENGTH_MISMATCH,
+    j.update_record(0, &[0x0, 0x1]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn update_record_returns_err_when_record_uncommitted() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert!(j.update_record(0, &[0x3, 0x4, 0x5]).is_err());
+}
+
+#[test]
+pub fn update_record_overwrites_data_in_place() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  j.update_record(0, &[0x7, 0x8, 0x9]).unwrap();
+  assert_eq!(23, j.txn_boundary().unwrap());
+  assert_eq!(vec!(0x7, 0x8, 0x9), j.next().unwrap());
+  assert_eq!(vec!(0x3, 0x4), j.next().unwrap());
+  assert_eq!(vec!(0, 12), j.recover_scan().unwrap());
+}
+
+#[test]
+pub fn update_record_recomputes_checksum_for_other_algos() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.set_checksum_algo(ChecksumAlgo::Crc32);
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.update_record(0, &[0x7, 0x8, 0x9]).unwrap();
+  j.jump_to(0).unwrap();
+  assert_eq!(vec!(0x7, 0x8, 0x9), j.read().unwrap());
+}
+
+// reserve() and fill_reserved() tests
+#[test]
+pub fn reserve_returns_record_offset() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(12, j.reserve(4).unwrap());
+  assert!(j.is_writing());
+  assert_eq!(18, j.write_offset());
+}
+
+#[test]
+pub fn reserve_returns_err_when_write_in_progress() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(
+    journal::ERR_WRITE_IN_PROGRESS,
+    j.reserve(4).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn reserve_returns_err_when_len_is_0() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    journal::ERR_NOTHING_TO_WRITE,
+    j.reserve(0).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn fill_reserved_returns_err_when_nothing_reserved() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    journal::ERR_NOTHING_RESERVED,
+    j.fill_reserved(&[0x0, 0x1]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn fill_reserved_returns_err_on_length_mismatch() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.reserve(4).unwrap();
+  assert_eq!(
+    journal::ERR_RESERVED_LENGTH_MISMATCH,
+    j.fill_reserved(&[0x0, 0x1]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn commit_returns_err_when_reserved_not_filled() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.reserve(4).unwrap();
+  assert_eq!(
+    journal::ERR_RESERVED_NOT_FILLED,
+    j.commit().unwrap_err().description()
+  );
+  j.discard().unwrap();
+  assert_eq!(0, j.write_offset());
+  assert_eq!(
+    journal::ERR_NOTHING_RESERVED,
+    j.fill_reserved(&[0x0, 0x1, 0x2, 0x3]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn reserved_record_can_be_read_at_reserved_offset() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  let offset = j.reserve(4).unwrap();
+  j.fill_reserved(&[0x3, 0x4, 0x5, 0x6]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x7, 0x8]).unwrap();
+  j.commit().unwrap();
+  j.jump_to(offset).unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x5, 0x6), j.read().unwrap());
+  assert_eq!(3, j.record_count());
+}
+
+// set_on_uncommitted_drop() tests
+#[test]
+pub fn drop_with_uncommitted_write_calls_hook() {
+  let dropped = Rc::new(RefCell::new(false));
+  let d = dropped.clone();
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_uncommitted_drop(Box::new(move || *d.borrow_mut() = true));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  drop(j);
+  assert!(*dropped.borrow());
+}
+
+#[test]
+pub fn drop_after_commit_does_not_call_hook() {
+  let dropped = Rc::new(RefCell::new(false));
+  let d = dropped.clone();
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_uncommitted_drop(Box::new(move || *d.borrow_mut() = true));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  drop(j);
+  assert!(!*dropped.borrow());
+}
+
+#[test]
+pub fn drop_after_discard_does_not_call_hook() {
+  let dropped = Rc::new(RefCell::new(false));
+  let d = dropped.clone();
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_uncommitted_drop(Box::new(move || *d.borrow_mut() = true));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.discard().unwrap();
+  drop(j);
+  assert!(!*dropped.borrow());
+}
+
+
+// set_on_commit() tests
+#[test]
+pub fn on_commit_fires_once_per_commit() {
+  let commits = Rc::new(RefCell::new(Vec::new()));
+  let c = commits.clone();
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_commit(Box::new(move |offset, data| {
+    c.borrow_mut().push((offset, data.to_vec()));
+  }));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(
+    vec!((0, vec!(0x0, 0x1, 0x2)), (12, vec!(0x3, 0x4))),
+    *commits.borrow()
+  );
+}
+
+#[test]
+pub fn on_commit_does_not_fire_on_discard() {
+  let commits = Rc::new(RefCell::new(Vec::new()));
+  let c = commits.clone();
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_on_commit(Box::new(move |offset, data| {
+    c.borrow_mut().push((offset, data.to_vec()));
+  }));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.discard().unwrap();
+  assert!(commits.borrow().is_empty());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!((0, vec!(0x3, 0x4, 0x5))), *commits.borrow());
+}
+
+// get_checksum_algo() and set_checksum_algo() tests
+#[test]
+pub fn checksum_algo_is_xor_by_default() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(ChecksumAlgo::Xor, j.get_checksum_algo());
+}
+
+#[test]
+pub fn set_checksum_algo_changes_checksum_algo() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_checksum_algo(ChecksumAlgo::Crc32c);
+  assert_eq!(ChecksumAlgo::Crc32c, j.get_checksum_algo());
+}
+
+#[test]
+pub fn records_verify_with_each_checksum_algo() {
+  for algo in vec!(
+    ChecksumAlgo::Xor, 
+    ChecksumAlgo::Crc32, 
+    ChecksumAlgo::Crc32c, 
+    ChecksumAlgo::Fnv
+  ) {
+    let mut j = Journal::new(new_storage(256, 256));
+    j.set_checksum_algo(algo);
+    j.open().unwrap();
+    j.write(&[0x0, 0x1, 0x2]).unwrap();
+    j.commit().unwrap();
+    j.write(&[0x3, 0x4, 0x5, 0x6]).unwrap();
+    j.commit().unwrap();
+    j.close().unwrap();
+    j.open().unwrap();
+    assert_eq!(2, j.record_count());
+    assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+    assert_eq!(vec!(0x3, 0x4, 0x5, 0x6), j.next().unwrap());
+    assert!(j.next().is_none());
+  }
+}
+
+#[test]
+pub fn records_with_different_checksum_algos_can_be_mixed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.set_checksum_algo(ChecksumAlgo::Crc32);
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  j.set_checksum_algo(ChecksumAlgo::Fnv);
+  j.write(&[0x5, 0x6]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+  j.set_checksum_algo(ChecksumAlgo::Xor);
+  j.open().unwrap();
+  assert_eq!(3, j.record_count());
+  let records: Vec<(usize, Vec<u8>)> = j.enumerate_records().collect();
+  assert_eq!(
+    vec!((0, vec!(0x0, 0x1, 0x2)), (12, vec!(0x3, 0x4)), (26, vec!(0x5, 0x6))),
+    records
+  );
+}
+
+#[test]
+pub fn post_data_len_depends_on_checksum_algo() {
+  for algo in vec!(
+    ChecksumAlgo::Xor, 
+    ChecksumAlgo::Crc32, 
+    ChecksumAlgo::Crc32c, 
+    ChecksumAlgo::Fnv
+  ) {
+    let mut j = Journal::new(new_storage(256, 256));
+    j.set_checksum_algo(algo);
+    j.open().unwrap();
+    j.write(&[0x0, 0x1, 0x2]).unwrap();
+    j.commit().unwrap();
+    assert_eq!(journal::PRE_DATA_LEN + 3 + journal::post_data_len(algo), j.write_offset());
+  }
+  assert_eq!(3, journal::post_data_len(ChecksumAlgo::Xor));
+  assert_eq!(6, journal::post_data_len(ChecksumAlgo::Crc32));
+}
+
+#[test]
+pub fn open_and_verify_returns_err_on_checksum_algo_mismatch() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  // Record written with Crc32 but tagged as Fnv
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x3, 0x0, 0x1, 0x2, 0x0, 0x0, 0x0, 0x0, 0x3, 0x3]
+  ).unwrap();
+  let crc = ChecksumAlgo::Crc32.checksum(&[0x0, 0x1, 0x2]);
+  s.w_u32(9, crc).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  assert_eq!(
+    journal::ERR_CHECKSUM_MISMATCH,
+    j.open().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn open_and_verify_returns_err_on_unknown_checksum_algo() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(
+    0, 
+    &[0x2, 0x2, 0x3, 0x0, 0x0, 0x9, 0x1, 0x2, 0x3, 0x0, 0x3, 0x3]
+  ).unwrap();
+  s.close().unwrap();
+  let mut j = Journal::new(s);
+  assert_eq!(
+    journal::ERR_UNKNOWN_CHECKSUM_ALGO,
+    j.open().unwrap_err().description()
+  );
+}
+
+// is_writing() tests
+#[test]
+pub fn is_not_writing_when_new() {
+  let j = Journal::new(new_storage(256, 256));
+  assert!(!j.is_writing());
+}
+
+#[test]
+pub fn is_not_writing_when_newly_opened() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert!(!j.is_writing());
+}
+
+#[test]
+pub fn is_writing_after_write() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert!(j.is_writing());
+}
+
+#[test]
+pub fn is_not_writing_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.close().unwrap();
+  assert!(!j.is_writing());
+}
+
+#[test]
+pub fn is_writing_when_reopened_before_commit() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  assert!(j.is_writing());
+}
+
+#[test]
+pub fn is_not_writing_after_commit() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert!(!j.is_writing());
+}
+
+#[test]
+pub fn is_not_writing_after_discard() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.discard().unwrap();
+  assert!(!j.is_writing());
+}
+
+// has_start() tests
+#[test]
+pub fn has_start_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.has_start().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn has_start_returns_err_when_past_txn_boundary() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.has_start().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn has_start_returns_ok_when_open() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert!(j.has_start().is_ok());
+}
+
+#[test]
+pub fn has_start_returns_true_when_record_exists() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert!(j.has_start().unwrap());
+}
+
+// has_end() tests
+#[test]
+pub fn has_end_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.has_end().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn has_end_returns_err_when_past_txn_boundary() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.has_end().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn has_end_returns_true_when_record_is_committed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert!(j.has_end().is_ok());
+}
+
+// read() tests
+#[test]
+pub fn read_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.read().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_returns_err_when_no_data() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.read().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_returns_err_when_uncommitted_data() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.read().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_returns_ok_when_committed_data() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert!(j.read().is_ok());
+}
+
+#[test]
+pub fn read_returns_first_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+}
+
+#[test]
+pub fn read_returns_record_multiple_times() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+}
+
+// jump_to() tests
+#[test]
+pub fn jump_to_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.jump_to(6).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn jump_to_returns_err_when_past_txn_boundary() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.jump_to(6).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn jump_to_returns_ok_when_at_record_start() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  assert!(j.jump_to(12).is_ok());
+}
+
+#[test]
+pub fn jump_to_returns_err_when_not_at_record_start() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.jump_to(13).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn jump_to_returns_err_when_at_uncommitted_record_start() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    j.jump_to(12).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn jump_to_still_jumps_when_err() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.jump_to(11).unwrap_err();
+  assert_eq!(11, j.read_offset());
+}
+
+#[test]
+pub fn jump_to_jumps_when_complete_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.jump_to(12).unwrap();
+  assert_eq!(12, j.read_offset());
+}
+
+#[test]
+pub fn jump_to_allows_record_read_at_jump_location() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.jump_to(12).unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x5), j.read().unwrap());
+}
+
+// reset() tests
+#[test]
+pub fn reset_does_not_change_read_offset_when_already_0() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(0, j.read_offset());
+  j.reset();
+  assert_eq!(0, j.read_offset());
+}
+
+#[test]
+pub fn reset_changes_read_offset_to_0() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(0, j.read_offset());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.jump_to(12).unwrap();
+  assert_eq!(12, j.read_offset());
+  j.reset();
+  assert_eq!(0, j.read_offset());
+}
+
+#[test]
+pub fn reset_allows_reading_from_first_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.jump_to(12).unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x5), j.read().unwrap());
+  j.reset();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.read().unwrap());
+}
+
+// next() tests
+#[test]
+pub fn next_returns_none_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert!(j.next().is_none());
+}
+
+#[test]
+pub fn next_returns_none_when_no_records() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert!(j.next().is_none());
+}
+
+#[test]
+pub fn next_returns_records_in_order() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x3, 0x4, 0x5), j.next().unwrap());
+}
+
+#[test]
+pub fn next_returns_none_when_no_more_records_available() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  assert!(j.next().is_none());
+}
+
+#[test]
+pub fn next_returns_records_as_they_become_available() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  assert!(j.next().is_none());
+  j.write(&[0x4, 0x5, 0x6]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x4, 0x5, 0x6), j.next().unwrap());
+  assert!(j.next().is_none());
+}
+
+#[test]
+pub fn reopen_preserves_iteration_order() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  for i in 0..10 {
+    j.write(&[i, i + 1, i + 2]).unwrap();
+    j.commit().unwrap();
+  }
+  let before: Vec<Vec<u8>> = j.by_ref().collect();
+  assert_eq!(10, before.len());
+
+  j.close().unwrap();
+  j.open().unwrap();
+  let after: Vec<Vec<u8>> = j.by_ref().collect();
+  assert_eq!(before, after);
+  assert_eq!(10, j.record_count());
+
+  // And again after appending past the reopen
+  j.write(&[0xa, 0xb]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  let mut expected = before.clone();
+  expected.push(vec!(0xa, 0xb));
+  assert_eq!(expected, j.by_ref().collect::<Vec<Vec<u8>>>());
+}
+
+#[test]
+pub fn new_journal_over_written_storage_resumes_after_last_record() {
+  let path = rnd_path();
+  let mut j = Journal::new(TransactionalStorage::new(
+    FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+  ));
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  let before: Vec<Vec<u8>> = j.by_ref().collect();
+  let end = j.write_offset();
+  j.close().unwrap();
+
+  let mut j = Journal::new(TransactionalStorage::new(
+    FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+  ));
+  j.open().unwrap();
+  assert_eq!(end, j.write_offset());
+  assert_eq!(end, j.txn_boundary().unwrap());
+  assert_eq!(before, j.by_ref().collect::<Vec<Vec<u8>>>());
+
+  j.write(&[0x6, 0x7]).unwrap();
+  j.commit().unwrap();
+  j.reset();
+  assert_eq!(
+    vec!(vec!(0x1, 0x2), vec!(0x3, 0x4, 0x5), vec!(0x6, 0x7)),
+    j.by_ref().collect::<Vec<Vec<u8>>>()
+  );
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn discard_after_reopen_drops_uncommitted_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.close().unwrap();
+  j.open().unwrap();
+  assert!(j.is_writing());
+  j.discard().unwrap();
+  assert_eq!(11, j.write_offset());
+  j.write(&[0x5, 0x6]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(vec!(0x1, 0x2), vec!(0x5, 0x6)), all_records(&mut j));
+}
+
+// enumerate_records() tests
+#[test]
+pub fn enumerate_records_returns_none_when_no_records() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert!(j.enumerate_records().next().is_none());
+}
+
+#[test]
+pub fn enumerate_records_returns_offsets_and_data() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x5, 0x6, 0x7, 0x8]).unwrap();
+  j.commit().unwrap();
+  let records: Vec<(usize, Vec<u8>)> = j.enumerate_records().collect();
+  assert_eq!(
+    vec!(
+      (0, vec!(0x0, 0x1, 0x2)),
+      (journal::PRE_DATA_LEN + 3 + journal::post_data_len(ChecksumAlgo::Xor), vec!(0x3, 0x4)),
+      (2 * (journal::PRE_DATA_LEN + journal::post_data_len(ChecksumAlgo::Xor)) + 5, vec!(0x5, 0x6, 0x7, 0x8))
+    ),
+    records
+  );
+}
+
+#[test]
+pub fn enumerate_records_starts_from_first_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.commit().unwrap();
+  j.next().unwrap();
+  let records: Vec<(usize, Vec<u8>)> = j.enumerate_records().collect();
+  assert_eq!(2, records.len());
+  assert_eq!(0, records[0].0);
+}
+
+#[test]
+pub fn enumerate_records_does_not_return_uncommitted_records() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  let records: Vec<(usize, Vec<u8>)> = j.enumerate_records().collect();
+  assert_eq!(vec!((0, vec!(0x0, 0x1, 0x2))), records);
+}
+
+
+// filter_records() tests
+#[test]
+pub fn filter_records_returns_empty_when_no_records() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert!(j.filter_records(|_| true).is_empty());
+}
+
+#[test]
+pub fn filter_records_returns_matching_records_in_order() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  for rec in [[0xa, 0x1], [0xb, 0x2], [0xa, 0x3], [0xc, 0x4], [0xa, 0x5]].iter() {
+    j.write(rec).unwrap();
+    j.commit().unwrap();
+  }
+  assert_eq!(
+    vec!(vec!(0xa, 0x1), vec!(0xa, 0x3), vec!(0xa, 0x5)),
+    j.filter_records(|r| r[0] == 0xa)
+  );
+  assert_eq!(vec!(vec!(0xc, 0x4)), j.filter_records(|r| r[0] == 0xc));
+  assert!(j.filter_records(|r| r[0] == 0xd).is_empty());
+}
+
+#[test]
+pub fn filter_records_starts_from_first_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0xa, 0x1]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0xa, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.next().unwrap();
+  assert_eq!(2, j.filter_records(|_| true).len());
+}
+
+#[test]
+pub fn filter_records_does_not_return_uncommitted_records() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0xa, 0x1]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0xa, 0x2]).unwrap();
+  assert_eq!(vec!(vec!(0xa, 0x1)), j.filter_records(|_| true));
+}
+
+// read_offset() tests
+#[test]
+pub fn read_offset_starts_at_0() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(0, j.read_offset());
+}
+
+#[test]
+pub fn read_offset_moves_on_next() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(0, j.read_offset());
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  assert_eq!(12, j.read_offset());
+}
+
+#[test]
+pub fn read_offset_resets_after_reopening() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), j.next().unwrap());
+  assert_eq!(12, j.read_offset());
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(0, j.read_offset());
+}
+
+// write_offset() tests
+#[test]
+pub fn write_offset_starts_at_0() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(0, j.read_offset());
+}
+
+#[test]
+pub fn write_offset_increases_on_write() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(10, j.write_offset());
+}
+
+#[test]
+pub fn write_offset_increases_on_commit() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(10, j.write_offset());
+  j.commit().unwrap();
+  assert_eq!(12, j.write_offset());
+}
+
+#[test]
+pub fn write_offset_resets_on_discard() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(10, j.write_offset());
+  j.discard().unwrap();
+  assert_eq!(0, j.write_offset());
+}
+
+#[test]
+pub fn write_offset_goes_to_uncommitted_record_end_when_reopened_before_commit() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(10, j.write_offset());
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(10, j.write_offset());
+}
+
+// capacity() tests
+#[test]
+pub fn capacity_returns_err_when_closed() {
+  let j = Journal::new(new_storage(16, 16));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.capacity().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn capacity_returns_ok_when_open() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  assert!(j.capacity().is_ok());
+}
+
+#[test]
+pub fn capacity_returns_initial_capacity() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  assert_eq!(16, j.capacity().unwrap());
+}
+
+#[test]
+pub fn capacity_returns_expanded_capacity() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  assert_eq!(16, j.capacity().unwrap());
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(16, j.capacity().unwrap());
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(32, j.capacity().unwrap());
+}
+
+// txn_boundary() tests
+#[test]
+pub fn txn_boundary_returns_err_when_closed() {
+  let j = Journal::new(new_storage(16, 16));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.txn_boundary().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn txn_boundary_starts_at_0() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  assert_eq!(0, j.txn_boundary().unwrap());
+}
+
+#[test]
+pub fn txn_boundary_does_not_advance_on_write() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(0, j.txn_boundary().unwrap());
+}
+
+#[test]
+pub fn txn_boundary_advances_on_commit() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(0, j.txn_boundary().unwrap());
+  j.commit().unwrap();
+  assert_eq!(12, j.txn_boundary().unwrap());
+}
+
+#[test]
+pub fn txn_boundary_does_not_advance_on_discard() {
+  let mut j = Journal::new(new_storage(16, 16));
+  j.open().unwrap();
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  assert_eq!(0, j.txn_boundary().unwrap());
+  j.discard().unwrap();
+  assert_eq!(0, j.txn_boundary().unwrap());
+  j.write(&[0x0, 0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(12, j.txn_boundary().unwrap());
+}
+
+// group_commit() and flush_commits() tests
+fn group_commit_journal(
+  window: u64
+) -> (Journal<MemoryBinaryStorage>, Rc<RefCell<u64>>, Rc<RefCell<u32>>) {
+  let ticks = Rc::new(RefCell::new(0));
+  let syncs = Rc::new(RefCell::new(0));
+  let t = ticks.clone();
+  let c = syncs.clone();
+
+  let mut j = Journal::new(new_storage(256, 256));
+  j.group_commit(window, Box::new(move || *t.borrow()));
+  j.set_on_sync(Box::new(move || *c.borrow_mut() += 1));
+  j.open().unwrap();
+  (j, ticks, syncs)
+}
+
+fn commit_rec(j: &mut Journal<MemoryBinaryStorage>, data: &[u8]) {
+  j.write(data).unwrap();
+  j.commit().unwrap();
+}
+
+#[test]
+pub fn commit_does_not_sync_without_group_commit() {
+  let mut j = Journal::new(new_storage(256, 256));
+  let syncs = Rc::new(RefCell::new(0));
+  let c = syncs.clone();
+  j.set_on_sync(Box::new(move || *c.borrow_mut() += 1));
+  j.open().unwrap();
+  assert!(!j.is_group_commit());
+  commit_rec(&mut j, &[0x1, 0x2]);
+  commit_rec(&mut j, &[0x3, 0x4]);
+  assert!(!j.has_unsynced_commits());
+  assert_eq!(0, *syncs.borrow());
+}
+
+#[test]
+pub fn group_commit_shares_one_sync_within_window() {
+  let (mut j, ticks, syncs) = group_commit_journal(10);
+  assert!(j.is_group_commit());
+
+  commit_rec(&mut j, &[0x1, 0x1]);
+  *ticks.borrow_mut() = 3;
+  commit_rec(&mut j, &[0x2, 0x2]);
+  *ticks.borrow_mut() = 9;
+  commit_rec(&mut j, &[0x3, 0x3]);
+  assert_eq!(0, *syncs.borrow());
+  assert!(j.has_unsynced_commits());
+
+  *ticks.borrow_mut() = 10;
+  commit_rec(&mut j, &[0x4, 0x4]);
+  assert_eq!(1, *syncs.borrow());
+  assert!(!j.has_unsynced_commits());
+}
+
+#[test]
+pub fn group_commit_starts_new_window_after_sync() {
+  let (mut j, ticks, syncs) = group_commit_journal(10);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  *ticks.borrow_mut() = 10;
+  commit_rec(&mut j, &[0x2, 0x2]);
+  assert_eq!(1, *syncs.borrow());
+
+  *ticks.borrow_mut() = 15;
+  commit_rec(&mut j, &[0x3, 0x3]);
+  *ticks.borrow_mut() = 24;
+  commit_rec(&mut j, &[0x4, 0x4]);
+  assert_eq!(1, *syncs.borrow());
+  *ticks.borrow_mut() = 25;
+  commit_rec(&mut j, &[0x5, 0x5]);
+  assert_eq!(2, *syncs.borrow());
+}
+
+#[test]
+pub fn group_commit_with_0_window_syncs_every_commit() {
+  let (mut j, _, syncs) = group_commit_journal(0);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  commit_rec(&mut j, &[0x3, 0x3]);
+  assert_eq!(3, *syncs.borrow());
+}
+
+#[test]
+pub fn discard_does_not_start_window() {
+  let (mut j, _, syncs) = group_commit_journal(10);
+  j.write(&[0x1, 0x1]).unwrap();
+  j.discard().unwrap();
+  assert!(!j.has_unsynced_commits());
+  j.flush_commits().unwrap();
+  assert_eq!(0, *syncs.borrow());
+}
+
+#[test]
+pub fn flush_commits_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.flush_commits().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn flush_commits_syncs_pending_commits_once() {
+  let (mut j, _, syncs) = group_commit_journal(10);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  j.flush_commits().unwrap();
+  assert_eq!(1, *syncs.borrow());
+  assert!(!j.has_unsynced_commits());
+  j.flush_commits().unwrap();
+  assert_eq!(1, *syncs.borrow());
+}
+
+#[test]
+pub fn close_flushes_pending_commits() {
+  let (mut j, _, syncs) = group_commit_journal(10);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  j.close().unwrap();
+  assert_eq!(1, *syncs.borrow());
+  j.open().unwrap();
+  assert_eq!(1, j.record_count());
+}
+
+
+// set_sync_every() tests
+fn sync_every_journal(n: usize) -> (Journal<MemoryBinaryStorage>, Rc<RefCell<u32>>) {
+  let syncs = Rc::new(RefCell::new(0));
+  let c = syncs.clone();
+
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_sync_every(n);
+  j.set_on_sync(Box::new(move || *c.borrow_mut() += 1));
+  j.open().unwrap();
+  (j, syncs)
+}
+
+#[test]
+pub fn sync_every_defaults_to_0() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(0, j.get_sync_every());
+}
+
+#[test]
+pub fn sync_every_syncs_on_every_nth_commit() {
+  let (mut j, syncs) = sync_every_journal(3);
+  assert_eq!(3, j.get_sync_every());
+
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  assert_eq!(0, *syncs.borrow());
+  assert!(j.has_unsynced_commits());
+  commit_rec(&mut j, &[0x3, 0x3]);
+  assert_eq!(1, *syncs.borrow());
+  assert!(!j.has_unsynced_commits());
+
+  commit_rec(&mut j, &[0x4, 0x4]);
+  commit_rec(&mut j, &[0x5, 0x5]);
+  assert_eq!(1, *syncs.borrow());
+  commit_rec(&mut j, &[0x6, 0x6]);
+  assert_eq!(2, *syncs.borrow());
+}
+
+#[test]
+pub fn sync_every_1_syncs_every_commit() {
+  let (mut j, syncs) = sync_every_journal(1);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  assert_eq!(2, *syncs.borrow());
+}
+
+#[test]
+pub fn sync_every_does_not_count_discarded_writes() {
+  let (mut j, syncs) = sync_every_journal(2);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  j.write(&[0x2, 0x2]).unwrap();
+  j.discard().unwrap();
+  assert_eq!(0, *syncs.borrow());
+  commit_rec(&mut j, &[0x3, 0x3]);
+  assert_eq!(1, *syncs.borrow());
+}
+
+#[test]
+pub fn close_syncs_commits_short_of_sync_every() {
+  let (mut j, syncs) = sync_every_journal(3);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  j.close().unwrap();
+  assert_eq!(1, *syncs.borrow());
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+}
+
+#[test]
+pub fn close_does_not_sync_again_after_nth_commit() {
+  let (mut j, syncs) = sync_every_journal(2);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  j.close().unwrap();
+  assert_eq!(1, *syncs.borrow());
+}
+
+#[test]
+pub fn sync_every_0_never_syncs_on_commit() {
+  let (mut j, syncs) = sync_every_journal(0);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  assert!(!j.has_unsynced_commits());
+  j.close().unwrap();
+  assert_eq!(0, *syncs.borrow());
+}
+
+
+// set_fixed_slot_size() and seek_to_record() tests
+fn slotted_journal(slot_size: usize) -> Journal<MemoryBinaryStorage> {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_fixed_slot_size(Some(slot_size)).unwrap();
+  j.open().unwrap();
+  j
+}
+
+#[test]
+pub fn set_fixed_slot_size_returns_err_when_open() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_OPEN,
+    j.set_fixed_slot_size(Some(16)).unwrap_err().description()
+  );
+  assert_eq!(None, j.get_fixed_slot_size());
+}
+
+#[test]
+pub fn set_fixed_slot_size_returns_err_when_slot_cannot_hold_a_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    journal::ERR_SLOT_SIZE_TOO_SMALL,
+    j.set_fixed_slot_size(Some(9)).unwrap_err().description()
+  );
+  assert!(j.set_fixed_slot_size(Some(10)).is_ok());
+  assert_eq!(Some(10), j.get_fixed_slot_size());
+}
+
+#[test]
+pub fn fixed_slot_records_start_at_slot_multiples() {
+  let mut j = slotted_journal(16);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2, 0x2, 0x2, 0x2]);
+  commit_rec(&mut j, &[0x3, 0x3, 0x3]);
+  assert_eq!(48, j.write_offset());
+  assert_eq!(48, j.txn_boundary().unwrap());
+  let recs: Vec<(usize, Vec<u8>)> = j.enumerate_records().collect();
+  assert_eq!(
+    vec!(
+      (0, vec!(0x1, 0x1)), 
+      (16, vec!(0x2, 0x2, 0x2, 0x2, 0x2)), 
+      (32, vec!(0x3, 0x3, 0x3))
+    ),
+    recs
+  );
+}
+
+#[test]
+pub fn fixed_slot_write_returns_err_when_record_too_large() {
+  let mut j = slotted_journal(16);
+  assert_eq!(
+    journal::ERR_RECORD_TOO_LARGE,
+    j.write(&[0x1; 8]).unwrap_err().description()
+  );
+  assert!(!j.is_writing());
+  commit_rec(&mut j, &[0x1; 7]);
+  assert_eq!(16, j.write_offset());
+}
+
+#[test]
+pub fn fixed_slot_record_may_fill_whole_slot() {
+  let mut j = slotted_journal(11);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2]);
+  assert_eq!(22, j.write_offset());
+  assert_eq!(vec!(vec!(0x1, 0x1), vec!(0x2, 0x2)), all_records(&mut j));
+}
+
+#[test]
+pub fn fixed_slot_records_survive_reopen() {
+  let mut j = slotted_journal(16);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2, 0x2]);
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(vec!(0x1, 0x1), vec!(0x2, 0x2, 0x2)), all_records(&mut j));
+  assert_eq!(vec!(0, 16), j.recover_scan().unwrap());
+}
+
+#[test]
+pub fn fixed_slot_update_record_rewrites_within_slot() {
+  let mut j = slotted_journal(16);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2, 0x2, 0x2, 0x2]);
+  commit_rec(&mut j, &[0x3, 0x3]);
+
+  j.update_record(16, &[0x4, 0x4]).unwrap();
+  assert_eq!(
+    vec!(vec!(0x1, 0x1), vec!(0x4, 0x4), vec!(0x3, 0x3)), 
+    all_records(&mut j)
+  );
+
+  j.update_record(0, &[0x5, 0x5, 0x5, 0x5]).unwrap();
+  assert_eq!(
+    vec!(vec!(0x5, 0x5, 0x5, 0x5), vec!(0x4, 0x4), vec!(0x3, 0x3)), 
+    all_records(&mut j)
+  );
+  assert_eq!(48, j.write_offset());
+  j.close().unwrap();
+  j.open().unwrap();
+  assert_eq!(vec!(0, 16, 32), j.recover_scan().unwrap());
+}
+
+#[test]
+pub fn fixed_slot_update_record_returns_err_when_too_large() {
+  let mut j = slotted_journal(16);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  assert_eq!(
+    journal::ERR_RECORD_TOO_LARGE,
+    j.update_record(0, &[0x2; 8]).unwrap_err().description()
+  );
+  assert_eq!(vec!(vec!(0x1, 0x1)), all_records(&mut j));
+}
+
+#[test]
+pub fn seek_to_record_returns_err_when_past_last_record() {
+  let mut j = slotted_journal(16);
+  commit_rec(&mut j, &[0x1, 0x1]);
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.seek_to_record(1).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn seek_to_record_jumps_to_slot() {
+  let mut j = slotted_journal(16);
+  for i in 0..5 {
+    commit_rec(&mut j, &[i, i, i]);
+  }
+  j.seek_to_record(3).unwrap();
+  assert_eq!(48, j.read_offset());
+  assert_eq!(vec!(0x3, 0x3, 0x3), j.read().unwrap());
+  assert_eq!(Some(vec!(0x3, 0x3, 0x3)), j.next());
+  assert_eq!(Some(vec!(0x4, 0x4, 0x4)), j.next());
+  assert_eq!(None, j.next());
+}
+
+#[test]
+pub fn seek_to_record_walks_without_fixed_slots() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  commit_rec(&mut j, &[0x1, 0x1]);
+  commit_rec(&mut j, &[0x2, 0x2, 0x2]);
+  commit_rec(&mut j, &[0x3, 0x3]);
+  j.seek_to_record(2).unwrap();
+  assert_eq!(23, j.read_offset());
+  assert_eq!(vec!(0x3, 0x3), j.read().unwrap());
+}
+
+
+// read_nth() tests
+#[test]
+pub fn read_nth_returns_err_when_closed() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    j.read_nth(0).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_nth_returns_err_when_past_last_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.read_nth(0).unwrap_err().description()
+  );
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.read_nth(1).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_nth_reads_records_out_of_order_after_reopen() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  let written: Vec<Vec<u8>> = (0..10).map(|i| vec![i as u8; (i * 7) % 11 + 2]).collect();
+  for r in written.iter() {
+    j.write(r.as_slice()).unwrap();
+    j.commit().unwrap();
+  }
+  j.close().unwrap();
+
+  j.open().unwrap();
+  for &n in [7, 2, 9, 0, 4, 4, 1, 8, 3, 6, 5].iter() {
+    assert_eq!(written[n], j.read_nth(n).unwrap());
+  }
+  assert_eq!(
+    journal::ERR_NO_COMMITTED_RECORD,
+    j.read_nth(10).unwrap_err().description()
+  );
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn read_nth_reads_records_committed_since_open() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write(&[0x1, 0x2, 0x3]).unwrap();
+  j.commit().unwrap();
+  j.write_batch(&[&[0x4, 0x5], &[0x6, 0x7, 0x8, 0x9]]).unwrap();
+  assert_eq!(vec!(0x6, 0x7, 0x8, 0x9), j.read_nth(2).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), j.read_nth(0).unwrap());
+  assert_eq!(vec!(0x4, 0x5), j.read_nth(1).unwrap());
+}
+
+#[test]
+pub fn read_nth_leaves_read_offset_at_record() {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.open().unwrap();
+  j.write_batch(&[&[0x1, 0x2, 0x3], &[0x4, 0x5], &[0x6, 0x7]]).unwrap();
+  j.read_nth(1).unwrap();
+  assert_eq!(12, j.read_offset());
+  assert_eq!(Some(vec!(0x4, 0x5)), j.next());
+  assert_eq!(Some(vec!(0x6, 0x7)), j.next());
+}
+
+#[test]
+pub fn read_nth_follows_records_moved_by_compact_expired() {
+  let mut j = expiring_journal();
+  j.compact_expired(250, new_storage(256, 256)).unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(0x3, 0x3), j.read_nth(1).unwrap());
+  assert_eq!(vec!(0x2, 0x2), j.read_nth(0).unwrap());
+}
+
+#[test]
+pub fn read_nth_skips_corrupt_record_dropped_by_verify() {
+  let calls = Rc::new(RefCell::new(Vec::new()));
+  let mut j = journal_with_corruption_action(CorruptionAction::Skip, calls);
+  j.open().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(0x7, 0x8, 0x9), j.read_nth(1).unwrap());
+  assert_eq!(24, j.read_offset());
+}
+
+
+// set_compress_min_size() tests
+fn compressing_journal(min_size: usize) -> Journal<MemoryBinaryStorage> {
+  let mut j = Journal::new(new_storage(256, 256));
+  j.set_compress_min_size(Some(min_size));
+  j.open().unwrap();
+  j
+}
+
+#[test]
+pub fn compress_min_size_defaults_to_none() {
+  let mut j = Journal::new(new_storage(256, 256));
+  assert_eq!(None, j.get_compress_min_size());
+  j.set_compress_min_size(Some(16));
+  assert_eq!(Some(16), j.get_compress_min_size());
+}
+
+#[test]
+pub fn record_below_compress_min_size_stored_raw() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x7; 8]);
+  assert_eq!(journal::PRE_DATA_LEN + 8 + journal::post_data_len(ChecksumAlgo::Xor), j.write_offset());
+  assert_eq!(vec!(vec![0x7; 8]), all_records(&mut j));
+}
+
+#[test]
+pub fn record_at_compress_min_size_stored_compressed() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x7; 100]);
+  // 100 identical bytes encode to a single two byte run
+  assert_eq!(journal::PRE_DATA_LEN + 2 + journal::post_data_len(ChecksumAlgo::Xor), j.write_offset());
+  assert_eq!(vec!(vec![0x7; 100]), all_records(&mut j));
+}
+
+#[test]
+pub fn incompressible_record_stored_raw() {
+  let mut j = compressing_journal(4);
+  let data: Vec<u8> = (0..20).collect();
+  commit_rec(&mut j, data.as_slice());
+  assert_eq!(journal::PRE_DATA_LEN + 20 + journal::post_data_len(ChecksumAlgo::Xor), j.write_offset());
+  assert_eq!(vec!(data), all_records(&mut j));
+}
+
+#[test]
+pub fn raw_and_compressed_records_read_back_after_reopen() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x1, 0x2, 0x3]);
+  commit_rec(&mut j, &[0x4; 64]);
+  commit_rec(&mut j, &[0x5, 0x6]);
+  j.close().unwrap();
+
+  // Each record says whether it is compressed, so the setting doesn't matter
+  j.set_compress_min_size(None);
+  j.open().unwrap();
+  assert_eq!(3, j.record_count());
+  assert_eq!(
+    vec!(vec!(0x1, 0x2, 0x3), vec![0x4; 64], vec!(0x5, 0x6)),
+    all_records(&mut j)
+  );
+}
+
+#[test]
+pub fn on_commit_sees_uncompressed_data() {
+  let mut j = compressing_journal(16);
+  let seen = Rc::new(RefCell::new(Vec::new()));
+  let s = seen.clone();
+  j.set_on_commit(Box::new(move |_, data| s.borrow_mut().push(data.to_vec())));
+  commit_rec(&mut j, &[0x4; 64]);
+  assert_eq!(vec!(vec![0x4; 64]), *seen.borrow());
+}
+
+#[test]
+pub fn update_record_rewrites_compressed_record() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x4; 64]);
+  j.update_record(0, &[0x5; 64]).unwrap();
+  assert_eq!(vec!(vec![0x5; 64]), all_records(&mut j));
+  assert_eq!(
+    journal::ERR_RECORD_LENGTH_MISMATCH,
+    j.update_record(0, &[0x5; 300]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn update_record_returns_err_when_compressed_data_length_differs() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x4; 64]);
+  assert_eq!(
+    journal::ERR_RECORD_LENGTH_MISMATCH,
+    j.update_record(0, &[0x5; 100]).unwrap_err().description()
+  );
+  assert_eq!(vec!(vec![0x4; 64]), all_records(&mut j));
+}
+
+#[test]
+pub fn update_record_returns_err_when_data_compresses_differently() {
+  let mut j = compressing_journal(16);
+  commit_rec(&mut j, &[0x4; 64]);
+  let data: Vec<u8> = (0..64).collect();
+  assert_eq!(
+    journal::ERR_ENCODED_LENGTH_MISMATCH,
+    j.update_record(0, data.as_slice()).unwrap_err().description()
+  );
+  assert_eq!(vec!(vec![0x4; 64]), all_records(&mut j));
+}
+
+// record_slices() tests
+fn file_journal(path: String) -> Journal<FileBinaryStorage> {
+  Journal::new(TransactionalStorage::new(
+    FileBinaryStorage::new(path, true, 256, 16, 16, 256).unwrap()
+  ))
+}
+
+fn file_bytes(path: &str) -> Vec<u8> {
+  let mut buf = Vec::new();
+  fs::File::open(path).unwrap().read_to_end(&mut buf).unwrap();
+  buf
+}
+
+#[test]
+pub fn record_slices_borrows_records_from_journal_file() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  let written: Vec<Vec<u8>> = (0..20).map(|i| vec![i as u8; (i % 5) + 2]).collect();
+  for r in written.iter() {
+    j.write(r.as_slice()).unwrap();
+    j.commit().unwrap();
+  }
+  j.close().unwrap();
+
+  let bytes = file_bytes(&path);
+  let start = bytes.as_ptr() as usize;
+  let end = start + bytes.len();
+  let mut count = 0;
+  for (rec, expected) in j.record_slices(bytes.as_slice(), Endianness::Little).zip(written.iter()) {
+    match rec {
+      Cow::Borrowed(b) => {
+        assert_eq!(expected.as_slice(), b);
+        let p = b.as_ptr() as usize;
+        assert!(p >= start && p + b.len() <= end);
+      },
+      Cow::Owned(_) => panic!("expected a borrowed record")
+    }
+    count += 1;
+  }
+  assert_eq!(20, count);
+  assert_eq!(20, j.record_slices(bytes.as_slice(), Endianness::Little).count());
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn one_byte_record_survives_reopen_and_verify() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  j.write(&[0x7]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x8, 0x9]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+
+  j.open().unwrap();
+  j.verify().unwrap();
+  assert_eq!(2, j.record_count());
+  assert_eq!(vec!(vec!(0x7), vec!(0x8, 0x9)), j.by_ref().collect::<Vec<Vec<u8>>>());
+  j.close().unwrap();
+
+  let bytes = file_bytes(&path);
+  let recs: Vec<Vec<u8>> =
+    j.record_slices(bytes.as_slice(), Endianness::Little).map(|r| r.into_owned()).collect();
+  assert_eq!(vec!(vec!(0x7), vec!(0x8, 0x9)), recs);
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_stops_at_uncommitted_record() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  let bytes = file_bytes(&path);
+  let mut slices = j.record_slices(bytes.as_slice(), Endianness::Little);
+  assert_eq!(Some(Cow::Borrowed(&[0x1, 0x2][..])), slices.next());
+  assert_eq!(11, slices.offset());
+  assert_eq!(None, slices.next());
+  j.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_stops_at_checksum_mismatch() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.open().unwrap();
+  for i in 0..3 {
+    j.write(&[i, i + 1]).unwrap();
+    j.commit().unwrap();
+  }
+  j.close().unwrap();
+  let mut bytes = file_bytes(&path);
+  bytes[11 + journal::PRE_DATA_LEN] = 0xff;
+  assert_eq!(
+    vec!(vec!(0x0, 0x1)),
+    j.record_slices(bytes.as_slice(), Endianness::Little).map(|r| r.into_owned()).collect::<Vec<Vec<u8>>>()
+  );
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_decodes_compressed_and_expiring_records() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.set_compress_min_size(Some(8));
+  j.open().unwrap();
+  j.write(vec![0x7; 40].as_slice()).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x1, 0x2], 100).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x3, 0x4], 300).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+
+  let bytes = file_bytes(&path);
+  let recs: Vec<Cow<[u8]>> = j.record_slices(bytes.as_slice(), Endianness::Little).collect();
+  assert_eq!(3, recs.len());
+  match recs[0] {
+    Cow::Owned(ref v) => assert_eq!(&vec![0x7; 40], v),
+    Cow::Borrowed(_) => panic!("expected a decoded record")
+  };
+  assert_eq!(Cow::Borrowed(&[0x1, 0x2][..]), recs[1]);
+  assert_eq!(Cow::Borrowed(&[0x3, 0x4][..]), recs[2]);
+
+  j.set_expiry_now(Some(200));
+  assert_eq!(
+    vec!(vec![0x7; 40], vec!(0x3, 0x4)),
+    j.record_slices(bytes.as_slice(), Endianness::Little).map(|r| r.into_owned()).collect::<Vec<Vec<u8>>>()
+  );
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_reads_big_endian_journal_file() {
+  let path = rnd_path();
+  let mut s = FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap();
+  s.set_endianness(Endianness::Big);
+  let mut j = Journal::new(TransactionalStorage::new(s));
+  j.open().unwrap();
+  j.write(&[0x1, 0x2, 0x3]).unwrap();
+  j.commit().unwrap();
+  j.set_checksum_algo(ChecksumAlgo::Crc32);
+  j.write(&[0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x6, 0x7], 100).unwrap();
+  j.commit().unwrap();
+  j.write_expiring(&[0x8, 0x9], 300).unwrap();
+  j.commit().unwrap();
+  j.set_expiry_now(Some(200));
+  j.reset();
+  let expected = vec!(vec!(0x1, 0x2, 0x3), vec!(0x4, 0x5), vec!(0x8, 0x9));
+  assert_eq!(expected, j.by_ref().collect::<Vec<Vec<u8>>>());
+  j.close().unwrap();
+
+  let bytes = file_bytes(&path);
+  assert_eq!(
+    expected,
+    j.record_slices(bytes.as_slice(), Endianness::Big).map(|r| r.into_owned()).collect::<Vec<Vec<u8>>>()
+  );
+  assert_eq!(0, j.record_slices(bytes.as_slice(), Endianness::Little).count());
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_skips_slot_padding() {
+  let path = rnd_path();
+  let mut j = file_journal(path.clone());
+  j.set_fixed_slot_size(Some(16)).unwrap();
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4, 0x5]).unwrap();
+  j.commit().unwrap();
+  j.close().unwrap();
+
+  let bytes = file_bytes(&path);
+  let mut slices = j.record_slices(bytes.as_slice(), Endianness::Little);
+  assert_eq!(Some(Cow::Borrowed(&[0x1, 0x2][..])), slices.next());
+  assert_eq!(16, slices.offset());
+  assert_eq!(Some(Cow::Borrowed(&[0x3, 0x4, 0x5][..])), slices.next());
+  assert_eq!(None, slices.next());
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn record_slices_returns_nothing_for_empty_bytes() {
+  let j = Journal::new(new_storage(256, 256));
+  assert_eq!(0, j.record_slices(&[], Endianness::Little).count());
+  assert_eq!(0, j.record_slices(&[0x0; 64], Endianness::Little).count());
 }
@@ -0,0 +1,90 @@
+use std::error::Error;
+use storage::lock_table;
+use storage::lock_table::LockTable;
+
+
+// lock_range() tests
+#[test]
+fn lock_range_returns_ok_when_nothing_locked() {
+  let mut t = LockTable::new();
+  assert!(t.lock_range(0, 16).is_ok());
+  assert_eq!(1, t.get_num_locks());
+}
+
+#[test]
+fn lock_range_returns_err_when_len_is_0() {
+  let mut t = LockTable::new();
+  assert_eq!(
+    lock_table::ERR_LOCK_NOTHING,
+    t.lock_range(0, 0).unwrap_err().description()
+  );
+}
+
+#[test]
+fn lock_range_returns_ok_for_non_overlapping_ranges() {
+  let mut t = LockTable::new();
+  t.lock_range(0, 16).unwrap();
+  assert!(t.lock_range(16, 16).is_ok());
+  assert!(t.lock_range(48, 16).is_ok());
+  assert_eq!(3, t.get_num_locks());
+}
+
+#[test]
+fn lock_range_returns_err_for_overlapping_ranges() {
+  let mut t = LockTable::new();
+  t.lock_range(16, 16).unwrap();
+  assert_eq!(
+    lock_table::ERR_RANGE_LOCKED,
+    t.lock_range(31, 4).unwrap_err().description()
+  );
+  assert_eq!(
+    lock_table::ERR_RANGE_LOCKED,
+    t.lock_range(8, 9).unwrap_err().description()
+  );
+  assert_eq!(
+    lock_table::ERR_RANGE_LOCKED,
+    t.lock_range(20, 2).unwrap_err().description()
+  );
+  assert_eq!(
+    lock_table::ERR_RANGE_LOCKED,
+    t.lock_range(0, 64).unwrap_err().description()
+  );
+  assert_eq!(1, t.get_num_locks());
+}
+
+#[test]
+fn lock_range_returns_err_on_overflow() {
+  let mut t = LockTable::new();
+  assert!(t.lock_range(usize::max_value(), 2).is_err());
+}
+
+// unlock_range() tests
+#[test]
+fn unlock_range_returns_err_when_not_locked() {
+  let mut t = LockTable::new();
+  t.lock_range(0, 16).unwrap();
+  assert_eq!(
+    lock_table::ERR_RANGE_NOT_LOCKED,
+    t.unlock_range(0, 8).unwrap_err().description()
+  );
+}
+
+#[test]
+fn unlock_range_allows_range_to_be_locked_again() {
+  let mut t = LockTable::new();
+  t.lock_range(0, 16).unwrap();
+  t.unlock_range(0, 16).unwrap();
+  assert_eq!(0, t.get_num_locks());
+  assert!(t.lock_range(4, 4).is_ok());
+}
+
+// is_locked() tests
+#[test]
+fn is_locked_detects_overlapping_ranges() {
+  let mut t = LockTable::new();
+  t.lock_range(16, 16).unwrap();
+  assert!(t.is_locked(0, 17));
+  assert!(t.is_locked(31, 1));
+  assert!(!t.is_locked(0, 16));
+  assert!(!t.is_locked(32, 16));
+}
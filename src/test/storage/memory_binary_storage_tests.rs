@@ -1,9 +1,11 @@
 use std::str;
+use std::time::{ Duration, Instant };
 use std::error::Error;
 
 use test::storage::binary_storage_tests;
 use storage::binary_storage;
-use storage::binary_storage::BinaryStorage;
+use storage::binary_storage::{ BinaryStorage, WorkloadHint };
+use storage::memory_binary_storage;
 use storage::memory_binary_storage::MemoryBinaryStorage;
 
 // open(), close(), and is_open() tests 
@@ -70,6 +72,31 @@ fn new_sets_expand_size() {
   assert_eq!(512, s.get_expand_size());
 }
 
+#[test]
+fn with_hint_uses_default_expand_size_for_each_hint() {
+  let s = MemoryBinaryStorage::with_hint(256, WorkloadHint::Append, None).unwrap();
+  assert_eq!(65536, s.get_expand_size());
+  let s = MemoryBinaryStorage::with_hint(256, WorkloadHint::RandomWrite, None).unwrap();
+  assert_eq!(4096, s.get_expand_size());
+  let s = MemoryBinaryStorage::with_hint(256, WorkloadHint::ReadMostly, None).unwrap();
+  assert_eq!(4096, s.get_expand_size());
+}
+
+#[test]
+fn with_hint_prefers_explicit_expand_size() {
+  let s = MemoryBinaryStorage::with_hint(256, WorkloadHint::Append, Some(512)).unwrap();
+  assert_eq!(512, s.get_expand_size());
+}
+
+#[test]
+fn with_hint_checks_explicit_expand_size() {
+  let s = MemoryBinaryStorage::with_hint(256, WorkloadHint::Append, Some(0));
+  assert_eq!(
+    binary_storage::ERR_EXPAND_SIZE_TOO_SMALL,
+    s.unwrap_err().description()
+  );
+}
+
 #[test]
 fn new_requires_initial_capacity_greater_than_0() {
   let s = MemoryBinaryStorage::new(0, 512);
@@ -375,6 +402,36 @@ fn w_u64_over_capacity_expands_storage() {
   );
 }
 
+// w_i128() tests
+#[test]
+fn w_i128_returns_err_when_closed() {
+  binary_storage_tests::w_i128_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn w_i128_over_capacity_expands_storage() {
+  binary_storage_tests::w_i128_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+// w_u128() tests
+#[test]
+fn w_u128_returns_err_when_closed() {
+  binary_storage_tests::w_u128_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn w_u128_over_capacity_expands_storage() {
+  binary_storage_tests::w_u128_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
 // w_f32() tests
 #[test]
 fn w_f32_returns_err_when_closed() {
@@ -878,6 +935,64 @@ fn r_u64_result_is_not_mutated_on_subsequent_write() {
   );
 }
 
+// r_i128() tests
+#[test]
+fn r_i128_returns_err_when_closed() {
+  binary_storage_tests::r_i128_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_i128_reads_written_data() {
+  binary_storage_tests::r_i128_reads_written_data(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_i128_does_not_read_past_capacity() {
+  binary_storage_tests::r_i128_does_not_read_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_i128_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i128_result_is_not_mutated_on_subsequent_write(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+// r_u128() tests
+#[test]
+fn r_u128_returns_err_when_closed() {
+  binary_storage_tests::r_u128_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_u128_reads_written_data() {
+  binary_storage_tests::r_u128_reads_written_data(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_u128_does_not_read_past_capacity() {
+  binary_storage_tests::r_u128_does_not_read_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_u128_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u128_result_is_not_mutated_on_subsequent_write(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
 // r_f32() tests
 #[test]
 fn r_f32_returns_err_when_closed() {
@@ -1050,6 +1165,49 @@ fn r_bytes_result_is_not_mutated_on_subsequent_write() {
   );
 }
 
+// r_bytes_into() tests
+#[test]
+fn r_bytes_into_returns_err_when_closed() {
+  binary_storage_tests::r_bytes_into_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_bytes_into_fills_buffer_with_written_data() {
+  binary_storage_tests::r_bytes_into_fills_buffer_with_written_data(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_bytes_into_reads_part_of_written_data() {
+  binary_storage_tests::r_bytes_into_reads_part_of_written_data(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_bytes_into_matches_r_bytes() {
+  binary_storage_tests::r_bytes_into_matches_r_bytes(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_bytes_into_with_empty_buffer_reads_nothing() {
+  binary_storage_tests::r_bytes_into_with_empty_buffer_reads_nothing(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn r_bytes_into_does_not_read_past_capacity() {
+  binary_storage_tests::r_bytes_into_does_not_read_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
 // r_str() tests
 #[test]
 fn r_str_returns_err_when_closed() {
@@ -1235,6 +1393,92 @@ fn is_filled_goes_to_end_when_end_offset_is_none() {
   );
 }
 
+// crc32() tests
+#[test]
+fn crc32_returns_err_when_closed() {
+  binary_storage_tests::crc32_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn crc32_returns_err_when_range_is_bad() {
+  binary_storage_tests::crc32_returns_err_when_range_is_bad(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn crc32_matches_known_pattern() {
+  binary_storage_tests::crc32_matches_known_pattern(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn crc32_changes_after_single_byte_mutation() {
+  binary_storage_tests::crc32_changes_after_single_byte_mutation(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+// find_bytes() tests
+#[test]
+fn find_bytes_returns_err_when_closed() {
+  binary_storage_tests::find_bytes_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_start_past_capacity() {
+  binary_storage_tests::find_bytes_returns_err_when_start_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_pattern_empty() {
+  binary_storage_tests::find_bytes_returns_err_when_pattern_empty(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_start() {
+  binary_storage_tests::find_bytes_finds_pattern_at_start(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_finds_first_occurrence_after_start() {
+  binary_storage_tests::find_bytes_finds_first_occurrence_after_start(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_across_page_boundary() {
+  binary_storage_tests::find_bytes_finds_pattern_across_page_boundary(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_end_of_capacity() {
+  binary_storage_tests::find_bytes_finds_pattern_at_end_of_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn find_bytes_returns_none_when_pattern_absent() {
+  binary_storage_tests::find_bytes_returns_none_when_pattern_absent(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
 // get_expand_size() and set_expand_size() tests
 #[test]
 fn get_expand_size_returns_initial_expand_size() {
@@ -1292,6 +1536,13 @@ fn capacity_increases_to_increments_of_last_set_expand_size() {
   );
 }
 
+#[test]
+fn capacity_increases_to_increments_of_larger_expand_size() {
+  binary_storage_tests::capacity_increases_to_increments_of_larger_expand_size(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
 // get_capacity() tests
 #[test]
 fn get_capacity_returns_err_when_closed() {
@@ -1391,3 +1642,809 @@ fn expand_changes_capacity_by_multiples_of_expand_size_when_successful() {
     MemoryBinaryStorage::new(256, 512).unwrap()
   );
 }
+
+// read_all() tests
+#[test]
+fn read_all_returns_err_when_closed() {
+  binary_storage_tests::read_all_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn read_all_returns_capacity_length_bytes() {
+  binary_storage_tests::read_all_returns_capacity_length_bytes(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn read_all_returns_written_data_and_zero_fill() {
+  binary_storage_tests::read_all_returns_written_data_and_zero_fill(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// pread() and pwrite() tests
+#[test]
+fn pread_returns_err_when_closed() {
+  binary_storage_tests::pread_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn pwrite_returns_err_when_closed() {
+  binary_storage_tests::pwrite_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn pread_returns_err_when_past_capacity() {
+  binary_storage_tests::pread_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn pwrite_over_capacity_expands_storage() {
+  binary_storage_tests::pwrite_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn pread_and_pwrite_are_positional() {
+  binary_storage_tests::pread_and_pwrite_are_positional(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// valid_len_from() tests
+#[test]
+fn valid_len_from_returns_err_when_closed() {
+  binary_storage_tests::valid_len_from_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn valid_len_from_returns_err_when_past_capacity() {
+  binary_storage_tests::valid_len_from_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn valid_len_from_returns_0_when_zero_filled() {
+  binary_storage_tests::valid_len_from_returns_0_when_zero_filled(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn valid_len_from_returns_written_extent() {
+  binary_storage_tests::valid_len_from_returns_written_extent(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// copy() tests
+#[test]
+fn copy_returns_err_when_closed() {
+  binary_storage_tests::copy_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_returns_err_when_source_past_capacity() {
+  binary_storage_tests::copy_returns_err_when_source_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_copies_separate_range() {
+  binary_storage_tests::copy_copies_separate_range(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_copies_forward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_forward_over_overlapping_range(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_copies_backward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_backward_over_overlapping_range(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_expands_to_hold_destination() {
+  binary_storage_tests::copy_expands_to_hold_destination(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn copy_of_nothing_changes_nothing() {
+  binary_storage_tests::copy_of_nothing_changes_nothing(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+// swap_ranges() tests
+#[test]
+fn swap_ranges_returns_err_when_closed() {
+  binary_storage_tests::swap_ranges_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_ranges_overlap() {
+  binary_storage_tests::swap_ranges_returns_err_when_ranges_overlap(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_past_capacity() {
+  binary_storage_tests::swap_ranges_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn swap_ranges_exchanges_contents() {
+  binary_storage_tests::swap_ranges_exchanges_contents(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn swap_ranges_exchanges_adjacent_ranges() {
+  binary_storage_tests::swap_ranges_exchanges_adjacent_ranges(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// set_alloc_retries() tests
+#[test]
+fn alloc_retries_default_to_0() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  assert_eq!(0, s.get_alloc_retries());
+}
+
+#[test]
+fn set_alloc_retries_sets_retry_count() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.set_alloc_retries(3, Duration::from_millis(1));
+  assert_eq!(3, s.get_alloc_retries());
+}
+
+#[test]
+fn expand_succeeds_with_alloc_retries_set() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.set_alloc_retries(3, Duration::from_millis(1));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.expand(1024).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert_eq!(0x1, s.r_u8(0).unwrap());
+  assert!(s.is_filled(Some(256), None, 0x0).unwrap());
+}
+
+#[test]
+fn expand_returns_err_when_every_alloc_retry_fails() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.set_alloc_retries(3, Duration::from_millis(5));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  let start = Instant::now();
+  assert_eq!(
+    binary_storage::ERR_STORAGE_ALLOC,
+    s.expand(usize::max_value() - 1024).unwrap_err().description()
+  );
+  // Each of the 3 retries waits before trying again
+  assert!(start.elapsed() >= Duration::from_millis(15));
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert_eq!(0x1, s.r_u8(0).unwrap());
+}
+
+#[test]
+fn expand_succeeds_when_alloc_retry_after_failures_succeeds() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.set_alloc_retries(3, Duration::from_millis(1));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.w_u8(255, 0x2).unwrap();
+  s.fail_next_allocs(3);
+  s.expand(1024).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert_eq!(0x1, s.r_u8(0).unwrap());
+  assert_eq!(0x2, s.r_u8(255).unwrap());
+  assert!(s.is_filled(Some(256), None, 0x0).unwrap());
+}
+
+#[test]
+fn expand_returns_err_when_failures_outlast_alloc_retries() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.set_alloc_retries(2, Duration::from_millis(1));
+  s.open().unwrap();
+  s.w_u8(0, 0x1).unwrap();
+  s.fail_next_allocs(3);
+  assert_eq!(
+    binary_storage::ERR_STORAGE_ALLOC,
+    s.expand(1024).unwrap_err().description()
+  );
+  assert_eq!(256, s.get_capacity().unwrap());
+  assert_eq!(0x1, s.r_u8(0).unwrap());
+  // The failures are used up, so the next expand goes through
+  s.expand(1024).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+}
+
+// snapshot_reader() tests
+#[test]
+fn snapshot_reader_returns_err_when_closed() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.snapshot_reader().unwrap_err().description()
+  );
+}
+
+#[test]
+fn snapshot_reader_copies_current_contents() {
+  let mut s = MemoryBinaryStorage::new(256, 4).unwrap();
+  s.open().unwrap();
+  s.w_bytes(254, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  let snap = s.snapshot_reader().unwrap();
+  assert!(snap.is_open());
+  assert_eq!(260, snap.get_capacity().unwrap());
+  assert_eq!(s.read_all().unwrap(), snap.read_all().unwrap());
+}
+
+#[test]
+fn snapshot_reader_does_not_see_later_writes() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  let snap = s.snapshot_reader().unwrap();
+  s.w_bytes(10, &[0x7, 0x8, 0x9]).unwrap();
+  s.w_u8(300, 0x5).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), snap.r_bytes(10, 3).unwrap());
+  assert_eq!(256, snap.get_capacity().unwrap());
+  assert_eq!(vec!(0x7, 0x8, 0x9), s.r_bytes(10, 3).unwrap());
+}
+
+#[test]
+fn snapshot_reader_writes_do_not_affect_original() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let mut snap = s.snapshot_reader().unwrap();
+  snap.w_u8(0, 0x1).unwrap();
+  assert_eq!(0x0, s.r_u8(0).unwrap());
+}
+
+// w_u32_array() and w_u64_array() tests
+#[test]
+fn w_u32_array_returns_err_when_closed() {
+  binary_storage_tests::w_u32_array_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_u32_array_writes_values_read_back_one_at_a_time() {
+  binary_storage_tests::w_u32_array_writes_values_read_back_one_at_a_time(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_u32_array_over_capacity_expands_storage() {
+  binary_storage_tests::w_u32_array_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_u32_array_writes_nothing_for_empty_slice() {
+  binary_storage_tests::w_u32_array_writes_nothing_for_empty_slice(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_u64_array_writes_values_read_back_one_at_a_time() {
+  binary_storage_tests::w_u64_array_writes_values_read_back_one_at_a_time(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_u32_array_reallocates_once_for_over_capacity_write() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let values = vec![0x7; 200];
+  s.w_u32_array(0, values.as_slice()).unwrap();
+  assert_eq!(1024, s.get_capacity().unwrap());
+  assert_eq!(1, s.generation());
+}
+
+// r_u32_array() and r_u64_array() tests
+#[test]
+fn r_u32_array_returns_err_when_closed() {
+  binary_storage_tests::r_u32_array_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u32_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u32_array_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u32_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u32_array_reads_values_written_one_at_a_time(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u32_array_returns_empty_for_0_count() {
+  binary_storage_tests::r_u32_array_returns_empty_for_0_count(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_array_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u64_array_reads_values_written_one_at_a_time(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// r_u64_opt() tests
+#[test]
+fn r_u64_opt_returns_err_when_closed() {
+  binary_storage_tests::r_u64_opt_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_opt_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_none_when_unwritten() {
+  binary_storage_tests::r_u64_opt_returns_none_when_unwritten(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_written() {
+  binary_storage_tests::r_u64_opt_returns_some_when_written(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_writing_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_writing_zero(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_filling_with_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_filling_with_zero(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_one_byte_written() {
+  binary_storage_tests::r_u64_opt_returns_some_when_one_byte_written(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// w_uuid() and r_uuid() tests
+#[test]
+fn w_uuid_returns_err_when_closed() {
+  binary_storage_tests::w_uuid_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_closed() {
+  binary_storage_tests::r_uuid_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_past_capacity() {
+  binary_storage_tests::r_uuid_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_uuid_and_r_uuid_round_trip() {
+  binary_storage_tests::w_uuid_and_r_uuid_round_trip(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_uuid_writes_raw_bytes() {
+  binary_storage_tests::w_uuid_writes_raw_bytes(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_uuid_over_capacity_expands_storage() {
+  binary_storage_tests::w_uuid_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_uuid_returns_err_when_end_overflows() {
+  binary_storage_tests::w_uuid_returns_err_when_end_overflows(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// shrink() tests
+#[test]
+fn shrink_returns_err_when_closed() {
+  binary_storage_tests::shrink_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn shrink_does_nothing_when_capacity_already_small() {
+  binary_storage_tests::shrink_does_nothing_when_capacity_already_small(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn shrink_reduces_capacity_to_multiple_of_expand_size() {
+  binary_storage_tests::shrink_reduces_capacity_to_multiple_of_expand_size(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn shrink_does_not_reduce_capacity_below_expand_size() {
+  binary_storage_tests::shrink_does_not_reduce_capacity_below_expand_size(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn shrink_keeps_data_below_new_capacity() {
+  binary_storage_tests::shrink_keeps_data_below_new_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn shrink_then_expand_reads_zeros_past_old_capacity() {
+  binary_storage_tests::shrink_then_expand_reads_zeros_past_old_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// truncate() tests
+#[test]
+fn truncate_returns_err_when_closed() {
+  binary_storage_tests::truncate_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn truncate_returns_err_when_past_capacity() {
+  binary_storage_tests::truncate_returns_err_when_past_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn truncate_reduces_capacity_to_multiple_of_expand_size() {
+  binary_storage_tests::truncate_reduces_capacity_to_multiple_of_expand_size(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+#[test]
+fn truncate_drops_data_past_new_capacity() {
+  binary_storage_tests::truncate_drops_data_past_new_capacity(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  );
+}
+
+// w_bytes_reporting() tests
+#[test]
+fn w_bytes_reporting_returns_err_when_closed() {
+  binary_storage_tests::w_bytes_reporting_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_within_capacity() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_within_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_bytes_reporting_reports_expansion_past_capacity() {
+  binary_storage_tests::w_bytes_reporting_reports_expansion_past_capacity(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_after_expanding() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_after_expanding(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// Signed round-trip tests
+#[test]
+fn w_i8_r_i8_round_trip_signed_range() {
+  binary_storage_tests::w_i8_r_i8_round_trip_signed_range(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_i16_r_i16_round_trip_signed_range() {
+  binary_storage_tests::w_i16_r_i16_round_trip_signed_range(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_i32_r_i32_round_trip_signed_range() {
+  binary_storage_tests::w_i32_r_i32_round_trip_signed_range(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_i64_r_i64_round_trip_signed_range() {
+  binary_storage_tests::w_i64_r_i64_round_trip_signed_range(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// sync() tests
+#[test]
+fn sync_returns_err_when_closed() {
+  binary_storage_tests::sync_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn sync_keeps_written_data() {
+  binary_storage_tests::sync_keeps_written_data(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+// r_slice(), generation(), and assert_generation() tests
+#[test]
+fn r_slice_returns_err_when_closed() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.r_slice(0, 4).unwrap_err().description()
+  );
+}
+
+#[test]
+fn r_slice_returns_err_when_past_end() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_READ_PAST_END,
+    s.r_slice(250, 7).unwrap_err().description()
+  );
+  assert_eq!(6, s.r_slice(250, 6).unwrap().len());
+}
+
+#[test]
+fn r_slice_reads_stored_bytes() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(&[0x1, 0x2, 0x3], s.r_slice(10, 3).unwrap());
+}
+
+#[test]
+fn generation_starts_at_0() {
+  let s = MemoryBinaryStorage::new(256, 256).unwrap();
+  assert_eq!(0, s.generation());
+}
+
+#[test]
+fn generation_unchanged_by_writes_within_capacity() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1; 256]).unwrap();
+  s.expand(100).unwrap();
+  assert_eq!(0, s.generation());
+}
+
+#[test]
+fn generation_increments_on_expand_and_shrink() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.expand(1000).unwrap();
+  assert_eq!(1, s.generation());
+  s.w_u8(2000, 0x1).unwrap();
+  assert_eq!(2, s.generation());
+  s.shrink(256).unwrap();
+  assert_eq!(3, s.generation());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn assert_generation_returns_ok_when_not_reallocated() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  let gen = {
+    let slice = s.r_slice(0, 4).unwrap();
+    assert_eq!(4, slice.len());
+    s.generation()
+  };
+  s.w_u8(0, 0x1).unwrap();
+  assert!(s.assert_generation(gen).is_ok());
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn assert_generation_returns_err_after_reallocating_expand() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  let gen = {
+    let slice = s.r_slice(0, 4).unwrap();
+    assert_eq!(&[0x1, 0x2, 0x3, 0x4], slice);
+    s.generation()
+  };
+  s.expand(1 << 20).unwrap();
+  assert_eq!(
+    memory_binary_storage::ERR_STALE_GENERATION,
+    s.assert_generation(gen).unwrap_err().description()
+  );
+  assert_eq!(&[0x1, 0x2, 0x3, 0x4], s.r_slice(0, 4).unwrap());
+  assert!(s.assert_generation(s.generation()).is_ok());
+}
+
+// w_blob() and r_blob() tests
+#[test]
+fn w_blob_returns_err_when_closed() {
+  binary_storage_tests::w_blob_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_closed() {
+  binary_storage_tests::r_blob_returns_err_when_closed(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_blob_writes_length_prefix() {
+  binary_storage_tests::w_blob_writes_length_prefix(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_blob_r_blob_round_trip_varying_sizes() {
+  binary_storage_tests::w_blob_r_blob_round_trip_varying_sizes(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn w_blob_over_capacity_expands_storage() {
+  binary_storage_tests::w_blob_over_capacity_expands_storage(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_length_runs_past_end() {
+  binary_storage_tests::r_blob_returns_err_when_length_runs_past_end(
+    MemoryBinaryStorage::new(256, 512).unwrap()
+  );
+}
+
+
+// prefault() tests
+#[test]
+fn prefault_returns_err_when_closed() {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    s.prefault().unwrap_err().description()
+  );
+}
+
+#[test]
+fn prefault_leaves_empty_storage_zeroed() {
+  let mut s = MemoryBinaryStorage::new(16384, 16384).unwrap();
+  s.open().unwrap();
+  s.prefault().unwrap();
+  assert!(s.is_filled(None, None, 0x0).unwrap());
+  assert_eq!(16384, s.get_capacity().unwrap());
+}
+
+#[test]
+fn prefault_keeps_written_data() {
+  let mut s = MemoryBinaryStorage::new(8192, 8192).unwrap();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2]).unwrap();
+  s.w_bytes(4095, &[0x3, 0x4]).unwrap();
+  s.w_u8(8191, 0x5).unwrap();
+  let before = s.read_all().unwrap();
+
+  s.prefault().unwrap();
+  assert_eq!(before, s.read_all().unwrap());
+  assert_eq!(8192, s.get_capacity().unwrap());
+  assert_eq!(0, s.generation());
+}
+
+#[test]
+fn prefault_covers_expanded_capacity() {
+  let mut s = MemoryBinaryStorage::new(4096, 4096).unwrap();
+  s.open().unwrap();
+  s.expand(12288).unwrap();
+  s.w_u8(12287, 0x1).unwrap();
+  s.prefault().unwrap();
+  assert_eq!(12288, s.get_capacity().unwrap());
+  assert_eq!(0x1, s.r_u8(12287).unwrap());
+  assert!(s.is_filled(None, Some(12287), 0x0).unwrap());
+}
@@ -6,6 +6,14 @@ mod transactional_storage_tests;
 mod file_page_tests;
 mod file_synced_buffer_tests;
 mod util_tests;
+mod lock_table_tests;
+mod replication_tests;
+mod record_schema_tests;
+mod ring_journal_tests;
+mod bitmap_tests;
+mod segment_pool_tests;
+mod xor_storage_tests;
+mod binary_storage_reader_tests;
 mod bplus_tree;
 
 
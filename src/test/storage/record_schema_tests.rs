@@ -0,0 +1,126 @@
+use std::error::Error;
+use storage::record_schema;
+use storage::record_schema::{ RecordSchema, FieldType };
+use storage::binary_storage::BinaryStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+
+fn header_schema() -> RecordSchema {
+  let mut schema = RecordSchema::new();
+  schema.add_field("kind", FieldType::U8).unwrap();
+  schema.add_field("flags", FieldType::U16).unwrap();
+  schema.add_field("len", FieldType::U32).unwrap();
+  schema.add_field("ptr", FieldType::U64).unwrap();
+  schema.add_field("key", FieldType::Bytes(3)).unwrap();
+  schema
+}
+
+
+// add_field() tests
+#[test]
+fn add_field_lays_fields_out_in_order() {
+  let schema = header_schema();
+  assert_eq!(0, schema.offset_of("kind").unwrap());
+  assert_eq!(1, schema.offset_of("flags").unwrap());
+  assert_eq!(3, schema.offset_of("len").unwrap());
+  assert_eq!(7, schema.offset_of("ptr").unwrap());
+  assert_eq!(15, schema.offset_of("key").unwrap());
+  assert_eq!(18, schema.len());
+  assert_eq!(5, schema.get_num_fields());
+}
+
+#[test]
+fn add_field_returns_err_on_duplicate_name() {
+  let mut schema = header_schema();
+  assert_eq!(
+    record_schema::ERR_DUPLICATE_FIELD,
+    schema.add_field("len", FieldType::U8).unwrap_err().description()
+  );
+  assert_eq!(18, schema.len());
+}
+
+#[test]
+fn add_field_returns_err_on_zero_width() {
+  let mut schema = RecordSchema::new();
+  assert_eq!(
+    record_schema::ERR_FIELD_TOO_SMALL,
+    schema.add_field("empty", FieldType::Bytes(0)).unwrap_err().description()
+  );
+}
+
+// offset_of() and type_of() tests
+#[test]
+fn offset_of_returns_err_for_unknown_field() {
+  let schema = header_schema();
+  assert_eq!(
+    record_schema::ERR_NO_SUCH_FIELD,
+    schema.offset_of("nope").unwrap_err().description()
+  );
+}
+
+#[test]
+fn type_of_returns_field_type() {
+  let schema = header_schema();
+  assert_eq!(FieldType::U32, schema.type_of("len").unwrap());
+  assert_eq!(FieldType::Bytes(3), schema.type_of("key").unwrap());
+}
+
+// get_*() and set_*() tests
+#[test]
+fn set_and_get_fields_by_name() {
+  let schema = header_schema();
+  let mut s = MemoryBinaryStorage::new(64, 64).unwrap();
+  s.open().unwrap();
+  schema.set_u8(&mut s, 10, "kind", 0x7).unwrap();
+  schema.set_u16(&mut s, 10, "flags", 0x0102).unwrap();
+  schema.set_u32(&mut s, 10, "len", 123456).unwrap();
+  schema.set_u64(&mut s, 10, "ptr", 0xFFFFFFFFFF).unwrap();
+  schema.set_bytes(&mut s, 10, "key", &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(0x7, schema.get_u8(&s, 10, "kind").unwrap());
+  assert_eq!(0x0102, schema.get_u16(&s, 10, "flags").unwrap());
+  assert_eq!(123456, schema.get_u32(&s, 10, "len").unwrap());
+  assert_eq!(0xFFFFFFFFFF, schema.get_u64(&s, 10, "ptr").unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), schema.get_bytes(&s, 10, "key").unwrap());
+  assert_eq!(0x7, s.r_u8(10).unwrap());
+  assert_eq!(123456, s.r_u32(13).unwrap());
+}
+
+#[test]
+fn get_returns_err_on_type_mismatch() {
+  let schema = header_schema();
+  let mut s = MemoryBinaryStorage::new(64, 64).unwrap();
+  s.open().unwrap();
+  assert_eq!(
+    record_schema::ERR_FIELD_TYPE_MISMATCH,
+    schema.get_u64(&s, 0, "len").unwrap_err().description()
+  );
+  assert_eq!(
+    record_schema::ERR_FIELD_TYPE_MISMATCH,
+    schema.get_bytes(&s, 0, "ptr").unwrap_err().description()
+  );
+}
+
+#[test]
+fn set_returns_err_on_type_mismatch() {
+  let schema = header_schema();
+  let mut s = MemoryBinaryStorage::new(64, 64).unwrap();
+  s.open().unwrap();
+  assert_eq!(
+    record_schema::ERR_FIELD_TYPE_MISMATCH,
+    schema.set_u8(&mut s, 0, "flags", 0x1).unwrap_err().description()
+  );
+  assert_eq!(
+    record_schema::ERR_FIELD_TYPE_MISMATCH,
+    schema.set_bytes(&mut s, 0, "ptr", &[0x0; 8]).unwrap_err().description()
+  );
+}
+
+#[test]
+fn set_bytes_returns_err_on_width_mismatch() {
+  let schema = header_schema();
+  let mut s = MemoryBinaryStorage::new(64, 64).unwrap();
+  s.open().unwrap();
+  assert_eq!(
+    record_schema::ERR_FIELD_WIDTH_MISMATCH,
+    schema.set_bytes(&mut s, 0, "key", &[0x1, 0x2]).unwrap_err().description()
+  );
+}
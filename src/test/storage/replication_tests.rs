@@ -0,0 +1,91 @@
+use std::error::Error;
+use storage::replication;
+use storage::journal::Journal;
+use storage::binary_storage::BinaryStorage;
+use storage::transactional_storage::TransactionalStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+
+fn new_journal() -> Journal<MemoryBinaryStorage> {
+  Journal::new(TransactionalStorage::new(
+    MemoryBinaryStorage::new(256, 256).unwrap()
+  ))
+}
+
+fn populated_storage() -> MemoryBinaryStorage {
+  let mut s = MemoryBinaryStorage::new(64, 64).unwrap();
+  s.open().unwrap();
+  for i in 0..64 {
+    s.w_u8(i, (i * 3) as u8).unwrap();
+  }
+  s
+}
+
+
+// to_journal() tests
+#[test]
+fn to_journal_returns_err_when_chunk_is_0() {
+  let s = populated_storage();
+  let mut j = new_journal();
+  j.open().unwrap();
+  assert_eq!(
+    replication::ERR_CHUNK_SIZE_TOO_SMALL,
+    replication::to_journal(&s, &mut j, 0).unwrap_err().description()
+  );
+}
+
+#[test]
+fn to_journal_returns_err_when_source_closed() {
+  let s = MemoryBinaryStorage::new(64, 64).unwrap();
+  let mut j = new_journal();
+  j.open().unwrap();
+  assert!(replication::to_journal(&s, &mut j, 16).is_err());
+}
+
+#[test]
+fn to_journal_writes_one_record_per_chunk() {
+  let s = populated_storage();
+  let mut j = new_journal();
+  j.open().unwrap();
+  assert_eq!(4, replication::to_journal(&s, &mut j, 16).unwrap());
+  assert_eq!(4, j.record_count());
+}
+
+#[test]
+fn to_journal_writes_short_final_chunk() {
+  let s = populated_storage();
+  let mut j = new_journal();
+  j.open().unwrap();
+  assert_eq!(3, replication::to_journal(&s, &mut j, 30).unwrap());
+  let records: Vec<Vec<u8>> = j.collect();
+  assert_eq!(8 + 4, records[2].len());
+  assert_eq!(vec!(60, 0, 0, 0, 0, 0, 0, 0), records[2][0..8].to_vec());
+}
+
+// replay_into() tests
+#[test]
+fn replay_into_reproduces_source_storage() {
+  let src = populated_storage();
+  let mut j = new_journal();
+  j.open().unwrap();
+  replication::to_journal(&src, &mut j, 10).unwrap();
+
+  let mut dst = MemoryBinaryStorage::new(16, 16).unwrap();
+  dst.open().unwrap();
+  assert_eq!(7, replication::replay_into(&mut j, &mut dst).unwrap());
+  assert_eq!(src.read_all().unwrap(), dst.read_all().unwrap());
+}
+
+#[test]
+fn replay_into_returns_err_on_short_record() {
+  let mut j = new_journal();
+  j.open().unwrap();
+  j.write(&[0x1, 0x2, 0x3]).unwrap();
+  j.commit().unwrap();
+
+  let mut dst = MemoryBinaryStorage::new(16, 16).unwrap();
+  dst.open().unwrap();
+  assert_eq!(
+    replication::ERR_BAD_PATCH_RECORD,
+    replication::replay_into(&mut j, &mut dst).unwrap_err().description()
+  );
+}
@@ -0,0 +1,308 @@
+use std::error::Error;
+use storage::ring_journal;
+use storage::ring_journal::RingJournal;
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+
+// 256 bytes holds the header plus any of the ring sizes used below
+fn new_ring(ring_size: usize) -> RingJournal<MemoryBinaryStorage> {
+  RingJournal::new(MemoryBinaryStorage::new(256, 256).unwrap(), ring_size).unwrap()
+}
+
+fn records(r: &mut RingJournal<MemoryBinaryStorage>) -> Vec<Vec<u8>> {
+  r.reset();
+  r.collect()
+}
+
+
+// new() and open() tests
+#[test]
+pub fn new_returns_err_when_ring_size_is_0() {
+  let res = RingJournal::new(MemoryBinaryStorage::new(256, 256).unwrap(), 0);
+  assert_eq!(
+    ring_journal::ERR_RING_SIZE_TOO_SMALL,
+    res.err().unwrap().description()
+  );
+}
+
+#[test]
+pub fn open_starts_empty() {
+  let mut r = new_ring(64);
+  r.open().unwrap();
+  assert_eq!(0, r.record_count());
+  assert_eq!(0, r.used());
+  assert_eq!(None, r.read().unwrap());
+}
+
+#[test]
+pub fn open_expands_storage_to_hold_ring() {
+  let mut r = RingJournal::new(MemoryBinaryStorage::new(16, 16).unwrap(), 100).unwrap();
+  r.open().unwrap();
+  r.write(vec![0x1; 96].as_slice()).unwrap();
+  assert_eq!(vec!(vec![0x1; 96]), records(&mut r));
+}
+
+
+// write() tests
+#[test]
+pub fn write_returns_err_when_closed() {
+  let mut r = new_ring(64);
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    r.write(&[0x1]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn write_returns_err_when_empty() {
+  let mut r = new_ring(64);
+  r.open().unwrap();
+  assert_eq!(
+    ring_journal::ERR_NOTHING_TO_WRITE,
+    r.write(&[]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn write_returns_err_when_record_bigger_than_ring() {
+  let mut r = new_ring(16);
+  r.open().unwrap();
+  assert_eq!(
+    ring_journal::ERR_RECORD_TOO_BIG_FOR_RING,
+    r.write(vec![0x1; 13].as_slice()).unwrap_err().description()
+  );
+  assert!(r.write(vec![0x1; 12].as_slice()).is_ok());
+}
+
+#[test]
+pub fn write_appends_records_in_order_until_full() {
+  let mut r = new_ring(32);
+  r.open().unwrap();
+  for i in 0..4 {
+    r.write(&[i, i, i, i]).unwrap();
+  }
+  assert_eq!(4, r.record_count());
+  assert_eq!(32, r.used());
+  assert_eq!(
+    vec!(vec!(0, 0, 0, 0), vec!(1, 1, 1, 1), vec!(2, 2, 2, 2), vec!(3, 3, 3, 3)),
+    records(&mut r)
+  );
+}
+
+#[test]
+pub fn write_overwrites_oldest_records_when_full() {
+  let mut r = new_ring(32);
+  r.open().unwrap();
+  for i in 0..6 {
+    r.write(&[i, i, i, i]).unwrap();
+  }
+  assert_eq!(4, r.record_count());
+  assert_eq!(
+    vec!(vec!(2, 2, 2, 2), vec!(3, 3, 3, 3), vec!(4, 4, 4, 4), vec!(5, 5, 5, 5)),
+    records(&mut r)
+  );
+}
+
+#[test]
+pub fn write_drops_several_records_to_fit_a_big_one() {
+  let mut r = new_ring(32);
+  r.open().unwrap();
+  for i in 0..4 {
+    r.write(&[i, i, i, i]).unwrap();
+  }
+  r.write(vec![0x9; 20].as_slice()).unwrap();
+  assert_eq!(2, r.record_count());
+  assert_eq!(vec!(vec!(3, 3, 3, 3), vec![0x9; 20]), records(&mut r));
+}
+
+#[test]
+pub fn write_wraps_records_around_end_of_ring() {
+  let mut r = new_ring(30);
+  r.open().unwrap();
+  for i in 0..10 {
+    r.write(&[i, i + 1, i + 2]).unwrap();
+  }
+  // Each record is 7 bytes, so 4 fit and some of them straddle the end
+  assert_eq!(4, r.record_count());
+  assert_eq!(
+    vec!(vec!(6, 7, 8), vec!(7, 8, 9), vec!(8, 9, 10), vec!(9, 10, 11)),
+    records(&mut r)
+  );
+}
+
+#[test]
+pub fn write_handles_mixed_record_sizes_across_wrap() {
+  let mut r = new_ring(40);
+  r.open().unwrap();
+  let mut written = Vec::new();
+  for i in 1..30 {
+    let rec = vec![i as u8; (i % 7) + 1];
+    r.write(rec.as_slice()).unwrap();
+    written.push(rec);
+  }
+  let recs = records(&mut r);
+  let n = recs.len();
+  assert_eq!(n, r.record_count());
+  assert_eq!(&written[written.len() - n..], recs.as_slice());
+  assert!(r.used() <= 40);
+}
+
+
+// read(), next(), and reset() tests
+#[test]
+pub fn read_returns_err_when_closed() {
+  let r = new_ring(64);
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    r.read().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn read_does_not_advance() {
+  let mut r = new_ring(64);
+  r.open().unwrap();
+  r.write(&[0x1, 0x2]).unwrap();
+  r.write(&[0x3, 0x4]).unwrap();
+  assert_eq!(Some(vec!(0x1, 0x2)), r.read().unwrap());
+  assert_eq!(Some(vec!(0x1, 0x2)), r.read().unwrap());
+}
+
+#[test]
+pub fn next_stops_at_newest_record() {
+  let mut r = new_ring(64);
+  r.open().unwrap();
+  r.write(&[0x1, 0x2]).unwrap();
+  r.write(&[0x3, 0x4]).unwrap();
+  assert_eq!(Some(vec!(0x1, 0x2)), r.next());
+  assert_eq!(Some(vec!(0x3, 0x4)), r.next());
+  assert_eq!(None, r.next());
+  assert_eq!(None, r.read().unwrap());
+}
+
+#[test]
+pub fn reset_moves_back_to_oldest_record() {
+  let mut r = new_ring(16);
+  r.open().unwrap();
+  r.write(&[0x1, 0x2, 0x3, 0x4]).unwrap();
+  r.write(&[0x5, 0x6, 0x7, 0x8]).unwrap();
+  r.write(&[0x9, 0xa, 0xb, 0xc]).unwrap();
+  r.next().unwrap();
+  r.reset();
+  assert_eq!(Some(vec!(0x5, 0x6, 0x7, 0x8)), r.next());
+}
+
+
+// Persistence tests
+#[test]
+pub fn reopen_keeps_valid_window() {
+  let mut r = new_ring(30);
+  r.open().unwrap();
+  for i in 0..10 {
+    r.write(&[i, i + 1, i + 2]).unwrap();
+  }
+  r.close().unwrap();
+  assert!(!r.is_open());
+
+  r.open().unwrap();
+  assert_eq!(4, r.record_count());
+  assert_eq!(
+    vec!(vec!(6, 7, 8), vec!(7, 8, 9), vec!(8, 9, 10), vec!(9, 10, 11)),
+    records(&mut r)
+  );
+
+  r.write(&[10, 11, 12]).unwrap();
+  assert_eq!(
+    vec!(vec!(7, 8, 9), vec!(8, 9, 10), vec!(9, 10, 11), vec!(10, 11, 12)),
+    records(&mut r)
+  );
+}
+
+// Storage holding a ring header with the given head, used space and record
+// count, and a first record claiming to be len bytes long
+fn storage_with_header(
+  head: u64, 
+  used: u64, 
+  record_count: u64, 
+  len: u32
+) -> MemoryBinaryStorage {
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  s.w_u64(0, head).unwrap();
+  s.w_u64(8, used).unwrap();
+  s.w_u64(16, record_count).unwrap();
+  s.w_u32(24, len).unwrap();
+  s.close().unwrap();
+  s
+}
+
+#[test]
+pub fn open_returns_err_when_head_outside_ring() {
+  let mut r = RingJournal::new(storage_with_header(16, 5, 1, 1), 16).unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_HEADER_INVALID,
+    r.open().unwrap_err().description()
+  );
+  assert!(!r.is_open());
+}
+
+#[test]
+pub fn open_returns_err_when_used_bigger_than_ring() {
+  let mut r = RingJournal::new(storage_with_header(0, 17, 1, 1), 16).unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_HEADER_INVALID,
+    r.open().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn open_returns_err_when_record_count_does_not_fit_used() {
+  let mut r = RingJournal::new(storage_with_header(0, 10, 3, 1), 16).unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_HEADER_INVALID,
+    r.open().unwrap_err().description()
+  );
+  let mut r = RingJournal::new(storage_with_header(0, 10, 0, 1), 16).unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_HEADER_INVALID,
+    r.open().unwrap_err().description()
+  );
+  let mut r = RingJournal::new(storage_with_header(0, 0, 1, 1), 16).unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_HEADER_INVALID,
+    r.open().unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn open_accepts_header_written_by_ring() {
+  let mut r = RingJournal::new(storage_with_header(0, 5, 1, 1), 16).unwrap();
+  r.open().unwrap();
+  assert_eq!(1, r.record_count());
+  assert_eq!(5, r.used());
+}
+
+#[test]
+pub fn write_returns_err_when_dropped_record_longer_than_used() {
+  // The header says 10 bytes are used, but the oldest record claims 10 
+  // bytes of data
+  let mut r = RingJournal::new(storage_with_header(0, 10, 1, 10), 16).unwrap();
+  r.open().unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_CORRUPT,
+    r.write(&[0x1; 8]).unwrap_err().description()
+  );
+  assert_eq!(1, r.record_count());
+  assert_eq!(10, r.used());
+}
+
+#[test]
+pub fn read_returns_err_when_record_longer_than_ring() {
+  let mut r = RingJournal::new(storage_with_header(0, 10, 1, 100), 16).unwrap();
+  r.open().unwrap();
+  assert_eq!(
+    ring_journal::ERR_RING_CORRUPT,
+    r.read().unwrap_err().description()
+  );
+}
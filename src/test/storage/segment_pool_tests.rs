@@ -0,0 +1,170 @@
+use std::fs;
+use std::error::Error;
+use uuid::Uuid;
+
+use storage::binary_storage;
+use storage::binary_storage::BinaryStorage;
+use storage::file_binary_storage::FileBinaryStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+use storage::segment_pool;
+use storage::segment_pool::SegmentPool;
+
+
+pub static BASE_PATH: &'static str = "./test_data/storage/file_binary_storage/";
+
+fn rnd_path() -> String {
+  BASE_PATH.to_string() 
+    + Uuid::new_v4().simple().to_string().as_str()
+    + ".tmp"
+}
+
+// A pool of num_segments new segment files, returned with their paths
+fn get_file_pool(
+  num_segments: usize, 
+  max_open: usize
+) -> (SegmentPool<FileBinaryStorage>, Vec<String>) {
+  let mut p = SegmentPool::new(max_open).unwrap();
+  let mut paths = Vec::new();
+  for _ in 0..num_segments {
+    let path = rnd_path();
+    p.add_segment(
+      FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap()
+    ).unwrap();
+    paths.push(path);
+  }
+  (p, paths)
+}
+
+fn rm_tmp(paths: Vec<String>) {
+  for p in paths {
+    fs::remove_file(p).unwrap();
+  }
+}
+
+fn get_memory_pool(num_segments: usize, max_open: usize) -> SegmentPool<MemoryBinaryStorage> {
+  let mut p = SegmentPool::new(max_open).unwrap();
+  for _ in 0..num_segments {
+    p.add_segment(MemoryBinaryStorage::new(256, 256).unwrap()).unwrap();
+  }
+  p
+}
+
+
+// new() and add_segment() tests
+#[test]
+pub fn new_returns_err_when_max_open_is_0() {
+  let res = SegmentPool::<MemoryBinaryStorage>::new(0);
+  assert_eq!(
+    segment_pool::ERR_MAX_OPEN_TOO_SMALL,
+    res.err().unwrap().description()
+  );
+}
+
+#[test]
+pub fn new_starts_empty() {
+  let p = SegmentPool::<MemoryBinaryStorage>::new(4).unwrap();
+  assert_eq!(0, p.num_segments());
+  assert_eq!(0, p.open_count());
+  assert_eq!(4, p.get_max_open());
+}
+
+#[test]
+pub fn add_segment_returns_indices_in_order() {
+  let mut p = SegmentPool::new(4).unwrap();
+  for i in 0..5 {
+    assert_eq!(i, p.add_segment(MemoryBinaryStorage::new(256, 256).unwrap()).unwrap());
+  }
+  assert_eq!(5, p.num_segments());
+  assert_eq!(0, p.open_count());
+}
+
+#[test]
+pub fn add_segment_returns_err_when_storage_open() {
+  let mut p = SegmentPool::new(4).unwrap();
+  let mut s = MemoryBinaryStorage::new(256, 256).unwrap();
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_OPEN,
+    p.add_segment(s).unwrap_err().description()
+  );
+}
+
+
+// segment() tests
+#[test]
+pub fn segment_returns_err_when_no_such_segment() {
+  let mut p = get_memory_pool(2, 4);
+  assert_eq!(
+    segment_pool::ERR_NO_SUCH_SEGMENT,
+    p.segment(2).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn segment_opens_segment_on_demand() {
+  let mut p = get_memory_pool(3, 4);
+  assert!(p.segment(1).unwrap().is_open());
+  assert!(p.is_segment_open(1));
+  assert!(!p.is_segment_open(0));
+  assert_eq!(1, p.open_count());
+}
+
+#[test]
+pub fn segment_closes_least_recently_used_when_full() {
+  let mut p = get_memory_pool(4, 2);
+  p.segment(0).unwrap();
+  p.segment(1).unwrap();
+  p.segment(0).unwrap();
+  p.segment(2).unwrap();
+  assert!(p.is_segment_open(0));
+  assert!(!p.is_segment_open(1));
+  assert!(p.is_segment_open(2));
+  assert_eq!(2, p.open_count());
+}
+
+#[test]
+pub fn segment_keeps_tracking_least_recent_segment_when_close_fails() {
+  let mut p = get_memory_pool(4, 2);
+  p.segment(0).unwrap().close().unwrap();
+  p.segment(1).unwrap();
+  assert_eq!(
+    binary_storage::ERR_OPERATION_INVALID_WHEN_CLOSED,
+    p.segment(2).err().unwrap().description()
+  );
+  assert!(p.is_segment_open(0));
+  assert!(!p.is_segment_open(2));
+  assert_eq!(2, p.open_count());
+}
+
+#[test]
+pub fn close_all_closes_every_segment() {
+  let mut p = get_memory_pool(4, 3);
+  p.segment(0).unwrap();
+  p.segment(3).unwrap();
+  p.close_all().unwrap();
+  assert_eq!(0, p.open_count());
+  assert!(!p.is_segment_open(0));
+  assert!(!p.is_segment_open(3));
+}
+
+#[test]
+pub fn reads_across_many_file_segments_stay_within_pool_limit() {
+  let (mut p, paths) = get_file_pool(40, 3);
+
+  for i in 0..40 {
+    p.segment(i).unwrap().w_u64(8, i as u64 * 1000).unwrap();
+    assert!(p.open_count() <= 3);
+  }
+
+  // Read back in an order that keeps reopening closed segments
+  for round in 0..2 {
+    for i in 0..40 {
+      let idx = if round == 0 { 39 - i } else { (i * 7) % 40 };
+      assert_eq!(idx as u64 * 1000, p.segment(idx).unwrap().r_u64(8).unwrap());
+      assert!(p.open_count() <= 3);
+    }
+  }
+
+  p.close_all().unwrap();
+  rm_tmp(paths);
+}
@@ -1,15 +1,31 @@
 use std::str;
 use std::error::Error;
+use test::storage::binary_storage_tests;
 use storage::transactional_storage;
+use storage::binary_storage;
 use storage::binary_storage::BinaryStorage;
+use storage::util;
 use storage::transactional_storage::TransactionalStorage;
 use storage::memory_binary_storage::MemoryBinaryStorage;
+use storage::journal::Journal;
 
 
 fn new_storage() -> TransactionalStorage<MemoryBinaryStorage> {
   TransactionalStorage::new(MemoryBinaryStorage::new(256, 256).unwrap())
 }
 
+// Reads are not checked against the boundary, so the wrapper can be run
+// through the generic BinaryStorage tests like the raw backends
+fn new_unchecked_storage(
+  initial_capacity: usize, 
+  expand_size: usize
+) -> TransactionalStorage<MemoryBinaryStorage> {
+  TransactionalStorage::with_check_on_read(
+    MemoryBinaryStorage::new(initial_capacity, expand_size).unwrap(),
+    false
+  )
+}
+
 
 // writer tests
 #[test]
@@ -132,6 +148,21 @@ pub fn w_u64_does_not_write_before_txn_boundary() {
   assert_eq!(u64::max_value(), s.r_u64(8).unwrap()); 
 }
 
+#[test]
+pub fn w_u128_does_not_write_before_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(16).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.w_u128(15, u128::max_value()).unwrap_err().description()
+  );
+  assert!(s.w_u128(16, u128::max_value()).is_ok());
+  s.set_txn_boundary(32).unwrap();
+  assert_eq!(0, s.r_u128(0).unwrap());
+  assert_eq!(u128::max_value(), s.r_u128(16).unwrap()); 
+}
+
 #[test]
 pub fn w_f32_does_not_write_before_txn_boundary() {
   let mut s = new_storage();
@@ -211,6 +242,73 @@ pub fn w_str_does_not_write_before_txn_boundary() {
 }
 
 
+// new store (boundary at 0) tests
+#[test]
+pub fn new_store_starts_with_txn_boundary_at_0() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  assert_eq!(0, s.get_txn_boundary().unwrap());
+}
+
+#[test]
+pub fn new_store_allows_write_at_offset_0() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  assert!(s.w_u8(0, 0x1).is_ok());
+  assert!(s.w_bytes(0, &[0x1, 0x2, 0x3]).is_ok());
+  assert!(s.pwrite(0, &[0x1]).is_ok());
+  assert!(s.fill(None, Some(4), 0x1).is_ok());
+}
+
+#[test]
+pub fn new_store_does_not_read_at_offset_0_before_commit() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_u8(0).unwrap_err().description()
+  );
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_bytes(0, 3).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn new_store_reads_offset_0_after_commit() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.set_txn_boundary(3).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(0, 3).unwrap());
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.w_u8(0, 0x7).unwrap_err().description()
+  );
+}
+
+
+// with_check_on_read() tests
+#[test]
+pub fn with_check_on_read_sets_check_on_read() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.open().unwrap();
+  assert!(!s.get_check_on_read().unwrap());
+  assert!(s.r_u8(0).is_ok());
+  let mut s = TransactionalStorage::with_check_on_read(
+    MemoryBinaryStorage::new(256, 256).unwrap(),
+    true
+  );
+  s.open().unwrap();
+  assert!(s.get_check_on_read().unwrap());
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_u8(0).unwrap_err().description()
+  );
+}
+
+
 // reader tests
 #[test]
 pub fn r_i8_does_not_read_past_txn_boundary() {
@@ -308,6 +406,30 @@ pub fn r_u64_does_not_read_past_txn_boundary() {
   );
 }
 
+#[test]
+pub fn r_u128_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(16).unwrap();
+  assert!(s.r_u128(0).is_ok());
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_u128(1).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn r_u64_opt_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(8).unwrap();
+  assert_eq!(None, s.r_u64_opt(0).unwrap());
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_u64_opt(1).unwrap_err().description()
+  );
+}
+
 #[test]
 pub fn r_f32_does_not_read_past_txn_boundary() {
   let mut s = new_storage();
@@ -357,6 +479,19 @@ pub fn r_bytes_does_not_read_past_txn_boundary() {
   );
 }
 
+#[test]
+pub fn r_bytes_into_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(8).unwrap();
+  let mut buf = [0x0; 2];
+  assert!(s.r_bytes_into(6, &mut buf).is_ok());
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_bytes_into(7, &mut buf).unwrap_err().description()
+  );
+}
+
 #[test]
 pub fn r_str_does_not_read_past_txn_boundary() {
   let mut s = new_storage();
@@ -369,6 +504,160 @@ pub fn r_str_does_not_read_past_txn_boundary() {
   );
 }
 
+#[test]
+pub fn read_all_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(8).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.read_all().unwrap_err().description()
+  );
+  s.set_txn_boundary(256).unwrap();
+  assert_eq!(256, s.read_all().unwrap().len());
+}
+
+#[test]
+pub fn pread_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(8).unwrap();
+  assert!(s.pread(6, 2).is_ok());
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.pread(7, 2).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn pwrite_does_not_write_before_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(4).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.pwrite(3, &[0x1, 0x2]).unwrap_err().description()
+  );
+  assert!(s.pwrite(4, &[0x1, 0x2]).is_ok());
+  s.set_txn_boundary(16).unwrap();
+  assert_eq!(vec!(0x0, 0x1, 0x2), s.pread(3, 3).unwrap());
+}
+
+#[test]
+pub fn valid_len_from_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.set_txn_boundary(3).unwrap();
+  s.set_check_on_read(true).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.valid_len_from(0).unwrap_err().description()
+  );
+  s.set_check_on_read(false).unwrap();
+  assert_eq!(3, s.valid_len_from(0).unwrap());
+}
+
+#[test]
+pub fn crc32_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.set_txn_boundary(3).unwrap();
+  s.set_check_on_read(true).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.crc32(Some(0), Some(4)).unwrap_err().description()
+  );
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.crc32(Some(1), None).unwrap_err().description()
+  );
+  assert_eq!(util::crc32(&[0x1, 0x2, 0x3]), s.crc32(Some(0), Some(3)).unwrap());
+}
+
+#[test]
+pub fn find_bytes_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.set_txn_boundary(3).unwrap();
+  s.set_check_on_read(true).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.find_bytes(&[0x2, 0x3], 0).unwrap_err().description()
+  );
+  s.set_check_on_read(false).unwrap();
+  assert_eq!(Some(1), s.find_bytes(&[0x2, 0x3], 0).unwrap());
+}
+
+#[test]
+pub fn r_u32_array_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_u32(0, 0x1).unwrap();
+  s.w_u32(4, 0x2).unwrap();
+  s.set_txn_boundary(6).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.r_u32_array(0, 2).unwrap_err().description()
+  );
+  assert_eq!(vec!(0x1), s.r_u32_array(0, 1).unwrap());
+}
+
+#[test]
+pub fn copy_does_not_read_past_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(3).unwrap();
+  s.set_check_on_read(true).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_READ_AFTER_TXN_BOUNDARY,
+    s.copy(2, 8, 2).unwrap_err().description()
+  );
+  s.copy(1, 8, 2).unwrap();
+  s.set_check_on_read(false).unwrap();
+  assert_eq!(vec!(0x2, 0x3), s.r_bytes(8, 2).unwrap());
+}
+
+#[test]
+pub fn copy_fails_when_destination_is_before_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(2).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.copy(2, 1, 2).unwrap_err().description()
+  );
+  s.copy(0, 2, 2).unwrap();
+  s.set_txn_boundary(4).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x1, 0x2), s.r_bytes(0, 4).unwrap());
+}
+
+#[test]
+pub fn swap_ranges_fails_when_either_range_is_before_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(2).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.swap_ranges(0, 8, 2).unwrap_err().description()
+  );
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.swap_ranges(8, 1, 2).unwrap_err().description()
+  );
+  s.swap_ranges(2, 8, 2).unwrap();
+  s.set_txn_boundary(16).unwrap();
+  assert_eq!(
+    vec!(0x1, 0x2, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x3, 0x4), 
+    s.r_bytes(0, 10).unwrap()
+  );
+}
+
 #[test]
 pub fn fill_fails_when_explicitly_starting_before_txn_boundary() {
   let mut s = new_storage();    
@@ -422,3 +711,2205 @@ pub fn fill_writes_bytes_in_range_without_end() {
   assert!(s.is_filled(Some(10), None, 0x1).unwrap());
 }
 
+// checkpoint() and restore() tests
+#[test]
+pub fn checkpoint_returns_err_when_closed() {
+  let mut s = new_storage();
+  assert!(s.checkpoint("a").is_err());
+  assert!(!s.has_checkpoint("a"));
+}
+
+#[test]
+pub fn checkpoint_records_checkpoint() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  assert!(!s.has_checkpoint("a"));
+  s.checkpoint("a").unwrap();
+  assert!(s.has_checkpoint("a"));
+}
+
+#[test]
+pub fn restore_returns_err_when_closed() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.checkpoint("a").unwrap();
+  s.close().unwrap();
+  assert!(s.restore("a").is_err());
+}
+
+#[test]
+pub fn restore_returns_err_when_no_such_checkpoint() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  assert_eq!(
+    transactional_storage::ERR_NO_SUCH_CHECKPOINT,
+    s.restore("a").unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn restore_resets_to_earlier_checkpoint() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(4).unwrap();
+  s.checkpoint("first").unwrap();
+  s.w_bytes(4, &[0x5, 0x6, 0x7, 0x8]).unwrap();
+  s.set_txn_boundary(8).unwrap();
+  s.checkpoint("second").unwrap();
+  s.w_bytes(8, &[0x9, 0xa]).unwrap();
+  s.restore("first").unwrap();
+  assert_eq!(4, s.get_txn_boundary().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4), s.r_bytes(0, 4).unwrap());
+  s.set_check_on_read(false).unwrap();
+  assert!(s.is_filled(Some(4), None, 0x0).unwrap());
+}
+
+#[test]
+pub fn restore_allows_restoring_later_checkpoint_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(4).unwrap();
+  s.checkpoint("first").unwrap();
+  s.w_bytes(4, &[0x5, 0x6, 0x7, 0x8]).unwrap();
+  s.set_txn_boundary(8).unwrap();
+  s.checkpoint("second").unwrap();
+  s.restore("second").unwrap();
+  assert_eq!(8, s.get_txn_boundary().unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8), s.r_bytes(0, 8).unwrap());
+}
+
+#[test]
+pub fn restore_drops_checkpoints_past_restored_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(4).unwrap();
+  s.checkpoint("first").unwrap();
+  s.checkpoint("also_first").unwrap();
+  s.w_bytes(4, &[0x5, 0x6, 0x7, 0x8]).unwrap();
+  s.set_txn_boundary(8).unwrap();
+  s.checkpoint("second").unwrap();
+  s.restore("first").unwrap();
+  assert!(s.has_checkpoint("first"));
+  assert!(s.has_checkpoint("also_first"));
+  assert!(!s.has_checkpoint("second"));
+  assert_eq!(
+    transactional_storage::ERR_NO_SUCH_CHECKPOINT,
+    s.restore("second").unwrap_err().description()
+  );
+  assert_eq!(4, s.get_txn_boundary().unwrap());
+}
+
+
+#[test]
+pub fn w_bytes_reporting_does_not_write_before_txn_boundary() {
+  let mut s = new_storage();
+  s.open().unwrap();
+  s.set_txn_boundary(4).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.w_bytes_reporting(3, &[0x1, 0x2]).unwrap_err().description()
+  );
+  assert!(!s.w_bytes_reporting(4, &[0x1, 0x2]).unwrap().expanded);
+}
+
+// shrink() tests
+#[test]
+pub fn shrink_returns_err_when_below_txn_boundary() {
+  let mut s = TransactionalStorage::new(MemoryBinaryStorage::new(256, 16).unwrap());
+  s.open().unwrap();
+  s.expand(1000).unwrap();
+  s.set_txn_boundary(100).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_SHRINK_BEFORE_TXN_BOUNDARY,
+    s.shrink(99).unwrap_err().description()
+  );
+  assert_eq!(1008, s.get_capacity().unwrap());
+}
+
+#[test]
+pub fn truncate_returns_err_when_below_txn_boundary() {
+  let mut s = TransactionalStorage::new(MemoryBinaryStorage::new(256, 16).unwrap());
+  s.open().unwrap();
+  s.expand(1000).unwrap();
+  s.set_txn_boundary(100).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_SHRINK_BEFORE_TXN_BOUNDARY,
+    s.truncate(99).unwrap_err().description()
+  );
+  assert_eq!(1008, s.get_capacity().unwrap());
+  s.truncate(100).unwrap();
+  assert_eq!(112, s.get_capacity().unwrap());
+}
+
+#[test]
+pub fn truncate_returns_err_when_past_capacity() {
+  let mut s = TransactionalStorage::new(MemoryBinaryStorage::new(256, 16).unwrap());
+  s.open().unwrap();
+  assert_eq!(
+    binary_storage::ERR_TRUNCATE_PAST_END,
+    s.truncate(300).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn shrink_to_txn_boundary_succeeds() {
+  let mut s = TransactionalStorage::new(MemoryBinaryStorage::new(256, 16).unwrap());
+  s.open().unwrap();
+  s.expand(1000).unwrap();
+  s.set_txn_boundary(100).unwrap();
+  s.shrink(100).unwrap();
+  assert_eq!(112, s.get_capacity().unwrap());
+}
+
+
+// Generic BinaryStorage tests
+
+// open(), close(), and is_open() tests
+#[test]
+fn open_returns_err_when_already_open() {
+  binary_storage_tests::open_returns_err_when_already_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn close_returns_err_when_already_closed() {
+  binary_storage_tests::close_returns_err_when_already_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn open_returns_ok_when_previously_closed() {
+  binary_storage_tests::open_returns_ok_when_previously_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn close_returns_ok_when_previously_open() {
+  binary_storage_tests::close_returns_ok_when_previously_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_closed_when_new() {
+  binary_storage_tests::is_closed_when_new(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_open_after_open() {
+  binary_storage_tests::is_open_after_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_closed_after_open_and_close() {
+  binary_storage_tests::is_closed_after_open_and_close(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_i8() tests
+#[test]
+fn w_i8_returns_err_when_closed() {
+  binary_storage_tests::w_i8_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i8_returns_ok_when_open() {
+  binary_storage_tests::w_i8_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i8_does_not_write_when_closed() {
+  binary_storage_tests::w_i8_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i8_over_capacity_expands_storage() {
+  binary_storage_tests::w_i8_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_i16() tests
+#[test]
+fn w_i16_returns_err_when_closed() {
+  binary_storage_tests::w_i16_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i16_returns_ok_when_open() {
+  binary_storage_tests::w_i16_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i16_does_not_write_when_closed() {
+  binary_storage_tests::w_i16_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i16_over_capacity_expands_storage() {
+  binary_storage_tests::w_i16_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_i32() tests
+#[test]
+fn w_i32_returns_err_when_closed() {
+  binary_storage_tests::w_i32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i32_returns_ok_when_open() {
+  binary_storage_tests::w_i32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i32_does_not_write_when_closed() {
+  binary_storage_tests::w_i32_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i32_over_capacity_expands_storage() {
+  binary_storage_tests::w_i32_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_i64() tests
+#[test]
+fn w_i64_returns_err_when_closed() {
+  binary_storage_tests::w_i64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i64_returns_ok_when_open() {
+  binary_storage_tests::w_i64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i64_does_not_write_when_closed() {
+  binary_storage_tests::w_i64_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i64_over_capacity_expands_storage() {
+  binary_storage_tests::w_i64_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_u8() tests
+#[test]
+fn w_u8_returns_err_when_closed() {
+  binary_storage_tests::w_u8_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u8_returns_ok_when_open() {
+  binary_storage_tests::w_u8_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u8_does_not_write_when_closed() {
+  binary_storage_tests::w_u8_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u8_over_capacity_expands_storage() {
+  binary_storage_tests::w_u8_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_u16() tests
+#[test]
+fn w_u16_returns_err_when_closed() {
+  binary_storage_tests::w_u16_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u16_returns_ok_when_open() {
+  binary_storage_tests::w_u16_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u16_does_not_write_when_closed() {
+  binary_storage_tests::w_u16_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u16_over_capacity_expands_storage() {
+  binary_storage_tests::w_u16_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_u32() tests
+#[test]
+fn w_u32_returns_err_when_closed() {
+  binary_storage_tests::w_u32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u32_returns_ok_when_open() {
+  binary_storage_tests::w_u32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u32_does_not_write_when_closed() {
+  binary_storage_tests::w_u32_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u32_over_capacity_expands_storage() {
+  binary_storage_tests::w_u32_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_u64() tests
+#[test]
+fn w_u64_returns_err_when_closed() {
+  binary_storage_tests::w_u64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u64_returns_ok_when_open() {
+  binary_storage_tests::w_u64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u64_does_not_write_when_closed() {
+  binary_storage_tests::w_u64_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u64_over_capacity_expands_storage() {
+  binary_storage_tests::w_u64_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_f32() tests
+#[test]
+fn w_f32_returns_err_when_closed() {
+  binary_storage_tests::w_f32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f32_returns_ok_when_open() {
+  binary_storage_tests::w_f32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f32_does_not_write_when_closed() {
+  binary_storage_tests::w_f32_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f32_over_capacity_expands_storage() {
+  binary_storage_tests::w_f32_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_f64() tests
+#[test]
+fn w_f64_returns_err_when_closed() {
+  binary_storage_tests::w_f64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f64_returns_ok_when_open() {
+  binary_storage_tests::w_f64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f64_does_not_write_when_closed() {
+  binary_storage_tests::w_f64_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f64_over_capacity_expands_storage() {
+  binary_storage_tests::w_f64_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_bool() tests
+#[test]
+fn w_bool_returns_err_when_closed() {
+  binary_storage_tests::w_bool_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bool_returns_ok_when_open() {
+  binary_storage_tests::w_bool_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bool_does_not_write_when_closed() {
+  binary_storage_tests::w_bool_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bool_over_capacity_expands_storage() {
+  binary_storage_tests::w_bool_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// w_bytes() tests
+#[test]
+fn w_bytes_returns_err_when_closed() {
+  binary_storage_tests::w_bytes_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_returns_ok_when_open() {
+  binary_storage_tests::w_bytes_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_does_not_write_when_closed() {
+  binary_storage_tests::w_bytes_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_over_capacity_expands_storage() {
+  binary_storage_tests::w_bytes_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_over_capacity_expands_storage_multiple_times() {
+  binary_storage_tests::w_bytes_over_capacity_expands_storage_multiple_times(
+    new_unchecked_storage(256, 4)
+  );
+}
+
+// w_str() tests
+#[test]
+fn w_str_returns_err_when_closed() {
+  binary_storage_tests::w_str_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_returns_ok_when_open() {
+  binary_storage_tests::w_str_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_does_not_write_when_closed() {
+  binary_storage_tests::w_str_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_over_capacity_expands_storage() {
+  binary_storage_tests::w_str_over_capacity_expands_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_over_capacity_expands_storage_multiple_times() {
+  binary_storage_tests::w_str_over_capacity_expands_storage_multiple_times(
+    new_unchecked_storage(256, 4)
+  );
+}
+
+// r_i8() tests
+#[test]
+fn r_i8_returns_err_when_closed() {
+  binary_storage_tests::r_i8_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_returns_ok_when_open() {
+  binary_storage_tests::r_i8_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_i8_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_reads_written_data() {
+  binary_storage_tests::r_i8_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_does_not_read_past_capacity() {
+  binary_storage_tests::r_i8_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i8_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_i16() tests
+#[test]
+fn r_i16_returns_err_when_closed() {
+  binary_storage_tests::r_i16_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_returns_ok_when_open() {
+  binary_storage_tests::r_i16_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_i16_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_reads_written_data() {
+  binary_storage_tests::r_i16_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_does_not_read_past_capacity() {
+  binary_storage_tests::r_i16_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i16_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_i32() tests
+#[test]
+fn r_i32_returns_err_when_closed() {
+  binary_storage_tests::r_i32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_returns_ok_when_open() {
+  binary_storage_tests::r_i32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_i32_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_reads_written_data() {
+  binary_storage_tests::r_i32_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_does_not_read_past_capacity() {
+  binary_storage_tests::r_i32_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i32_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_i64() tests
+#[test]
+fn r_i64_returns_err_when_closed() {
+  binary_storage_tests::r_i64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_returns_ok_when_open() {
+  binary_storage_tests::r_i64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_i64_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_reads_written_data() {
+  binary_storage_tests::r_i64_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_does_not_read_past_capacity() {
+  binary_storage_tests::r_i64_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i64_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_u8() tests
+#[test]
+fn r_u8_returns_err_when_closed() {
+  binary_storage_tests::r_u8_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_returns_ok_when_open() {
+  binary_storage_tests::r_u8_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_u8_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_reads_written_data() {
+  binary_storage_tests::r_u8_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_does_not_read_past_capacity() {
+  binary_storage_tests::r_u8_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u8_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_u16() tests
+#[test]
+fn r_u16_returns_err_when_closed() {
+  binary_storage_tests::r_u16_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_returns_ok_when_open() {
+  binary_storage_tests::r_u16_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_u16_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_reads_written_data() {
+  binary_storage_tests::r_u16_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_does_not_read_past_capacity() {
+  binary_storage_tests::r_u16_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u16_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_u32() tests
+#[test]
+fn r_u32_returns_err_when_closed() {
+  binary_storage_tests::r_u32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_returns_ok_when_open() {
+  binary_storage_tests::r_u32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_u32_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_reads_written_data() {
+  binary_storage_tests::r_u32_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_does_not_read_past_capacity() {
+  binary_storage_tests::r_u32_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u32_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_u64() tests
+#[test]
+fn r_u64_returns_err_when_closed() {
+  binary_storage_tests::r_u64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_returns_ok_when_open() {
+  binary_storage_tests::r_u64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_u64_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_reads_written_data() {
+  binary_storage_tests::r_u64_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_does_not_read_past_capacity() {
+  binary_storage_tests::r_u64_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u64_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_f32() tests
+#[test]
+fn r_f32_returns_err_when_closed() {
+  binary_storage_tests::r_f32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_returns_ok_when_open() {
+  binary_storage_tests::r_f32_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_f32_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_reads_written_data() {
+  binary_storage_tests::r_f32_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_does_not_read_past_capacity() {
+  binary_storage_tests::r_f32_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_f32_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_f64() tests
+#[test]
+fn r_f64_returns_err_when_closed() {
+  binary_storage_tests::r_f64_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_returns_ok_when_open() {
+  binary_storage_tests::r_f64_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_reads_zero_from_unwritten_storage() {
+  binary_storage_tests::r_f64_reads_zero_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_reads_written_data() {
+  binary_storage_tests::r_f64_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_does_not_read_past_capacity() {
+  binary_storage_tests::r_f64_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_f64_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_bool() tests
+#[test]
+fn r_bool_returns_err_when_closed() {
+  binary_storage_tests::r_bool_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_returns_ok_when_open() {
+  binary_storage_tests::r_bool_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_reads_false_from_unwritten_storage() {
+  binary_storage_tests::r_bool_reads_false_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_reads_written_data() {
+  binary_storage_tests::r_bool_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_does_not_read_past_capacity() {
+  binary_storage_tests::r_bool_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_bool_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_bytes() tests
+#[test]
+fn r_bytes_returns_err_when_closed() {
+  binary_storage_tests::r_bytes_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_returns_ok_when_open() {
+  binary_storage_tests::r_bytes_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_reads_zeros_from_unwritten_storage() {
+  binary_storage_tests::r_bytes_reads_zeros_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_reads_written_data() {
+  binary_storage_tests::r_bytes_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_does_not_read_past_capacity() {
+  binary_storage_tests::r_bytes_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_bytes_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_bytes_into() tests
+#[test]
+fn r_bytes_into_returns_err_when_closed() {
+  binary_storage_tests::r_bytes_into_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_fills_buffer_with_written_data() {
+  binary_storage_tests::r_bytes_into_fills_buffer_with_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_reads_part_of_written_data() {
+  binary_storage_tests::r_bytes_into_reads_part_of_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_matches_r_bytes() {
+  binary_storage_tests::r_bytes_into_matches_r_bytes(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_with_empty_buffer_reads_nothing() {
+  binary_storage_tests::r_bytes_into_with_empty_buffer_reads_nothing(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_does_not_read_past_capacity() {
+  binary_storage_tests::r_bytes_into_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// r_str() tests
+#[test]
+fn r_str_returns_err_when_closed() {
+  binary_storage_tests::r_str_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_returns_ok_when_open() {
+  binary_storage_tests::r_str_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_reads_nulls_from_unwritten_storage() {
+  binary_storage_tests::r_str_reads_nulls_from_unwritten_storage(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_reads_written_data() {
+  binary_storage_tests::r_str_reads_written_data(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_does_not_read_past_capacity() {
+  binary_storage_tests::r_str_does_not_read_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_str_result_is_not_mutated_on_subsequent_write(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// fill() tests
+#[test]
+fn fill_returns_err_when_closed() {
+  binary_storage_tests::fill_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_does_not_write_when_closed() {
+  binary_storage_tests::fill_does_not_write_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_ok_when_open() {
+  binary_storage_tests::fill_returns_ok_when_open(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_repeats_byte_in_storage_range() {
+  binary_storage_tests::fill_repeats_byte_in_storage_range(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_starts_from_beginning_when_start_offset_is_none() {
+  binary_storage_tests::fill_starts_from_beginning_when_start_offset_is_none(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_goes_to_end_when_end_offset_is_none() {
+  binary_storage_tests::fill_goes_to_end_when_end_offset_is_none(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_err_when_end_offset_is_before_start_offset() {
+  binary_storage_tests::fill_returns_err_when_end_offset_is_before_start_offset(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_does_not_write_when_end_offset_is_before_start_offset() {
+  binary_storage_tests::fill_does_not_write_when_end_offset_is_before_start_offset(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_err_when_past_capacity() {
+  binary_storage_tests::fill_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_does_not_write_when_past_capacity() {
+  binary_storage_tests::fill_does_not_write_when_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_does_not_expand_capacity() {
+  binary_storage_tests::fill_does_not_expand_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// assert_filled() tests
+#[test]
+fn is_filled_retuns_err_when_closed() {
+  binary_storage_tests::is_filled_retuns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_returns_err_when_start_offset_past_capacity() {
+  binary_storage_tests::is_filled_returns_err_when_start_offset_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_returns_err_when_end_offset_at_or_before_start_offset() {
+  binary_storage_tests::is_filled_returns_err_when_end_offset_at_or_before_start_offset(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_returns_err_when_end_offset_past_capacity() {
+  binary_storage_tests::is_filled_returns_err_when_end_offset_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_checks_whether_all_bytes_in_range_match_value() {
+  binary_storage_tests::is_filled_checks_whether_all_bytes_in_range_match_value(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_starts_from_start_offset() {
+  binary_storage_tests::is_filled_starts_from_start_offset(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_starts_from_beginning_when_start_offset_is_none() {
+  binary_storage_tests::is_filled_starts_from_beginning_when_start_offset_is_none(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_goes_to_end_offset() {
+  binary_storage_tests::is_filled_goes_to_end_offset(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_filled_goes_to_end_when_end_offset_is_none() {
+  binary_storage_tests::is_filled_goes_to_end_when_end_offset_is_none(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// crc32() tests
+#[test]
+fn crc32_returns_err_when_closed() {
+  binary_storage_tests::crc32_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_returns_err_when_range_is_bad() {
+  binary_storage_tests::crc32_returns_err_when_range_is_bad(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_matches_known_pattern() {
+  binary_storage_tests::crc32_matches_known_pattern(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_changes_after_single_byte_mutation() {
+  binary_storage_tests::crc32_changes_after_single_byte_mutation(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// find_bytes() tests
+#[test]
+fn find_bytes_returns_err_when_closed() {
+  binary_storage_tests::find_bytes_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_start_past_capacity() {
+  binary_storage_tests::find_bytes_returns_err_when_start_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_pattern_empty() {
+  binary_storage_tests::find_bytes_returns_err_when_pattern_empty(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_start() {
+  binary_storage_tests::find_bytes_finds_pattern_at_start(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_first_occurrence_after_start() {
+  binary_storage_tests::find_bytes_finds_first_occurrence_after_start(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_across_page_boundary() {
+  binary_storage_tests::find_bytes_finds_pattern_across_page_boundary(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_end_of_capacity() {
+  binary_storage_tests::find_bytes_finds_pattern_at_end_of_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_none_when_pattern_absent() {
+  binary_storage_tests::find_bytes_returns_none_when_pattern_absent(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// get_expand_size() and set_expand_size() tests
+#[test]
+fn get_expand_size_returns_initial_expand_size() {
+  binary_storage_tests::get_expand_size_returns_initial_expand_size(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_err_when_expand_size_is_zero() {
+  binary_storage_tests::set_expand_size_returns_err_when_expand_size_is_zero(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_does_not_change_expand_size_when_expand_size_is_zero() {
+  binary_storage_tests::set_expand_size_does_not_change_expand_size_when_expand_size_is_zero(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_err_when_expand_size_is_not_power_of_2() {
+  binary_storage_tests::set_expand_size_returns_err_when_expand_size_is_not_power_of_2(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_does_not_change_expand_size_when_expand_size_is_not_power_of_2() {
+  binary_storage_tests::set_expand_size_does_not_change_expand_size_when_expand_size_is_not_power_of_2(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_true_when_checks_pass() {
+  binary_storage_tests::set_expand_size_returns_true_when_checks_pass(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_changes_expand_size_when_checks_pass() {
+  binary_storage_tests::set_expand_size_changes_expand_size_when_checks_pass(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn capacity_increases_to_increments_of_last_set_expand_size() {
+  binary_storage_tests::capacity_increases_to_increments_of_last_set_expand_size(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn capacity_increases_to_increments_of_larger_expand_size() {
+  binary_storage_tests::capacity_increases_to_increments_of_larger_expand_size(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// get_capacity() tests
+#[test]
+fn get_capacity_returns_err_when_closed() {
+  binary_storage_tests::get_capacity_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn get_capacity_returns_initial_capacity_when_open() {
+  binary_storage_tests::get_capacity_returns_initial_capacity_when_open(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn get_capacity_returns_new_capacity_after_expansion() {
+  binary_storage_tests::get_capacity_returns_new_capacity_after_expansion(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// expand() tests
+#[test]
+fn expand_returns_err_when_closed() {
+  binary_storage_tests::expand_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_closed() {
+  binary_storage_tests::expand_does_not_change_capacity_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_ok_when_already_has_capacity() {
+  binary_storage_tests::expand_returns_ok_when_already_has_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_already_has_capacity() {
+  binary_storage_tests::expand_does_not_change_capacity_when_already_has_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_err_when_allocation_arithmetic_overflows() {
+  binary_storage_tests::expand_returns_err_when_allocation_arithmetic_overflows(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_allocation_arithmetic_overflows() {
+  binary_storage_tests::expand_does_not_change_capacity_when_allocation_arithmetic_overflows(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_err_when_allocation_fails() {
+  binary_storage_tests::expand_returns_err_when_allocation_fails(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_allocation_fails() {
+  binary_storage_tests::expand_does_not_change_capacity_when_allocation_fails(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_ok_when_successful() {
+  binary_storage_tests::expand_returns_ok_when_successful(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_changes_capacity_by_expand_size_when_successful() {
+  binary_storage_tests::expand_changes_capacity_by_expand_size_when_successful(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_changes_capacity_by_multiples_of_expand_size_when_successful() {
+  binary_storage_tests::expand_changes_capacity_by_multiples_of_expand_size_when_successful(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// read_all() tests
+#[test]
+fn read_all_returns_err_when_closed() {
+  binary_storage_tests::read_all_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn read_all_returns_capacity_length_bytes() {
+  binary_storage_tests::read_all_returns_capacity_length_bytes(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn read_all_returns_written_data_and_zero_fill() {
+  binary_storage_tests::read_all_returns_written_data_and_zero_fill(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// pread() and pwrite() tests
+#[test]
+fn pread_returns_err_when_closed() {
+  binary_storage_tests::pread_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn pwrite_returns_err_when_closed() {
+  binary_storage_tests::pwrite_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn pread_returns_err_when_past_capacity() {
+  binary_storage_tests::pread_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn pwrite_over_capacity_expands_storage() {
+  binary_storage_tests::pwrite_over_capacity_expands_storage(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn pread_and_pwrite_are_positional() {
+  binary_storage_tests::pread_and_pwrite_are_positional(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// valid_len_from() tests
+#[test]
+fn valid_len_from_returns_err_when_closed() {
+  binary_storage_tests::valid_len_from_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn valid_len_from_returns_err_when_past_capacity() {
+  binary_storage_tests::valid_len_from_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn valid_len_from_returns_0_when_zero_filled() {
+  binary_storage_tests::valid_len_from_returns_0_when_zero_filled(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn valid_len_from_returns_written_extent() {
+  binary_storage_tests::valid_len_from_returns_written_extent(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// copy() tests
+#[test]
+fn copy_returns_err_when_closed() {
+  binary_storage_tests::copy_returns_err_when_closed(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_returns_err_when_source_past_capacity() {
+  binary_storage_tests::copy_returns_err_when_source_past_capacity(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_separate_range() {
+  binary_storage_tests::copy_copies_separate_range(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_forward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_forward_over_overlapping_range(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_backward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_backward_over_overlapping_range(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_expands_to_hold_destination() {
+  binary_storage_tests::copy_expands_to_hold_destination(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_of_nothing_changes_nothing() {
+  binary_storage_tests::copy_of_nothing_changes_nothing(
+    new_unchecked_storage(256, 256)
+  );
+}
+
+// swap_ranges() tests
+#[test]
+fn swap_ranges_returns_err_when_closed() {
+  binary_storage_tests::swap_ranges_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_ranges_overlap() {
+  binary_storage_tests::swap_ranges_returns_err_when_ranges_overlap(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_past_capacity() {
+  binary_storage_tests::swap_ranges_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_exchanges_contents() {
+  binary_storage_tests::swap_ranges_exchanges_contents(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_exchanges_adjacent_ranges() {
+  binary_storage_tests::swap_ranges_exchanges_adjacent_ranges(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// w_u32_array() and w_u64_array() tests
+#[test]
+fn w_u32_array_returns_err_when_closed() {
+  binary_storage_tests::w_u32_array_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u32_array_writes_values_read_back_one_at_a_time() {
+  binary_storage_tests::w_u32_array_writes_values_read_back_one_at_a_time(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u32_array_over_capacity_expands_storage() {
+  binary_storage_tests::w_u32_array_over_capacity_expands_storage(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u32_array_writes_nothing_for_empty_slice() {
+  binary_storage_tests::w_u32_array_writes_nothing_for_empty_slice(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u64_array_writes_values_read_back_one_at_a_time() {
+  binary_storage_tests::w_u64_array_writes_values_read_back_one_at_a_time(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// r_u32_array() and r_u64_array() tests
+#[test]
+fn r_u32_array_returns_err_when_closed() {
+  binary_storage_tests::r_u32_array_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u32_array_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u32_array_reads_values_written_one_at_a_time(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_returns_empty_for_0_count() {
+  binary_storage_tests::r_u32_array_returns_empty_for_0_count(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_array_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u64_array_reads_values_written_one_at_a_time(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// r_u64_opt() tests
+#[test]
+fn r_u64_opt_returns_err_when_closed() {
+  binary_storage_tests::r_u64_opt_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_opt_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_none_when_unwritten() {
+  binary_storage_tests::r_u64_opt_returns_none_when_unwritten(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_written() {
+  binary_storage_tests::r_u64_opt_returns_some_when_written(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_writing_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_writing_zero(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_filling_with_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_filling_with_zero(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_when_one_byte_written() {
+  binary_storage_tests::r_u64_opt_returns_some_when_one_byte_written(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+// w_uuid() and r_uuid() tests
+#[test]
+fn w_uuid_returns_err_when_closed() {
+  binary_storage_tests::w_uuid_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_closed() {
+  binary_storage_tests::r_uuid_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_past_capacity() {
+  binary_storage_tests::r_uuid_returns_err_when_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_and_r_uuid_round_trip() {
+  binary_storage_tests::w_uuid_and_r_uuid_round_trip(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_writes_raw_bytes() {
+  binary_storage_tests::w_uuid_writes_raw_bytes(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_over_capacity_expands_storage() {
+  binary_storage_tests::w_uuid_over_capacity_expands_storage(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_returns_err_when_end_overflows() {
+  binary_storage_tests::w_uuid_returns_err_when_end_overflows(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_returns_err_when_closed() {
+  binary_storage_tests::shrink_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_does_nothing_when_capacity_already_small() {
+  binary_storage_tests::shrink_does_nothing_when_capacity_already_small(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_reduces_capacity_to_multiple_of_expand_size() {
+  binary_storage_tests::shrink_reduces_capacity_to_multiple_of_expand_size(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_does_not_reduce_capacity_below_expand_size() {
+  binary_storage_tests::shrink_does_not_reduce_capacity_below_expand_size(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_keeps_data_below_new_capacity() {
+  binary_storage_tests::shrink_keeps_data_below_new_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_then_expand_reads_zeros_past_old_capacity() {
+  binary_storage_tests::shrink_then_expand_reads_zeros_past_old_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_returns_err_when_closed() {
+  binary_storage_tests::w_bytes_reporting_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_within_capacity() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_within_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_reports_expansion_past_capacity() {
+  binary_storage_tests::w_bytes_reporting_reports_expansion_past_capacity(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_after_expanding() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_after_expanding(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i8_r_i8_round_trip_signed_range() {
+  binary_storage_tests::w_i8_r_i8_round_trip_signed_range(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i16_r_i16_round_trip_signed_range() {
+  binary_storage_tests::w_i16_r_i16_round_trip_signed_range(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i32_r_i32_round_trip_signed_range() {
+  binary_storage_tests::w_i32_r_i32_round_trip_signed_range(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i64_r_i64_round_trip_signed_range() {
+  binary_storage_tests::w_i64_r_i64_round_trip_signed_range(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn sync_returns_err_when_closed() {
+  binary_storage_tests::sync_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn sync_keeps_written_data() {
+  binary_storage_tests::sync_keeps_written_data(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_returns_err_when_closed() {
+  binary_storage_tests::w_blob_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_closed() {
+  binary_storage_tests::r_blob_returns_err_when_closed(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_writes_length_prefix() {
+  binary_storage_tests::w_blob_writes_length_prefix(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_r_blob_round_trip_varying_sizes() {
+  binary_storage_tests::w_blob_r_blob_round_trip_varying_sizes(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_over_capacity_expands_storage() {
+  binary_storage_tests::w_blob_over_capacity_expands_storage(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_length_runs_past_end() {
+  binary_storage_tests::r_blob_returns_err_when_length_runs_past_end(
+    new_unchecked_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_returns_err_before_txn_boundary() {
+  let mut s = new_unchecked_storage(256, 512);
+  s.open().unwrap();
+  s.set_txn_boundary(16).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_WRITE_BEFORE_TXN_BOUNDARY,
+    s.w_blob(8, &[0x1]).unwrap_err().description()
+  );
+  assert_eq!(5, s.w_blob(16, &[0x1]).unwrap());
+}
+
+// set_strict_append() tests
+#[test]
+fn strict_append_defaults_to_false() {
+  let s = new_storage();
+  assert!(!s.get_strict_append());
+}
+
+#[test]
+fn writes_behind_cursor_allowed_when_not_strict() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(8, &[0x1, 0x2]).unwrap();
+  s.w_bytes(0, &[0x3]).unwrap();
+  assert_eq!(10, s.get_append_cursor());
+  assert_eq!(vec!(0x3), s.r_bytes(0, 1).unwrap());
+}
+
+#[test]
+fn strict_append_accepts_writes_in_order() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.set_strict_append(true);
+  s.open().unwrap();
+  s.w_u16(0, 0x0201).unwrap();
+  s.w_u32(2, 0x06050403).unwrap();
+  s.w_bytes(6, &[0x7, 0x8]).unwrap();
+  s.w_u8(10, 0x9).unwrap(); // Gaps are fine
+  assert_eq!(11, s.get_append_cursor());
+  assert_eq!(
+    vec!(0x1, 0x2, 0x3, 0x4, 0x5, 0x6, 0x7, 0x8, 0x0, 0x0, 0x9),
+    s.r_bytes(0, 11).unwrap()
+  );
+}
+
+#[test]
+fn strict_append_returns_err_on_write_behind_cursor() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.set_strict_append(true);
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  assert_eq!(
+    transactional_storage::ERR_NON_APPEND_WRITE,
+    s.w_bytes(2, &[0x9, 0x9]).unwrap_err().description()
+  );
+  assert_eq!(
+    transactional_storage::ERR_NON_APPEND_WRITE,
+    s.w_u8(3, 0x9).unwrap_err().description()
+  );
+  assert_eq!(
+    transactional_storage::ERR_NON_APPEND_WRITE,
+    s.fill(Some(0), Some(8), 0x9).unwrap_err().description()
+  );
+  assert_eq!(vec!(0x1, 0x2, 0x3, 0x4, 0x0), s.r_bytes(0, 5).unwrap());
+  assert_eq!(4, s.get_append_cursor());
+  assert!(s.w_bytes(4, &[0x5]).is_ok());
+}
+
+#[test]
+fn strict_append_cursor_not_moved_by_failed_write() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.set_strict_append(true);
+  s.open().unwrap();
+  s.set_txn_boundary(8).unwrap();
+  assert!(s.w_bytes(4, &[0x1]).is_err());
+  assert_eq!(8, s.get_append_cursor());
+  assert!(s.w_bytes(8, &[0x1]).is_ok());
+}
+
+#[test]
+fn set_txn_boundary_moves_append_cursor() {
+  let mut s = new_unchecked_storage(256, 256);
+  s.set_strict_append(true);
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3, 0x4]).unwrap();
+  s.set_txn_boundary(2).unwrap();
+  assert_eq!(2, s.get_append_cursor());
+  s.w_bytes(2, &[0x5, 0x6]).unwrap();
+  assert_eq!(vec!(0x1, 0x2, 0x5, 0x6), s.r_bytes(0, 4).unwrap());
+}
+
+#[test]
+fn journal_writes_in_strict_append_mode() {
+  let mut s = new_storage();
+  s.set_strict_append(true);
+  let mut j = Journal::new(s);
+  j.open().unwrap();
+  j.write(&[0x1, 0x2]).unwrap();
+  j.commit().unwrap();
+  j.write(&[0x3, 0x4]).unwrap();
+  j.discard().unwrap();
+  j.write(&[0x5, 0x6]).unwrap();
+  j.commit().unwrap();
+  assert_eq!(vec!(vec!(0x1, 0x2), vec!(0x5, 0x6)), j.collect::<Vec<Vec<u8>>>());
+}
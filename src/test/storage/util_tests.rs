@@ -1,4 +1,13 @@
-use storage::util::xor_checksum;
+use std::io;
+use std::time::Duration;
+
+use std::error::Error;
+
+use storage::util;
+use storage::binary_storage;
+use storage::util::{ 
+  xor_checksum, retry_io, crc32, crc32c, fnv1a, rle_compress, rle_decompress, ChecksumAlgo 
+};
 
 #[test]
 pub fn xor_checksum_xors_all_bytes() {
@@ -10,3 +19,191 @@ pub fn xor_checksum_xors_all_bytes() {
   assert_eq!(0xFF, xor_checksum(&[170, 85]));
 }
 
+#[test]
+pub fn retry_io_succeeds_within_retry_budget() {
+  let mut attempts = 0;
+  let res = retry_io(3, Duration::from_millis(1), || {
+    attempts += 1;
+    if attempts <= 2 { 
+      Err(io::Error::new(io::ErrorKind::TimedOut, "timed out")) 
+    } else { 
+      Ok(attempts) 
+    }
+  });
+  assert_eq!(3, res.unwrap());
+}
+
+#[test]
+pub fn retry_io_returns_last_err_when_retries_exhausted() {
+  let mut attempts = 0;
+  let res: Result<(), io::Error> = retry_io(2, Duration::from_millis(1), || {
+    attempts += 1;
+    Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted"))
+  });
+  assert_eq!(io::ErrorKind::Interrupted, res.unwrap_err().kind());
+  assert_eq!(3, attempts);
+}
+
+#[test]
+pub fn retry_io_does_not_retry_non_retryable_errs() {
+  let mut attempts = 0;
+  let res: Result<(), io::Error> = retry_io(5, Duration::from_millis(1), || {
+    attempts += 1;
+    Err(io::Error::new(io::ErrorKind::NotFound, "not found"))
+  });
+  assert_eq!(io::ErrorKind::NotFound, res.unwrap_err().kind());
+  assert_eq!(1, attempts);
+}
+
+#[test]
+pub fn crc32_matches_reference_values() {
+  assert_eq!(0x00000000, crc32(&[]));
+  assert_eq!(0xCBF43926, crc32(b"123456789"));
+}
+
+#[test]
+pub fn crc32c_matches_reference_values() {
+  assert_eq!(0x00000000, crc32c(&[]));
+  assert_eq!(0xE3069283, crc32c(b"123456789"));
+}
+
+#[test]
+pub fn fnv1a_matches_reference_values() {
+  assert_eq!(0x811C9DC5, fnv1a(&[]));
+  assert_eq!(0xE40C292C, fnv1a(b"a"));
+  assert_eq!(0xBF9CF968, fnv1a(b"foobar"));
+}
+
+#[test]
+pub fn checksum_algo_round_trips_ids() {
+  for algo in vec!(
+    ChecksumAlgo::Xor, 
+    ChecksumAlgo::Crc32, 
+    ChecksumAlgo::Crc32c, 
+    ChecksumAlgo::Fnv
+  ) {
+    assert_eq!(Some(algo), ChecksumAlgo::from_id(algo.id()));
+  }
+  assert_eq!(None, ChecksumAlgo::from_id(4));
+}
+
+#[test]
+pub fn checksum_algo_uses_selected_algorithm() {
+  assert_eq!(0x04, ChecksumAlgo::Xor.checksum(&[0x05, 0x06, 0x07]));
+  assert_eq!(0xCBF43926, ChecksumAlgo::Crc32.checksum(b"123456789"));
+  assert_eq!(0xE3069283, ChecksumAlgo::Crc32c.checksum(b"123456789"));
+  assert_eq!(0xBF9CF968, ChecksumAlgo::Fnv.checksum(b"foobar"));
+}
+
+#[test]
+pub fn rle_compress_encodes_runs() {
+  assert_eq!(Vec::<u8>::new(), rle_compress(&[]));
+  assert_eq!(vec!(3, 0x7), rle_compress(&[0x7, 0x7, 0x7]));
+  assert_eq!(
+    vec!(1, 0x1, 2, 0x2, 1, 0x1),
+    rle_compress(&[0x1, 0x2, 0x2, 0x1])
+  );
+}
+
+#[test]
+pub fn rle_compress_splits_runs_longer_than_255() {
+  assert_eq!(vec!(255, 0x9, 45, 0x9), rle_compress(vec![0x9; 300].as_slice()));
+}
+
+#[test]
+pub fn rle_decompress_round_trips() {
+  let data = vec!(0x1, 0x1, 0x2, 0x3, 0x3, 0x3, 0x0, 0x0);
+  assert_eq!(data, rle_decompress(rle_compress(data.as_slice()).as_slice()).unwrap());
+  let long = vec![0x5; 1000];
+  assert_eq!(long, rle_decompress(rle_compress(long.as_slice()).as_slice()).unwrap());
+}
+
+#[test]
+pub fn rle_decompress_returns_err_when_truncated() {
+  assert_eq!(
+    util::ERR_RLE_DATA_TRUNCATED,
+    rle_decompress(&[3, 0x7, 2]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn required_capacity_is_0_for_no_writes() {
+  assert_eq!(0, util::required_capacity(&[]).unwrap());
+}
+
+#[test]
+pub fn required_capacity_is_furthest_write_end() {
+  assert_eq!(20, util::required_capacity(&[(0, 8), (16, 4), (8, 8)]).unwrap());
+  // Overlapping writes
+  assert_eq!(12, util::required_capacity(&[(4, 8), (0, 10), (6, 2)]).unwrap());
+  // Non-contiguous writes, with a gap before the furthest
+  assert_eq!(1004, util::required_capacity(&[(1000, 4), (0, 1)]).unwrap());
+  // Empty writes still count their offset
+  assert_eq!(300, util::required_capacity(&[(300, 0), (0, 16)]).unwrap());
+}
+
+#[test]
+pub fn required_capacity_returns_err_on_overflow() {
+  assert_eq!(
+    binary_storage::ERR_ARITHMETIC_OVERFLOW,
+    util::required_capacity(&[(0, 8), (usize::max_value(), 1)]).unwrap_err().description()
+  );
+}
+
+#[test]
+pub fn u128_le_bytes_put_low_byte_first() {
+  let mut expected = [0x0; 16];
+  expected[0] = 0x1;
+  expected[15] = 0x80;
+  assert_eq!(expected, util::u128_to_le_bytes(1 | 1 << 127));
+  assert_eq!(1 | 1 << 127, util::u128_from_le_bytes(&expected));
+}
+
+#[test]
+pub fn u128_le_bytes_round_trip() {
+  for n in vec!(0, 1, 0x0102030405060708090A0B0C0D0E0F10, u128::max_value()) {
+    assert_eq!(n, util::u128_from_le_bytes(&util::u128_to_le_bytes(n)));
+  }
+}
+
+#[test]
+pub fn add_range_merges_overlapping_and_touching_ranges() {
+  let mut r = Vec::new();
+  util::add_range(&mut r, 10, 20);
+  util::add_range(&mut r, 30, 40);
+  assert_eq!(vec!((10, 20), (30, 40)), r);
+  util::add_range(&mut r, 0, 5);
+  assert_eq!(vec!((0, 5), (10, 20), (30, 40)), r);
+  util::add_range(&mut r, 20, 30);
+  assert_eq!(vec!((0, 5), (10, 40)), r);
+  util::add_range(&mut r, 3, 50);
+  assert_eq!(vec!((0, 50)), r);
+  util::add_range(&mut r, 60, 60);
+  assert_eq!(vec!((0, 50)), r);
+}
+
+#[test]
+pub fn overlaps_range_ignores_touching_ranges() {
+  let r = vec!((10, 20), (30, 40));
+  assert!(util::overlaps_range(r.as_slice(), 15, 16));
+  assert!(util::overlaps_range(r.as_slice(), 0, 11));
+  assert!(util::overlaps_range(r.as_slice(), 19, 31));
+  assert!(!util::overlaps_range(r.as_slice(), 20, 30));
+  assert!(!util::overlaps_range(r.as_slice(), 0, 10));
+  assert!(!util::overlaps_range(r.as_slice(), 40, 48));
+}
+
+#[test]
+pub fn cut_range_removes_bytes_and_moves_later_ranges_down() {
+  let mut r = vec!((0, 10), (20, 30), (40, 50));
+  util::cut_range(&mut r, 5, 20);
+  assert_eq!(vec!((0, 10), (20, 30)), r);
+
+  let mut r = vec!((0, 10), (20, 30));
+  util::cut_range(&mut r, 12, 4);
+  assert_eq!(vec!((0, 10), (16, 26)), r);
+
+  let mut r = vec!((0, 10), (20, 30));
+  util::cut_range(&mut r, 8, usize::max_value() - 8);
+  assert_eq!(vec!((0, 8)), r);
+}
@@ -0,0 +1,1747 @@
+use std::fs;
+use std::io::Read;
+use std::error::Error;
+
+use uuid::Uuid;
+
+use test::storage::binary_storage_tests;
+use storage::xor_storage;
+use storage::xor_storage::XorStorage;
+use storage::binary_storage::BinaryStorage;
+use storage::memory_binary_storage::MemoryBinaryStorage;
+use storage::file_binary_storage::FileBinaryStorage;
+
+pub static BASE_PATH: &'static str = "./test_data/storage/file_binary_storage/";
+
+// Odd length so that it doesn't line up with any value size
+static KEY: [u8; 5] = [0x5a, 0xc3, 0x17, 0x88, 0x3e];
+
+fn rnd_path() -> String {
+  BASE_PATH.to_string() 
+    + Uuid::new_v4().simple().to_string().as_str()
+    + ".tmp"
+}
+
+fn new_storage(
+  initial_capacity: usize, 
+  expand_size: usize
+) -> XorStorage<MemoryBinaryStorage> {
+  XorStorage::new(
+    MemoryBinaryStorage::new(initial_capacity, expand_size).unwrap(),
+    &KEY
+  ).unwrap()
+}
+
+
+// new() and get_storage() tests
+#[test]
+pub fn new_returns_err_for_empty_key() {
+  let res = XorStorage::new(MemoryBinaryStorage::new(256, 256).unwrap(), &[]);
+  assert_eq!(
+    xor_storage::ERR_EMPTY_XOR_KEY,
+    res.err().unwrap().description()
+  );
+}
+
+#[test]
+pub fn underlying_storage_does_not_hold_plaintext() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_str(3, "plaintext").unwrap();
+  s.w_u64(40, 0x0102030405060708).unwrap();
+
+  let raw = s.get_storage().r_bytes(3, 9).unwrap();
+  assert!(raw.as_slice() != "plaintext".as_bytes());
+  for (i, b) in raw.iter().enumerate() {
+    assert_eq!("plaintext".as_bytes()[i] ^ KEY[(3 + i) % KEY.len()], *b);
+  }
+  assert!(s.get_storage().r_u64(40).unwrap() != 0x0102030405060708);
+
+  assert_eq!("plaintext", s.r_str(3, 9).unwrap());
+  assert_eq!(0x0102030405060708, s.r_u64(40).unwrap());
+}
+
+#[test]
+pub fn same_bytes_encode_differently_at_different_offsets() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.w_bytes(1, &[0x1, 0x2, 0x3]).unwrap();
+  s.w_bytes(10, &[0x1, 0x2, 0x3]).unwrap();
+  assert_eq!(vec!(0x1, 0x1, 0x2, 0x3), s.r_bytes(0, 4).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(10, 3).unwrap());
+  assert!(
+    s.get_storage().r_bytes(1, 3).unwrap() != 
+    s.get_storage().r_bytes(10, 3).unwrap()
+  );
+}
+
+#[test]
+pub fn unwritten_storage_reads_as_key() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  assert_eq!(KEY.to_vec(), s.r_bytes(0, 5).unwrap());
+  assert_eq!(KEY[255 % KEY.len()], s.r_u8(255).unwrap());
+  s.fill(None, None, 0x0).unwrap();
+  assert!(s.is_filled(None, None, 0x0).unwrap());
+  assert_eq!(0, s.valid_len_from(0).unwrap());
+}
+
+#[test]
+pub fn file_never_contains_plaintext() {
+  let path = rnd_path();
+  let mut s = XorStorage::new(
+    FileBinaryStorage::new(path.clone(), true, 256, 16, 16, 256).unwrap(),
+    &KEY
+  ).unwrap();
+  s.open().unwrap();
+  s.w_str(0, "secret secret secret").unwrap();
+  s.w_blob(64, "another secret".as_bytes()).unwrap();
+  s.close().unwrap();
+
+  let mut raw = Vec::new();
+  fs::File::open(path.as_str()).unwrap().read_to_end(&mut raw).unwrap();
+  assert!(!raw.windows(6).any(|w| w == "secret".as_bytes()));
+
+  let mut s = XorStorage::new(
+    FileBinaryStorage::new(path.clone(), false, 256, 16, 16, 256).unwrap(),
+    &KEY
+  ).unwrap();
+  s.open().unwrap();
+  assert_eq!("secret secret secret", s.r_str(0, 20).unwrap());
+  assert_eq!("another secret".as_bytes().to_vec(), s.r_blob(64).unwrap());
+  s.close().unwrap();
+  fs::remove_file(path).unwrap();
+}
+
+#[test]
+pub fn typed_values_round_trip() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_i8(0, -3).unwrap();
+  s.w_i16(1, -300).unwrap();
+  s.w_i32(3, -70000).unwrap();
+  s.w_i64(7, i64::min_value()).unwrap();
+  s.w_u16(15, 0xbeef).unwrap();
+  s.w_u32(17, 0xdeadbeef).unwrap();
+  s.w_f32(21, 1.5).unwrap();
+  s.w_f64(25, -2.25).unwrap();
+  s.w_bool(33, true).unwrap();
+  s.w_u32_array(34, &[1, 2, 3]).unwrap();
+  s.w_u64_array(46, &[u64::max_value(), 0]).unwrap();
+  let id = Uuid::new_v4();
+  s.w_uuid(62, id).unwrap();
+
+  assert_eq!(-3, s.r_i8(0).unwrap());
+  assert_eq!(-300, s.r_i16(1).unwrap());
+  assert_eq!(-70000, s.r_i32(3).unwrap());
+  assert_eq!(i64::min_value(), s.r_i64(7).unwrap());
+  assert_eq!(0xbeef, s.r_u16(15).unwrap());
+  assert_eq!(0xdeadbeef, s.r_u32(17).unwrap());
+  assert_eq!(1.5, s.r_f32(21).unwrap());
+  assert_eq!(-2.25, s.r_f64(25).unwrap());
+  assert!(s.r_bool(33).unwrap());
+  assert_eq!(vec!(1, 2, 3), s.r_u32_array(34, 3).unwrap());
+  assert_eq!(vec!(u64::max_value(), 0), s.r_u64_array(46, 2).unwrap());
+  assert_eq!(Some(0), s.r_u64_opt(54).unwrap());
+  assert_eq!(id, s.r_uuid(62).unwrap());
+}
+
+#[test]
+pub fn swap_ranges_reencodes_for_new_offsets() {
+  let mut s = new_storage(256, 256);
+  s.open().unwrap();
+  s.w_bytes(0, &[0x1, 0x2, 0x3]).unwrap();
+  s.w_bytes(7, &[0x4, 0x5, 0x6]).unwrap();
+  s.swap_ranges(0, 7, 3).unwrap();
+  assert_eq!(vec!(0x4, 0x5, 0x6), s.r_bytes(0, 3).unwrap());
+  assert_eq!(vec!(0x1, 0x2, 0x3), s.r_bytes(7, 3).unwrap());
+}
+
+
+// Generic BinaryStorage tests. Those that expect never-written space to read 
+// as zeros are left out, since it reads as the key through the wrapper.
+
+// open(), close(), and is_open() tests
+#[test]
+fn open_returns_err_when_already_open() {
+  binary_storage_tests::open_returns_err_when_already_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn close_returns_err_when_already_closed() {
+  binary_storage_tests::close_returns_err_when_already_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn open_returns_ok_when_previously_closed() {
+  binary_storage_tests::open_returns_ok_when_previously_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn close_returns_ok_when_previously_open() {
+  binary_storage_tests::close_returns_ok_when_previously_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_closed_when_new() {
+  binary_storage_tests::is_closed_when_new(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_open_after_open() {
+  binary_storage_tests::is_open_after_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn is_closed_after_open_and_close() {
+  binary_storage_tests::is_closed_after_open_and_close(
+    new_storage(256, 256)
+  );
+}
+
+// w_i8() tests
+#[test]
+fn w_i8_returns_err_when_closed() {
+  binary_storage_tests::w_i8_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i8_returns_ok_when_open() {
+  binary_storage_tests::w_i8_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i8_over_capacity_expands_storage() {
+  binary_storage_tests::w_i8_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_i16() tests
+#[test]
+fn w_i16_returns_err_when_closed() {
+  binary_storage_tests::w_i16_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i16_returns_ok_when_open() {
+  binary_storage_tests::w_i16_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i16_over_capacity_expands_storage() {
+  binary_storage_tests::w_i16_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_i32() tests
+#[test]
+fn w_i32_returns_err_when_closed() {
+  binary_storage_tests::w_i32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i32_returns_ok_when_open() {
+  binary_storage_tests::w_i32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i32_over_capacity_expands_storage() {
+  binary_storage_tests::w_i32_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_i64() tests
+#[test]
+fn w_i64_returns_err_when_closed() {
+  binary_storage_tests::w_i64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i64_returns_ok_when_open() {
+  binary_storage_tests::w_i64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_i64_over_capacity_expands_storage() {
+  binary_storage_tests::w_i64_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_u8() tests
+#[test]
+fn w_u8_returns_err_when_closed() {
+  binary_storage_tests::w_u8_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u8_returns_ok_when_open() {
+  binary_storage_tests::w_u8_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u8_over_capacity_expands_storage() {
+  binary_storage_tests::w_u8_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_u16() tests
+#[test]
+fn w_u16_returns_err_when_closed() {
+  binary_storage_tests::w_u16_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u16_returns_ok_when_open() {
+  binary_storage_tests::w_u16_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u16_over_capacity_expands_storage() {
+  binary_storage_tests::w_u16_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_u32() tests
+#[test]
+fn w_u32_returns_err_when_closed() {
+  binary_storage_tests::w_u32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u32_returns_ok_when_open() {
+  binary_storage_tests::w_u32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u32_over_capacity_expands_storage() {
+  binary_storage_tests::w_u32_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_u64() tests
+#[test]
+fn w_u64_returns_err_when_closed() {
+  binary_storage_tests::w_u64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u64_returns_ok_when_open() {
+  binary_storage_tests::w_u64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_u64_over_capacity_expands_storage() {
+  binary_storage_tests::w_u64_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_f32() tests
+#[test]
+fn w_f32_returns_err_when_closed() {
+  binary_storage_tests::w_f32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f32_returns_ok_when_open() {
+  binary_storage_tests::w_f32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f32_over_capacity_expands_storage() {
+  binary_storage_tests::w_f32_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_f64() tests
+#[test]
+fn w_f64_returns_err_when_closed() {
+  binary_storage_tests::w_f64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f64_returns_ok_when_open() {
+  binary_storage_tests::w_f64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_f64_over_capacity_expands_storage() {
+  binary_storage_tests::w_f64_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_bool() tests
+#[test]
+fn w_bool_returns_err_when_closed() {
+  binary_storage_tests::w_bool_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bool_returns_ok_when_open() {
+  binary_storage_tests::w_bool_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bool_over_capacity_expands_storage() {
+  binary_storage_tests::w_bool_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+// w_bytes() tests
+#[test]
+fn w_bytes_returns_err_when_closed() {
+  binary_storage_tests::w_bytes_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_returns_ok_when_open() {
+  binary_storage_tests::w_bytes_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_over_capacity_expands_storage() {
+  binary_storage_tests::w_bytes_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_bytes_over_capacity_expands_storage_multiple_times() {
+  binary_storage_tests::w_bytes_over_capacity_expands_storage_multiple_times(
+    new_storage(256, 4)
+  );
+}
+
+// w_str() tests
+#[test]
+fn w_str_returns_err_when_closed() {
+  binary_storage_tests::w_str_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_returns_ok_when_open() {
+  binary_storage_tests::w_str_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_over_capacity_expands_storage() {
+  binary_storage_tests::w_str_over_capacity_expands_storage(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn w_str_over_capacity_expands_storage_multiple_times() {
+  binary_storage_tests::w_str_over_capacity_expands_storage_multiple_times(
+    new_storage(256, 4)
+  );
+}
+
+// r_i8() tests
+#[test]
+fn r_i8_returns_err_when_closed() {
+  binary_storage_tests::r_i8_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_returns_ok_when_open() {
+  binary_storage_tests::r_i8_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_reads_written_data() {
+  binary_storage_tests::r_i8_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_does_not_read_past_capacity() {
+  binary_storage_tests::r_i8_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i8_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i8_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_i16() tests
+#[test]
+fn r_i16_returns_err_when_closed() {
+  binary_storage_tests::r_i16_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_returns_ok_when_open() {
+  binary_storage_tests::r_i16_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_reads_written_data() {
+  binary_storage_tests::r_i16_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_does_not_read_past_capacity() {
+  binary_storage_tests::r_i16_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i16_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i16_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_i32() tests
+#[test]
+fn r_i32_returns_err_when_closed() {
+  binary_storage_tests::r_i32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_returns_ok_when_open() {
+  binary_storage_tests::r_i32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_reads_written_data() {
+  binary_storage_tests::r_i32_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_does_not_read_past_capacity() {
+  binary_storage_tests::r_i32_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i32_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_i64() tests
+#[test]
+fn r_i64_returns_err_when_closed() {
+  binary_storage_tests::r_i64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_returns_ok_when_open() {
+  binary_storage_tests::r_i64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_reads_written_data() {
+  binary_storage_tests::r_i64_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_does_not_read_past_capacity() {
+  binary_storage_tests::r_i64_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_i64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_i64_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_u8() tests
+#[test]
+fn r_u8_returns_err_when_closed() {
+  binary_storage_tests::r_u8_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_returns_ok_when_open() {
+  binary_storage_tests::r_u8_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_reads_written_data() {
+  binary_storage_tests::r_u8_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_does_not_read_past_capacity() {
+  binary_storage_tests::r_u8_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u8_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u8_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_u16() tests
+#[test]
+fn r_u16_returns_err_when_closed() {
+  binary_storage_tests::r_u16_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_returns_ok_when_open() {
+  binary_storage_tests::r_u16_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_reads_written_data() {
+  binary_storage_tests::r_u16_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_does_not_read_past_capacity() {
+  binary_storage_tests::r_u16_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u16_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u16_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_u32() tests
+#[test]
+fn r_u32_returns_err_when_closed() {
+  binary_storage_tests::r_u32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_returns_ok_when_open() {
+  binary_storage_tests::r_u32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_reads_written_data() {
+  binary_storage_tests::r_u32_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_does_not_read_past_capacity() {
+  binary_storage_tests::r_u32_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u32_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_u64() tests
+#[test]
+fn r_u64_returns_err_when_closed() {
+  binary_storage_tests::r_u64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_returns_ok_when_open() {
+  binary_storage_tests::r_u64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_reads_written_data() {
+  binary_storage_tests::r_u64_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_does_not_read_past_capacity() {
+  binary_storage_tests::r_u64_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_u64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_u64_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_f32() tests
+#[test]
+fn r_f32_returns_err_when_closed() {
+  binary_storage_tests::r_f32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_returns_ok_when_open() {
+  binary_storage_tests::r_f32_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_reads_written_data() {
+  binary_storage_tests::r_f32_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_does_not_read_past_capacity() {
+  binary_storage_tests::r_f32_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f32_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_f32_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_f64() tests
+#[test]
+fn r_f64_returns_err_when_closed() {
+  binary_storage_tests::r_f64_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_returns_ok_when_open() {
+  binary_storage_tests::r_f64_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_reads_written_data() {
+  binary_storage_tests::r_f64_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_does_not_read_past_capacity() {
+  binary_storage_tests::r_f64_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_f64_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_f64_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_bool() tests
+#[test]
+fn r_bool_returns_err_when_closed() {
+  binary_storage_tests::r_bool_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_returns_ok_when_open() {
+  binary_storage_tests::r_bool_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_reads_written_data() {
+  binary_storage_tests::r_bool_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_does_not_read_past_capacity() {
+  binary_storage_tests::r_bool_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bool_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_bool_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_bytes() tests
+#[test]
+fn r_bytes_returns_err_when_closed() {
+  binary_storage_tests::r_bytes_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_returns_ok_when_open() {
+  binary_storage_tests::r_bytes_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_reads_written_data() {
+  binary_storage_tests::r_bytes_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_does_not_read_past_capacity() {
+  binary_storage_tests::r_bytes_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_bytes_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// r_bytes_into() tests
+#[test]
+fn r_bytes_into_returns_err_when_closed() {
+  binary_storage_tests::r_bytes_into_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_fills_buffer_with_written_data() {
+  binary_storage_tests::r_bytes_into_fills_buffer_with_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_reads_part_of_written_data() {
+  binary_storage_tests::r_bytes_into_reads_part_of_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_matches_r_bytes() {
+  binary_storage_tests::r_bytes_into_matches_r_bytes(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_with_empty_buffer_reads_nothing() {
+  binary_storage_tests::r_bytes_into_with_empty_buffer_reads_nothing(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_bytes_into_does_not_read_past_capacity() {
+  binary_storage_tests::r_bytes_into_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+// r_str() tests
+#[test]
+fn r_str_returns_err_when_closed() {
+  binary_storage_tests::r_str_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_reads_written_data() {
+  binary_storage_tests::r_str_reads_written_data(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_does_not_read_past_capacity() {
+  binary_storage_tests::r_str_does_not_read_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn r_str_result_is_not_mutated_on_subsequent_write() {
+  binary_storage_tests::r_str_result_is_not_mutated_on_subsequent_write(
+    new_storage(256, 256)
+  );
+}
+
+// fill() tests
+#[test]
+fn fill_returns_err_when_closed() {
+  binary_storage_tests::fill_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_ok_when_open() {
+  binary_storage_tests::fill_returns_ok_when_open(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_err_when_end_offset_is_before_start_offset() {
+  binary_storage_tests::fill_returns_err_when_end_offset_is_before_start_offset(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_returns_err_when_past_capacity() {
+  binary_storage_tests::fill_returns_err_when_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn fill_does_not_expand_capacity() {
+  binary_storage_tests::fill_does_not_expand_capacity(
+    new_storage(256, 256)
+  );
+}
+
+// assert_filled() tests
+#[test]
+fn is_filled_retuns_err_when_closed() {
+  binary_storage_tests::is_filled_retuns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+// crc32() tests
+#[test]
+fn crc32_returns_err_when_closed() {
+  binary_storage_tests::crc32_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_returns_err_when_range_is_bad() {
+  binary_storage_tests::crc32_returns_err_when_range_is_bad(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_matches_known_pattern() {
+  binary_storage_tests::crc32_matches_known_pattern(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn crc32_changes_after_single_byte_mutation() {
+  binary_storage_tests::crc32_changes_after_single_byte_mutation(
+    new_storage(256, 256)
+  );
+}
+
+// find_bytes() tests
+#[test]
+fn find_bytes_returns_err_when_closed() {
+  binary_storage_tests::find_bytes_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_start_past_capacity() {
+  binary_storage_tests::find_bytes_returns_err_when_start_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_err_when_pattern_empty() {
+  binary_storage_tests::find_bytes_returns_err_when_pattern_empty(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_start() {
+  binary_storage_tests::find_bytes_finds_pattern_at_start(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_first_occurrence_after_start() {
+  binary_storage_tests::find_bytes_finds_first_occurrence_after_start(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_across_page_boundary() {
+  binary_storage_tests::find_bytes_finds_pattern_across_page_boundary(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_finds_pattern_at_end_of_capacity() {
+  binary_storage_tests::find_bytes_finds_pattern_at_end_of_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn find_bytes_returns_none_when_pattern_absent() {
+  binary_storage_tests::find_bytes_returns_none_when_pattern_absent(
+    new_storage(256, 256)
+  );
+}
+
+// get_expand_size() and set_expand_size() tests
+#[test]
+fn get_expand_size_returns_initial_expand_size() {
+  binary_storage_tests::get_expand_size_returns_initial_expand_size(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_err_when_expand_size_is_zero() {
+  binary_storage_tests::set_expand_size_returns_err_when_expand_size_is_zero(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_does_not_change_expand_size_when_expand_size_is_zero() {
+  binary_storage_tests::set_expand_size_does_not_change_expand_size_when_expand_size_is_zero(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_err_when_expand_size_is_not_power_of_2() {
+  binary_storage_tests::set_expand_size_returns_err_when_expand_size_is_not_power_of_2(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_does_not_change_expand_size_when_expand_size_is_not_power_of_2() {
+  binary_storage_tests::set_expand_size_does_not_change_expand_size_when_expand_size_is_not_power_of_2(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_returns_true_when_checks_pass() {
+  binary_storage_tests::set_expand_size_returns_true_when_checks_pass(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn set_expand_size_changes_expand_size_when_checks_pass() {
+  binary_storage_tests::set_expand_size_changes_expand_size_when_checks_pass(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn capacity_increases_to_increments_of_last_set_expand_size() {
+  binary_storage_tests::capacity_increases_to_increments_of_last_set_expand_size(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn capacity_increases_to_increments_of_larger_expand_size() {
+  binary_storage_tests::capacity_increases_to_increments_of_larger_expand_size(
+    new_storage(256, 512)
+  );
+}
+
+// get_capacity() tests
+#[test]
+fn get_capacity_returns_err_when_closed() {
+  binary_storage_tests::get_capacity_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn get_capacity_returns_initial_capacity_when_open() {
+  binary_storage_tests::get_capacity_returns_initial_capacity_when_open(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn get_capacity_returns_new_capacity_after_expansion() {
+  binary_storage_tests::get_capacity_returns_new_capacity_after_expansion(
+    new_storage(256, 512)
+  );
+}
+
+// expand() tests
+#[test]
+fn expand_returns_err_when_closed() {
+  binary_storage_tests::expand_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_closed() {
+  binary_storage_tests::expand_does_not_change_capacity_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_ok_when_already_has_capacity() {
+  binary_storage_tests::expand_returns_ok_when_already_has_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_already_has_capacity() {
+  binary_storage_tests::expand_does_not_change_capacity_when_already_has_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_err_when_allocation_arithmetic_overflows() {
+  binary_storage_tests::expand_returns_err_when_allocation_arithmetic_overflows(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_allocation_arithmetic_overflows() {
+  binary_storage_tests::expand_does_not_change_capacity_when_allocation_arithmetic_overflows(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_err_when_allocation_fails() {
+  binary_storage_tests::expand_returns_err_when_allocation_fails(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_does_not_change_capacity_when_allocation_fails() {
+  binary_storage_tests::expand_does_not_change_capacity_when_allocation_fails(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_returns_ok_when_successful() {
+  binary_storage_tests::expand_returns_ok_when_successful(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_changes_capacity_by_expand_size_when_successful() {
+  binary_storage_tests::expand_changes_capacity_by_expand_size_when_successful(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn expand_changes_capacity_by_multiples_of_expand_size_when_successful() {
+  binary_storage_tests::expand_changes_capacity_by_multiples_of_expand_size_when_successful(
+    new_storage(256, 512)
+  );
+}
+
+// read_all() tests
+#[test]
+fn read_all_returns_err_when_closed() {
+  binary_storage_tests::read_all_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn read_all_returns_capacity_length_bytes() {
+  binary_storage_tests::read_all_returns_capacity_length_bytes(
+    new_storage(256, 512)
+  );
+}
+
+// pread() and pwrite() tests
+#[test]
+fn pread_returns_err_when_closed() {
+  binary_storage_tests::pread_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn pwrite_returns_err_when_closed() {
+  binary_storage_tests::pwrite_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn pread_returns_err_when_past_capacity() {
+  binary_storage_tests::pread_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn pwrite_over_capacity_expands_storage() {
+  binary_storage_tests::pwrite_over_capacity_expands_storage(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn pread_and_pwrite_are_positional() {
+  binary_storage_tests::pread_and_pwrite_are_positional(
+    new_storage(256, 512)
+  );
+}
+
+// valid_len_from() tests
+#[test]
+fn valid_len_from_returns_err_when_closed() {
+  binary_storage_tests::valid_len_from_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn valid_len_from_returns_err_when_past_capacity() {
+  binary_storage_tests::valid_len_from_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+// copy() tests
+#[test]
+fn copy_returns_err_when_closed() {
+  binary_storage_tests::copy_returns_err_when_closed(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_returns_err_when_source_past_capacity() {
+  binary_storage_tests::copy_returns_err_when_source_past_capacity(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_separate_range() {
+  binary_storage_tests::copy_copies_separate_range(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_forward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_forward_over_overlapping_range(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_copies_backward_over_overlapping_range() {
+  binary_storage_tests::copy_copies_backward_over_overlapping_range(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_expands_to_hold_destination() {
+  binary_storage_tests::copy_expands_to_hold_destination(
+    new_storage(256, 256)
+  );
+}
+
+#[test]
+fn copy_of_nothing_changes_nothing() {
+  binary_storage_tests::copy_of_nothing_changes_nothing(
+    new_storage(256, 256)
+  );
+}
+
+// swap_ranges() tests
+#[test]
+fn swap_ranges_returns_err_when_closed() {
+  binary_storage_tests::swap_ranges_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_ranges_overlap() {
+  binary_storage_tests::swap_ranges_returns_err_when_ranges_overlap(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_returns_err_when_past_capacity() {
+  binary_storage_tests::swap_ranges_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn swap_ranges_exchanges_adjacent_ranges() {
+  binary_storage_tests::swap_ranges_exchanges_adjacent_ranges(
+    new_storage(256, 512)
+  );
+}
+
+// w_u32_array() and w_u64_array() tests
+#[test]
+fn w_u32_array_returns_err_when_closed() {
+  binary_storage_tests::w_u32_array_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u32_array_over_capacity_expands_storage() {
+  binary_storage_tests::w_u32_array_over_capacity_expands_storage(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_u64_array_writes_values_read_back_one_at_a_time() {
+  binary_storage_tests::w_u64_array_writes_values_read_back_one_at_a_time(
+    new_storage(256, 512)
+  );
+}
+
+// r_u32_array() and r_u64_array() tests
+#[test]
+fn r_u32_array_returns_err_when_closed() {
+  binary_storage_tests::r_u32_array_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u32_array_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u32_array_reads_values_written_one_at_a_time(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u32_array_returns_empty_for_0_count() {
+  binary_storage_tests::r_u32_array_returns_empty_for_0_count(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_array_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_array_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_array_reads_values_written_one_at_a_time() {
+  binary_storage_tests::r_u64_array_reads_values_written_one_at_a_time(
+    new_storage(256, 512)
+  );
+}
+
+// r_u64_opt() tests
+#[test]
+fn r_u64_opt_returns_err_when_closed() {
+  binary_storage_tests::r_u64_opt_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_err_when_past_capacity() {
+  binary_storage_tests::r_u64_opt_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_none_when_unwritten() {
+  binary_storage_tests::r_u64_opt_returns_none_when_unwritten(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_writing_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_writing_zero(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_u64_opt_returns_some_after_filling_with_zero() {
+  binary_storage_tests::r_u64_opt_returns_some_after_filling_with_zero(
+    new_storage(256, 512)
+  );
+}
+
+// w_uuid() and r_uuid() tests
+#[test]
+fn w_uuid_returns_err_when_closed() {
+  binary_storage_tests::w_uuid_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_closed() {
+  binary_storage_tests::r_uuid_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_uuid_returns_err_when_past_capacity() {
+  binary_storage_tests::r_uuid_returns_err_when_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_and_r_uuid_round_trip() {
+  binary_storage_tests::w_uuid_and_r_uuid_round_trip(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_over_capacity_expands_storage() {
+  binary_storage_tests::w_uuid_over_capacity_expands_storage(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_uuid_returns_err_when_end_overflows() {
+  binary_storage_tests::w_uuid_returns_err_when_end_overflows(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_returns_err_when_closed() {
+  binary_storage_tests::shrink_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_does_nothing_when_capacity_already_small() {
+  binary_storage_tests::shrink_does_nothing_when_capacity_already_small(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_reduces_capacity_to_multiple_of_expand_size() {
+  binary_storage_tests::shrink_reduces_capacity_to_multiple_of_expand_size(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_does_not_reduce_capacity_below_expand_size() {
+  binary_storage_tests::shrink_does_not_reduce_capacity_below_expand_size(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn shrink_keeps_data_below_new_capacity() {
+  binary_storage_tests::shrink_keeps_data_below_new_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_returns_err_when_closed() {
+  binary_storage_tests::w_bytes_reporting_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_within_capacity() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_within_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_reports_expansion_past_capacity() {
+  binary_storage_tests::w_bytes_reporting_reports_expansion_past_capacity(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_bytes_reporting_does_not_report_expansion_after_expanding() {
+  binary_storage_tests::w_bytes_reporting_does_not_report_expansion_after_expanding(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i8_r_i8_round_trip_signed_range() {
+  binary_storage_tests::w_i8_r_i8_round_trip_signed_range(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i16_r_i16_round_trip_signed_range() {
+  binary_storage_tests::w_i16_r_i16_round_trip_signed_range(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i32_r_i32_round_trip_signed_range() {
+  binary_storage_tests::w_i32_r_i32_round_trip_signed_range(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_i64_r_i64_round_trip_signed_range() {
+  binary_storage_tests::w_i64_r_i64_round_trip_signed_range(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn sync_returns_err_when_closed() {
+  binary_storage_tests::sync_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn sync_keeps_written_data() {
+  binary_storage_tests::sync_keeps_written_data(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_returns_err_when_closed() {
+  binary_storage_tests::w_blob_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_closed() {
+  binary_storage_tests::r_blob_returns_err_when_closed(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_writes_length_prefix() {
+  binary_storage_tests::w_blob_writes_length_prefix(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_r_blob_round_trip_varying_sizes() {
+  binary_storage_tests::w_blob_r_blob_round_trip_varying_sizes(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn w_blob_over_capacity_expands_storage() {
+  binary_storage_tests::w_blob_over_capacity_expands_storage(
+    new_storage(256, 512)
+  );
+}
+
+#[test]
+fn r_blob_returns_err_when_length_runs_past_end() {
+  binary_storage_tests::r_blob_returns_err_when_length_runs_past_end(
+    new_storage(256, 512)
+  );
+}